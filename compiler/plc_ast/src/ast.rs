@@ -432,11 +432,33 @@ impl ConfigVariable {
     }
 }
 
+/// Whether an access path of a `VAR_ACCESS` block may be written through
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default, Serialize, Deserialize)]
+pub enum AccessDirection {
+    #[default]
+    ReadOnly,
+    ReadWrite,
+}
+
+/// An access path of a `VAR_ACCESS` block, e.g. `speed : main.motor.speed : REAL READ_WRITE;`
+#[derive(Debug, PartialEq, Clone, Serialize, Deserialize)]
+#[serde(bound(deserialize = "'de: 'static"))]
+pub struct AccessVariable {
+    /// The name external clients use for the path
+    pub name: String,
+    /// The accessed variable, either a qualified reference or a direct address
+    pub path: AstNode,
+    pub data_type: DataTypeDeclaration,
+    pub direction: AccessDirection,
+    pub location: SourceLocation,
+}
+
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[serde(bound(deserialize = "'de: 'static"))]
 pub struct CompilationUnit {
     pub global_vars: Vec<VariableBlock>,
     pub var_config: Vec<ConfigVariable>,
+    pub var_access: Vec<AccessVariable>,
     /// List of POU definitions (signature and some additional metadata)
     pub pous: Vec<Pou>,
     /// List of statements within a POU body
@@ -451,6 +473,7 @@ impl CompilationUnit {
         CompilationUnit {
             global_vars: Vec::new(),
             var_config: Vec::new(),
+            var_access: Vec::new(),
             pous: Vec::new(),
             implementations: Vec::new(),
             interfaces: Vec::new(),
@@ -534,6 +557,10 @@ pub struct VariableBlock {
     pub access: AccessModifier,
     pub constant: bool,
     pub retain: bool,
+    /// Declared `PERSISTENT`, keeping its values across a download of the program as well
+    pub persistent: bool,
+    /// Declared `NON_RETAIN`, losing its values on every restart even where variables are retained by default
+    pub non_retain: bool,
     pub variables: Vec<Variable>,
     pub kind: VariableBlockType,
     pub linkage: LinkageType,
@@ -562,6 +589,8 @@ impl Default for VariableBlock {
             access: AccessModifier::Internal,
             constant: false,
             retain: false,
+            persistent: false,
+            non_retain: false,
             variables: vec![],
             kind: VariableBlockType::Local,
             linkage: LinkageType::Internal,
@@ -1,3 +1,5 @@
+use std::path::PathBuf;
+
 use rustc_hash::FxHashMap;
 
 use crate::{
@@ -7,7 +9,8 @@ use crate::{
     },
     reporter::{
         clang::ClangFormatDiagnosticReporter, codespan::CodeSpanDiagnosticReporter,
-        null::NullDiagnosticReporter, DiagnosticReporter, ResolvedDiagnostics, ResolvedLocation,
+        null::NullDiagnosticReporter, sarif::SarifDiagnosticReporter, DiagnosticReporter,
+        ResolvedDiagnostics, ResolvedLocation,
     },
 };
 
@@ -98,6 +101,15 @@ impl Diagnostician {
         }
     }
 
+    /// Creates a sarif-diagnostician that collects diagnostics as a SARIF log, written to the given file
+    pub fn sarif_diagnostician(output: Option<PathBuf>) -> Diagnostician {
+        Diagnostician {
+            reporter: Box::new(SarifDiagnosticReporter::new(output)),
+            assessor: Box::<DiagnosticsRegistry>::default(),
+            filename_fileid_mapping: FxHashMap::default(),
+        }
+    }
+
     pub fn with_configuration(self, configuration: DiagnosticsConfiguration) -> Self {
         let mut res = self;
         let registry = DiagnosticsRegistry::default().with_configuration(configuration);
@@ -340,7 +340,15 @@ impl Diagnostic {
 mod tests {
     use codespan_reporting::files::{Location, SimpleFile};
 
-    use crate::{diagnostics::Severity, reporter::clang::ClangFormatDiagnosticReporter};
+    use plc_source::source_location::{CodeSpan, TextLocation};
+
+    use crate::{
+        diagnostics::Severity,
+        reporter::{
+            clang::ClangFormatDiagnosticReporter, sarif::SarifDiagnosticReporter, DiagnosticReporter,
+            ResolvedDiagnostics, ResolvedLocation,
+        },
+    };
 
     #[test]
     fn test_build_diagnostic_msg() {
@@ -419,4 +427,46 @@ mod tests {
 
         assert_eq!(res, "error[E001]: This is an error");
     }
+
+    #[test]
+    fn test_sarif_log() {
+        let mut reporter = SarifDiagnosticReporter::default();
+        let file_handle =
+            reporter.register("src/test.st".to_string(), "PROGRAM p\nx := 1;\nEND_PROGRAM".to_string());
+        let start = TextLocation { line: 1, column: 0, offset: 10 };
+        let end = TextLocation { line: 1, column: 1, offset: 11 };
+
+        reporter.report(&[
+            ResolvedDiagnostics {
+                code: "E048".to_string(),
+                message: "Could not resolve reference to x".to_string(),
+                severity: Severity::Error,
+                main_location: ResolvedLocation { file_handle, span: CodeSpan::Range(start..end) },
+                additional_locations: None,
+            },
+            ResolvedDiagnostics {
+                code: "E090".to_string(),
+                message: "Ignored".to_string(),
+                severity: Severity::Ignore,
+                main_location: ResolvedLocation { file_handle, span: CodeSpan::None },
+                additional_locations: None,
+            },
+        ]);
+
+        let log = reporter.build_log();
+        assert_eq!(log["version"], "2.1.0");
+        assert_eq!(log["runs"][0]["tool"]["driver"]["rules"][0]["id"], "E048");
+
+        let results = log["runs"][0]["results"].as_array().unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0]["ruleId"], "E048");
+        assert_eq!(results[0]["level"], "error");
+        assert_eq!(results[0]["message"]["text"], "Could not resolve reference to x");
+
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "src/test.st");
+        assert_eq!(location["region"]["startLine"], 2);
+        assert_eq!(location["region"]["startColumn"], 1);
+        assert_eq!(location["region"]["endColumn"], 2);
+    }
 }
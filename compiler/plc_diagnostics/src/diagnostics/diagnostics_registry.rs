@@ -225,6 +225,12 @@ lazy_static! {
         E122,   Error,      include_str!("./error_codes/E122.md"),  // Invalid enum base type
         E123,   Error,      include_str!("./error_codes/E123.md"),  // Division by zero
         E124,   Error,      include_str!("./error_codes/E124.md"),  // Invalid escape sequence in string literal
+        E125,   Warning,    include_str!("./error_codes/E125.md"),  // Function exported without a return type
+        E126,   Warning,    include_str!("./error_codes/E126.md"),  // Call of a function the XML target has no counterpart of
+        E127,   Warning,    include_str!("./error_codes/E127.md"),  // Location outside the text of its source
+        E128,   Warning,    include_str!("./error_codes/E128.md"),  // State machine exported as ST
+        E129,   Info,       include_str!("./error_codes/E129.md"),  // XML document built as a whole despite streaming
+        E130,   Warning,    include_str!("./error_codes/E130.md"),  // Unknown network publish mode
     );
 }
 
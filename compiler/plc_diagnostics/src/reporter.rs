@@ -5,6 +5,7 @@ use crate::diagnostics::Severity;
 pub mod clang;
 pub mod codespan;
 pub mod null;
+pub mod sarif;
 
 /// the DiagnosticReporter decides on the format and where to report the diagnostic to.
 /// possible implementations could print to either std-out, std-err or a file, etc.
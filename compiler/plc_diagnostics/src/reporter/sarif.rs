@@ -0,0 +1,140 @@
+use std::path::PathBuf;
+
+use codespan_reporting::files::{Files, SimpleFiles};
+use serde_json::{json, Value};
+
+use crate::diagnostics::Severity;
+
+use super::{DiagnosticReporter, ResolvedDiagnostics, ResolvedLocation};
+
+pub const SARIF_SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+pub const SARIF_VERSION: &str = "2.1.0";
+
+/// A reporter that collects diagnostics as a SARIF log, so code scanning services can show them on pull requests.
+/// A SARIF log is a single JSON document, so the file is rewritten with all diagnostics collected so far whenever
+/// new diagnostics are reported. Without a file the log is only kept in the buffer.
+pub struct SarifDiagnosticReporter {
+    files: SimpleFiles<String, String>,
+    results: Vec<Value>,
+    rules: Vec<String>,
+    output: Option<PathBuf>,
+}
+
+impl SarifDiagnosticReporter {
+    pub fn new(output: Option<PathBuf>) -> Self {
+        let reporter = SarifDiagnosticReporter {
+            files: SimpleFiles::new(),
+            results: Vec::new(),
+            rules: Vec::new(),
+            output,
+        };
+        reporter.write_log(); //a run without diagnostics still produces a valid, empty log
+        reporter
+    }
+
+    /// The complete SARIF log of all diagnostics reported so far
+    pub fn build_log(&self) -> Value {
+        let rules: Vec<Value> = self.rules.iter().map(|it| json!({ "id": it })).collect();
+
+        json!({
+            "$schema": SARIF_SCHEMA,
+            "version": SARIF_VERSION,
+            "runs": [{
+                "tool": {
+                    "driver": {
+                        "name": "rusty",
+                        "informationUri": "https://github.com/PLC-lang/rusty",
+                        "version": env!("CARGO_PKG_VERSION"),
+                        "rules": rules,
+                    }
+                },
+                "results": self.results,
+            }]
+        })
+    }
+
+    fn write_log(&self) {
+        let Some(output) = &self.output else {
+            return;
+        };
+
+        let log = serde_json::to_string_pretty(&self.build_log()).expect("SARIF log is valid JSON");
+        if let Err(err) = std::fs::write(output, log) {
+            eprintln!("Could not write the SARIF log to {}: {err}", output.display());
+        }
+    }
+
+    fn physical_location(&self, location: &ResolvedLocation) -> Option<Value> {
+        if location.is_internal() {
+            return None;
+        }
+
+        let file = self.files.get(location.file_handle).ok()?;
+        let mut physical_location = json!({ "artifactLocation": { "uri": file.name().replace('\\', "/") } });
+
+        if let Some(range) = location.span.to_range() {
+            let start = self.files.location(location.file_handle, range.start).ok()?;
+            let end = self.files.location(location.file_handle, range.end).ok()?;
+            physical_location["region"] = json!({
+                "startLine": start.line_number,
+                "startColumn": start.column_number,
+                "endLine": end.line_number,
+                "endColumn": end.column_number,
+            });
+        }
+        Some(json!({ "physicalLocation": physical_location }))
+    }
+}
+
+impl Default for SarifDiagnosticReporter {
+    fn default() -> Self {
+        SarifDiagnosticReporter::new(None)
+    }
+}
+
+impl DiagnosticReporter for SarifDiagnosticReporter {
+    fn report(&mut self, diagnostics: &[ResolvedDiagnostics]) {
+        for diagnostic in diagnostics.iter().filter(|it| it.severity > Severity::Ignore) {
+            let level = match diagnostic.severity {
+                Severity::Error => "error",
+                Severity::Warning => "warning",
+                _ => "note",
+            };
+
+            let mut result = json!({
+                "ruleId": diagnostic.code,
+                "level": level,
+                "message": { "text": diagnostic.message },
+            });
+
+            if let Some(location) = self.physical_location(&diagnostic.main_location) {
+                result["locations"] = json!([location]);
+            }
+
+            let related: Vec<Value> = diagnostic
+                .additional_locations
+                .iter()
+                .flatten()
+                .filter_map(|it| self.physical_location(it))
+                .collect();
+            if !related.is_empty() {
+                result["relatedLocations"] = json!(related);
+            }
+
+            if !self.rules.contains(&diagnostic.code) {
+                self.rules.push(diagnostic.code.clone());
+            }
+            self.results.push(result);
+        }
+
+        self.write_log();
+    }
+
+    fn register(&mut self, path: String, src: String) -> usize {
+        self.files.add(path, src)
+    }
+
+    fn buffer(&self) -> Option<String> {
+        serde_json::to_string_pretty(&self.build_log()).ok()
+    }
+}
@@ -1,14 +1,29 @@
 use anyhow::{bail, Result};
 // Copyright (c) 2021 Ghaith Hachem and Mathias Rieder
-use clap::{ArgGroup, Parser, Subcommand};
+use clap::{builder::PossibleValuesParser, ArgGroup, Parser, Subcommand};
 use encoding_rs::Encoding;
 use plc_diagnostics::diagnostics::{diagnostics_registry::DiagnosticsConfiguration, Diagnostic};
-use plc_xmlgen::xml_gen::GenerationParameters;
 use plc_header_generator::GenerateLanguage;
+use plc_xmlgen::{
+    cross_reference::CrossReferenceFormat,
+    emission_order::EmissionOrder,
+    ignition_tags::DEFAULT_IGNITION_OPC_SERVER,
+    library::LibraryProperties,
+    literal_format::{BoolCase, RealFormat, RealNotation},
+    modbus_map::ModbusMapFormat,
+    serializer::SerializerOptions,
+    source_comments::{BodyComments, LineEndings},
+    st_printer::{IdentifierCase, KeywordCase},
+    xml_gen::GenerationParameters,
+    xml_target::{omron::DEFAULT_OMRON_STRING_LENGTH, TargetRegistry},
+};
 use std::{env, ffi::OsStr, num::ParseIntError, path::PathBuf};
 
 use plc::output::FormatOption;
-use plc::{ConfigFormat, DebugLevel, ErrorFormat, Target, Threads, DEFAULT_GOT_LAYOUT_FILE};
+use plc::{
+    ConfigFormat, DebugLevel, ErrorFormat, Target, Threads, DEFAULT_GOT_LAYOUT_FILE,
+    DEFAULT_SARIF_OUTPUT_FILE,
+};
 
 pub type ParameterError = clap::Error;
 
@@ -28,6 +43,378 @@ pub struct CompileParameters {
     )]
     pub output_xml_omron: bool,
 
+    #[clap(
+        long = "xml-target",
+        name = "xml-target",
+        group = "format",
+        global = true,
+        use_value_delimiter = true,
+        ignore_case = true,
+        value_parser = PossibleValuesParser::new(TargetRegistry::default().names()),
+        help = "Emit IEC 61131-10 open XML exchange format for the given registered targets. May be given several times to emit all of them in one pass"
+    )]
+    pub xml_targets: Vec<String>,
+
+    #[clap(
+        long = "sysmac-csv",
+        global = true,
+        help = "Emit the global variables as a Sysmac Studio variable table (CSV) next to the XML output. Can be used without an XML target"
+    )]
+    pub output_sysmac_csv: bool,
+
+    #[clap(
+        long = "tag-list",
+        global = true,
+        help = "Emit all variables (globals and POU interfaces) as a spreadsheet tag list (xlsx) next to the XML output. Can be used without an XML target"
+    )]
+    pub output_tag_list: bool,
+
+    #[clap(
+        long = "modbus-map",
+        global = true,
+        possible_values = &["csv", "json"],
+        help = "Emit a Modbus register map of all located variables (%IW, %QX, %MD, ...) in the given format next to the XML output. Can be used without an XML target"
+    )]
+    pub output_modbus_map: Option<String>,
+
+    #[clap(
+        long = "eip-tags",
+        global = true,
+        help = "Emit EtherNet/IP tag and assembly definitions of the globals marked with {attribute 'network_publish' := '...'} next to the XML output. Can be used without an XML target"
+    )]
+    pub output_eip_tags: bool,
+
+    #[clap(
+        long = "emit-json",
+        global = true,
+        help = "Emit the project model (data types, globals, POUs with their variables and bodies) as JSON next to the XML output. Can be used without an XML target"
+    )]
+    pub output_project_json: bool,
+
+    #[clap(
+        long = "emit-yaml",
+        global = true,
+        help = "Emit the project model as YAML next to the XML output, for reviewing exports in a diff. Can be used without an XML target"
+    )]
+    pub output_project_yaml: bool,
+
+    #[clap(
+        long = "call-graph",
+        global = true,
+        help = "Emit the POU call graph (programs, function block instances and function calls) as a Graphviz DOT file next to the XML output. Can be used without an XML target"
+    )]
+    pub output_call_graph: bool,
+
+    #[clap(
+        long = "doc-markdown",
+        global = true,
+        help = "Emit Markdown documentation of the user types, POUs and globals, including their comments, next to the XML output. Can be used without an XML target"
+    )]
+    pub output_markdown_docs: bool,
+
+    #[clap(
+        long = "doc-html",
+        global = true,
+        help = "Emit cross-linked HTML documentation of the user types, POUs and globals, with highlighted bodies, next to the XML output. Can be used without an XML target"
+    )]
+    pub output_html_docs: bool,
+
+    #[clap(
+        long = "ignition-tags",
+        name = "ignition-tags",
+        global = true,
+        help = "Emit an Ignition tag export (JSON) of the globals marked with {attribute 'network_publish' := '...'} next to the XML output. Can be used without an XML target"
+    )]
+    pub output_ignition_tags: bool,
+
+    #[clap(
+        long = "ignition-opc-server",
+        global = true,
+        requires = "ignition-tags",
+        help = "The name of the OPC connection the Ignition tags are read through. Defaults to the Ignition OPC UA Server"
+    )]
+    pub ignition_opc_server: Option<String>,
+
+    #[clap(
+        long = "cross-reference",
+        global = true,
+        possible_values = &["csv", "json"],
+        help = "Emit a cross-reference report (which POUs read and write each variable) in the given format next to the XML output. Can be used without an XML target"
+    )]
+    pub output_cross_reference: Option<String>,
+
+    #[clap(
+        long = "xml-string-length",
+        global = true,
+        help = "The length of exported strings whose declared length is unknown, for targets which require a length. Defaults to 1986, the longest string of Sysmac Studio"
+    )]
+    pub xml_string_length: Option<u32>,
+
+    #[clap(
+        long = "xml-real-digits",
+        global = true,
+        help = "Round exported REAL and LREAL values like initial values to the given number of significant digits"
+    )]
+    pub xml_real_digits: Option<usize>,
+
+    #[clap(
+        long = "xml-real-notation",
+        global = true,
+        possible_values = &["decimal", "scientific"],
+        help = "Write exported REAL and LREAL values as decimals (1500.0) or in scientific notation (1.5E3). Defaults to decimal"
+    )]
+    pub xml_real_notation: Option<String>,
+
+    #[clap(
+        long = "xml-bool-case",
+        global = true,
+        possible_values = &["upper", "lower"],
+        help = "Write exported BOOL values as TRUE and FALSE (upper) or true and false (lower) for every target. Without it each target uses the spelling its IDE expects"
+    )]
+    pub xml_bool_case: Option<String>,
+
+    #[clap(
+        long = "xml-ascii-identifiers",
+        global = true,
+        help = "Export identifiers with non-ASCII characters transliterated, e.g. Größe as Groesse, for IDEs which only accept ASCII names"
+    )]
+    pub xml_ascii_identifiers: bool,
+
+    #[clap(
+        long = "xml-referenced-globals-only",
+        global = true,
+        help = "Export only the globals the exported POUs use, instead of every global of the project"
+    )]
+    pub xml_referenced_globals_only: bool,
+
+    #[clap(
+        long = "xml-global-groups",
+        global = true,
+        help = "Export every VAR_GLOBAL block as a variable group of its own instead of merging the blocks by their qualifiers"
+    )]
+    pub xml_global_groups: bool,
+
+    #[clap(
+        long = "xml-default-task",
+        global = true,
+        help = "The task the exported programs without a task attribute run in. Without it these programs are not instantiated"
+    )]
+    pub xml_default_task: Option<String>,
+
+    #[clap(
+        long = "xml-configuration",
+        global = true,
+        help = "Export the globals of every file into a single configuration of this name instead of one configuration per file"
+    )]
+    pub xml_configuration: Option<String>,
+
+    #[clap(
+        long = "xml-external-stubs",
+        global = true,
+        help = "Export {external} POUs with their declaration but without a body, for targets whose libraries do not provide them"
+    )]
+    pub xml_external_stubs: bool,
+
+    #[clap(
+        long = "xml-no-en-eno",
+        global = true,
+        help = "Export functions and function blocks without the implicit EN input and ENO output, for targets which add them automatically"
+    )]
+    pub xml_no_en_eno: bool,
+
+    #[clap(
+        long = "xml-strict-return-types",
+        global = true,
+        help = "Fail the export at functions without a return type the export can name, instead of exporting them as returning BOOL"
+    )]
+    pub xml_strict_return_types: bool,
+
+    #[clap(
+        long = "xml-emission-order",
+        global = true,
+        possible_values = &["source", "alphabetical", "dependency"],
+        help = "Write the exported data types and POUs as declared (source), by name (alphabetical) or each after the declarations it uses (dependency). Defaults to source"
+    )]
+    pub xml_emission_order: Option<String>,
+
+    #[clap(
+        long = "xml-fbd-bodies",
+        global = true,
+        help = "Export bodies made of assignments and function block calls only as FBD networks instead of ST, for targets with FBD"
+    )]
+    pub xml_fbd_bodies: bool,
+
+    #[clap(
+        long = "xml-canonical-bodies",
+        global = true,
+        help = "Write ST bodies from the parsed statements in a uniform layout, instead of copying them from the source files"
+    )]
+    pub xml_canonical_bodies: bool,
+
+    #[clap(
+        long = "xml-keyword-case",
+        global = true,
+        possible_values = &["upper", "lower"],
+        help = "Spell the keywords of bodies written with --xml-canonical-bodies in upper or lower case. Defaults to upper"
+    )]
+    pub xml_keyword_case: Option<String>,
+
+    #[clap(
+        long = "xml-identifier-case",
+        global = true,
+        possible_values = &["upper", "lower"],
+        help = "Spell the identifiers of bodies written with --xml-canonical-bodies in upper or lower case, instead of as written"
+    )]
+    pub xml_identifier_case: Option<String>,
+
+    #[clap(
+        long = "xml-body-comments",
+        global = true,
+        possible_values = &["keep", "strip", "convert"],
+        help = "Keep the comments inside ST bodies as written (keep), leave them out (strip) or rewrite them in the comment syntax of the XML target (convert). Defaults to keep"
+    )]
+    pub xml_body_comments: Option<String>,
+
+    #[clap(
+        long = "xml-line-endings",
+        global = true,
+        possible_values = &["lf", "crlf"],
+        help = "Write the line breaks of exported ST bodies as LF or CRLF, instead of as in the source files"
+    )]
+    pub xml_line_endings: Option<String>,
+
+    #[clap(
+        long = "xml-monomorphize-generics",
+        global = true,
+        help = "Export one concrete POU per instantiation of a generic POU, e.g. MAX_OF_INT, instead of the generic POU"
+    )]
+    pub xml_monomorphize_generics: bool,
+
+    #[clap(
+        long = "xml-flatten-inheritance",
+        global = true,
+        help = "Copy the variables of the base into a function block extending it, for targets without inheritance"
+    )]
+    pub xml_flatten_inheritance: bool,
+
+    #[clap(
+        long = "xml-enum-base-type",
+        global = true,
+        use_value_delimiter = true,
+        parse(try_from_str = parse_type_mapping),
+        help = "Export enums declared with the given base type with another one, e.g. DINT=INT. May be given several times. Without a mapping the target decides"
+    )]
+    pub xml_enum_base_types: Vec<(String, String)>,
+
+    #[clap(
+        long = "xml-bit-string-type",
+        global = true,
+        use_value_delimiter = true,
+        parse(try_from_str = parse_type_mapping),
+        help = "Export network-published variables of a bit-string type with another type, e.g. WORD=UINT. May be given several times. Overrides the defaults of the target"
+    )]
+    pub xml_bit_string_types: Vec<(String, String)>,
+
+    #[clap(
+        long = "xml-function-name",
+        global = true,
+        use_value_delimiter = true,
+        parse(try_from_str = parse_type_mapping),
+        help = "Export calls of a function under another name in bodies written with --xml-canonical-bodies, e.g. SEL=MUX2. May be given several times. Overrides the defaults of the target"
+    )]
+    pub xml_function_names: Vec<(String, String)>,
+
+    #[clap(
+        long = "xml-archive",
+        name = "xml-archive",
+        global = true,
+        help = "Package the emitted XML together with the --xml-archive-file files into a zip archive next to it (codesys)"
+    )]
+    pub xml_archive: bool,
+
+    #[clap(
+        long = "split-output",
+        global = true,
+        help = "Additionally write every data type and POU of the emitted XML as a document of its own, listed in an index, into a folder next to it (e.g. out.split)"
+    )]
+    pub split_output: bool,
+
+    #[clap(
+        long = "xml-source-map",
+        global = true,
+        help = "Additionally write a source map next to each emitted XML document (e.g. out.sourcemap.json), mapping every line of its bodies back to the ST file and line"
+    )]
+    pub xml_source_map: bool,
+
+    #[clap(
+        long = "xml-streaming",
+        global = true,
+        help = "Write each XML document while it is generated instead of building it in memory first, for large projects"
+    )]
+    pub xml_streaming: bool,
+
+    #[clap(
+        long = "xml-provenance",
+        global = true,
+        help = "Start each emitted XML document with a comment naming the compiler version, the source files and the generation time"
+    )]
+    pub xml_provenance: bool,
+
+    #[clap(
+        long = "xml-indent",
+        global = true,
+        help = "Indent the nested elements of emitted XML documents by this many spaces, or write them without indentation and line breaks if 0. Defaults to 2"
+    )]
+    pub xml_indent: Option<usize>,
+
+    #[clap(
+        long = "xml-indent-tabs",
+        global = true,
+        help = "Indent the nested elements of emitted XML documents by a tab per level instead of spaces"
+    )]
+    pub xml_indent_tabs: bool,
+
+    #[clap(
+        long = "xml-archive-file",
+        name = "xml-archive-file",
+        global = true,
+        requires = "xml-archive",
+        help = "Add an auxiliary file to the packaged archive. May be given several times"
+    )]
+    pub xml_archive_files: Vec<String>,
+
+    #[clap(
+        long = "xml-library-title",
+        global = true,
+        requires = "xml-archive",
+        help = "The library title written into the project information of the packaged archive (codesys). Defaults to the name of the output file"
+    )]
+    pub xml_library_title: Option<String>,
+
+    #[clap(
+        long = "xml-library-version",
+        global = true,
+        requires = "xml-archive",
+        help = "The library version written into the project information of the packaged archive (codesys)"
+    )]
+    pub xml_library_version: Option<String>,
+
+    #[clap(
+        long = "xml-library-company",
+        global = true,
+        requires = "xml-archive",
+        help = "The library company written into the project information of the packaged archive (codesys)"
+    )]
+    pub xml_library_company: Option<String>,
+
+    #[clap(
+        long = "xml-library-namespace",
+        global = true,
+        requires = "xml-archive",
+        help = "The library namespace written into the project information of the packaged archive (codesys). Defaults to the library title"
+    )]
+    pub xml_library_namespace: Option<String>,
+
     #[clap(short, long, global = true, name = "output-file", help = "Write output to <output-file>")]
     pub output: Option<String>,
 
@@ -181,6 +568,15 @@ pub struct CompileParameters {
     )]
     pub error_format: ErrorFormat,
 
+    #[clap(
+        name = "sarif-output",
+        long,
+        help = "The file the diagnostics are written to when using the sarif error format",
+        default_value = DEFAULT_SARIF_OUTPUT_FILE,
+        global = true
+    )]
+    pub sarif_output: String,
+
     #[clap(name = "linker", long, help = "Define a custom (cc compatible) linker command", global = true)]
     pub linker: Option<String>,
 
@@ -409,6 +805,15 @@ fn validate_config(config_name: &str) -> Result<String, String> {
     }
 }
 
+fn parse_type_mapping(mapping: &str) -> Result<(String, String), String> {
+    match mapping.split_once('=') {
+        Some((declared, exported)) if !declared.trim().is_empty() && !exported.trim().is_empty() => {
+            Ok((declared.trim().to_string(), exported.trim().to_string()))
+        }
+        _ => Err(format!("Expected a mapping like DINT=INT, got {mapping}")),
+    }
+}
+
 fn get_parallel_threads(thread_count: &str) -> Result<Threads, ParseIntError> {
     if thread_count.is_empty() {
         Ok(Threads::Full)
@@ -429,11 +834,112 @@ pub fn get_config_format(name: &str) -> Option<ConfigFormat> {
 
 impl CompileParameters {
     pub fn to_gen_parameters(&self) -> GenerationParameters {
-        GenerationParameters { 
-            output_xml_omron: self.output_xml_omron
+        GenerationParameters {
+            output_xml_omron: self.output_xml_omron,
+            xml_targets: self.xml_targets.clone(),
+            package_archive: self.xml_archive,
+            split_output: self.split_output,
+            source_map: self.xml_source_map,
+            streaming: self.xml_streaming,
+            provenance_comment: self.xml_provenance,
+            serializer_options: self.serializer_options(),
+            archive_files: self.xml_archive_files.iter().map(PathBuf::from).collect(),
+            library: self.library_properties(),
+            sysmac_csv: self.output_sysmac_csv,
+            tag_list: self.output_tag_list,
+            modbus_map: self.output_modbus_map.as_deref().map(|format| match format {
+                "json" => ModbusMapFormat::Json,
+                _ => ModbusMapFormat::Csv,
+            }),
+            eip_tags: self.output_eip_tags,
+            project_json: self.output_project_json,
+            project_yaml: self.output_project_yaml,
+            call_graph: self.output_call_graph,
+            markdown_docs: self.output_markdown_docs,
+            html_docs: self.output_html_docs,
+            ignition_tags: self.output_ignition_tags.then(|| {
+                self.ignition_opc_server.clone().unwrap_or_else(|| String::from(DEFAULT_IGNITION_OPC_SERVER))
+            }),
+            cross_reference: self.output_cross_reference.as_deref().map(|format| match format {
+                "json" => CrossReferenceFormat::Json,
+                _ => CrossReferenceFormat::Csv,
+            }),
+            default_string_length: self.xml_string_length.unwrap_or(DEFAULT_OMRON_STRING_LENGTH),
+            enum_base_types: self.xml_enum_base_types.clone(),
+            bit_string_types: self.xml_bit_string_types.clone(),
+            function_names: self.xml_function_names.clone(),
+            real_format: RealFormat {
+                digits: self.xml_real_digits,
+                notation: match self.xml_real_notation.as_deref() {
+                    Some("scientific") => RealNotation::Scientific,
+                    _ => RealNotation::Decimal,
+                },
+            },
+            bool_case: self.xml_bool_case.as_deref().map(|case| match case {
+                "lower" => BoolCase::Lower,
+                _ => BoolCase::Upper,
+            }),
+            ascii_identifiers: self.xml_ascii_identifiers,
+            referenced_globals_only: self.xml_referenced_globals_only,
+            global_groups: self.xml_global_groups,
+            default_task: self.xml_default_task.clone(),
+            configuration_name: self.xml_configuration.clone(),
+            external_stubs: self.xml_external_stubs,
+            implicit_en_eno: !self.xml_no_en_eno,
+            strict_return_types: self.xml_strict_return_types,
+            emission_order: match self.xml_emission_order.as_deref() {
+                Some("alphabetical") => EmissionOrder::Alphabetical,
+                Some("dependency") => EmissionOrder::Dependency,
+                _ => EmissionOrder::Source,
+            },
+            fbd_bodies: self.xml_fbd_bodies,
+            canonical_bodies: self.xml_canonical_bodies,
+            keyword_case: match self.xml_keyword_case.as_deref() {
+                Some("lower") => KeywordCase::Lower,
+                _ => KeywordCase::Upper,
+            },
+            identifier_case: match self.xml_identifier_case.as_deref() {
+                Some("upper") => IdentifierCase::Upper,
+                Some("lower") => IdentifierCase::Lower,
+                _ => IdentifierCase::AsWritten,
+            },
+            body_comments: match self.xml_body_comments.as_deref() {
+                Some("strip") => BodyComments::Strip,
+                Some("convert") => BodyComments::Convert,
+                _ => BodyComments::Keep,
+            },
+            line_endings: match self.xml_line_endings.as_deref() {
+                Some("lf") => LineEndings::Lf,
+                Some("crlf") => LineEndings::Crlf,
+                _ => LineEndings::Preserve,
+            },
+            monomorphize_generics: self.xml_monomorphize_generics,
+            flatten_inheritance: self.xml_flatten_inheritance,
+            ..GenerationParameters::new()
+        }
+    }
+
+    fn library_properties(&self) -> LibraryProperties {
+        let defaults = LibraryProperties::default();
+        LibraryProperties {
+            title: self.xml_library_title.clone(),
+            version: self.xml_library_version.clone().unwrap_or(defaults.version),
+            company: self.xml_library_company.clone().unwrap_or(defaults.company),
+            namespace: self.xml_library_namespace.clone(),
         }
     }
 
+    fn serializer_options(&self) -> SerializerOptions {
+        let defaults = SerializerOptions::default();
+        let indent = match (self.xml_indent_tabs, self.xml_indent) {
+            (true, _) => Some(String::from("\t")),
+            (false, Some(0)) => None,
+            (false, Some(spaces)) => Some(" ".repeat(spaces)),
+            (false, None) => defaults.indent.clone(),
+        };
+        SerializerOptions { indent, ..defaults }
+    }
+
     pub fn parse<T: AsRef<OsStr> + AsRef<str>>(args: &[T]) -> Result<CompileParameters, ParameterError> {
         CompileParameters::try_parse_from(args)
     }
@@ -472,8 +978,21 @@ impl CompileParameters {
         } else if self.output_obj_code {
             Some(FormatOption::Static)
         } else if self.output_reloc_code {
-            Some(FormatOption::Relocatable)        
-        } else if self.output_xml_omron {
+            Some(FormatOption::Relocatable)
+        } else if self.output_xml_omron
+            || !self.xml_targets.is_empty()
+            || self.output_sysmac_csv
+            || self.output_tag_list
+            || self.output_modbus_map.is_some()
+            || self.output_eip_tags
+            || self.output_project_json
+            || self.output_project_yaml
+            || self.output_call_graph
+            || self.output_markdown_docs
+            || self.output_html_docs
+            || self.output_ignition_tags
+            || self.output_cross_reference.is_some()
+        {
             Some(FormatOption::XML)
         } else {
             //Keep the parameter default as static
@@ -593,9 +1112,18 @@ mod cli_tests {
     use super::{CompileParameters, SubCommands};
     use clap::ErrorKind;
     use plc::{output::FormatOption, ConfigFormat, ErrorFormat, OptimizationLevel};
+    use plc_xmlgen::{
+        cross_reference::CrossReferenceFormat,
+        emission_order::EmissionOrder,
+        literal_format::{BoolCase, RealFormat, RealNotation},
+        modbus_map::ModbusMapFormat,
+        source_comments::{BodyComments, LineEndings},
+        st_printer::{IdentifierCase, KeywordCase},
+    };
     use pretty_assertions::assert_eq;
     use std::ffi::OsStr;
     use std::fmt::Debug;
+    use std::path::PathBuf;
 
     #[test]
     fn verify_cli() {
@@ -643,29 +1171,691 @@ mod cli_tests {
         expect_argument_error(vec_of_strings!["input.st", "--ir", "-u"], ErrorKind::UnknownArgument);
     }
 
-    //#[test]
-    //fn valid_output_files() {
-    //    //short -o
-    //    let parameters =
-    //        CompileParameters::parse(vec_of_strings!("input.st", "--ir", "-o", "myout.out")).unwrap();
-    //    assert_eq!(parameters.output_name(), "myout.out".to_string());
+    #[test]
+    fn xml_archive_sets_packaging_parameters() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "codesys",
+            "--xml-archive",
+            "--xml-archive-file",
+            "notes.txt"
+        ))
+        .unwrap();
+        let generation = parameters.to_gen_parameters();
+        assert!(generation.package_archive);
+        assert_eq!(generation.archive_files, vec![PathBuf::from("notes.txt")]);
 
-    //    //long --output
-    //    let parameters =
-    //        CompileParameters::parse(vec_of_strings!("input.st", "--ir", "--output", "myout2.out")).unwrap();
-    //    assert_eq!(parameters.output_name(), "myout2.out".to_string());
-    //}
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-target", "codesys", "--xml-archive-file", "notes.txt"],
+            ErrorKind::MissingRequiredArgument,
+        );
+    }
 
-    // #[test]
-    // fn test_default_output_names() {
-    //     let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "--ir")).unwrap();
-    //     assert_eq!(parameters.output_name(), "alpha.ll".to_string());
+    #[test]
+    fn split_output_sets_generation_parameter() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron", "--split-output"))
+                .unwrap();
+        assert!(parameters.to_gen_parameters().split_output);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().split_output);
+    }
 
-    //     let parameters = CompileParameters::parse(vec_of_strings!("bravo", "--shared")).unwrap();
-    //     assert_eq!(parameters.output_name(), "bravo.so".to_string());
+    #[test]
+    fn xml_source_map_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-source-map"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().source_map);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().source_map);
+    }
 
-    //     let parameters = CompileParameters::parse(vec_of_strings!("examples/charlie.st", "--pic")).unwrap();
-    //     assert_eq!(parameters.output_name(), "charlie.so".to_string());
+    #[test]
+    fn xml_streaming_sets_generation_parameter() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron", "--xml-streaming"))
+                .unwrap();
+        assert!(parameters.to_gen_parameters().streaming);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().streaming);
+    }
+
+    #[test]
+    fn xml_indent_sets_serializer_options() {
+        let indent = |args: &[&str]| {
+            CompileParameters::parse(args).unwrap().to_gen_parameters().serializer_options.indent
+        };
+        assert_eq!(indent(vec_of_strings!("input.st", "--xml-target", "omron")), Some(String::from("  ")));
+        assert_eq!(
+            indent(vec_of_strings!("input.st", "--xml-target", "omron", "--xml-indent", "4")),
+            Some(String::from("    "))
+        );
+        assert_eq!(indent(vec_of_strings!("input.st", "--xml-target", "omron", "--xml-indent", "0")), None);
+        assert_eq!(
+            indent(vec_of_strings!("input.st", "--xml-target", "omron", "--xml-indent-tabs")),
+            Some(String::from("\t"))
+        );
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-indent", "wide"],
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn xml_provenance_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-provenance"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().provenance_comment);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().provenance_comment);
+    }
+
+    #[test]
+    fn xml_library_sets_library_properties() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "codesys",
+            "--xml-archive",
+            "--xml-library-version",
+            "2.0.0.1",
+            "--xml-library-namespace",
+            "util"
+        ))
+        .unwrap();
+        let library = parameters.to_gen_parameters().library;
+        assert_eq!(library.title, None);
+        assert_eq!(library.version, "2.0.0.1");
+        assert_eq!(library.company, "");
+        assert_eq!(library.namespace.as_deref(), Some("util"));
+    }
+
+    #[test]
+    fn sysmac_csv_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--sysmac-csv")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert!(parameters.to_gen_parameters().sysmac_csv);
+        assert!(parameters.to_gen_parameters().target_names().is_empty());
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-omron", "--sysmac-csv")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["omron"]);
+    }
+
+    #[test]
+    fn tag_list_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--tag-list")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert!(parameters.to_gen_parameters().tag_list);
+        assert!(parameters.to_gen_parameters().writes_side_outputs());
+    }
+
+    #[test]
+    fn modbus_map_selects_xml_format() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--modbus-map", "json")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert_eq!(parameters.to_gen_parameters().modbus_map, Some(ModbusMapFormat::Json));
+        expect_argument_error(vec_of_strings!["input.st", "--modbus-map", "xml"], ErrorKind::InvalidValue);
+    }
+
+    #[test]
+    fn eip_tags_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--eip-tags")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert!(parameters.to_gen_parameters().eip_tags);
+    }
+
+    #[test]
+    fn emit_json_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--emit-json")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert!(parameters.to_gen_parameters().project_json);
+    }
+
+    #[test]
+    fn emit_yaml_is_written_next_to_the_targets() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "iec", "--emit-yaml"))
+                .unwrap();
+        assert!(parameters.to_gen_parameters().project_yaml);
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["iec"]);
+    }
+
+    #[test]
+    fn call_graph_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--call-graph")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert!(parameters.to_gen_parameters().call_graph);
+    }
+
+    #[test]
+    fn doc_markdown_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--doc-markdown")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert!(parameters.to_gen_parameters().markdown_docs);
+        assert!(!parameters.to_gen_parameters().html_docs);
+    }
+
+    #[test]
+    fn doc_html_can_be_combined_with_doc_markdown() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--doc-markdown", "--doc-html")).unwrap();
+        assert!(parameters.to_gen_parameters().markdown_docs);
+        assert!(parameters.to_gen_parameters().html_docs);
+    }
+
+    #[test]
+    fn ignition_tags_selects_xml_format() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--ignition-tags")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert_eq!(parameters.to_gen_parameters().ignition_tags.as_deref(), Some("Ignition OPC UA Server"));
+    }
+
+    #[test]
+    fn ignition_opc_server_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--ignition-tags",
+            "--ignition-opc-server",
+            "Line PLC"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().ignition_tags.as_deref(), Some("Line PLC"));
+        expect_argument_error(
+            vec_of_strings!["input.st", "--ignition-opc-server", "Line PLC"],
+            ErrorKind::MissingRequiredArgument,
+        );
+    }
+
+    #[test]
+    fn cross_reference_selects_xml_format() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--cross-reference", "csv")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert_eq!(parameters.to_gen_parameters().cross_reference, Some(CrossReferenceFormat::Csv));
+        expect_argument_error(
+            vec_of_strings!["input.st", "--cross-reference", "html"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_target_selects_xml_format() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.output_format(), Some(FormatOption::XML));
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["omron"]);
+
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-target", "omron", "--ir"],
+            ErrorKind::ArgumentConflict,
+        );
+    }
+
+    #[test]
+    fn xml_omron_selects_the_omron_target() {
+        let parameters = CompileParameters::parse(vec_of_strings!("input.st", "--xml-omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["omron"]);
+    }
+
+    #[test]
+    fn xml_target_accepts_several_targets() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-target",
+            "iec"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["omron", "iec"]);
+
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron,iec")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["omron", "iec"]);
+    }
+
+    #[test]
+    fn xml_target_only_accepts_registered_targets() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "CODESYS")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().target_names(), vec!["CODESYS"]);
+
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-target", "plcopen"],
+            ErrorKind::InvalidValue,
+        );
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-target", "omron,plcopen"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_string_length_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-string-length",
+            "80"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().default_string_length, 80);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().default_string_length, 1986);
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-string-length", "long"],
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn xml_real_format_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-real-digits",
+            "6",
+            "--xml-real-notation",
+            "scientific"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.to_gen_parameters().real_format,
+            RealFormat { digits: Some(6), notation: RealNotation::Scientific }
+        );
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().real_format, RealFormat::default());
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-real-notation", "hex"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_bool_case_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "aml",
+            "--xml-bool-case",
+            "upper"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().bool_case, Some(BoolCase::Upper));
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "aml")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().bool_case, None);
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-bool-case", "title"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_ascii_identifiers_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-ascii-identifiers"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().ascii_identifiers);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().ascii_identifiers);
+    }
+
+    #[test]
+    fn xml_referenced_globals_only_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-referenced-globals-only"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().referenced_globals_only);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().referenced_globals_only);
+    }
+
+    #[test]
+    fn xml_global_groups_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-global-groups"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().global_groups);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().global_groups);
+    }
+
+    #[test]
+    fn xml_default_task_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-default-task",
+            "MainTask"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().default_task.as_deref(), Some("MainTask"));
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().default_task, None);
+    }
+
+    #[test]
+    fn xml_configuration_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-configuration",
+            "Controller"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().configuration_name.as_deref(), Some("Controller"));
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().configuration_name, None);
+    }
+
+    #[test]
+    fn xml_external_stubs_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-external-stubs"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().external_stubs);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().external_stubs);
+    }
+
+    #[test]
+    fn xml_no_en_eno_sets_generation_parameter() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "tia", "--xml-no-en-eno"))
+                .unwrap();
+        assert!(!parameters.to_gen_parameters().implicit_en_eno);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "tia")).unwrap();
+        assert!(parameters.to_gen_parameters().implicit_en_eno);
+    }
+
+    #[test]
+    fn xml_strict_return_types_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-strict-return-types"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().strict_return_types);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().strict_return_types);
+    }
+
+    #[test]
+    fn xml_emission_order_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-emission-order",
+            "dependency"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().emission_order, EmissionOrder::Dependency);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().emission_order, EmissionOrder::Source);
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-emission-order", "random"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_fbd_bodies_sets_generation_parameter() {
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "iec", "--xml-fbd-bodies"))
+                .unwrap();
+        assert!(parameters.to_gen_parameters().fbd_bodies);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "iec")).unwrap();
+        assert!(!parameters.to_gen_parameters().fbd_bodies);
+    }
+
+    #[test]
+    fn xml_canonical_bodies_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "iec",
+            "--xml-canonical-bodies"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().canonical_bodies);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "iec")).unwrap();
+        assert!(!parameters.to_gen_parameters().canonical_bodies);
+    }
+
+    #[test]
+    fn xml_keyword_and_identifier_case_set_generation_parameters() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "iec",
+            "--xml-canonical-bodies",
+            "--xml-keyword-case",
+            "lower",
+            "--xml-identifier-case",
+            "upper"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().keyword_case, KeywordCase::Lower);
+        assert_eq!(parameters.to_gen_parameters().identifier_case, IdentifierCase::Upper);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "iec")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().keyword_case, KeywordCase::Upper);
+        assert_eq!(parameters.to_gen_parameters().identifier_case, IdentifierCase::AsWritten);
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-keyword-case", "camel"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_body_comments_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-body-comments",
+            "strip"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().body_comments, BodyComments::Strip);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().body_comments, BodyComments::Keep);
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-body-comments", "random"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_line_endings_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-line-endings",
+            "crlf"
+        ))
+        .unwrap();
+        assert_eq!(parameters.to_gen_parameters().line_endings, LineEndings::Crlf);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert_eq!(parameters.to_gen_parameters().line_endings, LineEndings::Preserve);
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-line-endings", "cr"],
+            ErrorKind::InvalidValue,
+        );
+    }
+
+    #[test]
+    fn xml_monomorphize_generics_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-monomorphize-generics"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().monomorphize_generics);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().monomorphize_generics);
+    }
+
+    #[test]
+    fn xml_flatten_inheritance_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-flatten-inheritance"
+        ))
+        .unwrap();
+        assert!(parameters.to_gen_parameters().flatten_inheritance);
+        let parameters =
+            CompileParameters::parse(vec_of_strings!("input.st", "--xml-target", "omron")).unwrap();
+        assert!(!parameters.to_gen_parameters().flatten_inheritance);
+    }
+
+    #[test]
+    fn xml_enum_base_type_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-enum-base-type",
+            "DINT=INT,udint=UINT"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.to_gen_parameters().enum_base_types,
+            vec![(String::from("DINT"), String::from("INT")), (String::from("udint"), String::from("UINT"))]
+        );
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-enum-base-type", "DINT"],
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn xml_bit_string_type_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-bit-string-type",
+            "WORD=UINT",
+            "--xml-bit-string-type",
+            "BYTE=USINT"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.to_gen_parameters().bit_string_types,
+            vec![(String::from("WORD"), String::from("UINT")), (String::from("BYTE"), String::from("USINT"))]
+        );
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-bit-string-type", "WORD"],
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    #[test]
+    fn xml_function_name_sets_generation_parameter() {
+        let parameters = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--xml-target",
+            "omron",
+            "--xml-function-name",
+            "SEL=MUX2"
+        ))
+        .unwrap();
+        assert_eq!(
+            parameters.to_gen_parameters().function_names,
+            vec![(String::from("SEL"), String::from("MUX2"))]
+        );
+        expect_argument_error(
+            vec_of_strings!["input.st", "--xml-function-name", "SEL"],
+            ErrorKind::ValueValidation,
+        );
+    }
+
+    //#[test]
+    //fn valid_output_files() {
+    //    //short -o
+    //    let parameters =
+    //        CompileParameters::parse(vec_of_strings!("input.st", "--ir", "-o", "myout.out")).unwrap();
+    //    assert_eq!(parameters.output_name(), "myout.out".to_string());
+
+    //    //long --output
+    //    let parameters =
+    //        CompileParameters::parse(vec_of_strings!("input.st", "--ir", "--output", "myout2.out")).unwrap();
+    //    assert_eq!(parameters.output_name(), "myout2.out".to_string());
+    //}
+
+    // #[test]
+    // fn test_default_output_names() {
+    //     let parameters = CompileParameters::parse(vec_of_strings!("alpha.st", "--ir")).unwrap();
+    //     assert_eq!(parameters.output_name(), "alpha.ll".to_string());
+
+    //     let parameters = CompileParameters::parse(vec_of_strings!("bravo", "--shared")).unwrap();
+    //     assert_eq!(parameters.output_name(), "bravo.so".to_string());
+
+    //     let parameters = CompileParameters::parse(vec_of_strings!("examples/charlie.st", "--pic")).unwrap();
+    //     assert_eq!(parameters.output_name(), "charlie.so".to_string());
 
     //     let parameters =
     //         CompileParameters::parse(vec_of_strings!("examples/test/delta.st", "--static", "-c")).unwrap();
@@ -1056,6 +2246,18 @@ mod cli_tests {
         // set clang as error format
         let params = CompileParameters::parse(vec_of_strings!("input.st", "--error-format=clang")).unwrap();
         assert_eq!(params.error_format, ErrorFormat::Clang);
+        // set sarif as error format, written to the default or the given file
+        let params = CompileParameters::parse(vec_of_strings!("input.st", "--error-format=sarif")).unwrap();
+        assert_eq!(params.error_format, ErrorFormat::Sarif);
+        assert_eq!(params.sarif_output, "diagnostics.sarif");
+        let params = CompileParameters::parse(vec_of_strings!(
+            "input.st",
+            "--error-format=sarif",
+            "--sarif-output",
+            "build/rusty.sarif"
+        ))
+        .unwrap();
+        assert_eq!(params.sarif_output, "build/rusty.sarif");
         // set invalid error format
         expect_argument_error(vec_of_strings!("input.st", "--error-format=nothing"), ErrorKind::InvalidValue);
     }
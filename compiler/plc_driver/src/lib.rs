@@ -87,6 +87,8 @@ pub struct LinkOptions {
     pub build_location: Option<PathBuf>,
     pub linker_script: LinkerScript,
     pub module_name: Option<String>,
+    /// The XML targets the generated documents have to be copied for
+    pub xml_targets: Vec<String>,
 }
 
 #[derive(Clone, Default, Debug)]
@@ -158,7 +160,25 @@ pub fn compile<T: AsRef<str> + AsRef<OsStr> + Debug>(args: &[T]) -> Result<()> {
 pub fn compile_with_pipeline<T: SourceContainer + Clone + 'static>(
     mut pipeline: BuildPipeline<T>,
 ) -> Result<()> {
-    //register participants
+    register_participants(&mut pipeline);
+
+    let format = pipeline.compile_parameters.as_ref().map(|it| it.error_format).unwrap_or_default();
+
+    pipeline.run().map_err(|err| {
+        //Only report the hint if we are using rich error reporting
+        if matches!(format, ErrorFormat::Rich) {
+            anyhow!(
+                "{err}.
+Hint: You can use `plc explain <ErrorCode>` for more information"
+            )
+        } else {
+            err.into()
+        }
+    })
+}
+
+/// Registers the default participants and the codegen participant, which persists and links the generated modules
+pub fn register_participants<T: SourceContainer + Clone + 'static>(pipeline: &mut BuildPipeline<T>) {
     pipeline.register_default_participants();
     let target = pipeline.compile_parameters.as_ref().and_then(|it| it.target.clone()).unwrap_or_default();
     let codegen_participant = CodegenParticipant {
@@ -172,22 +192,9 @@ pub fn compile_with_pipeline<T: SourceContainer + Clone + 'static>(
         got_layout: Default::default(),
         compile_dirs: Default::default(),
         libraries: pipeline.project.get_libraries().to_vec(),
+        diagnostics: Default::default(),
     };
     pipeline.register_participant(Box::new(codegen_participant));
-
-    let format = pipeline.compile_parameters.as_ref().map(|it| it.error_format).unwrap_or_default();
-
-    pipeline.run().map_err(|err| {
-        //Only report the hint if we are using rich error reporting
-        if matches!(format, ErrorFormat::Rich) {
-            anyhow!(
-                "{err}.
-Hint: You can use `plc explain <ErrorCode>` for more information"
-            )
-        } else {
-            err.into()
-        }
-    })
 }
 
 /// Parses and annotates a given project. Can be used in tests or api calls
@@ -219,6 +226,7 @@ pub fn parse_and_annotate_with_diagnostics<T: SourceContainer + Clone>(
         mutable_participants: Vec::default(),
         participants: Vec::default(),
         module_name: Some("<internal>".to_string()),
+        xml_targets: Default::default(),
     };
     pipeline.register_default_participants();
     let Ok(project) = pipeline.parse() else { return Err(pipeline.diagnostician) };
@@ -269,6 +277,7 @@ fn generate_to_string_internal<T: SourceContainer>(
         mutable_participants: Vec::default(),
         participants: Vec::default(),
         module_name: Some("<internal>".to_string()),
+        xml_targets: Default::default(),
     };
     pipeline.register_default_participants();
     let project = pipeline.parse()?;
@@ -5,7 +5,7 @@ use std::{
     fs::{self, File},
     io::Write,
     path::{Path, PathBuf},
-    sync::Mutex,
+    sync::{Arc, Mutex},
 };
 
 use crate::{
@@ -13,8 +13,10 @@ use crate::{
     get_project, CompileOptions, LinkOptions, LinkerScript,
 };
 use ast::{
-    ast::{pre_process, CompilationUnit, LinkageType},
+    ast::{pre_process, AstNode, AstStatement, CallStatement, CompilationUnit, LinkageType},
+    literals::AstLiteral,
     provider::IdProvider,
+    visitor::{AstVisitor, Walker},
 };
 
 use itertools::Itertools;
@@ -30,9 +32,10 @@ use plc::{
     output::FormatOption,
     parser::parse_file,
     resolver::{
-        const_evaluator::UnresolvableConstant, AnnotationMapImpl, AstAnnotations, Dependency, StringLiterals,
-        TypeAnnotator,
+        const_evaluator::UnresolvableConstant, AnnotationMap, AnnotationMapImpl, AstAnnotations, Dependency,
+        StatementAnnotation, StringLiterals, TypeAnnotator,
     },
+    typesystem::DataTypeInformation,
     validation::Validator,
     ConfigFormat, ErrorFormat, OnlineChange, Target, Threads,
 };
@@ -46,7 +49,11 @@ use plc_header_generator::{
 };
 use plc_index::GlobalContext;
 use plc_lowering::inheritance::InheritanceLowerer;
-use plc_xmlgen::xml_gen::{copy_xmlfile_to_output};
+use plc_xmlgen::{
+    source_comments::LoadedSources,
+    xml_gen::{copy_xmlfile_to_output, GenerationParameters, GenericCall},
+    xml_target::{TargetRegistry, XmlTarget},
+};
 use project::{
     object::Object,
     project::{LibraryInformation, Project},
@@ -70,6 +77,8 @@ pub struct BuildPipeline<T: SourceContainer> {
     pub mutable_participants: Vec<Box<dyn PipelineParticipantMut>>,
     pub participants: Vec<Box<dyn PipelineParticipant>>,
     pub module_name: Option<String>,
+    /// The targets available for XML generation, see [`BuildPipeline::register_xml_target`]
+    pub xml_targets: TargetRegistry,
 }
 
 pub trait Pipeline {
@@ -108,6 +117,9 @@ impl TryFrom<CompileParameters> for BuildPipeline<PathBuf> {
         let diagnostician = match compile_parameters.error_format {
             ErrorFormat::Rich => Diagnostician::default(),
             ErrorFormat::Clang => Diagnostician::clang_format_diagnostician(),
+            ErrorFormat::Sarif => {
+                Diagnostician::sarif_diagnostician(Some(PathBuf::from(&compile_parameters.sarif_output)))
+            }
             ErrorFormat::None => Diagnostician::null_diagnostician(),
         };
         let diagnostician = if let Some(configuration) = compile_parameters.get_error_configuration()? {
@@ -141,6 +153,7 @@ impl TryFrom<CompileParameters> for BuildPipeline<PathBuf> {
             mutable_participants: vec![],
             participants: vec![],
             module_name: None,
+            xml_targets: TargetRegistry::default(),
         })
     }
 }
@@ -163,10 +176,21 @@ impl<T: SourceContainer> BuildPipeline<T> {
     pub fn register_participant(&mut self, participant: Box<dyn PipelineParticipant>) {
         self.participants.push(participant)
     }
+
+    /// Makes a user defined XML target selectable through `--xml-target <name>`
+    pub fn register_xml_target(&mut self, target: Arc<dyn XmlTarget>) {
+        self.xml_targets.register(target)
+    }
     pub fn get_compile_options(&self) -> Option<CompileOptions> {
         self.compile_parameters.as_ref().map(|params| {
             let location = &self.project.get_location().map(|it| it.to_path_buf());
             let output_format = params.output_format().unwrap_or_else(|| self.project.get_output_format());
+            let generation = params.to_gen_parameters();
+            //the XML export and its side outputs read bodies and comments from the loaded sources instead of the files
+            let sources = match generation.target_names().is_empty() && !generation.writes_side_outputs() {
+                true => LoadedSources::default(),
+                false => LoadedSources::new(self.context.sources()),
+            };
 
             CompileOptions {
                 root: location.to_owned(),
@@ -177,7 +201,7 @@ impl<T: SourceContainer> BuildPipeline<T> {
                 error_format: params.error_format,
                 debug_level: params.debug_level(),
                 single_module: params.single_module,
-                generation: params.to_gen_parameters(),
+                generation: GenerationParameters { targets: self.xml_targets.clone(), sources, ..generation },
                 online_change: if params.online_change {
                     OnlineChange::Enabled {
                         file_name: params.got_layout_file.clone(),
@@ -221,6 +245,7 @@ impl<T: SourceContainer> BuildPipeline<T> {
                 build_location: params.get_build_location(),
                 linker_script,
                 module_name: self.get_module_name(),
+                xml_targets: params.to_gen_parameters().target_names().into_iter().map(String::from).collect(),
             }
         })
     }
@@ -460,6 +485,11 @@ impl<T: SourceContainer> Pipeline for BuildPipeline<T> {
         if let OnlineChange::Enabled { file_name, format } = &compile_options.online_change {
             write_got_layout(got_layout.into_inner().unwrap(), file_name, *format)?;
         }
+        let diagnostics: Vec<Diagnostic> =
+            self.participants.iter().flat_map(|participant| participant.take_diagnostics()).collect();
+        if self.diagnostician.handle(&diagnostics) == Severity::Error {
+            return Err(Diagnostic::new("Compilation aborted due to critical errors"));
+        }
         self.participants
             .iter()
             .map(|participant| participant.post_generate())
@@ -732,6 +762,40 @@ impl From<AnnotatedUnit> for CompilationUnit {
     }
 }
 
+/// Collects the calls of the project's own generic POUs, see [`AnnotatedProject::generic_calls`]
+struct GenericCallCollector<'a> {
+    index: &'a Index,
+    annotations: &'a AstAnnotations,
+    calls: Vec<GenericCall>,
+}
+
+impl AstVisitor for GenericCallCollector<'_> {
+    fn visit_call_statement(&mut self, stmt: &CallStatement, _node: &AstNode) {
+        if let Some(StatementAnnotation::Function {
+            qualified_name, generic_name: Some(generic), call_name, ..
+        }) = self.annotations.get(&stmt.operator)
+        {
+            let declared = self
+                .index
+                .find_pou(generic)
+                .is_some_and(|it| *it.get_linkage() == LinkageType::Internal);
+
+            // the resolver names an instantiation after the generic POU and its types, e.g. `MAX_OF__INT`
+            let instance = call_name.as_ref().unwrap_or(qualified_name);
+            let types = instance.strip_prefix(generic.as_str()).and_then(|it| it.strip_prefix("__"));
+
+            if let (true, Some(types)) = (declared, types) {
+                self.calls.push(GenericCall {
+                    generic: generic.clone(),
+                    types: types.split("__").map(String::from).collect(),
+                    location: stmt.operator.get_location(),
+                });
+            }
+        }
+        stmt.walk(self)
+    }
+}
+
 /// A project that has been annotated with information about different types and used units
 #[derive(Serialize, Deserialize)]
 #[serde(bound(deserialize = "'de: 'static"))]
@@ -742,6 +806,76 @@ pub struct AnnotatedProject {
 }
 
 impl AnnotatedProject {
+    /// The bounds of every array type with constant bounds like `ARRAY[0..MAX-1]` evaluated, for the XML export.
+    /// Arrays whose bounds could not be evaluated are left out
+    fn evaluated_array_dimensions(&self) -> Vec<(String, Vec<(i64, i64)>)> {
+        self.index
+            .get_types()
+            .values()
+            .filter_map(|it| match it.get_type_information() {
+                DataTypeInformation::Array { name, dimensions, .. } => {
+                    let ranges = dimensions
+                        .iter()
+                        .map(|it| it.get_range_inclusive(&self.index).ok().map(|range| (*range.start(), *range.end())))
+                        .collect::<Option<Vec<_>>>()?;
+                    Some((name.clone(), ranges))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// The literal values of initializers that are constant expressions like `2 * PI`, by the qualified name of the
+    /// global, POU variable or struct member, for the XML export. Initializers that could not be folded are left out
+    fn folded_initial_values(&self) -> Vec<(String, AstLiteral)> {
+        let constants = self.index.get_const_expressions();
+        let members = self.index.get_pou_types().values().chain(self.index.get_types().values()).flat_map(|it| it.get_members());
+
+        self.index
+            .get_globals()
+            .values()
+            .chain(members)
+            .filter_map(|it| {
+                match constants.get_resolved_constant_statement(it.initial_value.as_ref()?)?.get_stmt() {
+                    AstStatement::Literal(literal) => Some((it.get_qualified_name().to_string(), literal.clone())),
+                    _ => None,
+                }
+            })
+            .collect()
+    }
+
+    /// The names of all function blocks in the index, including those of the libraries, for the XML export
+    fn function_blocks(&self) -> Vec<String> {
+        self.index
+            .get_pous()
+            .values()
+            .filter(|it| it.is_function_block())
+            .map(|it| it.get_name().to_string())
+            .collect()
+    }
+
+    /// Every call of a generic POU declared in the project with the types it resolved to, for the XML export
+    fn generic_calls(&self) -> Vec<GenericCall> {
+        let mut collector =
+            GenericCallCollector { index: &self.index, annotations: &self.annotations, calls: Vec::new() };
+        for AnnotatedUnit { unit, .. } in &self.units {
+            collector.visit_compilation_unit(unit);
+        }
+        collector.calls
+    }
+
+    /// The given parameters of the XML export, filled in with what only the index and the annotations know, e.g. the
+    /// evaluated bounds of the array types
+    pub fn xml_generation(&self, generation: &GenerationParameters) -> GenerationParameters {
+        GenerationParameters {
+            array_dimensions: self.evaluated_array_dimensions(),
+            folded_initial_values: self.folded_initial_values(),
+            function_blocks: self.function_blocks(),
+            generic_calls: self.generic_calls(),
+            ..generation.clone()
+        }
+    }
+
     /// Validates the project, reports any new diagnostics on the fly
     pub fn validate(
         &self,
@@ -875,6 +1009,7 @@ impl AnnotatedProject {
         &'ctx self,
         compile_options: &CompileOptions,
         targets: &'ctx [Target],
+        diagnostician: &mut Diagnostician,
     ) -> Result<Vec<GeneratedProject>, Diagnostic> {
         let compile_directory = compile_options.build_location.clone().unwrap_or_else(|| {
             let tempdir = tempfile::tempdir().unwrap();
@@ -887,8 +1022,11 @@ impl AnnotatedProject {
             targets.iter().map(|target| self.generate_single_module(&context, compile_options, Some(target)));
         let mut result = vec![];
 
+        let generation = self.xml_generation(&compile_options.generation);
+
         for (target, module) in targets.iter().zip(modules) {
             let units = &self.units.iter().map(|current| &current.unit).collect();
+            let mut diagnostics = vec![];
             let obj: Object = module?
                 .unwrap()
                 .persist(
@@ -898,9 +1036,13 @@ impl AnnotatedProject {
                     target,
                     compile_options.optimization,
                     units,
-                    &compile_options.generation
+                    &generation,
+                    &mut diagnostics
                 )
                 .map(Into::into)?;
+            if diagnostician.handle(&diagnostics) == Severity::Error {
+                return Err(Diagnostic::new("Compilation aborted due to critical errors"));
+            }
 
             result.push(GeneratedProject { target: target.clone(), objects: vec![obj] });
         }
@@ -1091,7 +1233,7 @@ impl GeneratedProject {
                     FormatOption::XML => {
                         let paths: Vec<&Path> = self.objects.iter().map(|a| a.get_path()).collect();
 
-                        match copy_xmlfile_to_output(paths, output_location) {
+                        match copy_xmlfile_to_output(paths, output_location, &link_options.xml_targets) {
                             Ok(path) => Ok(path),
                             Err(error) => Err(Diagnostic::new(error.to_string())),
                         }
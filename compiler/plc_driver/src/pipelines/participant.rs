@@ -57,6 +57,11 @@ pub trait PipelineParticipant: Sync + Send {
     fn post_generate(&self) -> Result<(), Diagnostic> {
         Ok(())
     }
+    /// Implement this to report the diagnostics found while generating, e.g. warnings of the XML export
+    /// This happens after codegen, before `post_generate`
+    fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        vec![]
+    }
 }
 
 /// A Mutating Build particitpant for different steps in the pipeline
@@ -95,6 +100,7 @@ pub struct CodegenParticipant<T: SourceContainer> {
     pub compile_dirs: HashMap<Target, PathBuf>,
     pub objects: Arc<RwLock<GeneratedProject>>,
     pub libraries: Vec<LibraryInformation<T>>,
+    pub diagnostics: RwLock<Vec<Diagnostic>>,
 }
 
 impl<T: SourceContainer> CodegenParticipant<T> {
@@ -113,6 +119,26 @@ impl<T: SourceContainer> CodegenParticipant<T> {
         }
         Ok(())
     }
+
+    /// Exports the project as XML once, since every document covers all of its units. The document is written into the
+    /// compile directory and copied to the output when linking
+    fn export_xml(&self, annotated_project: &AnnotatedProject) -> Result<(), Diagnostic> {
+        let compile_directory = self.compile_dirs.get(&self.target).expect("Required dir");
+        let document_name = Path::new(&self.compile_options.output).with_extension("xml");
+        //a folder of its own, so the document is never the output it is copied to
+        let output = compile_directory.join("xml").join(document_name.file_name().unwrap_or_default());
+        fs::create_dir_all(compile_directory.join("xml"))?;
+
+        let units: Vec<&CompilationUnit> = annotated_project.units.iter().map(|current| &current.unit).collect();
+        let generation = annotated_project.xml_generation(&self.compile_options.generation);
+        let mut diagnostics = vec![];
+        let object: Object = GeneratedModule::persist_to_xml(output, &units, &generation, &mut diagnostics)?.into();
+
+        self.objects.write().expect("Failed to aquire read write lock").objects.push(object.with_target(&self.target));
+        self.diagnostics.write().expect("Failed to aquire read write lock").extend(diagnostics);
+        Ok(())
+    }
+
     pub fn read_got_layout(location: &str, format: ConfigFormat) -> Result<HashMap<String, u64>, Diagnostic> {
         let path = Path::new(location);
         if !path.is_file() {
@@ -133,7 +159,7 @@ impl<T: SourceContainer> CodegenParticipant<T> {
 }
 
 impl<T: SourceContainer + Send> PipelineParticipant for CodegenParticipant<T> {
-    fn pre_generate(&mut self, _annotated_project: &AnnotatedProject) -> Result<(), Diagnostic> {
+    fn pre_generate(&mut self, annotated_project: &AnnotatedProject) -> Result<(), Diagnostic> {
         self.ensure_compile_dirs()?;
 
         let got_layout =
@@ -143,10 +169,18 @@ impl<T: SourceContainer + Send> PipelineParticipant for CodegenParticipant<T> {
                 HashMap::default()
             };
         self.got_layout = Mutex::new(got_layout);
+
+        if self.compile_options.output_format == FormatOption::XML {
+            self.export_xml(annotated_project)?;
+        }
         Ok(())
     }
 
     fn generate(&self, module: &GeneratedModule, _annotated_project: &AnnotatedProject, _compile_options: &CompileOptions) -> Result<(), Diagnostic> {
+        if self.compile_options.output_format == FormatOption::XML {
+            return Ok(()); //the project was exported as a whole before its modules were generated
+        }
+
         let current_dir = env::current_dir()?;
         let current_dir = self.compile_options.root.as_deref().unwrap_or(&current_dir);
         let unit_location = module.get_unit_location();
@@ -174,6 +208,7 @@ impl<T: SourceContainer + Send> PipelineParticipant for CodegenParticipant<T> {
         let target = &self.target;
         let compile_directory = self.compile_dirs.get(target).expect("Required dir");
         let units: Vec<&CompilationUnit> = _annotated_project.units.iter().map(|current| &current.unit).collect();
+        let mut diagnostics = vec![];
 
         let object = module
             .persist(
@@ -183,11 +218,13 @@ impl<T: SourceContainer + Send> PipelineParticipant for CodegenParticipant<T> {
                 target,
                 self.compile_options.optimization,
                 &units,
-                &_compile_options.generation
+                &_compile_options.generation,
+                &mut diagnostics
             )
             .map(Into::into)
             .map(|it: Object| it.with_target(target))?;
         self.objects.write().expect("Failed to aquire read write lock").objects.push(object);
+        self.diagnostics.write().expect("Failed to aquire read write lock").extend(diagnostics);
         Ok(())
     }
 
@@ -214,6 +251,10 @@ impl<T: SourceContainer + Send> PipelineParticipant for CodegenParticipant<T> {
         }
         Ok(())
     }
+
+    fn take_diagnostics(&self) -> Vec<Diagnostic> {
+        std::mem::take(&mut self.diagnostics.write().expect("Failed to aquire read write lock"))
+    }
 }
 
 pub struct InitParticipant {
@@ -41,6 +41,7 @@ pub fn compile<T: Compilable>(codegen_context: &CodegenContext, source: T) -> Ge
         mutable_participants: Default::default(),
         participants: Default::default(),
         module_name: Some("<internal>".to_string()),
+        xml_targets: Default::default(),
     };
 
     pipeline.register_default_participants();
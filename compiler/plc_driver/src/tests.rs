@@ -163,6 +163,7 @@ where
         mutable_participants: Default::default(),
         participants: Default::default(),
         module_name: Some("<internal>".to_string()),
+        xml_targets: Default::default(),
     };
 
     pipeline.register_default_participants();
@@ -84,6 +84,11 @@ impl GlobalContext {
         self.sources.get(key)
     }
 
+    /// Returns every [`SourceCode`] read so far
+    pub fn sources(&self) -> impl Iterator<Item = &SourceCode> {
+        self.sources.values()
+    }
+
     /// Returns a cloned [`IdProvider`]
     pub fn provider(&self) -> IdProvider {
         self.provider.clone()
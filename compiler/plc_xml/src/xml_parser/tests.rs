@@ -231,7 +231,7 @@ fn return_connected_to_sink_parses() {
         Box::new(SConnector::id(4).with_name_str("s2").connect(3)),
         Box::new(SContinuation::id(5).with_name_str("s2")),
         Box::new(SReturn::id(6).with_execution_id(0).connect(5)),
-        Box::new(SInVariable::id(2).with_expression_str("x &lt; 0")), // TODO: The less-than symbol has to be written this way?
+        Box::new(SInVariable::id(2).with_expression_str("x < 0")),
         Box::new(SOutVariable::id(7).with_execution_id(1).with_expression_str("positiveOrZero").connect(8)),
         Box::new(SInVariable::id(8).with_expression_str("x")),
     ]);
@@ -0,0 +1,25 @@
+use std::{io::{Error, Seek, Write}, path::{Path, PathBuf}};
+
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+pub const ARCHIVE_EXTENSION: &str = "zip";
+pub const AUXILIARY_FOLDER: &str = "Auxiliary";
+
+/// The archive belonging to a generated document, e.g. `out.xml` is packaged into `out.zip`
+pub fn archive_path(document_path: &Path) -> PathBuf {
+    document_path.with_extension(ARCHIVE_EXTENSION)
+}
+
+/// Copies the file at `path` into the archive under the given entry name
+pub(crate) fn add_file_entry<W: Write + Seek>(zip: &mut ZipWriter<W>, entry: &str, path: &Path) -> Result<(), Error> {
+    zip.start_file(entry, SimpleFileOptions::default()).map_err(Error::other)?;
+    zip.write_all(&std::fs::read(path)?)?;
+    Ok(())
+}
+
+pub(crate) fn entry_name(path: &Path) -> Result<String, Error> {
+    match path.file_name() {
+        Some(name) => Ok(name.to_string_lossy().into_owned()),
+        None => Err(Error::new(std::io::ErrorKind::InvalidInput, format!("not a file: {}", path.display()))),
+    }
+}
@@ -0,0 +1,145 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::{
+    ast::{AstNode, CallStatement, CompilationUnit, LinkageType, PouType},
+    visitor::{AstVisitor, Walker},
+};
+
+use super::tag_list::declared_type_name;
+
+pub const CALL_GRAPH_EXTENSION: &str = "dot";
+
+/// A POU of the call graph
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallNode {
+    pub name: String,
+    pub kind: PouType,
+}
+
+/// A call from one POU to another. Calls through a function block instance carry the name of the instance
+#[derive(Debug, Clone, PartialEq)]
+pub struct CallEdge {
+    pub caller: String,
+    pub callee: String,
+    pub instance: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CallGraph {
+    pub nodes: Vec<CallNode>,
+    pub edges: Vec<CallEdge>,
+}
+
+/// The call graph belonging to a generated document, e.g. `out.xml` comes with `out.dot`
+pub fn call_graph_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(CALL_GRAPH_EXTENSION)
+}
+
+/// Collects the called operators of a body, in order of appearance
+#[derive(Default)]
+struct CallCollector {
+    operators: Vec<String>,
+}
+
+impl AstVisitor for CallCollector {
+    fn visit_call_statement(&mut self, stmt: &CallStatement, _node: &AstNode) {
+        if let Some(name) = stmt.operator.get_flat_reference_name() {
+            self.operators.push(String::from(name));
+        }
+        stmt.walk(self)
+    }
+}
+
+/// Builds the graph of programs, functions and function blocks. A POU declaring an instance of a function block
+/// points to the function block, calls to functions and programs within its body and actions point to the callee
+pub fn collect_call_graph(units: &Vec<&CompilationUnit>) -> CallGraph {
+    let mut graph = CallGraph::default();
+
+    let source_units: Vec<&&CompilationUnit> = units.iter()
+        .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+        .collect();
+
+    for current_unit in &source_units {
+        for current_pou in &current_unit.pous {
+            if current_pou.linkage != LinkageType::External && matches!(current_pou.kind, PouType::Program | PouType::Function | PouType::FunctionBlock) {
+                graph.nodes.push(CallNode { name: current_pou.name.clone(), kind: current_pou.kind.clone() });
+            }
+        }
+    }
+
+    let find_node = |graph: &CallGraph, name: &str| graph.nodes.iter().find(|it| it.name.eq_ignore_ascii_case(name)).cloned();
+
+    for current_unit in &source_units {
+        for current_pou in &current_unit.pous {
+            if find_node(&graph, &current_pou.name).is_none() {
+                continue;
+            }
+
+            //function block instances
+            let mut instances = Vec::new();
+            for current_variable in current_pou.variable_blocks.iter().flat_map(|it| &it.variables) {
+                let Some(type_name) = declared_type_name(&current_variable.data_type_declaration) else {
+                    continue;
+                };
+
+                if let Some(callee) = find_node(&graph, &type_name).filter(|it| it.kind == PouType::FunctionBlock) {
+                    instances.push(current_variable.name.clone());
+                    add_edge(&mut graph, CallEdge { caller: current_pou.name.clone(), callee: callee.name, instance: Some(current_variable.name.clone()) });
+                }
+            }
+
+            //calls within the body and the actions of the POU
+            let mut collector = CallCollector::default();
+            for current_impl in current_unit.implementations.iter().filter(|it| it.type_name.eq_ignore_ascii_case(&current_pou.name)) {
+                collector.visit_implementation(current_impl);
+            }
+
+            for operator in collector.operators {
+                if instances.iter().any(|it| it.eq_ignore_ascii_case(&operator)) {
+                    continue; //already connected through the instance
+                }
+
+                if let Some(callee) = find_node(&graph, &operator).filter(|it| it.kind != PouType::FunctionBlock) {
+                    add_edge(&mut graph, CallEdge { caller: current_pou.name.clone(), callee: callee.name, instance: None });
+                }
+            }
+        }
+    }
+    graph
+}
+
+fn add_edge(graph: &mut CallGraph, edge: CallEdge) {
+    if !graph.edges.contains(&edge) {
+        graph.edges.push(edge);
+    }
+}
+
+/// Programs are drawn as bold boxes, function blocks as components and functions as ellipses
+pub fn format_call_graph(graph: &CallGraph) -> String {
+    let mut output = String::from("digraph CallGraph {\n    rankdir=LR;\n");
+
+    for node in &graph.nodes {
+        let shape = match node.kind {
+            PouType::Program => "box, style=bold",
+            PouType::FunctionBlock => "component",
+            _ => "ellipse",
+        };
+        output.push_str(&format!("    {} [shape={shape}];\n", quote_id(&node.name)));
+    }
+
+    for edge in &graph.edges {
+        let label = edge.instance.as_ref().map(|it| format!(" [label={}]", quote_id(it))).unwrap_or_default();
+        output.push_str(&format!("    {} -> {}{label};\n", quote_id(&edge.caller), quote_id(&edge.callee)));
+    }
+    output.push_str("}\n");
+    output
+}
+
+fn quote_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\\\""))
+}
+
+/// Writes the call graph of all POUs as a Graphviz DOT file
+pub fn generate_call_graph(units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    fs::write(output_path, format_call_graph(&collect_call_graph(units)))
+}
@@ -0,0 +1,261 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::{
+    ast::{AstNode, AstStatement, Assignment, CallStatement, CompilationUnit, LinkageType, PouType, ReferenceAccess, ReferenceExpr, Variable, VariableBlockType},
+    visitor::AstVisitor,
+};
+use serde::Serialize;
+
+use super::sysmac_csv::format_csv_row;
+
+pub const CROSS_REFERENCE_HEADER: [&str; 5] = ["Variable", "Declared In", "POU", "Reads", "Writes"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CrossReferenceFormat {
+    Csv,
+    Json,
+}
+
+impl CrossReferenceFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            CrossReferenceFormat::Csv => "xref.csv",
+            CrossReferenceFormat::Json => "xref.json",
+        }
+    }
+}
+
+/// How often a POU reads and writes a variable
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CrossReference {
+    pub variable: String,
+    /// The POU declaring the variable, empty for globals
+    pub declared_in: String,
+    /// The POU or action accessing the variable
+    pub pou: String,
+    pub reads: u32,
+    pub writes: u32,
+}
+
+/// The cross-reference report belonging to a generated document, e.g. `out.xml` comes with `out.xref.csv`
+pub fn cross_reference_path(output_path: &Path, format: CrossReferenceFormat) -> PathBuf {
+    output_path.with_extension(format.extension())
+}
+
+/// Collects the variables referenced by a body, each with whether it is written. Members, array elements and
+/// bit accesses count as an access to the variable they belong to
+#[derive(Default)]
+struct AccessCollector {
+    writing: bool,
+    accesses: Vec<(String, bool)>,
+}
+
+impl AccessCollector {
+    fn visit_written(&mut self, node: &AstNode) {
+        let writing = std::mem::replace(&mut self.writing, true);
+        self.visit(node);
+        self.writing = writing;
+    }
+
+    fn visit_read(&mut self, node: &AstNode) {
+        let writing = std::mem::replace(&mut self.writing, false);
+        self.visit(node);
+        self.writing = writing;
+    }
+}
+
+impl AstVisitor for AccessCollector {
+    fn visit_assignment(&mut self, stmt: &Assignment, _node: &AstNode) {
+        self.visit_written(&stmt.left);
+        self.visit_read(&stmt.right);
+    }
+
+    fn visit_ref_assignment(&mut self, stmt: &Assignment, _node: &AstNode) {
+        self.visit_written(&stmt.left);
+        self.visit_read(&stmt.right);
+    }
+
+    fn visit_output_assignment(&mut self, stmt: &Assignment, _node: &AstNode) {
+        self.visit_written(&stmt.right); //the left side names the output of the callee
+    }
+
+    fn visit_call_statement(&mut self, stmt: &CallStatement, _node: &AstNode) {
+        self.visit_read(&stmt.operator);
+
+        let parameters = match stmt.parameters.as_deref().map(|it| &it.stmt) {
+            Some(AstStatement::ExpressionList(parameters)) => parameters.iter().collect(),
+            _ => stmt.parameters.as_deref().into_iter().collect::<Vec<_>>(),
+        };
+
+        for parameter in parameters {
+            match &parameter.stmt {
+                AstStatement::Assignment(assignment) => self.visit_read(&assignment.right), //the left side names the input of the callee
+                _ => self.visit(parameter),
+            }
+        }
+    }
+
+    fn visit_reference_expr(&mut self, stmt: &ReferenceExpr, _node: &AstNode) {
+        match (&stmt.access, &stmt.base) {
+            (ReferenceAccess::Member(member) | ReferenceAccess::Global(member), None) => {
+                if let Some(name) = member.get_flat_reference_name() {
+                    self.accesses.push((String::from(name), self.writing));
+                }
+            },
+            (ReferenceAccess::Index(index), _) => self.visit_read(index),
+            _ => (),
+        }
+
+        if let Some(base) = &stmt.base {
+            self.visit(base);
+        }
+    }
+}
+
+/// Counts the reads and writes of every global and POU variable per accessing POU
+pub fn collect_cross_references(units: &Vec<&CompilationUnit>) -> Vec<CrossReference> {
+    let source_units: Vec<&&CompilationUnit> = units.iter()
+        .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+        .collect();
+
+    let globals: Vec<&String> = source_units.iter()
+        .flat_map(|it| &it.global_vars)
+        .filter(|it| it.linkage != LinkageType::External)
+        .flat_map(|it| it.variables.iter().map(|it| &it.name))
+        .collect();
+
+    let mut references: Vec<CrossReference> = Vec::new();
+
+    for current_unit in &source_units {
+        for current_impl in &current_unit.implementations {
+            if current_impl.linkage == LinkageType::External || matches!(current_impl.pou_type, PouType::Init | PouType::ProjectInit) {
+                continue;
+            }
+
+            //actions access the variables of the POU they belong to
+            let owner = source_units.iter().flat_map(|it| &it.pous).find(|it| it.name.eq_ignore_ascii_case(&current_impl.type_name));
+
+            let mut collector = AccessCollector::default();
+            collector.visit_implementation(current_impl);
+
+            for (name, written) in collector.accesses {
+                let local = owner.and_then(|pou| {
+                    pou.variable_blocks.iter().flat_map(|it| &it.variables).find(|it| it.name.eq_ignore_ascii_case(&name)).map(|it| (it.name.clone(), pou.name.clone()))
+                });
+                let global = || globals.iter().find(|it| it.eq_ignore_ascii_case(&name)).map(|it| ((*it).clone(), String::new()));
+
+                let Some((variable, declared_in)) = local.or_else(global) else {
+                    continue; //POUs, enum values and the like
+                };
+
+                let position = references.iter().position(|it| it.variable == variable && it.declared_in == declared_in && it.pou == current_impl.name);
+                let reference = match position {
+                    Some(position) => &mut references[position],
+                    None => {
+                        references.push(CrossReference { variable, declared_in, pou: current_impl.name.clone(), reads: 0, writes: 0 });
+                        references.last_mut().expect("just pushed")
+                    }
+                };
+
+                if written {
+                    reference.writes += 1;
+                }
+
+                else {
+                    reference.reads += 1;
+                }
+            }
+        }
+    }
+
+    references.sort_by(|a, b| (&a.declared_in, a.variable.to_lowercase(), &a.pou).cmp(&(&b.declared_in, b.variable.to_lowercase(), &b.pou)));
+    references
+}
+
+/// The globals the exported POUs depend on: those used in their bodies and declarations, including `VAR_EXTERNAL`
+/// blocks, in user types, `VAR_ACCESS` paths and `VAR_CONFIG` entries, and in turn in the declarations of these globals
+pub fn collect_referenced_globals(units: &Vec<&CompilationUnit>) -> Vec<String> {
+    let source_units: Vec<&&CompilationUnit> = units.iter()
+        .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+        .collect();
+
+    let globals: Vec<&Variable> = source_units.iter()
+        .flat_map(|it| &it.global_vars)
+        .filter(|it| it.linkage != LinkageType::External)
+        .flat_map(|it| &it.variables)
+        .collect();
+
+    let mut collector = AccessCollector::default();
+
+    for current_unit in &source_units {
+        for current_pou in current_unit.pous.iter().filter(|it| it.linkage != LinkageType::External) {
+            collector.visit_pou(current_pou);
+
+            //an external stands for the global of the same name
+            let externals = current_pou.variable_blocks.iter().filter(|it| it.kind == VariableBlockType::External).flat_map(|it| &it.variables);
+            collector.accesses.extend(externals.map(|it| (it.name.clone(), false)));
+        }
+
+        for current_impl in current_unit.implementations.iter().filter(|it| it.linkage != LinkageType::External) {
+            collector.visit_implementation(current_impl);
+        }
+
+        for user_type in &current_unit.user_types {
+            collector.visit_user_type_declaration(user_type);
+        }
+
+        for access_variable in &current_unit.var_access {
+            collector.visit(&access_variable.path);
+        }
+
+        for config_variable in &current_unit.var_config {
+            collector.visit(&config_variable.reference);
+        }
+    }
+
+    let mut referenced: Vec<String> = Vec::new();
+    let mut pending = collector.accesses;
+
+    while let Some((name, _)) = pending.pop() {
+        if referenced.iter().any(|it| it.eq_ignore_ascii_case(&name)) {
+            continue;
+        }
+
+        let Some(global) = globals.iter().find(|it| it.name.eq_ignore_ascii_case(&name)) else {
+            continue; //locals, POUs, enum values and the like
+        };
+        referenced.push(global.name.clone());
+
+        //e.g. the constant sizing an array the global is declared as
+        let mut collector = AccessCollector::default();
+        collector.visit_variable(global);
+        pending.extend(collector.accesses);
+    }
+    referenced
+}
+
+pub fn format_cross_references(references: &[CrossReference], format: CrossReferenceFormat) -> Result<String, Error> {
+    match format {
+        CrossReferenceFormat::Csv => {
+            let mut output = format_csv_row(CROSS_REFERENCE_HEADER.iter().map(|it| String::from(*it)).collect());
+
+            for reference in references {
+                output.push_str(&format_csv_row(vec![
+                    reference.variable.clone(),
+                    reference.declared_in.clone(),
+                    reference.pou.clone(),
+                    reference.reads.to_string(),
+                    reference.writes.to_string(),
+                ]));
+            }
+            Ok(output)
+        },
+        CrossReferenceFormat::Json => serde_json::to_string_pretty(references).map_err(Error::other),
+    }
+}
+
+/// Writes the cross-reference report of all variables
+pub fn generate_cross_references(units: &Vec<&CompilationUnit>, output_path: &Path, format: CrossReferenceFormat) -> Result<(), Error> {
+    fs::write(output_path, format_cross_references(&collect_cross_references(units), format)?)
+}
@@ -0,0 +1,162 @@
+use std::{io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{CompilationUnit, LinkageType, NetworkPublishMode, VariableBlockType};
+use plc_source::source_location::CodeSpan;
+
+use super::serializer::*;
+use super::source_comments::LoadedSources;
+use super::tag_list::declared_type_name;
+use super::xml_gen::{variable_network_publish, write_xml_file};
+
+pub const EIP_TAGS_EXTENSION: &str = "eip.xml";
+
+/// The assembly instances a scanner connects to, consumed and produced as seen from the PLC
+pub const INPUT_ASSEMBLY_INSTANCE: u32 = 100;
+pub const OUTPUT_ASSEMBLY_INSTANCE: u32 = 150;
+
+/// The CIP data type code of structured types
+const CIP_STRUCT: u16 = 0xA0;
+
+/// A global published to the network
+#[derive(Debug, Clone, PartialEq)]
+pub struct EipTag {
+    pub name: String,
+    pub type_name: String,
+    pub mode: NetworkPublishMode,
+    pub cip_type: u16,
+    /// The size in bytes, `None` for structured types whose layout is not known here
+    pub size: Option<u32>,
+}
+
+/// The tag definitions belonging to a generated document, e.g. `out.xml` comes with `out.eip.xml`
+pub fn eip_tags_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(EIP_TAGS_EXTENSION)
+}
+
+/// Collects every global marked with the `network_publish` attribute, in declaration order
+pub fn collect_published_tags(sources: &LoadedSources, units: &Vec<&CompilationUnit>) -> Vec<EipTag> {
+    let mut tags = Vec::new();
+
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_global in &current_unit.global_vars {
+            if current_global.linkage == LinkageType::External || current_global.kind != VariableBlockType::Global {
+                continue; //same as the XML globals: only the users own globals
+            }
+
+            for current_variable in &current_global.variables {
+                if current_variable.location.span == CodeSpan::None {
+                    continue; //discard compiler interally generated variables
+                }
+
+                let mode = variable_network_publish(sources, current_global, current_variable);
+                if mode == NetworkPublishMode::DoNotPublish {
+                    continue;
+                }
+
+                let Some(type_name) = declared_type_name(&current_variable.data_type_declaration) else {
+                    continue;
+                };
+
+                let (cip_type, size) = cip_type(&type_name).unwrap_or((CIP_STRUCT, None));
+                tags.push(EipTag { name: current_variable.name.clone(), type_name, mode, cip_type, size });
+            }
+        }
+    }
+    tags
+}
+
+/// The CIP data type code and size in bytes of an elementary type
+fn cip_type(type_name: &str) -> Option<(u16, Option<u32>)> {
+    let upper = type_name.to_uppercase();
+
+    if upper == "STRING" || upper.starts_with("STRING[") {
+        let length = upper.strip_prefix("STRING[").and_then(|it| it.strip_suffix(']')).and_then(|it| it.parse::<u32>().ok()).unwrap_or(80);
+        return Some((0xD0, Some(length + 1))); //one byte per character plus the terminator
+    }
+
+    let cip_type = match upper.as_str() {
+        "BOOL" => (0xC1, 1),
+        "SINT" => (0xC2, 1),
+        "INT" => (0xC3, 2),
+        "DINT" => (0xC4, 4),
+        "LINT" => (0xC5, 8),
+        "USINT" => (0xC6, 1),
+        "UINT" => (0xC7, 2),
+        "UDINT" => (0xC8, 4),
+        "ULINT" => (0xC9, 8),
+        "REAL" => (0xCA, 4),
+        "LREAL" => (0xCB, 8),
+        "BYTE" => (0xD1, 1),
+        "WORD" => (0xD2, 2),
+        "DWORD" => (0xD3, 4),
+        "LWORD" => (0xD4, 8),
+        _ => {
+            return None
+        }
+    };
+    Some((cip_type.0, Some(cip_type.1)))
+}
+
+/// The tags and the two assemblies: `Input` tags are consumed from the scanner, `Output` tags produced to it.
+/// Members are packed in declaration order, structured types can't be laid out and are left out of the assemblies
+pub fn format_eip_tags(tags: &[EipTag]) -> Node {
+    let mut tag_list = SEipTags::new();
+    for tag in tags {
+        let access = if tag.mode == NetworkPublishMode::Output { "ReadOnly" } else { "ReadWrite" };
+
+        let mut element = SEipTag::new()
+            .attribute(String::from("name"), tag.name.clone())
+            .attribute(String::from("dataType"), tag.type_name.clone())
+            .attribute(String::from("cipType"), format!("0x{:02X}", tag.cip_type))
+            .attribute(String::from("publish"), tag.mode.to_string())
+            .attribute_str("access", access);
+
+        if let Some(size) = tag.size {
+            element = element.attribute_int("size", i64::from(size));
+        }
+        tag_list = tag_list.child(&element.close());
+    }
+
+    let assemblies = SEipAssemblies::new()
+        .child(&assembly(tags, "Input", INPUT_ASSEMBLY_INSTANCE, NetworkPublishMode::Input))
+        .child(&assembly(tags, "Output", OUTPUT_ASSEMBLY_INSTANCE, NetworkPublishMode::Output));
+
+    SEipRoot::new()
+        .attribute_str("producer", "rusty")
+        .attribute_str("version", env!("CARGO_PKG_VERSION"))
+        .child(&tag_list)
+        .child(&assemblies)
+        .inner()
+}
+
+fn assembly(tags: &[EipTag], name: &'static str, instance: u32, mode: NetworkPublishMode) -> SEipAssembly {
+    let mut assembly = SEipAssembly::new()
+        .attribute_str("name", name)
+        .attribute_int("instance", i64::from(instance));
+
+    let mut offset = 0;
+    for tag in tags.iter().filter(|it| it.mode == mode) {
+        let Some(size) = tag.size else {
+            continue;
+        };
+
+        assembly = assembly.child(&SEipMember::new()
+            .attribute(String::from("tag"), tag.name.clone())
+            .attribute_int("offset", i64::from(offset))
+            .attribute_int("size", i64::from(size))
+            .close());
+        offset += size;
+    }
+    assembly.attribute_int("size", i64::from(offset))
+}
+
+/// Writes the tag and assembly definitions of all published globals
+pub fn generate_eip_tags(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    write_xml_file(&output_path.to_path_buf(), format_eip_tags(&collect_published_tags(sources, units)))
+}
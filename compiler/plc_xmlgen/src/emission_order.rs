@@ -0,0 +1,98 @@
+use rustc_hash::FxHashSet;
+
+use super::serializer::*;
+
+/// The order the data types, interfaces and POUs of the document are written in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum EmissionOrder {
+    /// As declared, file by file in the order the files are compiled
+    #[default]
+    Source,
+
+    /// By name, ignoring case. Data types still come first, followed by the interfaces and then the POUs
+    Alphabetical,
+
+    /// Every declaration after the declarations it uses, otherwise as declared. Declarations using each other, e.g.
+    /// functions calling each other, are written in the order they are reached
+    Dependency,
+}
+
+/// Reorders the data types, interfaces and POUs of the populated tree
+pub fn order_declarations(output_root: &mut Node, order: EmissionOrder) {
+    let Some(namespace) = output_root.find_mut(GLOBAL_NAMESPACE_PATH) else {
+        return;
+    };
+
+    match order {
+        EmissionOrder::Source => (),
+        EmissionOrder::Alphabetical => namespace.children.sort_by_key(|it| (kind_rank(it), declaration_name(it).to_lowercase())),
+        EmissionOrder::Dependency => namespace.children = dependency_order(std::mem::take(&mut namespace.children)),
+    }
+}
+
+fn kind_rank(declaration: &Node) -> u8 {
+    match declaration.name.as_str() {
+        "DataTypeDecl" => 0,
+        "Interface" => 1,
+        _ => 2,
+    }
+}
+
+fn declaration_name(declaration: &Node) -> &str {
+    declaration.attributes.get("name").map(String::as_str).unwrap_or_default()
+}
+
+/// Sorts the declarations depth first, so each one follows the declarations it uses. A cycle is cut where it is
+/// entered again
+fn dependency_order(declarations: Vec<Node>) -> Vec<Node> {
+    let names: Vec<String> = declarations.iter().map(|it| declaration_name(it).to_lowercase()).collect();
+
+    let dependencies: Vec<Vec<usize>> = declarations.iter().enumerate()
+        .map(|(index, declaration)| {
+            let mut used = FxHashSet::default();
+            collect_used_names(declaration, &mut used);
+            names.iter().enumerate()
+                .filter(|(other, name)| *other != index && !name.is_empty() && used.contains(*name))
+                .map(|(other, _)| other)
+                .collect()
+        })
+        .collect();
+
+    let mut visited = vec![false; declarations.len()];
+    let mut order = Vec::with_capacity(declarations.len());
+    for index in 0..declarations.len() {
+        visit(index, &dependencies, &mut visited, &mut order);
+    }
+
+    let mut declarations: Vec<Option<Node>> = declarations.into_iter().map(Some).collect();
+    order.into_iter().filter_map(|it| declarations[it].take()).collect()
+}
+
+fn visit(index: usize, dependencies: &[Vec<usize>], visited: &mut [bool], order: &mut Vec<usize>) {
+    if visited[index] {
+        return;
+    }
+    visited[index] = true;
+
+    for dependency in &dependencies[index] {
+        visit(*dependency, dependencies, visited, order);
+    }
+    order.push(index);
+}
+
+/// The lower case names a declaration uses: the types of its members, variables and results, e.g. `Motor` in
+/// `ARRAY[0..3] OF Motor`, its base, the interfaces it implements and the identifiers of its bodies, e.g. called POUs
+fn collect_used_names(node: &Node, used: &mut FxHashSet<String>) {
+    match node.name.as_str() {
+        "TypeName" | "ST" => used.extend(node.content.iter().flat_map(|it| identifiers(it))),
+        "Extends" | "Implements" => used.extend(node.attributes.get("name").map(|it| it.to_lowercase())),
+        _ => (),
+    }
+    node.children.iter().for_each(|it| collect_used_names(it, used));
+}
+
+fn identifiers(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|it: char| !it.is_alphanumeric() && it != '_')
+        .filter(|it| !it.is_empty())
+        .map(|it| it.to_lowercase())
+}
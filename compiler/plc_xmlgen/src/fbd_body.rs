@@ -0,0 +1,297 @@
+use plc_ast::ast::{AstNode, AstStatement, Assignment, BinaryExpression, CallStatement, Operator, ReferenceAccess, ReferenceExpr, UnaryExpression};
+
+use super::serializer::*;
+use super::source_comments::{source_text, LoadedSources};
+
+/// The output of the blocks of operators and standard functions
+const BLOCK_OUTPUT: &str = "OUT";
+
+/// The height of a variable and of a row of block pins, which is also the margin between elements
+const GRID: i32 = 20;
+/// The width an element reserves per character of its label
+const CHARACTER_WIDTH: i32 = 8;
+const MIN_WIDTH: i32 = 80;
+/// The space between two columns, left for the connection lines
+const COLUMN_GAP: i32 = 40;
+
+/// The standard function block of an operator, with the inputs named `IN1`, `IN2` or `IN` for a single one
+fn operator_block(operator: &Operator) -> Option<&'static str> {
+    let block = match operator {
+        Operator::Plus => "ADD",
+        Operator::Minus => "SUB",
+        Operator::Multiplication => "MUL",
+        Operator::Division => "DIV",
+        Operator::Modulo => "MOD",
+        Operator::Exponentiation => "EXPT",
+        Operator::Equal => "EQ",
+        Operator::NotEqual => "NE",
+        Operator::Less => "LT",
+        Operator::Greater => "GT",
+        Operator::LessOrEqual => "LE",
+        Operator::GreaterOrEqual => "GE",
+        Operator::And => "AND",
+        Operator::Or => "OR",
+        Operator::Xor => "XOR",
+        Operator::Not => {
+            return None
+        }
+    };
+    Some(block)
+}
+
+/// An element of a network with the column and size it is laid out with
+struct Element {
+    node: Node,
+    local_id: i32,
+    /// The column of the element, the one after the columns of the elements feeding it
+    layer: usize,
+    /// The statement the element belongs to. The elements of a statement are laid out below those of the previous one
+    statement: usize,
+    width: i32,
+    height: i32,
+}
+
+/// Collects the elements of a network, numbering them and the order they are executed in
+struct NetworkBuilder<'a> {
+    /// The sources the labels of the variables are read from
+    sources: &'a LoadedSources,
+    elements: Vec<Element>,
+    statement: usize,
+    last_id: i32,
+    last_execution_id: i32,
+}
+
+impl NetworkBuilder<'_> {
+    fn next_id(&mut self) -> i32 {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    fn next_execution_id(&mut self) -> i32 {
+        self.last_execution_id += 1;
+        self.last_execution_id
+    }
+
+    fn push(&mut self, node: &dyn IntoNode, local_id: i32, layer: usize, width: i32, height: i32) {
+        self.elements.push(Element { node: node.inner(), local_id, layer, statement: self.statement, width, height });
+    }
+
+    /// The column of the element with the given id
+    fn layer(&self, local_id: i32) -> usize {
+        self.elements.iter().find(|it| it.local_id == local_id).map_or(0, |it| it.layer)
+    }
+
+    /// The column of an element fed by the given ones
+    fn next_layer(&self, sources: &[(i32, Option<&str>)]) -> usize {
+        sources.iter().map(|(local_id, _)| self.layer(*local_id) + 1).max().unwrap_or(0)
+    }
+
+    /// Adds the elements computing an expression. Returns the element producing its value, with the output it is
+    /// produced at for a block
+    fn add_expression(&mut self, expression: &AstNode) -> Option<(i32, Option<&'static str>)> {
+        match &expression.stmt {
+            AstStatement::ParenExpression(inner) => self.add_expression(inner),
+            AstStatement::Literal(_) | AstStatement::Identifier(_) | AstStatement::ReferenceExpr(_) => {
+                let local_id = self.next_id();
+                let expression = source_text(self.sources, &expression.location)?;
+                let width = label_width(&expression);
+                let variable = SInVariable::id(local_id)
+                    .child(&SConnectionPointOut::new().child(&rel_position(width, GRID / 2)))
+                    .with_expression(expression);
+                self.push(&variable, local_id, 0, width, GRID);
+                Some((local_id, None))
+            },
+            AstStatement::BinaryExpression(BinaryExpression { operator, left, right }) => {
+                let inputs = vec![("IN1", self.add_expression(left)?), ("IN2", self.add_expression(right)?)];
+                self.add_block(operator_block(operator)?, None, inputs)
+            },
+            AstStatement::UnaryExpression(UnaryExpression { operator, value }) => {
+                let block = match operator {
+                    Operator::Not => "NOT",
+                    Operator::Minus => "NEG",
+                    _ => {
+                        return None
+                    }
+                };
+                let input = self.add_expression(value)?;
+                self.add_block(block, None, vec![("IN", input)])
+            },
+            _ => None,
+        }
+    }
+
+    fn add_block(&mut self, type_name: &'static str, instance_name: Option<&str>, inputs: Vec<(&str, (i32, Option<&str>))>) -> Option<(i32, Option<&'static str>)> {
+        let local_id = self.next_id();
+        let (width, height) = block_size(type_name, inputs.len(), 1);
+        let layer = self.next_layer(&inputs.iter().map(|(_, source)| *source).collect::<Vec<_>>());
+        let block = SBlock::init(String::from(type_name), local_id, self.next_execution_id())
+            .with_input(inputs.into_iter().enumerate().map(|(index, (name, source))| input_variable(name, source, index)).collect())
+            .with_output(vec![output_variable(BLOCK_OUTPUT, width, 0)]);
+
+        self.push(&block.maybe_attribute(String::from("instanceName"), instance_name.map(String::from)), local_id, layer, width, height);
+        Some((local_id, Some(BLOCK_OUTPUT)))
+    }
+
+    /// Adds the `outVariable` writing the value produced by the given element to a variable
+    fn add_target(&mut self, target: &AstNode, (ref_local_id, formal_parameter): (i32, Option<&str>)) -> Option<()> {
+        let local_id = self.next_id();
+        let execution_id = self.next_execution_id();
+        let expression = source_text(self.sources, &target.location)?;
+        let width = label_width(&expression);
+        let variable = SOutVariable::id(local_id)
+            .with_execution_id(execution_id)
+            .child(&connection_point_in(GRID / 2, (ref_local_id, formal_parameter)))
+            .with_expression(expression);
+
+        let layer = self.layer(ref_local_id) + 1;
+        self.push(&variable, local_id, layer, width, GRID);
+        Some(())
+    }
+
+    fn add_statement(&mut self, statement: &AstNode, instance_type: &dyn Fn(&str) -> Option<String>) -> Option<()> {
+        match &statement.stmt {
+            AstStatement::EmptyStatement(_) => Some(()),
+            AstStatement::Assignment(Assignment { left, right }) if is_variable(left) => {
+                let source = self.add_expression(right)?;
+                self.add_target(left, source)
+            },
+            AstStatement::CallStatement(CallStatement { operator, parameters }) => {
+                let instance = unqualified_name(operator)?;
+                let type_name = instance_type(instance)?; //only calls of function block instances
+
+                let parameters = match parameters.as_deref().map(|it| &it.stmt) {
+                    Some(AstStatement::ExpressionList(parameters)) => parameters.iter().collect(),
+                    _ => parameters.as_deref().into_iter().collect::<Vec<_>>(),
+                };
+
+                let mut inputs = Vec::new();
+                let mut outputs = Vec::new();
+                for parameter in parameters {
+                    match &parameter.stmt {
+                        AstStatement::Assignment(Assignment { left, right }) => inputs.push((unqualified_name(left)?, self.add_expression(right)?)),
+                        AstStatement::OutputAssignment(Assignment { left, right }) if is_variable(right) => outputs.push((unqualified_name(left)?, right.as_ref())),
+                        _ => {
+                            return None //positional arguments name no parameter to connect
+                        }
+                    }
+                }
+
+                let local_id = self.next_id();
+                let (width, height) = block_size(&type_name, inputs.len(), outputs.len());
+                let layer = self.next_layer(&inputs.iter().map(|(_, source)| *source).collect::<Vec<_>>());
+                let block = SBlock::init(type_name, local_id, self.next_execution_id())
+                    .attribute(String::from("instanceName"), String::from(instance))
+                    .with_input(inputs.into_iter().enumerate().map(|(index, (name, source))| input_variable(name, source, index)).collect())
+                    .with_output(outputs.iter().enumerate().map(|(index, (name, _))| output_variable(name, width, index)).collect());
+                self.push(&block, local_id, layer, width, height);
+
+                for (name, target) in outputs {
+                    self.add_target(target, (local_id, Some(name)))?;
+                }
+                Some(())
+            },
+            _ => None,
+        }
+    }
+
+    /// Places the elements in columns from left to right, each fed by the columns before it. The elements of a column
+    /// are stacked from top to bottom, and the elements of each statement start below those of the previous one
+    fn laid_out(self) -> Vec<Box<dyn IntoNode>> {
+        let layers = self.elements.iter().map(|it| it.layer + 1).max().unwrap_or(0);
+        let mut columns = vec![GRID; layers];
+        for layer in 1..layers {
+            let previous_width = self.elements.iter().filter(|it| it.layer == layer - 1).map(|it| it.width).max().unwrap_or(0);
+            columns[layer] = columns[layer - 1] + previous_width + COLUMN_GAP;
+        }
+
+        let mut laid_out: Vec<Box<dyn IntoNode>> = Vec::with_capacity(self.elements.len());
+        let mut top = GRID;
+        for statement in self.elements.chunk_by(|a, b| a.statement == b.statement) {
+            let mut rows = vec![top; layers];
+
+            for element in statement {
+                let mut node = element.node.clone()
+                    .attribute_int("width", i64::from(element.width))
+                    .attribute_int("height", i64::from(element.height));
+                node.children.insert(0, position(columns[element.layer], rows[element.layer]).inner());
+                rows[element.layer] += element.height + GRID;
+                laid_out.push(Box::new(node));
+            }
+            top = rows.into_iter().max().unwrap_or(top);
+        }
+        laid_out
+    }
+}
+
+fn position(x: i32, y: i32) -> SPosition {
+    SPosition::new().attribute_int("x", i64::from(x)).attribute_int("y", i64::from(y)).close()
+}
+
+/// The position of a connection point relative to the element it belongs to
+fn rel_position(x: i32, y: i32) -> SRelPosition {
+    SRelPosition::new().attribute_int("x", i64::from(x)).attribute_int("y", i64::from(y)).close()
+}
+
+/// The width of a variable showing the given expression
+fn label_width(label: &str) -> i32 {
+    MIN_WIDTH.max(CHARACTER_WIDTH * label.chars().count() as i32 + GRID)
+}
+
+/// The width and height of a block with the given number of inputs and outputs, a row below its type name for each
+fn block_size(type_name: &str, inputs: usize, outputs: usize) -> (i32, i32) {
+    (label_width(type_name), GRID * (inputs.max(outputs) as i32 + 1))
+}
+
+/// The offset of the pin in the given row of a block from its top
+fn pin_offset(row: usize) -> i32 {
+    GRID + GRID / 2 + GRID * row as i32
+}
+
+/// The input connection at the given offset from the top of its element, connected to the element producing its value
+fn connection_point_in(offset: i32, (ref_local_id, formal_parameter): (i32, Option<&str>)) -> SConnectionPointIn {
+    let connection = SConnection::new()
+        .with_ref_id(ref_local_id)
+        .maybe_attribute(String::from("formalParameter"), formal_parameter.map(String::from))
+        .close();
+
+    SConnectionPointIn::new().child(&rel_position(0, offset)).child(&connection)
+}
+
+/// An input of a block in the given row, connected to the element producing its value
+fn input_variable(name: &str, source: (i32, Option<&str>), row: usize) -> Box<dyn IntoNode> {
+    Box::new(SVariable::new().with_name(String::from(name)).child(&connection_point_in(pin_offset(row), source)))
+}
+
+/// An output of a block of the given width in the given row
+fn output_variable(name: &str, width: i32, row: usize) -> Box<dyn IntoNode> {
+    Box::new(SVariable::new().with_name(String::from(name)).child(&SConnectionPointOut::new().child(&rel_position(width, pin_offset(row)))))
+}
+
+/// The name of a plain reference like `timer` or `IN`, none for qualified ones like `a.b`
+fn unqualified_name(node: &AstNode) -> Option<&str> {
+    match &node.stmt {
+        AstStatement::Identifier(name) => Some(name),
+        AstStatement::ReferenceExpr(ReferenceExpr { access: ReferenceAccess::Member(member), base: None }) => member.get_flat_reference_name(),
+        _ => None,
+    }
+}
+
+fn is_variable(node: &AstNode) -> bool {
+    matches!(node.stmt, AstStatement::Identifier(_) | AstStatement::ReferenceExpr(_))
+}
+
+/// Converts a body of assignments and function block calls into an FBD network, e.g. `sum := a + b;` into an `ADD`
+/// block fed by the input variables `a` and `b` and feeding the output variable `sum`, and `timer(IN := start,
+/// Q => done);` into a `TON` block of the instance `timer`. The instance type names the type of a called function
+/// block instance.
+///
+/// Returns `None` for bodies with any other statement, e.g. an `IF` or a function call, which only ST can express
+pub fn fbd_network(sources: &LoadedSources, statements: &[AstNode], instance_type: &dyn Fn(&str) -> Option<String>) -> Option<YFbd> {
+    let mut builder = NetworkBuilder { sources, elements: Vec::new(), statement: 0, last_id: 0, last_execution_id: 0 };
+
+    for (index, statement) in statements.iter().enumerate() {
+        builder.statement = index;
+        builder.add_statement(statement, instance_type)?;
+    }
+    Some(YFbd::new().children(builder.laid_out()))
+}
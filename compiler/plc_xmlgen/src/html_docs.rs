@@ -0,0 +1,212 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::CompilationUnit;
+
+use super::project_model::{collect_project_model, DataTypeModel, PouModel, ProjectModel, VariableModel};
+use super::source_comments::LoadedSources;
+
+pub const HTML_DOCS_EXTENSION: &str = "html";
+
+const STYLE: &str = "body{font-family:sans-serif;margin:2em;max-width:70em}\
+table{border-collapse:collapse;margin-bottom:1em}th,td{border:1px solid #ccc;padding:.2em .6em;text-align:left}\
+pre{background:#f6f8fa;padding:1em;overflow:auto}.kw{color:#0033b3;font-weight:bold}.cm{color:#8c8c8c;font-style:italic}\
+.st{color:#067d17}.nu{color:#1750eb}";
+
+const KEYWORDS: [&str; 44] = [
+    "IF", "THEN", "ELSIF", "ELSE", "END_IF", "CASE", "OF", "END_CASE", "FOR", "TO", "BY", "DO", "END_FOR", "WHILE",
+    "END_WHILE", "REPEAT", "UNTIL", "END_REPEAT", "EXIT", "CONTINUE", "RETURN", "AND", "OR", "XOR", "NOT", "MOD", "TRUE",
+    "FALSE", "VAR", "VAR_INPUT", "VAR_OUTPUT", "VAR_IN_OUT", "VAR_TEMP", "END_VAR", "PROGRAM", "END_PROGRAM",
+    "FUNCTION", "END_FUNCTION", "FUNCTION_BLOCK", "END_FUNCTION_BLOCK", "ACTION", "END_ACTION", "AT", "CONSTANT",
+];
+
+/// The documentation belonging to a generated document, e.g. `out.xml` comes with `out.html`
+pub fn html_docs_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(HTML_DOCS_EXTENSION)
+}
+
+/// A single self-contained page: an index of all user types and POUs, then one section each. Type names in the
+/// tables and POU names in the bodies link to their sections
+pub fn format_html_docs(model: &ProjectModel, title: &str) -> String {
+    let linker = Linker::new(model);
+    let title = escape_html(title);
+
+    let mut output = format!("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<title>{title}</title>\n<style>{STYLE}</style>\n</head>\n<body>\n<h1>{title}</h1>\n");
+
+    output.push_str("<nav id=\"index\">\n<h2>Index</h2>\n");
+    output.push_str(&index_list("Data types", model.data_types.iter().map(|it| &it.name)));
+    output.push_str(&index_list("POUs", model.pous.iter().map(|it| &it.name)));
+    if !model.globals.is_empty() {
+        output.push_str("<p><a href=\"#globals\">Globals</a></p>\n");
+    }
+    output.push_str("</nav>\n");
+
+    for data_type in &model.data_types {
+        output.push_str(&format_data_type(data_type, &linker));
+    }
+
+    for pou in &model.pous {
+        output.push_str(&format_pou(pou, &linker));
+    }
+
+    if !model.globals.is_empty() {
+        output.push_str("<section id=\"globals\">\n<h2>Globals</h2>\n");
+        output.push_str(&variable_table(&model.globals, false, &linker));
+        output.push_str("</section>\n");
+    }
+    output.push_str("</body>\n</html>\n");
+    output
+}
+
+/// Knows the anchors of all documented types and POUs
+struct Linker {
+    names: Vec<String>,
+}
+
+impl Linker {
+    fn new(model: &ProjectModel) -> Self {
+        let names = model.data_types.iter().map(|it| it.name.clone()).chain(model.pous.iter().map(|it| it.name.clone())).collect();
+        Linker { names }
+    }
+
+    /// The escaped name, linked if it is documented on this page
+    fn link(&self, name: &str) -> String {
+        match self.names.iter().find(|it| it.eq_ignore_ascii_case(name)) {
+            Some(target) => format!("<a href=\"#{}\">{}</a>", anchor(target), escape_html(name)),
+            None => escape_html(name),
+        }
+    }
+}
+
+fn anchor(name: &str) -> String {
+    format!("doc-{}", escape_html(&name.to_lowercase()))
+}
+
+fn index_list<'a>(heading: &str, names: impl Iterator<Item = &'a String>) -> String {
+    let items: Vec<String> = names.map(|it| format!("<li><a href=\"#{}\">{}</a></li>", anchor(it), escape_html(it))).collect();
+    if items.is_empty() {
+        return String::new();
+    }
+    format!("<h3>{heading}</h3>\n<ul>\n{}\n</ul>\n", items.join("\n"))
+}
+
+fn format_data_type(data_type: &DataTypeModel, linker: &Linker) -> String {
+    let mut output = format!("<section id=\"{}\">\n<h2>{}</h2>\n", anchor(&data_type.name), escape_html(&data_type.name));
+    push_documentation(&mut output, data_type.documentation.as_deref());
+
+    if let Some(base_type) = &data_type.base_type {
+        output.push_str(&format!("<p>Enumeration of <code>{}</code></p>\n<table>\n<tr><th>Value</th><th>Number</th></tr>\n", escape_html(base_type)));
+        for value in &data_type.values {
+            output.push_str(&format!("<tr><td>{}</td><td>{}</td></tr>\n", escape_html(&value.name), escape_html(&value.value)));
+        }
+        output.push_str("</table>\n");
+    }
+
+    else {
+        output.push_str("<p>Structure</p>\n");
+        output.push_str(&variable_table(&data_type.members, false, linker));
+    }
+    output.push_str("</section>\n");
+    output
+}
+
+fn format_pou(pou: &PouModel, linker: &Linker) -> String {
+    let mut output = format!("<section id=\"{}\">\n<h2>{}</h2>\n", anchor(&pou.name), escape_html(&pou.name));
+    push_documentation(&mut output, pou.documentation.as_deref());
+
+    match &pou.return_type {
+        Some(return_type) => output.push_str(&format!("<p>{} returning <code>{}</code></p>\n", pou.kind, linker.link(return_type))),
+        None => output.push_str(&format!("<p>{}</p>\n", pou.kind)),
+    }
+
+    if !pou.variables.is_empty() {
+        output.push_str(&variable_table(&pou.variables, true, linker));
+    }
+
+    if let Some(body) = &pou.body {
+        output.push_str(&format!("<pre><code>{}</code></pre>\n", highlight_st(body, linker)));
+    }
+    output.push_str("</section>\n");
+    output
+}
+
+fn push_documentation(output: &mut String, documentation: Option<&str>) {
+    if let Some(documentation) = documentation {
+        output.push_str(&format!("<p>{}</p>\n", escape_html(documentation).replace('\n', "<br>")));
+    }
+}
+
+fn variable_table(variables: &[VariableModel], with_scope: bool, linker: &Linker) -> String {
+    let scope_header = if with_scope { "<th>Scope</th>" } else { "" };
+    let mut output = format!("<table>\n<tr><th>Name</th>{scope_header}<th>Type</th><th>Initial value</th><th>Documentation</th></tr>\n");
+
+    for variable in variables {
+        let scope = if with_scope { format!("<td>{}</td>", escape_html(&variable.scope)) } else { String::new() };
+        output.push_str(&format!(
+            "<tr><td>{}</td>{scope}<td><code>{}</code></td><td>{}</td><td>{}</td></tr>\n",
+            escape_html(&variable.name),
+            linker.link(&variable.type_name),
+            escape_html(variable.initial_value.as_deref().unwrap_or_default()),
+            escape_html(variable.documentation.as_deref().unwrap_or_default()),
+        ));
+    }
+    output.push_str("</table>\n");
+    output
+}
+
+/// Wraps keywords, comments, strings and numbers of Structured Text into styled spans
+fn highlight_st(source: &str, linker: &Linker) -> String {
+    let mut output = String::new();
+    let mut rest = source;
+
+    while let Some(first) = rest.chars().next() {
+        let (token, class) = if rest.starts_with("//") {
+            (&rest[..rest.find('\n').unwrap_or(rest.len())], Some("cm"))
+        }
+
+        else if rest.starts_with("(*") {
+            (&rest[..rest.find("*)").map_or(rest.len(), |end| end + 2)], Some("cm"))
+        }
+
+        else if first == '\'' || first == '"' {
+            (&rest[..rest[1..].find(first).map_or(rest.len(), |end| end + 2)], Some("st"))
+        }
+
+        else if first.is_ascii_digit() {
+            (&rest[..rest.find(|it: char| !(it.is_ascii_alphanumeric() || it == '.' || it == '_' || it == '#')).unwrap_or(rest.len())], Some("nu"))
+        }
+
+        else if first.is_alphabetic() || first == '_' {
+            let word = &rest[..rest.find(|it: char| !(it.is_alphanumeric() || it == '_')).unwrap_or(rest.len())];
+            if KEYWORDS.contains(&word.to_uppercase().as_str()) {
+                (word, Some("kw"))
+            }
+
+            else {
+                output.push_str(&linker.link(word));
+                rest = &rest[word.len()..];
+                continue;
+            }
+        }
+
+        else {
+            (&rest[..first.len_utf8()], None)
+        };
+
+        match class {
+            Some(class) => output.push_str(&format!("<span class=\"{class}\">{}</span>", escape_html(token))),
+            None => output.push_str(&escape_html(token)),
+        }
+        rest = &rest[token.len()..];
+    }
+    output
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+/// Writes the documentation of all user types, POUs and globals as a single page, titled after the output file
+pub fn generate_html_docs(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    let title = output_path.file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
+    fs::write(output_path, format_html_docs(&collect_project_model(sources, units), &title))
+}
@@ -0,0 +1,241 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use super::serializer::Node;
+use super::source_comments::{block_comment, string_end};
+use super::sysmac_csv::format_csv_row;
+
+pub const RENAME_REPORT_EXTENSION: &str = "renames.csv";
+
+pub const RENAME_REPORT_HEADER: [&str; 2] = ["Identifier", "Exported As"];
+
+/// The elements whose `name` attribute declares an identifier
+const DECLARING_ELEMENTS: [&str; 7] = ["Variable", "Member", "Enumerator", "DataTypeDecl", "Program", "Function", "FunctionBlock"];
+
+/// What the target IDE accepts as an identifier
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct IdentifierRules {
+    /// Identifiers the target IDE reserves although they are legal in IEC 61131-3
+    pub reserved_words: &'static [&'static str],
+
+    /// The longest identifier in bytes, unlimited if not set
+    pub max_length: Option<usize>,
+
+    /// Whether identifiers may only consist of ASCII characters
+    pub ascii_only: bool,
+}
+
+impl IdentifierRules {
+    fn is_reserved(&self, name: &str) -> bool {
+        self.reserved_words.iter().any(|it| it.eq_ignore_ascii_case(name))
+    }
+
+    fn is_too_long(&self, name: &str) -> bool {
+        self.max_length.is_some_and(|it| name.len() > it)
+    }
+
+    fn is_invalid(&self, name: &str) -> bool {
+        self.is_reserved(name) || self.is_too_long(name) || (self.ascii_only && !name.is_ascii())
+    }
+
+    /// The candidate names for an identifier the target IDE does not accept: the name transliterated and shortened to
+    /// the maximum length, then the same with the suffixes `_1`, `_2` and so on, shortened so the suffix still fits
+    fn candidates(&self, name: &str) -> impl Iterator<Item = String> + '_ {
+        let name = match self.ascii_only {
+            true => transliterate(name),
+            false => String::from(name),
+        };
+        let shortened = (!self.is_reserved(&name)).then(|| String::from(truncate(&name, self.max_length.unwrap_or(usize::MAX))));
+
+        shortened.into_iter().chain((1..).map(move |suffix| {
+            let suffix = format!("_{suffix}");
+            let length = self.max_length.map_or(usize::MAX, |it| it.saturating_sub(suffix.len()));
+            format!("{}{suffix}", truncate(&name, length))
+        }))
+    }
+}
+
+/// Latin letters with diacritics and ligatures spelled in ASCII, the way they are written without them
+const TRANSLITERATIONS: [(char, &str); 52] = [
+    ('ä', "ae"), ('ö', "oe"), ('ü', "ue"), ('Ä', "Ae"), ('Ö', "Oe"), ('Ü', "Ue"), ('ß', "ss"), ('æ', "ae"), ('Æ', "Ae"),
+    ('œ', "oe"), ('Œ', "Oe"), ('ø', "o"), ('Ø', "O"), ('å', "a"), ('Å', "A"), ('à', "a"), ('á', "a"), ('â', "a"),
+    ('ã', "a"), ('À', "A"), ('Á', "A"), ('Â', "A"), ('Ã', "A"), ('ç', "c"), ('Ç', "C"), ('è', "e"), ('é', "e"),
+    ('ê', "e"), ('ë', "e"), ('È', "E"), ('É', "E"), ('Ê', "E"), ('Ë', "E"), ('ì', "i"), ('í', "i"), ('î', "i"),
+    ('ï', "i"), ('Í', "I"), ('ñ', "n"), ('Ñ', "N"), ('ò', "o"), ('ó', "o"), ('ô', "o"), ('õ', "o"), ('Ó', "O"),
+    ('ù', "u"), ('ú', "u"), ('û', "u"), ('Ú', "U"), ('ý', "y"), ('ÿ', "y"), ('Ý', "Y"),
+];
+
+/// The identifier in ASCII: letters with diacritics without them, e.g. `Größe` as `Groesse`, and every other character
+/// as its code point, e.g. `温度` as `U6E29U5EA6`
+pub fn transliterate(name: &str) -> String {
+    name.chars().map(|it| {
+        if it.is_ascii() {
+            return it.to_string();
+        }
+
+        match TRANSLITERATIONS.iter().find(|(letter, _)| *letter == it) {
+            Some((_, spelling)) => String::from(*spelling),
+            None => format!("U{:04X}", it as u32),
+        }
+    }).collect()
+}
+
+/// The longest prefix of the name with at most the given number of bytes
+fn truncate(name: &str, length: usize) -> &str {
+    let end = (0..=length.min(name.len())).rev().find(|it| name.is_char_boundary(*it)).unwrap_or(0);
+    &name[..end]
+}
+
+/// An identifier the target IDE does not accept and the name it is exported with instead
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rename {
+    pub original: String,
+    pub renamed: String,
+}
+
+/// The report of renamed identifiers belonging to a generated document, e.g. `out.xml` comes with `out.renames.csv`
+pub fn rename_report_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(RENAME_REPORT_EXTENSION)
+}
+
+/// Renames every declared identifier the target IDE does not accept, i.e. reserved words, names which are too long
+/// and, if only ASCII is allowed, names with other characters, along with all its uses in types, initial values,
+/// instance paths and bodies. The new names are chosen in declaration order from the candidates of [`IdentifierRules`],
+/// so the same project is always exported with the same names and no two identifiers end up with the same one
+pub fn rename_invalid_identifiers(output_root: &mut Node, rules: &IdentifierRules) -> Vec<Rename> {
+    if *rules == IdentifierRules::default() {
+        return Vec::new();
+    }
+
+    let mut declared_names = Vec::new();
+    collect_declared_names(output_root, &mut declared_names);
+
+    let renames = plan_renames(&declared_names, rules);
+    if !renames.is_empty() {
+        apply_renames(output_root, &renames);
+    }
+    renames
+}
+
+/// The new names of the declared names the target IDE does not accept, see [`rename_invalid_identifiers`]
+pub fn plan_renames(declared_names: &[String], rules: &IdentifierRules) -> Vec<Rename> {
+    let is_taken = |name: &str, renames: &[Rename]| {
+        declared_names.iter().any(|it| it.eq_ignore_ascii_case(name))
+            || rules.is_reserved(name)
+            || renames.iter().any(|it| it.renamed.eq_ignore_ascii_case(name))
+    };
+
+    let mut renames: Vec<Rename> = Vec::new();
+    for name in declared_names {
+        if !rules.is_invalid(name) || renames.iter().any(|it| it.original.eq_ignore_ascii_case(name)) {
+            continue;
+        }
+
+        let renamed = rules.candidates(name).find(|it| !is_taken(it, &renames)).expect("a free suffix");
+        renames.push(Rename { original: name.clone(), renamed });
+    }
+    renames
+}
+
+/// The names of the variables, members, enumerators, data types and POUs declared in the tree, in document order
+pub fn collect_declared_names(node: &Node, declared_names: &mut Vec<String>) {
+    if DECLARING_ELEMENTS.contains(&node.name.as_str()) && let Some(name) = node.attributes.get("name") && !declared_names.contains(name) {
+        declared_names.push(name.clone());
+    }
+
+    for child in &node.children {
+        collect_declared_names(child, declared_names);
+    }
+}
+
+/// Renames the declared identifiers and all their uses within the tree
+pub fn apply_renames(node: &mut Node, renames: &[Rename]) {
+    let renamed = |name: &str| renames.iter().find(|it| it.original.eq_ignore_ascii_case(name)).map(|it| it.renamed.clone());
+
+    for (key, value) in node.attributes.iter_mut() {
+        match key.as_str() {
+            "name" | "member" | "alias" => {
+                if let Some(new_value) = renamed(value) {
+                    *value = new_value;
+                }
+            },
+            "instancePathAndName" => {
+                let segments: Vec<String> = value.split('.').map(|it| renamed(it).unwrap_or_else(|| String::from(it))).collect();
+                *value = segments.join(".");
+            },
+            "value" if node.name == "SimpleValue" => *value = rename_identifiers(value, renames), //e.g. enum variants
+            _ => (),
+        }
+    }
+
+    if let Some(content) = &mut node.content {
+        match node.name.as_str() {
+            "TypeName" => {
+                if let Some(new_content) = renamed(content) {
+                    *content = new_content;
+                }
+            },
+            "ST" => *content = rename_identifiers(content, renames),
+            _ => (),
+        }
+    }
+
+    for child in &mut node.children {
+        apply_renames(child, renames);
+    }
+}
+
+/// Renames the identifiers of Structured Text, leaving comments, strings and numeric literals as they are
+pub fn rename_identifiers(text: &str, renames: &[Rename]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(first) = rest.chars().next() {
+        let length = if rest.starts_with("//") {
+            rest.find('\n').unwrap_or(rest.len())
+        }
+
+        else if rest.starts_with("(*") || rest.starts_with("/*") {
+            block_comment(rest, 0).1 //nested comments end with the outermost one
+        }
+
+        else if first == '\'' || first == '"' {
+            string_end(rest.as_bytes(), 0, first as u8) //`$'` does not end the string
+        }
+
+        else if first.is_ascii_digit() {
+            rest.find(|it: char| !(it.is_ascii_alphanumeric() || it == '.' || it == '_' || it == '#')).unwrap_or(rest.len())
+        }
+
+        else if first.is_alphabetic() || first == '_' {
+            let word = &rest[..rest.find(|it: char| !(it.is_alphanumeric() || it == '_')).unwrap_or(rest.len())];
+            match renames.iter().find(|it| it.original.eq_ignore_ascii_case(word)) {
+                Some(rename) => output.push_str(&rename.renamed),
+                None => output.push_str(word),
+            }
+            rest = &rest[word.len()..];
+            continue;
+        }
+
+        else {
+            first.len_utf8()
+        };
+
+        output.push_str(&rest[..length]);
+        rest = &rest[length..];
+    }
+    output
+}
+
+pub fn format_rename_report(renames: &[Rename]) -> String {
+    let mut output = format_csv_row(RENAME_REPORT_HEADER.iter().map(|it| String::from(*it)).collect());
+
+    for rename in renames {
+        output.push_str(&format_csv_row(vec![rename.original.clone(), rename.renamed.clone()]));
+    }
+    output
+}
+
+/// Writes which identifiers were exported under another name
+pub fn write_rename_report(renames: &[Rename], output_path: &Path) -> Result<(), Error> {
+    fs::write(output_path, format_rename_report(renames))
+}
@@ -0,0 +1,137 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{CompilationUnit, LinkageType, NetworkPublishMode, VariableBlockType};
+use plc_source::source_location::CodeSpan;
+use serde::Serialize;
+
+use super::source_comments::LoadedSources;
+use super::tag_list::declared_type_name;
+use super::xml_gen::variable_network_publish;
+use super::xml_target::opcua::UA_NODE_ID_PREFIX;
+
+pub const IGNITION_TAGS_EXTENSION: &str = "tags.json";
+
+/// The OPC UA server built into Ignition, used unless the connection to the PLC is named otherwise
+pub const DEFAULT_IGNITION_OPC_SERVER: &str = "Ignition OPC UA Server";
+
+/// A node of an Ignition tag export: the provider root, a folder or an OPC tag
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct IgnitionTag {
+    pub name: String,
+    pub tag_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub value_source: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data_type: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opc_server: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opc_item_path: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub read_only: bool,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<IgnitionTag>,
+}
+
+impl IgnitionTag {
+    fn folder(name: String, tag_type: &str) -> Self {
+        IgnitionTag {
+            name,
+            tag_type: String::from(tag_type),
+            value_source: None,
+            data_type: None,
+            opc_server: None,
+            opc_item_path: None,
+            read_only: false,
+            tags: Vec::new(),
+        }
+    }
+}
+
+/// The tag export belonging to a generated document, e.g. `out.xml` comes with `out.tags.json`
+pub fn ignition_tags_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(IGNITION_TAGS_EXTENSION)
+}
+
+/// One folder per source file holding its published globals. The OPC item paths address the variables of the
+/// `opcua` target, e.g. `ns=1;s=plant.gSpeed`
+pub fn collect_ignition_tags(sources: &LoadedSources, units: &Vec<&CompilationUnit>, opc_server: &str) -> IgnitionTag {
+    let mut provider = IgnitionTag::folder(String::new(), "Provider");
+
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        let folder_name = Path::new(unit_name).file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
+        let mut folder = IgnitionTag::folder(folder_name.clone(), "Folder");
+
+        for current_global in &current_unit.global_vars {
+            if current_global.linkage == LinkageType::External || current_global.kind != VariableBlockType::Global {
+                continue; //same as the XML globals: only the users own globals
+            }
+
+            for current_variable in &current_global.variables {
+                if current_variable.location.span == CodeSpan::None {
+                    continue; //discard compiler interally generated variables
+                }
+
+                let mode = variable_network_publish(sources, current_global, current_variable);
+                if mode == NetworkPublishMode::DoNotPublish {
+                    continue;
+                }
+
+                let Some(data_type) = declared_type_name(&current_variable.data_type_declaration).and_then(|it| ignition_data_type(&it)) else {
+                    continue; //instances of user types would need matching UDT definitions in Ignition
+                };
+
+                folder.tags.push(IgnitionTag {
+                    value_source: Some(String::from("opc")),
+                    data_type: Some(String::from(data_type)),
+                    opc_server: Some(String::from(opc_server)),
+                    opc_item_path: Some(format!("{UA_NODE_ID_PREFIX}{folder_name}.{}", current_variable.name)),
+                    read_only: current_global.constant || mode == NetworkPublishMode::Output, //outputs are produced by the PLC
+                    ..IgnitionTag::folder(current_variable.name.clone(), "AtomicTag")
+                });
+            }
+        }
+
+        if !folder.tags.is_empty() {
+            provider.tags.push(folder);
+        }
+    }
+    provider
+}
+
+/// The Ignition data type of an elementary type
+fn ignition_data_type(type_name: &str) -> Option<&'static str> {
+    let upper = type_name.to_uppercase();
+
+    if upper.starts_with("STRING") || upper.starts_with("WSTRING") {
+        return Some("String");
+    }
+
+    let data_type = match upper.as_str() {
+        "BOOL" => "Boolean",
+        "SINT" | "USINT" | "BYTE" => "Int1",
+        "INT" | "UINT" | "WORD" => "Int2",
+        "DINT" | "UDINT" | "DWORD" => "Int4",
+        "LINT" | "ULINT" | "LWORD" | "TIME" | "LTIME" => "Int8",
+        "REAL" => "Float4",
+        "LREAL" => "Float8",
+        "DATE" | "DATE_AND_TIME" | "DT" => "DateTime",
+        _ => {
+            return None
+        }
+    };
+    Some(data_type)
+}
+
+/// Writes the published globals as an Ignition tag export
+pub fn generate_ignition_tags(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path, opc_server: &str) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(&collect_ignition_tags(sources, units, opc_server)).map_err(Error::other)?;
+    fs::write(output_path, json)
+}
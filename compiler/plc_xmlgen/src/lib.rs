@@ -1,4 +1,29 @@
 
 pub mod xml_gen;
 pub mod serializer;
+pub mod xml_target;
+pub mod archive;
+pub mod library;
+pub mod sysmac_csv;
+pub mod source_comments;
+pub mod tag_list;
+pub mod modbus_map;
+pub mod eip_tags;
+pub mod project_model;
+pub mod yaml;
+pub mod call_graph;
+pub mod markdown_docs;
+pub mod html_docs;
+pub mod ignition_tags;
+pub mod cross_reference;
+pub mod literal_format;
+pub mod identifier_rules;
+pub mod split_output;
+pub mod emission_order;
+pub mod fbd_body;
+pub mod sfc_body;
+pub mod st_printer;
+pub mod source_map;
+pub mod streaming;
+pub mod node_diff;
 pub mod tests;
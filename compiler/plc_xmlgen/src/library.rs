@@ -0,0 +1,110 @@
+use std::{fs::File, io::{BufReader, Error}, path::{Path, PathBuf}};
+
+use xml::reader::{EventReader, XmlEvent};
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use super::archive::{add_file_entry, archive_path, entry_name, AUXILIARY_FOLDER};
+use super::serializer::*;
+use super::xml_gen::write_xml_document;
+
+pub const PROJECT_INFORMATION_NAME: &str = "projectinformation.xml";
+
+/// The library properties written into the project information of a packaged document
+#[derive(Debug, Clone)]
+pub struct LibraryProperties {
+    /// Defaults to the file name of the generated document
+    pub title: Option<String>,
+    pub version: String,
+    pub company: String,
+
+    /// The namespace the library's POUs and DUTs are referenced with. Defaults to the title
+    pub namespace: Option<String>,
+}
+
+impl Default for LibraryProperties {
+    fn default() -> Self {
+        LibraryProperties { title: None, version: String::from("1.0.0.0"), company: String::new(), namespace: None }
+    }
+}
+
+/// Packages a generated document into a zip archive next to it, together with the library properties and the POUs and
+/// DUTs it contains.
+///
+/// This is no CODESYS `.library`, which only CODESYS itself can write, and no IDE installs or imports the archive. The
+/// document inside follows the IEC 61131-10 schema of the target, not PLCopen TC6, so CODESYS does not import it as
+/// PLCopenXML either. The archive has the following layout:
+/// ```text
+/// out.zip
+/// ├── projectinformation.xml   the library properties and the POUs and DUTs it contains
+/// ├── out.xml                  the generated document
+/// └── Auxiliary/...            the auxiliary files, flattened to their file names
+/// ```
+pub fn package_library(document_path: &Path, properties: &LibraryProperties, auxiliary_files: &[PathBuf]) -> Result<PathBuf, Error> {
+    let output_path = archive_path(document_path);
+    let document_name = entry_name(document_path)?;
+    let title = match &properties.title {
+        Some(title) => title.clone(),
+        None => document_path.file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default(),
+    };
+    let namespace = properties.namespace.clone().unwrap_or_else(|| title.clone());
+
+    let mut contents = SLibraryContents::new().attribute(String::from("document"), document_name.clone());
+    for (kind, name) in read_library_objects(document_path)? {
+        contents = contents.child(&SLibraryObject::new()
+            .attribute(String::from("kind"), String::from(kind))
+            .attribute(String::from("name"), name)
+            .close());
+    }
+
+    let information = SProjectInformation::new()
+        .child(&library_property("Title", title))
+        .child(&library_property("Version", properties.version.clone()))
+        .child(&library_property("Company", properties.company.clone()))
+        .child(&library_property("DefaultNamespace", namespace))
+        .child(&contents);
+
+    let mut zip = ZipWriter::new(File::create(&output_path)?);
+    add_file_entry(&mut zip, &document_name, document_path)?;
+
+    for auxiliary_file in auxiliary_files {
+        let auxiliary_name = format!("{AUXILIARY_FOLDER}/{}", entry_name(auxiliary_file)?);
+        add_file_entry(&mut zip, &auxiliary_name, auxiliary_file)?;
+    }
+
+    zip.start_file(PROJECT_INFORMATION_NAME, SimpleFileOptions::default()).map_err(Error::other)?;
+    write_xml_document(&mut zip, &[], &SerializerOptions::default(), information.inner())?;
+    zip.finish().map_err(Error::other)?;
+    Ok(output_path)
+}
+
+fn library_property(name: &'static str, value: String) -> SProperty {
+    SProperty::new()
+        .attribute_str("name", name)
+        .attribute(String::from("value"), value)
+        .close()
+}
+
+/// Lists the POUs and DUTs of a generated document as `(kind, name)` in document order
+fn read_library_objects(document_path: &Path) -> Result<Vec<(&'static str, String)>, Error> {
+    let reader = EventReader::new(BufReader::new(File::open(document_path)?));
+    let mut objects = Vec::new();
+
+    for event in reader {
+        let XmlEvent::StartElement { name, attributes, .. } = event.map_err(Error::other)? else {
+            continue;
+        };
+
+        let kind = match name.local_name.as_str() {
+            "Program" => "program",
+            "Function" => "function",
+            "FunctionBlock" => "functionBlock",
+            "DataTypeDecl" => "dataType",
+            _ => continue,
+        };
+
+        if let Some(object_name) = attributes.into_iter().find(|it| it.name.local_name == "name") {
+            objects.push((kind, object_name.value));
+        }
+    }
+    Ok(objects)
+}
@@ -0,0 +1,196 @@
+use plc_ast::ast::{DirectAccessType, HardwareAccess, HardwareAccessType};
+use plc_ast::literals::{AstLiteral, StringValue, Time};
+
+/// The units of a duration with their length in nanoseconds, largest first
+const TIME_UNITS: [(i64, &str); 7] = [
+    (86_400_000_000_000, "d"),
+    (3_600_000_000_000, "h"),
+    (60_000_000_000, "m"),
+    (1_000_000_000, "s"),
+    (1_000_000, "ms"),
+    (1_000, "us"),
+    (1, "ns"),
+];
+
+/// How REAL and LREAL literals are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RealNotation {
+    /// `1500.0`
+    #[default]
+    Decimal,
+
+    /// `1.5E3`
+    Scientific,
+}
+
+/// The way REAL and LREAL literals like initial values are written, instead of as they are spelled in the source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct RealFormat {
+    /// The number of significant digits to round to. All digits of the value are written if not set
+    pub digits: Option<usize>,
+
+    pub notation: RealNotation,
+}
+
+/// The spelling of boolean literals
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoolCase {
+    /// `TRUE` and `FALSE`, as in IEC 61131-3
+    Upper,
+
+    /// `true` and `false`, as in XML schema
+    Lower,
+}
+
+/// Literals in the IEC 61131-3 short form, e.g. `T#1s500ms`, `D#2024-01-05`, `TOD#12:30:15` and
+/// `DT#2024-01-05-12:30:15`. Reals are written in the given format, all other literals as parsed
+pub fn format_iec_literal(literal: &AstLiteral, real_format: &RealFormat) -> String {
+    match literal {
+        AstLiteral::Real(value) => format_real(value, real_format),
+        AstLiteral::Bool(value) => format_bool(*value, BoolCase::Upper),
+        AstLiteral::Time(time) => format!("T#{}", format_time(time, "")),
+        AstLiteral::Date(date) => format!("D#{}", format_date(date.year(), date.month(), date.day())),
+        AstLiteral::TimeOfDay(tod) => format!("TOD#{}", format_clock(tod.hour(), tod.min(), tod.sec(), tod.nano())),
+        AstLiteral::DateAndTime(dt) => format!(
+            "DT#{}-{}",
+            format_date(dt.year(), dt.month(), dt.day()),
+            format_clock(dt.hour(), dt.min(), dt.sec(), dt.nano())
+        ),
+        AstLiteral::String(string) => format_string(string),
+        _ => literal.to_string(),
+    }
+}
+
+/// A string in the IEC 61131-3 syntax, e.g. `'it$'s 5$$'` or `"line$N"`. The delimiter, `$` and control characters are
+/// escaped, as are characters outside of ASCII: by their UTF-8 bytes in a `STRING`, e.g. `'$C3$A9'`, and by their UTF-16
+/// units in a `WSTRING`, e.g. `"$00E9"`. Parsing the result gives the same value again
+pub fn format_string(string: &StringValue) -> String {
+    let delimiter = if string.is_wide { '"' } else { '\'' };
+    let mut escaped = String::new();
+
+    for character in string.value.chars() {
+        match character {
+            '$' => escaped.push_str("$$"),
+            '\n' => escaped.push_str("$N"),
+            '\r' => escaped.push_str("$R"),
+            '\t' => escaped.push_str("$T"),
+            '\x0C' => escaped.push_str("$P"),
+            it if it == delimiter => escaped.push_str(&format!("${it}")),
+            it if it.is_ascii() && !it.is_ascii_control() => escaped.push(it),
+            it if string.is_wide => {
+                for unit in it.encode_utf16(&mut [0; 2]) {
+                    escaped.push_str(&format!("${unit:04X}"));
+                }
+            },
+            it => {
+                for byte in it.encode_utf8(&mut [0; 4]).bytes() {
+                    escaped.push_str(&format!("${byte:02X}"));
+                }
+            },
+        }
+    }
+    format!("{delimiter}{escaped}{delimiter}")
+}
+
+/// The non-zero components of a duration, largest unit first, e.g. `[(1, "s"), (500, "ms")]` for 1.5 seconds
+pub fn time_components(time: &Time) -> Vec<(i64, &'static str)> {
+    let mut rest = time.value().abs();
+    let mut components = Vec::new();
+
+    for (length, unit) in TIME_UNITS {
+        if rest >= length {
+            components.push((rest / length, unit));
+            rest %= length;
+        }
+    }
+    components
+}
+
+/// The components of a duration joined by the given separator, e.g. `1s500ms` or `1s_500ms`
+pub fn format_time(time: &Time, separator: &str) -> String {
+    let components: Vec<String> = time_components(time).iter().map(|(value, unit)| format!("{value}{unit}")).collect();
+
+    if components.is_empty() {
+        return String::from("0s");
+    }
+
+    let sign = if time.value() < 0 { "-" } else { "" };
+    format!("{sign}{}", components.join(separator))
+}
+
+pub fn format_date(year: i32, month: u32, day: u32) -> String {
+    format!("{year:04}-{month:02}-{day:02}")
+}
+
+/// `12:30:15`, with the fraction of a second only if there is one, e.g. `12:30:15.25`
+pub fn format_clock(hour: u32, min: u32, sec: u32, nano: u32) -> String {
+    let clock = format!("{hour:02}:{min:02}:{sec:02}");
+
+    if nano == 0 {
+        return clock;
+    }
+
+    let fraction = format!("{nano:09}");
+    format!("{clock}.{}", fraction.trim_end_matches('0'))
+}
+
+pub fn format_bool(value: bool, case: BoolCase) -> String {
+    match (value, case) {
+        (true, BoolCase::Upper) => String::from("TRUE"),
+        (false, BoolCase::Upper) => String::from("FALSE"),
+        (true, BoolCase::Lower) => String::from("true"),
+        (false, BoolCase::Lower) => String::from("false"),
+    }
+}
+
+/// A real as spelled in the source, e.g. `1_000.5` or `1e3`, in the given format. Written as spelled if it is no number
+pub fn format_real(value: &str, real_format: &RealFormat) -> String {
+    let Ok(mut number) = value.replace('_', "").parse::<f64>() else {
+        return String::from(value);
+    };
+
+    if let Some(digits) = real_format.digits.filter(|it| *it > 0) {
+        number = format!("{number:.*e}", digits - 1).parse().unwrap_or(number);
+    }
+
+    match real_format.notation {
+        RealNotation::Decimal => {
+            let decimal = number.to_string();
+            match decimal.contains('.') || !number.is_finite() {
+                true => decimal,
+                false => format!("{decimal}.0"), //keep it a real, e.g. `1000.0` instead of `1000`
+            }
+        },
+        RealNotation::Scientific => {
+            let scientific = format!("{number:E}");
+            match scientific.split_once('E') {
+                Some((mantissa, exponent)) if !mantissa.contains('.') => format!("{mantissa}.0E{exponent}"),
+                _ => scientific,
+            }
+        },
+    }
+}
+
+/// A located address in the IEC notation, e.g. `%IX1.2` or `%MW10`
+pub fn format_iec_address(hardware_access: &HardwareAccess) -> String {
+    let direction = match hardware_access.direction {
+        HardwareAccessType::Input => "I",
+        HardwareAccessType::Output => "Q",
+        HardwareAccessType::Memory => "M",
+        HardwareAccessType::Global => "G",
+    };
+
+    let access = match hardware_access.access {
+        DirectAccessType::Bit => "X",
+        DirectAccessType::Byte => "B",
+        DirectAccessType::Word => "W",
+        DirectAccessType::DWord => "D",
+        DirectAccessType::LWord => "L",
+        DirectAccessType::Template => "*",
+    };
+
+    let components: Vec<String> = hardware_access.address.iter()
+        .map(|it| it.get_literal_integer_value().map(|it| it.to_string()).unwrap_or_default())
+        .collect();
+    format!("%{direction}{access}{}", components.join("."))
+}
@@ -0,0 +1,129 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::CompilationUnit;
+
+use super::project_model::{collect_project_model, DataTypeModel, PouModel, ProjectModel, VariableModel};
+use super::source_comments::LoadedSources;
+
+pub const MARKDOWN_DOCS_EXTENSION: &str = "md";
+
+/// The scopes making up the interface of a POU, all other variables are listed separately
+const INTERFACE_SCOPES: [&str; 3] = ["input", "output", "inout"];
+
+/// The documentation belonging to a generated document, e.g. `out.xml` comes with `out.md`
+pub fn markdown_docs_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(MARKDOWN_DOCS_EXTENSION)
+}
+
+/// Renders one section per user type and POU, followed by the globals
+pub fn format_markdown_docs(model: &ProjectModel, title: &str) -> String {
+    let mut output = format!("# {title}\n");
+
+    if !model.data_types.is_empty() {
+        output.push_str("\n## Data types\n");
+        for data_type in &model.data_types {
+            output.push_str(&format_data_type(data_type));
+        }
+    }
+
+    if !model.pous.is_empty() {
+        output.push_str("\n## POUs\n");
+        for pou in &model.pous {
+            output.push_str(&format_pou(pou));
+        }
+    }
+
+    if !model.globals.is_empty() {
+        output.push_str("\n## Globals\n\n");
+        output.push_str(&variable_table(&model.globals, false));
+    }
+    output
+}
+
+fn format_data_type(data_type: &DataTypeModel) -> String {
+    let mut output = format!("\n### {}\n\n", data_type.name);
+    push_documentation(&mut output, data_type.documentation.as_deref());
+
+    if let Some(base_type) = &data_type.base_type {
+        output.push_str(&format!("Enumeration of `{base_type}`\n\n"));
+        output.push_str("| Value | Number |\n| --- | --- |\n");
+        for value in &data_type.values {
+            output.push_str(&table_row(&[&value.name, &value.value]));
+        }
+    }
+
+    else {
+        output.push_str("Structure\n\n");
+        output.push_str(&variable_table(&data_type.members, false));
+    }
+    output
+}
+
+fn format_pou(pou: &PouModel) -> String {
+    let mut output = format!("\n### {}\n\n", pou.name);
+    push_documentation(&mut output, pou.documentation.as_deref());
+
+    match &pou.return_type {
+        Some(return_type) => output.push_str(&format!("{} returning `{return_type}`\n", pou.kind)),
+        None => output.push_str(&format!("{}\n", pou.kind)),
+    }
+
+    let (interface, variables): (Vec<VariableModel>, Vec<VariableModel>) = pou.variables.iter()
+        .cloned()
+        .partition(|it| INTERFACE_SCOPES.contains(&it.scope.as_str()));
+
+    if !interface.is_empty() {
+        output.push_str("\n#### Interface\n\n");
+        output.push_str(&variable_table(&interface, true));
+    }
+
+    if !variables.is_empty() {
+        output.push_str("\n#### Variables\n\n");
+        output.push_str(&variable_table(&variables, true));
+    }
+    output
+}
+
+fn push_documentation(output: &mut String, documentation: Option<&str>) {
+    if let Some(documentation) = documentation {
+        output.push_str(documentation);
+        output.push_str("\n\n");
+    }
+}
+
+fn variable_table(variables: &[VariableModel], with_scope: bool) -> String {
+    let mut output = if with_scope {
+        String::from("| Name | Scope | Type | Initial value | Documentation |\n| --- | --- | --- | --- | --- |\n")
+    }
+
+    else {
+        String::from("| Name | Type | Initial value | Documentation |\n| --- | --- | --- | --- |\n")
+    };
+
+    for variable in variables {
+        let type_name = format!("`{}`", variable.type_name);
+        let initial_value = variable.initial_value.clone().unwrap_or_default();
+        let documentation = variable.documentation.clone().unwrap_or_default();
+
+        if with_scope {
+            output.push_str(&table_row(&[&variable.name, &variable.scope, &type_name, &initial_value, &documentation]));
+        }
+
+        else {
+            output.push_str(&table_row(&[&variable.name, &type_name, &initial_value, &documentation]));
+        }
+    }
+    output
+}
+
+/// Pipes would end the cell early, line breaks the row
+fn table_row(cells: &[&str]) -> String {
+    let escaped: Vec<String> = cells.iter().map(|it| it.replace('|', "\\|").replace('\n', " ")).collect();
+    format!("| {} |\n", escaped.join(" | "))
+}
+
+/// Writes the documentation of all user types, POUs and globals, titled after the output file
+pub fn generate_markdown_docs(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    let title = output_path.file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
+    fs::write(output_path, format_markdown_docs(&collect_project_model(sources, units), &title))
+}
@@ -0,0 +1,246 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{AstStatement, CompilationUnit, DirectAccessType, HardwareAccess, HardwareAccessType, LinkageType, PouType, VariableBlock};
+use plc_source::source_location::CodeSpan;
+use serde::Serialize;
+
+use super::literal_format::format_iec_address;
+use super::source_comments::{trailing_comment, LoadedSources};
+use super::sysmac_csv::format_csv_row;
+use super::tag_list::declared_type_name;
+
+pub const MODBUS_MAP_HEADER: [&str; 10] = ["Name", "POU", "Address", "Table", "Register", "Bit", "Data Type", "Length", "Scale", "Offset"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModbusMapFormat {
+    Csv,
+    Json,
+}
+
+impl ModbusMapFormat {
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ModbusMapFormat::Csv => "modbus.csv",
+            ModbusMapFormat::Json => "modbus.json",
+        }
+    }
+}
+
+/// The Modbus table a located variable is exposed in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ModbusTable {
+    Coil,
+    DiscreteInput,
+    InputRegister,
+    HoldingRegister,
+}
+
+impl ModbusTable {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ModbusTable::Coil => "coil",
+            ModbusTable::DiscreteInput => "discreteInput",
+            ModbusTable::InputRegister => "inputRegister",
+            ModbusTable::HoldingRegister => "holdingRegister",
+        }
+    }
+}
+
+/// A located variable as a Modbus master has to address it
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ModbusRegister {
+    pub name: String,
+    /// The POU declaring the variable, empty for globals
+    pub pou: String,
+    /// The IEC address, e.g. `%MW10`
+    pub address: String,
+    pub table: ModbusTable,
+    /// The register, or the bit number for coils and discrete inputs
+    pub register: u32,
+    /// The bit within the register, for single bits located in registers
+    pub bit: Option<u32>,
+    pub data_type: String,
+    /// The number of registers the variable occupies
+    pub length: u32,
+    pub scale: f64,
+    pub offset: f64,
+}
+
+/// The register map belonging to a generated document, e.g. `out.xml` comes with `out.modbus.csv`
+pub fn modbus_map_path(output_path: &Path, format: ModbusMapFormat) -> PathBuf {
+    output_path.with_extension(format.extension())
+}
+
+/// Collects every variable located in the input, output or memory area, in declaration order
+pub fn collect_modbus_registers(sources: &LoadedSources, units: &Vec<&CompilationUnit>) -> Vec<ModbusRegister> {
+    let mut registers = Vec::new();
+
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_global in &current_unit.global_vars {
+            if current_global.linkage != LinkageType::External {
+                collect_block_registers(sources, current_global, "", &mut registers);
+            }
+        }
+
+        for current_pou in &current_unit.pous {
+            if current_pou.linkage == LinkageType::External || !matches!(current_pou.kind, PouType::Program | PouType::Function | PouType::FunctionBlock) {
+                continue; //same POUs as the XML generation
+            }
+
+            for current_block in &current_pou.variable_blocks {
+                collect_block_registers(sources, current_block, &current_pou.name, &mut registers);
+            }
+        }
+    }
+    registers
+}
+
+fn collect_block_registers(sources: &LoadedSources, block: &VariableBlock, pou_name: &str, registers: &mut Vec<ModbusRegister>) {
+    for current_variable in &block.variables {
+        if current_variable.location.span == CodeSpan::None {
+            continue; //discard compiler interally generated variables
+        }
+
+        let Some(AstStatement::HardwareAccess(hardware_access)) = current_variable.address.as_ref().map(|it| &it.stmt) else {
+            continue; //only located variables are mapped
+        };
+
+        let Some((table, register, bit)) = locate_register(hardware_access) else {
+            continue;
+        };
+
+        let data_type = declared_type_name(&current_variable.data_type_declaration).unwrap_or_default();
+        let length = register_length(&data_type).unwrap_or(access_length(hardware_access.access));
+        let (scale, offset) = trailing_comment(sources, &current_variable.location).map(|it| parse_scaling(&it)).unwrap_or((1.0, 0.0));
+
+        registers.push(ModbusRegister {
+            name: current_variable.name.clone(),
+            pou: String::from(pou_name),
+            address: format_iec_address(hardware_access),
+            table,
+            register,
+            bit,
+            data_type,
+            length,
+            scale,
+            offset,
+        });
+    }
+}
+
+/// Maps an IEC address onto the Modbus tables: bits of the input and output area are discrete inputs and coils,
+/// addressed by `byte * 8 + bit`. Everything else lives in 16 bit registers, wider accesses being counted in their
+/// own unit, e.g. `%MD5` starts at register 10
+pub fn locate_register(hardware_access: &HardwareAccess) -> Option<(ModbusTable, u32, Option<u32>)> {
+    let mut components = hardware_access.address.iter().map(|it| it.get_literal_integer_value().and_then(|it| u32::try_from(it).ok()));
+    let index = components.next()??;
+    let bit = components.next().flatten().unwrap_or(0);
+
+    let register_table = match hardware_access.direction {
+        HardwareAccessType::Input => ModbusTable::InputRegister,
+        HardwareAccessType::Output | HardwareAccessType::Memory => ModbusTable::HoldingRegister,
+        HardwareAccessType::Global => {
+            return None
+        }
+    };
+
+    let located = match (hardware_access.direction, hardware_access.access) {
+        (HardwareAccessType::Input, DirectAccessType::Bit) => (ModbusTable::DiscreteInput, index * 8 + bit, None),
+        (HardwareAccessType::Output, DirectAccessType::Bit) => (ModbusTable::Coil, index * 8 + bit, None),
+        (_, DirectAccessType::Bit) => (register_table, index / 2, Some((index % 2) * 8 + bit)),
+        (_, DirectAccessType::Byte) => (register_table, index / 2, None),
+        (_, DirectAccessType::Word) => (register_table, index, None),
+        (_, DirectAccessType::DWord) => (register_table, index * 2, None),
+        (_, DirectAccessType::LWord) => (register_table, index * 4, None),
+        (_, DirectAccessType::Template) => {
+            return None //the location of %I* is only known once it is configured
+        }
+    };
+    Some(located)
+}
+
+/// The number of 16 bit registers occupied by an elementary type
+fn register_length(type_name: &str) -> Option<u32> {
+    let upper = type_name.to_uppercase();
+
+    if let Some(size) = upper.strip_prefix("STRING[").and_then(|it| it.strip_suffix(']')).and_then(|it| it.parse::<u32>().ok()) {
+        return Some((size + 2) / 2); //one byte per character plus the terminator
+    }
+
+    if let Some(size) = upper.strip_prefix("WSTRING[").and_then(|it| it.strip_suffix(']')).and_then(|it| it.parse::<u32>().ok()) {
+        return Some(size + 1);
+    }
+
+    let length = match upper.as_str() {
+        "BOOL" | "BYTE" | "SINT" | "USINT" | "CHAR" | "INT" | "UINT" | "WORD" | "WCHAR" => 1,
+        "DINT" | "UDINT" | "DWORD" | "REAL" => 2,
+        "LINT" | "ULINT" | "LWORD" | "LREAL" | "TIME" | "LTIME" | "DATE" | "TIME_OF_DAY" | "TOD" | "DATE_AND_TIME" | "DT" => 4,
+        _ => {
+            return None
+        }
+    };
+    Some(length)
+}
+
+fn access_length(access: DirectAccessType) -> u32 {
+    match access {
+        DirectAccessType::DWord => 2,
+        DirectAccessType::LWord => 4,
+        _ => 1,
+    }
+}
+
+/// Reads `scale=<factor>` and `offset=<value>` from the documentation of a variable, e.g.
+/// `temperature AT %IW3 : INT; // scale=0.1 offset=-40`. Defaults to the unscaled raw value
+pub fn parse_scaling(documentation: &str) -> (f64, f64) {
+    let mut scaling = (1.0, 0.0);
+
+    for token in documentation.split(|it: char| it.is_whitespace() || it == ',' || it == ';') {
+        if let Some(scale) = token.strip_prefix("scale=").and_then(|it| it.parse::<f64>().ok()) {
+            scaling.0 = scale;
+        }
+
+        else if let Some(offset) = token.strip_prefix("offset=").and_then(|it| it.parse::<f64>().ok()) {
+            scaling.1 = offset;
+        }
+    }
+    scaling
+}
+
+pub fn format_modbus_map(registers: &[ModbusRegister], format: ModbusMapFormat) -> Result<String, Error> {
+    match format {
+        ModbusMapFormat::Csv => {
+            let mut output = format_csv_row(MODBUS_MAP_HEADER.iter().map(|it| String::from(*it)).collect());
+
+            for register in registers {
+                output.push_str(&format_csv_row(vec![
+                    register.name.clone(),
+                    register.pou.clone(),
+                    register.address.clone(),
+                    String::from(register.table.name()),
+                    register.register.to_string(),
+                    register.bit.map(|it| it.to_string()).unwrap_or_default(),
+                    register.data_type.clone(),
+                    register.length.to_string(),
+                    register.scale.to_string(),
+                    register.offset.to_string(),
+                ]));
+            }
+            Ok(output)
+        },
+        ModbusMapFormat::Json => serde_json::to_string_pretty(registers).map_err(Error::other),
+    }
+}
+
+/// Writes the register map of all located variables
+pub fn generate_modbus_map(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path, format: ModbusMapFormat) -> Result<(), Error> {
+    fs::write(output_path, format_modbus_map(&collect_modbus_registers(sources, units), format)?)
+}
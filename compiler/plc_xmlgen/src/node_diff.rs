@@ -0,0 +1,145 @@
+use super::serializer::{Node, NodeKind};
+
+/// A difference between two trees. The path of an element names every level from the root the way [`Node::find_all`]
+/// takes it, e.g. `Project/Types/GlobalNamespace/DataTypeDecl[@name='Motor']`. Siblings which can't be told apart this
+/// way are numbered from 1 in the order they appear, e.g. `interface/localVars[2]`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NodeChange {
+    ElementAdded { path: String },
+    ElementRemoved { path: String },
+    AttributeAdded { path: String, key: String, value: String },
+    AttributeRemoved { path: String, key: String, value: String },
+    AttributeChanged { path: String, key: String, old: String, new: String },
+    ContentChanged { path: String, old: Option<String>, new: Option<String> },
+}
+
+impl std::fmt::Display for NodeChange {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            NodeChange::ElementAdded { path } => write!(f, "+ {path}"),
+            NodeChange::ElementRemoved { path } => write!(f, "- {path}"),
+            NodeChange::AttributeAdded { path, key, value } => write!(f, "+ {path} @{key}='{value}'"),
+            NodeChange::AttributeRemoved { path, key, value } => write!(f, "- {path} @{key}='{value}'"),
+            NodeChange::AttributeChanged { path, key, old, new } => write!(f, "~ {path} @{key}: '{old}' -> '{new}'"),
+            NodeChange::ContentChanged { path, old, new } => {
+                let quoted = |content: &Option<String>| content.as_ref().map_or(String::from("none"), |it| format!("'{it}'"));
+                write!(f, "~ {path} content: {} -> {}", quoted(old), quoted(new))
+            },
+        }
+    }
+}
+
+/// The elements and attributes which were added, removed or changed from the old to the new tree, in the order of the
+/// old tree with the children added to an element after its other changes. Children are matched by their name and
+/// `name` attribute, so a declaration which moved among its siblings is not a change, and children which share both by
+/// the order they appear in. The elements below an added or removed element are not listed on their own, and comments
+/// are not compared
+pub fn diff_nodes(old: &Node, new: &Node) -> Vec<NodeChange> {
+    let mut changes = Vec::new();
+    match old.name == new.name {
+        true => diff_elements(old, new, &old.name, &mut changes),
+        false => {
+            changes.push(NodeChange::ElementRemoved { path: old.name.clone() });
+            changes.push(NodeChange::ElementAdded { path: new.name.clone() });
+        },
+    }
+    changes
+}
+
+/// The changes one per line, or a note that there are none
+pub fn diff_report(changes: &[NodeChange]) -> String {
+    if changes.is_empty() {
+        return String::from("no changes\n");
+    }
+    changes.iter().map(|it| format!("{it}\n")).collect()
+}
+
+fn diff_elements(old: &Node, new: &Node, path: &str, changes: &mut Vec<NodeChange>) {
+    let mut keys: Vec<&String> = old.attributes.keys().chain(new.attributes.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    for key in keys {
+        let (path, key) = (path.to_string(), key.clone());
+        let change = match (old.attributes.get(&key), new.attributes.get(&key)) {
+            (Some(old), Some(new)) if old != new => NodeChange::AttributeChanged { path, key, old: old.clone(), new: new.clone() },
+            (Some(value), None) => NodeChange::AttributeRemoved { path, key, value: value.clone() },
+            (None, Some(value)) => NodeChange::AttributeAdded { path, key, value: value.clone() },
+            _ => continue,
+        };
+        changes.push(change);
+    }
+
+    let (old_text, new_text) = (text(old), text(new));
+    if old_text != new_text {
+        changes.push(NodeChange::ContentChanged { path: path.to_string(), old: old_text, new: new_text });
+    }
+
+    let old_children = identify(&old.children);
+    let mut new_children: Vec<Option<(ChildIdentity, &Node)>> = identify(&new.children).into_iter().map(Some).collect();
+
+    for (identity, child) in &old_children {
+        let child_path = format!("{path}/{}", identity.step(&old_children));
+        let counterpart = new_children.iter_mut().find(|it| it.as_ref().is_some_and(|(new_identity, _)| new_identity == identity));
+
+        match counterpart.and_then(Option::take) {
+            Some((_, counterpart)) => diff_elements(child, counterpart, &child_path, changes),
+            None => changes.push(NodeChange::ElementRemoved { path: child_path }),
+        }
+    }
+
+    let new_identities = identify(&new.children);
+    for (identity, _) in new_children.into_iter().flatten() {
+        changes.push(NodeChange::ElementAdded { path: format!("{path}/{}", identity.step(&new_identities)) });
+    }
+}
+
+/// The content of the element followed by the text among its children, if it has any
+fn text(node: &Node) -> Option<String> {
+    let text: Vec<&str> = node.content.iter()
+        .chain(node.children.iter().filter(|it| it.kind == NodeKind::Text).filter_map(|it| it.content.as_ref()))
+        .map(String::as_str)
+        .collect();
+    (!text.is_empty()).then(|| text.concat())
+}
+
+/// What tells a child apart from its siblings: its name and `name` attribute, and how many siblings before it share them
+#[derive(Debug, PartialEq, Eq)]
+struct ChildIdentity<'a> {
+    name: &'a str,
+    name_attribute: Option<&'a str>,
+    occurrence: usize,
+}
+
+impl ChildIdentity<'_> {
+    fn shares_name(&self, other: &ChildIdentity) -> bool {
+        self.name == other.name && self.name_attribute == other.name_attribute
+    }
+
+    /// The level of the path naming the child, numbered if a sibling shares its name
+    fn step(&self, siblings: &[(ChildIdentity, &Node)]) -> String {
+        let step = match self.name_attribute {
+            Some(name_attribute) => format!("{}[@name='{name_attribute}']", self.name),
+            None => self.name.to_string(),
+        };
+
+        match siblings.iter().filter(|(it, _)| it.shares_name(self)).count() {
+            1 => step,
+            _ => format!("{step}[{}]", self.occurrence + 1),
+        }
+    }
+}
+
+fn identify(children: &[Node]) -> Vec<(ChildIdentity<'_>, &Node)> {
+    let mut identified: Vec<(ChildIdentity, &Node)> = Vec::with_capacity(children.len());
+    for child in children.iter().filter(|it| it.kind == NodeKind::Element) {
+        let mut identity = ChildIdentity {
+            name: &child.name,
+            name_attribute: child.attributes.get("name").map(String::as_str),
+            occurrence: 0,
+        };
+        identity.occurrence = identified.iter().filter(|(it, _)| it.shares_name(&identity)).count();
+        identified.push((identity, child));
+    }
+    identified
+}
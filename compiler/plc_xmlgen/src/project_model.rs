@@ -0,0 +1,221 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{AstStatement, CompilationUnit, DataType, LinkageType, PouType, Variable, VariableBlock};
+use plc_source::source_location::CodeSpan;
+use serde::Serialize;
+
+use super::source_comments::{leading_comment, source_text, trailing_comment, LoadedSources};
+use super::tag_list::declared_type_name;
+use super::yaml::to_yaml;
+use super::xml_gen::parse_enum_expression;
+
+pub const PROJECT_JSON_EXTENSION: &str = "json";
+pub const PROJECT_YAML_EXTENSION: &str = "yaml";
+
+/// The compiler's view of the project: the same types, globals and POUs the XML generation exports, without the
+/// vendor specific shape of the XML
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectModel {
+    pub data_types: Vec<DataTypeModel>,
+    pub globals: Vec<VariableModel>,
+    pub pous: Vec<PouModel>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DataTypeModel {
+    pub name: String,
+    /// `struct` or `enum`
+    pub kind: String,
+    /// The numeric type of an enum
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub base_type: Option<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub members: Vec<VariableModel>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub values: Vec<EnumValueModel>,
+    /// The comment above the declaration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EnumValueModel {
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PouModel {
+    pub name: String,
+    /// `Program`, `Function` or `FunctionBlock`
+    pub kind: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub return_type: Option<String>,
+    pub variables: Vec<VariableModel>,
+    /// The comment above the declaration
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+    /// The source text of the implementation
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub body: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VariableModel {
+    pub name: String,
+    pub type_name: String,
+    /// The variable block the variable is declared in, e.g. `global` or `input`
+    #[serde(skip_serializing_if = "String::is_empty")]
+    pub scope: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub initial_value: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub address: Option<String>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub constant: bool,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub retain: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub documentation: Option<String>,
+}
+
+/// The project model belonging to a generated document, e.g. `out.xml` comes with `out.json`
+pub fn project_json_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(PROJECT_JSON_EXTENSION)
+}
+
+/// The YAML dump of the project model, e.g. `out.xml` comes with `out.yaml`
+pub fn project_yaml_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(PROJECT_YAML_EXTENSION)
+}
+
+/// Collects the users own types, globals and POUs of all units, in declaration order
+pub fn collect_project_model(sources: &LoadedSources, units: &Vec<&CompilationUnit>) -> ProjectModel {
+    let mut model = ProjectModel::default();
+
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_usertype in &current_unit.user_types {
+            if current_usertype.location.span == CodeSpan::None || current_usertype.linkage == LinkageType::External {
+                continue; //same types as the XML generation
+            }
+
+            if let Some(mut data_type) = data_type_model(sources, &current_usertype.data_type) {
+                data_type.documentation = leading_comment(sources, &current_usertype.location);
+                model.data_types.push(data_type);
+            }
+        }
+
+        for current_global in &current_unit.global_vars {
+            if current_global.linkage != LinkageType::External {
+                model.globals.extend(block_variables(sources, current_global));
+            }
+        }
+
+        for current_pou in &current_unit.pous {
+            if current_pou.linkage == LinkageType::External || !matches!(current_pou.kind, PouType::Program | PouType::Function | PouType::FunctionBlock) {
+                continue; //same POUs as the XML generation
+            }
+
+            let body = current_unit.implementations.iter()
+                .find(|it| it.name == current_pou.name)
+                .and_then(|it| source_text(sources, &it.location));
+
+            model.pous.push(PouModel {
+                name: current_pou.name.clone(),
+                kind: current_pou.kind.to_string(),
+                return_type: current_pou.return_type.as_ref().and_then(declared_type_name),
+                variables: current_pou.variable_blocks.iter().flat_map(|it| block_variables(sources, it)).collect(),
+                documentation: leading_comment(sources, &current_pou.location),
+                body,
+            });
+        }
+    }
+    model
+}
+
+fn data_type_model(sources: &LoadedSources, data_type: &DataType) -> Option<DataTypeModel> {
+    match data_type {
+        DataType::StructType { name: Some(name), variables } => Some(DataTypeModel {
+            name: name.clone(),
+            kind: String::from("struct"),
+            base_type: None,
+            members: variables.iter().filter_map(|it| variable_model(sources, it, "", false, false)).collect(),
+            values: Vec::new(),
+            documentation: None,
+        }),
+        DataType::EnumType { name: Some(name), numeric_type, elements } => {
+            let assignments = match &elements.stmt {
+                AstStatement::ExpressionList(nodes) => nodes.iter().collect(),
+                _ => vec![elements],
+            };
+
+            let values = assignments.into_iter().filter_map(|it| match &it.stmt {
+                AstStatement::Assignment(assignment) => {
+                    let parsed = parse_enum_expression(assignment);
+                    Some(EnumValueModel { name: parsed.name, value: parsed.initial_value })
+                },
+                _ => None,
+            })
+            .collect();
+
+            Some(DataTypeModel {
+                name: name.clone(),
+                kind: String::from("enum"),
+                base_type: Some(numeric_type.clone()),
+                members: Vec::new(),
+                values,
+                documentation: None,
+            })
+        },
+        _ => None,
+    }
+}
+
+fn block_variables(sources: &LoadedSources, block: &VariableBlock) -> Vec<VariableModel> {
+    let scope = block.kind.to_string();
+
+    block.variables.iter()
+        .filter(|it| it.location.span != CodeSpan::None) //discard compiler interally generated variables
+        .filter_map(|it| variable_model(sources, it, &scope, block.constant, block.retain))
+        .collect()
+}
+
+fn variable_model(sources: &LoadedSources, variable: &Variable, scope: &str, constant: bool, retain: bool) -> Option<VariableModel> {
+    let literal_text = |statement: Option<&AstStatement>| match statement {
+        Some(AstStatement::Literal(literal)) => Some(literal.to_string()),
+        _ => None,
+    };
+
+    Some(VariableModel {
+        name: variable.name.clone(),
+        type_name: declared_type_name(&variable.data_type_declaration)?,
+        scope: String::from(scope),
+        initial_value: literal_text(variable.initializer.as_ref().map(|it| &it.stmt)),
+        address: literal_text(variable.address.as_ref().map(|it| &it.stmt)),
+        constant,
+        retain,
+        documentation: trailing_comment(sources, &variable.location),
+    })
+}
+
+/// Writes the project model as indented JSON
+pub fn generate_project_json(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(&collect_project_model(sources, units)).map_err(Error::other)?;
+    fs::write(output_path, json)
+}
+
+/// Writes the project model as YAML, for reviewing changes to the exported project in a diff
+pub fn generate_project_yaml(sources: &LoadedSources, units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    let value = serde_json::to_value(collect_project_model(sources, units)).map_err(Error::other)?;
+    fs::write(output_path, to_yaml(&value))
+}
@@ -1,6 +1,11 @@
 #![allow(clippy::new_without_default)]
 
+use std::io::{Error, ErrorKind, Read};
+
 use rustc_hash::FxHashMap;
+use xml::{namespace::Namespace, reader::XmlEvent, ParserConfig};
+
+use super::xml_gen::write_xml_element;
 
 #[derive(Clone)]
 pub struct Node {
@@ -17,17 +22,98 @@ pub struct Node {
     /// Indicates if an element has a closed form, e.g. `<position x="1" y="2"/>`
     pub closed: bool,
 
-    /// Indicates if an element has some text wrapped inside itself, e.g. `<expression>a + b</expression>`
+    /// The text an element wraps, e.g. `a + b` of `<expression>a + b</expression>`, written before any children, see
+    /// [`Node::is_mixed`]. For the other kinds of nodes their text, or the data of a processing instruction
     pub content: Option<String>,
+
+    /// Whether the node is an element, a comment, a processing instruction or text
+    pub kind: NodeKind,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum NodeKind {
+    #[default]
+    Element,
+
+    /// A comment between the elements, e.g. `<!-- Generated from main.st -->`, which readers of the document skip
+    Comment,
+
+    /// An instruction for the application reading the document, e.g. `<?mso-application progid="Excel.Sheet"?>`, named
+    /// after the application it targets
+    ProcessingInstruction,
+
+    /// Text among the children of an element, e.g. `Press ` and ` to start` in `<p>Press <b>F5</b> to start</p>`
+    Text,
+}
+
+/// How the writer formats documents and elements. Lines are not limited to a width: an element is written on one line
+/// with all its attributes, however many there are, and long text is not wrapped either
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SerializerOptions {
+    /// What every level of nested elements is indented by, e.g. two spaces or a tab. Without an indentation the
+    /// elements follow each other without line breaks
+    pub indent: Option<String>,
+
+    /// What ends every line of the document, e.g. `\r\n`
+    pub line_separator: String,
+
+    /// Whether empty elements are written with a space before their `/>`, e.g. `<FileHeader />` rather than
+    /// `<FileHeader/>`
+    pub pad_self_closing: bool,
+}
+
+impl Default for SerializerOptions {
+    fn default() -> Self {
+        Self { indent: Some(String::from("  ")), line_separator: String::from("\n"), pad_self_closing: true }
+    }
 }
 
 pub trait IntoNode {
     fn inner(&self) -> Node;
 }
 
+impl IntoNode for Node {
+    fn inner(&self) -> Node {
+        self.clone()
+    }
+}
+
 impl Node {
     pub fn new(name: String) -> Self {
-        Self { name, attributes: FxHashMap::default(), children: Vec::new(), closed: false, content: None }
+        Self { name, attributes: FxHashMap::default(), children: Vec::new(), closed: false, content: None, kind: NodeKind::Element }
+    }
+
+    /// A comment with the given text. Every `--` of the text is written as `- -`, since it would end the comment
+    pub fn comment(text: &str) -> Self {
+        let mut text = text.to_string();
+        while text.contains("--") {
+            text = text.replace("--", "- -");
+        }
+        if text.ends_with('-') {
+            text.push(' ');
+        }
+
+        Self { kind: NodeKind::Comment, content: Some(text), ..Self::new(String::new()) }
+    }
+
+    /// Text to put among the children of an element, written as escaped text rather than CDATA
+    pub fn text(text: &str) -> Self {
+        Self { kind: NodeKind::Text, content: Some(text.to_string()), ..Self::new(String::new()) }
+    }
+
+    /// Whether the element has mixed content, i.e. text among its children or content as well as children. Mixed content
+    /// is written as it is, without indenting the children
+    pub fn is_mixed(&self) -> bool {
+        self.kind == NodeKind::Element
+            && !self.children.is_empty()
+            && (self.content.is_some() || self.children.iter().any(|it| it.kind == NodeKind::Text))
+    }
+
+    /// A processing instruction for the given target application. Every `?>` of the data is written as `? >`, since it
+    /// would end the instruction
+    pub fn processing_instruction(target: &str, data: &str) -> Self {
+        let data = (!data.is_empty()).then(|| data.replace("?>", "? >"));
+        Self { kind: NodeKind::ProcessingInstruction, content: data, ..Self::new(target.to_string()) }
     }
 
     pub fn new_str(name: &'static str) -> Self {
@@ -48,6 +134,21 @@ impl Node {
         Self::attribute(self, key.to_string(), value.to_string())
     }    
 
+    /// Sets the attribute to `true` or `false`, the way XML Schema writes booleans
+    pub fn attribute_bool(self, key: &'static str, value: bool) -> Self {
+        Self::attribute(self, key.to_string(), value.to_string())
+    }
+
+    /// Sets the attribute to the decimal digits of the number, with a leading `-` if it is negative
+    pub fn attribute_int(self, key: &'static str, value: i64) -> Self {
+        Self::attribute(self, key.to_string(), value.to_string())
+    }
+
+    /// Sets the attribute to the decimal digits of a count or position, e.g. an order number
+    pub fn attribute_usize(self, key: &'static str, value: usize) -> Self {
+        Self::attribute(self, key.to_string(), value.to_string())
+    }
+
     pub fn child(mut self, node: &dyn IntoNode) -> Self {
         self.children.push(node.inner());
         self
@@ -69,18 +170,173 @@ impl Node {
         self
     }
 
+    /// The first child element with the given name
+    pub fn find_child(&self, name: &str) -> Option<&Node> {
+        self.children.iter().find(|it| it.name == name)
+    }
+
+    /// The first element at the given path below this one in document order, see [`Node::find_all`]
+    pub fn find(&self, path: &str) -> Option<&Node> {
+        self.find_all(path).into_iter().next()
+    }
+
+    /// The elements at the given path below this one in document order. The path names an element for every level
+    /// separated by `/`, each of which may require the values of attributes, e.g.
+    /// `Types/GlobalNamespace/DataTypeDecl[@name='Motor']`. `*` names any element.
+    ///
+    /// A malformed path, e.g. one whose predicate lacks its quotes, finds no element
+    pub fn find_all(&self, path: &str) -> Vec<&Node> {
+        let Some(steps) = path_steps(path) else {
+            return Vec::new();
+        };
+
+        let mut found = vec![self];
+        for step in steps {
+            found = found.into_iter().flat_map(|it| it.children.iter()).filter(|it| step.matches(it)).collect();
+        }
+        found
+    }
+
+    /// The first element at the given path below this one in document order, see [`Node::find_all`]
+    pub fn find_mut(&mut self, path: &str) -> Option<&mut Node> {
+        self.find_steps_mut(&path_steps(path)?)
+    }
+
+    fn find_steps_mut(&mut self, steps: &[PathStep]) -> Option<&mut Node> {
+        let Some((step, rest)) = steps.split_first() else {
+            return Some(self);
+        };
+
+        for child in self.children.iter_mut().filter(|it| step.matches(it)) {
+            if let Some(found) = child.find_steps_mut(rest) {
+                return Some(found);
+            }
+        }
+        None
+    }
+
+    /// Reads a document back into the tree it was written from by `write_xml_file`, see [`Node::parse_with_prolog`]
+    pub fn parse<R: Read>(source: R) -> Result<Node, Error> {
+        Node::parse_with_prolog(source).map(|(_, root)| root)
+    }
+
+    /// Reads a document into the processing instructions and comments before its root element and the tree of the root
+    /// element. The text and CDATA sections of an element become its content, or text nodes among its children if it
+    /// has mixed content. The whitespace between elements is dropped, and elements without children or content are
+    /// closed. Namespaces are declared by `xmlns` attributes like in the templates
+    pub fn parse_with_prolog<R: Read>(source: R) -> Result<(Vec<Node>, Node), Error> {
+        let reader = ParserConfig::new()
+            .ignore_comments(false)
+            .create_reader(source);
+
+        let mut prolog = Vec::new();
+        let mut open: Vec<(Node, Namespace)> = Vec::new();
+        let mut root = None;
+
+        for event in reader {
+            let node = match event.map_err(Error::other)? {
+                XmlEvent::StartElement { name, attributes, namespace } => {
+                    let mut element = Node::new(name.borrow().to_repr());
+                    //only the namespaces declared by this element, not the ones in scope from its ancestors
+                    let inherited = open.last().map(|(_, it)| it);
+                    for (prefix, uri) in &namespace {
+                        if matches!(prefix, "xml" | "xmlns") || inherited.and_then(|it| it.get(prefix)).unwrap_or_default() == uri {
+                            continue;
+                        }
+
+                        let key = match prefix {
+                            "" => String::from("xmlns"),
+                            _ => format!("xmlns:{prefix}"),
+                        };
+                        element.attributes.insert(key, uri.to_string());
+                    }
+                    for attribute in attributes {
+                        element.attributes.insert(attribute.name.borrow().to_repr(), attribute.value);
+                    }
+
+                    open.push((element, namespace));
+                    continue;
+                },
+                XmlEvent::EndElement { .. } => {
+                    let (mut element, _) = open.pop().ok_or_else(|| Error::new(ErrorKind::InvalidData, "an element was closed before it was opened"))?;
+                    if element.children.iter().all(|it| it.kind == NodeKind::Text) {
+                        let text: Vec<Node> = std::mem::take(&mut element.children);
+                        element.content = (!text.is_empty()).then(|| text.into_iter().filter_map(|it| it.content).collect());
+                    }
+                    element.closed = element.children.is_empty() && element.content.is_none();
+                    element
+                },
+                XmlEvent::Characters(text) | XmlEvent::CData(text) => {
+                    match open.last_mut().and_then(|(element, _)| element.children.last_mut()) {
+                        Some(previous) if previous.kind == NodeKind::Text => previous.content.get_or_insert_with(String::new).push_str(&text),
+                        _ => {
+                            let Some((element, _)) = open.last_mut() else {
+                                continue;
+                            };
+                            element.children.push(Node::text(&text));
+                        },
+                    }
+                    continue;
+                },
+                XmlEvent::Comment(text) => Node::comment(&text),
+                XmlEvent::ProcessingInstruction { name, data } => Node::processing_instruction(&name, data.as_deref().unwrap_or_default()),
+                _ => continue,
+            };
+
+            match open.last_mut() {
+                Some((parent, _)) => parent.children.push(node),
+                None if node.kind == NodeKind::Element => root = Some(node),
+                None if root.is_none() => prolog.push(node),
+                None => {}, //after the root element
+            }
+        }
+
+        let root = root.ok_or_else(|| Error::new(ErrorKind::InvalidData, "the document has no root element"))?;
+        Ok((prolog, root))
+    }
+
+    /// The element and everything below it exactly as `write_xml_file` writes it into the document, without the XML
+    /// declaration, so tests can compare it with written documents
+    pub fn to_xml_string(&self) -> Result<String, Error> {
+        self.to_formatted_xml_string(&SerializerOptions::default())
+    }
+
+    /// The element like [`Node::to_xml_string`], formatted as the options ask for
+    pub fn to_formatted_xml_string(&self, options: &SerializerOptions) -> Result<String, Error> {
+        let mut buffer = Vec::new();
+        write_xml_element(&mut buffer, options, self.clone())?;
+        String::from_utf8(buffer).map_err(Error::other)
+    }
+
     pub fn indent(level: usize) -> String {
         " ".repeat(level * 4)
     }
 
     fn serialize_content(indent: String, name: String, content: String) -> String {
-        format!("{indent}<{name}>{content}</{name}>\n")
+        format!("{indent}<{name}>{}</{name}>\n", escape(&content))
     }
 
+    /// Writes the element and its children as indented XML. Unlike the document written by `write_xml_file`, the content
+    /// is escaped text rather than CDATA, which reads the same and is understood by readers only taking text. See
+    /// [`Node::to_xml_string`] for the element exactly as the document contains it
     #[allow(unused_assignments)]
     pub fn serialize(&self, level: usize) -> String {
         let (name, indent) = (self.name.clone(), Node::indent(level));
-        let attributes = self.attributes.iter().map(|(key, value)| format!("{key}=\"{value}\""));
+        if self.kind == NodeKind::Comment {
+            //padded with spaces like the comments written by `write_xml_file`
+            let text = self.content.as_deref().unwrap_or_default();
+            let start = if text.starts_with(char::is_whitespace) { "" } else { " " };
+            let end = if text.ends_with(char::is_whitespace) { "" } else { " " };
+            return format!("{indent}<!--{start}{text}{end}-->\n");
+        }
+        if self.kind == NodeKind::ProcessingInstruction {
+            return match &self.content {
+                Some(data) => format!("{indent}<?{name} {data}?>\n"),
+                None => format!("{indent}<?{name}?>\n"),
+            };
+        }
+
+        let attributes = self.attributes.iter().map(|(key, value)| format!("{key}=\"{}\"", escape(value)));
         let attributes_str = attributes.collect::<Vec<_>>().join(" ");
         let mut result = String::new();
 
@@ -88,6 +344,15 @@ impl Node {
             return format!("{indent}<{name} {attributes_str}/>\n");
         }
 
+        if self.kind == NodeKind::Text {
+            return escape(self.content.as_deref().unwrap_or_default());
+        }
+        if self.is_mixed() {
+            let children = self.children.iter().map(|child| child.serialize(0).trim_end_matches('\n').to_string()).collect::<String>();
+            let content = escape(self.content.as_deref().unwrap_or_default());
+            return format!("{indent}<{name} {attributes_str}>{content}{children}</{name}>\n");
+        }
+
         if let Some(content) = self.content.clone() {
             return Node::serialize_content(indent.to_string(), name, content);
         }
@@ -100,6 +365,66 @@ impl Node {
     }
 }
 
+/// The text with the characters which would end or break the text of an element or attribute replaced by their
+/// entities, e.g. `x < 0` as `x &lt; 0`
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            _ => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+/// A level of a path given to [`Node::find_all`]: the name of the element and the attributes it needs to have
+struct PathStep<'a> {
+    name: &'a str,
+    attributes: Vec<(&'a str, &'a str)>,
+}
+
+impl PathStep<'_> {
+    fn matches(&self, node: &Node) -> bool {
+        node.kind == NodeKind::Element
+            && (self.name == "*" || node.name == self.name)
+            && self.attributes.iter().all(|(key, value)| node.attributes.get(*key).is_some_and(|it| it == value))
+    }
+}
+
+/// Splits a path into its levels, or `None` if it is malformed. Slashes within the quoted values of attributes don't
+/// separate levels
+fn path_steps(path: &str) -> Option<Vec<PathStep<'_>>> {
+    let mut steps = Vec::new();
+    let mut rest = path;
+
+    while !rest.is_empty() {
+        let name_end = rest.find(['[', '/']).unwrap_or(rest.len());
+        let mut step = PathStep { name: &rest[..name_end], attributes: Vec::new() };
+        rest = &rest[name_end..];
+
+        while let Some(predicate) = rest.strip_prefix("[@") {
+            let (key, value) = predicate.split_once('=')?;
+            let quote = value.chars().next().filter(|it| *it == '\'' || *it == '"')?;
+            let value_end = value[1..].find(quote)? + 1;
+            step.attributes.push((key, &value[1..value_end]));
+
+            rest = value[value_end + 1..].strip_prefix(']')?;
+        }
+
+        //every level names an element
+        if step.name.is_empty() || !(rest.is_empty() || rest.starts_with('/')) {
+            return None;
+        }
+        steps.push(step);
+        rest = rest.strip_prefix('/').unwrap_or(rest);
+    }
+    Some(steps)
+}
+
 macro_rules! newtype_impl {
     ($name_struct:ident, $name_node:expr, $negatable:expr) => {
         pub struct $name_struct(Node);
@@ -113,7 +438,7 @@ macro_rules! newtype_impl {
         impl $name_struct {
             pub fn new() -> Self {
                 match $negatable {
-                    true => Self(Node::new_str($name_node).attribute_str("negated", "false")),
+                    true => Self(Node::new_str($name_node).attribute_bool("negated", false)),
                     false => Self(Node::new_str($name_node)),
                 }
             }
@@ -137,6 +462,18 @@ macro_rules! newtype_impl {
                 Self(self.inner().attribute_str(key, value))
             }            
 
+            pub fn attribute_bool(self, key: &'static str, value: bool) -> Self {
+                Self(self.inner().attribute_bool(key, value))
+            }
+
+            pub fn attribute_int(self, key: &'static str, value: i64) -> Self {
+                Self(self.inner().attribute_int(key, value))
+            }
+
+            pub fn attribute_usize(self, key: &'static str, value: usize) -> Self {
+                Self(self.inner().attribute_usize(key, value))
+            }
+
             pub fn maybe_attribute(self, key: String, value: Option<String>) -> Self {
                 match value {
                     Some(value) => Self(self.inner().attribute(key, value)),
@@ -156,16 +493,20 @@ macro_rules! newtype_impl {
                 self.inner().serialize(0)
             }
 
+            pub fn to_xml_string(self) -> Result<String, Error> {
+                self.inner().to_xml_string()
+            }
+
             pub fn with_id<T: std::fmt::Display>(self, id: T) -> Self {
-                self.attribute_str("localId", Box::leak(id.to_string().into_boxed_str()))
+                self.attribute(String::from("localId"), id.to_string())
             }
 
             pub fn with_ref_id<T: std::fmt::Display>(self, id: T) -> Self {
-                self.attribute_str("refLocalId", Box::leak(id.to_string().into_boxed_str()))
+                self.attribute(String::from("refLocalId"), id.to_string())
             }
 
             pub fn with_execution_id<T: std::fmt::Display>(self, id: T) -> Self {
-                self.attribute_str("executionOrderId", Box::leak(id.to_string().into_boxed_str()))
+                self.attribute(String::from("executionOrderId"), id.to_string())
             }
 
             pub fn close(self) -> Self {
@@ -207,12 +548,21 @@ newtype_impl!(SJump, "jump", false);
 newtype_impl!(SLabel, "label", false);
 newtype_impl!(SAction, "action", false);
 newtype_impl!(SActions, "actions", false);
+newtype_impl!(SSfc, "SFC", false);
+newtype_impl!(SStep, "step", false);
+newtype_impl!(SJumpStep, "jumpStep", false);
+newtype_impl!(STransition, "transition", false);
+newtype_impl!(SCondition, "condition", false);
+newtype_impl!(SSelectionDivergence, "selectionDivergence", false);
+newtype_impl!(SActionBlock, "actionBlock", false);
+newtype_impl!(SInline, "inline", false);
+newtype_impl!(SXhtml, "xhtml", false);
 newtype_impl!(SFileHeader, FILE_HEADER, false);
 newtype_impl!(SContentHeader, CONTENT_HEADER, false);
 newtype_impl!(STypes, TYPES, false);
 
-pub const FILE_HEADER: &'static str = "FileHeader";
-pub const CONTENT_HEADER: &'static str = "ContentHeader";
+pub const FILE_HEADER: &str = "FileHeader";
+pub const CONTENT_HEADER: &str = "ContentHeader";
 
 pub trait SizedVariable: IntoNode + Sized {
 
@@ -282,7 +632,7 @@ impl SReturn {
 
     pub fn negate(self, value: bool) -> Self {
         self.child(&SAddData::new().child(&SData::new().child(
-            &SNegate::new().attribute(String::from("value"), value.to_string()).close(),
+            &SNegate::new().attribute_bool("value", value).close(),
         )))
     }
 }
@@ -459,7 +809,7 @@ impl SJump {
 
     pub fn negate(self) -> Self {
         self.child(
-            &SAddData::new().child(&SData::new().child(&SNegate::new().attribute(String::from("value"), String::from("true")).close())),
+            &SAddData::new().child(&SData::new().child(&SNegate::new().attribute_bool("value", true).close())),
         )
     }
 }
@@ -490,10 +840,14 @@ newtype_impl!(SInstances, INSTANCES, false);
 newtype_impl!(SConfiguration, CONFIGURATION, false);
 newtype_impl!(SResource, RESOURCE, false);
 newtype_impl!(SGlobalVars, "GlobalVars", false);
+newtype_impl!(STask, "Task", false);
+newtype_impl!(SProgramInstance, "ProgramInstance", false);
 newtype_impl!(SType, "Type", false);
 newtype_impl!(STypeName, "TypeName", false);
 newtype_impl!(SInitialValue, "InitialValue", false);
 newtype_impl!(SSimpleValue, "SimpleValue", false);
+newtype_impl!(SStructValue, "StructValue", false);
+newtype_impl!(SValue, "Value", false);
 newtype_impl!(SGenVariable, "Variable", false);
 newtype_impl!(SOmronAddData, "AddData", false);
 newtype_impl!(SOmronData, "Data", false);
@@ -504,12 +858,21 @@ newtype_impl!(SUserDefinedTypeSpec, "UserDefinedTypeSpec", false);
 newtype_impl!(SMember, "Member", false);
 newtype_impl!(SEnumerator, "Enumerator", false);
 newtype_impl!(SBaseType, "BaseType", false);
+newtype_impl!(SDimension, "Dimension", false);
+newtype_impl!(SArrayType, "ArrayType", false);
 newtype_impl!(SST, "ST", false);
 newtype_impl!(SBodyContent, "BodyContent", false);
 newtype_impl!(SMainBody, "MainBody", false);
 newtype_impl!(SProgram, "Program", false);
 newtype_impl!(SFunction, "Function", false);
 newtype_impl!(SFunctionBlock, "FunctionBlock", false);
+newtype_impl!(SClass, "Class", false);
+newtype_impl!(SMethod, "Method", false);
+newtype_impl!(SInterfaceDecl, "Interface", false);
+newtype_impl!(SImplements, "Implements", false);
+newtype_impl!(SExtends, "Extends", false);
+newtype_impl!(SGet, "Get", false);
+newtype_impl!(SSet, "Set", false);
 newtype_impl!(SVars, "Vars", false);
 newtype_impl!(STempVars, "TempVars", false);
 newtype_impl!(SPouInfo, "smcext:PouInfo", false);
@@ -520,9 +883,104 @@ newtype_impl!(SInputVars, "InputVars", false);
 newtype_impl!(SInoutVars, "InoutVars", false);
 newtype_impl!(SOutputVars, "OutputVars", false);
 newtype_impl!(SAddress, "Address", false);
-
-pub const GLOBAL_NAMESPACE: &'static str = "GlobalNamespace";
-pub const INSTANCES: &'static str = "Instances";
-pub const CONFIGURATION: &'static str = "Configuration";
-pub const RESOURCE: &'static str = "Resource";
-pub const TYPES: &'static str = "Types";
+newtype_impl!(SConfigVars, "ConfigVars", false);
+newtype_impl!(SConfigVariable, "ConfigVariable", false);
+newtype_impl!(SAccessPaths, "AccessPaths", false);
+newtype_impl!(SAccessVariable, "AccessVariable", false);
+
+pub const GLOBAL_NAMESPACE: &str = "GlobalNamespace";
+/// The path of the namespace holding the data types, interfaces and POUs below the root of a document
+pub const GLOBAL_NAMESPACE_PATH: &str = "Types/GlobalNamespace";
+pub const INSTANCES: &str = "Instances";
+pub const CONFIGURATION: &str = "Configuration";
+pub const RESOURCE: &str = "Resource";
+pub const TYPES: &str = "Types";
+
+//archive manifest
+newtype_impl!(SManifest, "Manifest", false);
+newtype_impl!(SManifestFile, "File", false);
+
+//library package
+newtype_impl!(SProjectInformation, "ProjectInformation", false);
+newtype_impl!(SProperty, "Property", false);
+newtype_impl!(SLibraryContents, "Contents", false);
+newtype_impl!(SLibraryObject, "Object", false);
+
+//TIA Portal Openness
+newtype_impl!(STiaDocument, "Document", false);
+newtype_impl!(STiaEngineering, "Engineering", false);
+newtype_impl!(STiaAttributeList, "AttributeList", false);
+newtype_impl!(STiaObjectList, "ObjectList", false);
+newtype_impl!(STiaInterface, "Interface", false);
+newtype_impl!(STiaSections, "Sections", false);
+newtype_impl!(STiaSection, "Section", false);
+newtype_impl!(STiaMember, "Member", false);
+newtype_impl!(STiaStartValue, "StartValue", false);
+newtype_impl!(STiaSubelement, "Subelement", false);
+newtype_impl!(STiaName, "Name", false);
+newtype_impl!(STiaMemoryLayout, "MemoryLayout", false);
+newtype_impl!(STiaProgrammingLanguage, "ProgrammingLanguage", false);
+newtype_impl!(STiaSetEnoAutomatically, "SetENOAutomatically", false);
+newtype_impl!(STiaCompileUnit, "SW.Blocks.CompileUnit", false);
+newtype_impl!(STiaNetworkSource, "NetworkSource", false);
+newtype_impl!(STiaStructuredText, "StructuredText", false);
+newtype_impl!(STiaFunctionBlock, "SW.Blocks.FB", false);
+newtype_impl!(STiaFunction, "SW.Blocks.FC", false);
+newtype_impl!(STiaGlobalDB, "SW.Blocks.GlobalDB", false);
+newtype_impl!(STiaPlcStruct, "SW.Types.PlcStruct", false);
+
+//OPC UA NodeSet2
+newtype_impl!(SUaNodeSet, "UANodeSet", false);
+newtype_impl!(SUaNamespaceUris, "NamespaceUris", false);
+newtype_impl!(SUaUri, "Uri", false);
+newtype_impl!(SUaAliases, "Aliases", false);
+newtype_impl!(SUaAlias, "Alias", false);
+newtype_impl!(SUaObject, "UAObject", false);
+newtype_impl!(SUaVariable, "UAVariable", false);
+newtype_impl!(SUaDataType, "UADataType", false);
+newtype_impl!(SUaDisplayName, "DisplayName", false);
+newtype_impl!(SUaReferences, "References", false);
+newtype_impl!(SUaReference, "Reference", false);
+newtype_impl!(SUaDefinition, "Definition", false);
+newtype_impl!(SUaField, "Field", false);
+
+//AutomationML (CAEX)
+newtype_impl!(SCaexFile, "CAEXFile", false);
+newtype_impl!(SCaexSourceDocumentInformation, "SourceDocumentInformation", false);
+newtype_impl!(SCaexInstanceHierarchy, "InstanceHierarchy", false);
+newtype_impl!(SCaexInternalElement, "InternalElement", false);
+newtype_impl!(SCaexDescription, "Description", false);
+newtype_impl!(SCaexAttribute, "Attribute", false);
+newtype_impl!(SCaexValue, "Value", false);
+newtype_impl!(SCaexDefaultValue, "DefaultValue", false);
+newtype_impl!(SCaexAttributeTypeLib, "AttributeTypeLib", false);
+newtype_impl!(SCaexAttributeType, "AttributeType", false);
+newtype_impl!(SCaexConstraint, "Constraint", false);
+newtype_impl!(SCaexNominalScaledType, "NominalScaledType", false);
+newtype_impl!(SCaexRequiredValue, "RequiredValue", false);
+newtype_impl!(SCaexSystemUnitClassLib, "SystemUnitClassLib", false);
+newtype_impl!(SCaexSystemUnitClass, "SystemUnitClass", false);
+
+//SpreadsheetML (xlsx)
+newtype_impl!(SXlsxTypes, "Types", false);
+newtype_impl!(SXlsxDefault, "Default", false);
+newtype_impl!(SXlsxOverride, "Override", false);
+newtype_impl!(SXlsxRelationships, "Relationships", false);
+newtype_impl!(SXlsxRelationship, "Relationship", false);
+newtype_impl!(SXlsxWorkbook, "workbook", false);
+newtype_impl!(SXlsxSheets, "sheets", false);
+newtype_impl!(SXlsxSheet, "sheet", false);
+newtype_impl!(SXlsxWorksheet, "worksheet", false);
+newtype_impl!(SXlsxSheetData, "sheetData", false);
+newtype_impl!(SXlsxRow, "row", false);
+newtype_impl!(SXlsxCell, "c", false);
+newtype_impl!(SXlsxInlineString, "is", false);
+newtype_impl!(SXlsxText, "t", false);
+
+//EtherNet/IP
+newtype_impl!(SEipRoot, "EtherNetIP", false);
+newtype_impl!(SEipTags, "Tags", false);
+newtype_impl!(SEipTag, "Tag", false);
+newtype_impl!(SEipAssemblies, "Assemblies", false);
+newtype_impl!(SEipAssembly, "Assembly", false);
+newtype_impl!(SEipMember, "Member", false);
@@ -0,0 +1,238 @@
+use plc_ast::{
+    ast::{AstNode, AstStatement, Assignment, Pou, Variable},
+    control_statements::{AstControlStatement, CaseStatement, IfStatement},
+    visitor::AstVisitor,
+};
+
+use super::serializer::*;
+use super::source_comments::{preceding_attributes, source_text, LoadedSources};
+
+pub const SFC_STATE_ATTRIBUTE: &str = "sfc_state";
+
+pub const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// A branch of the state machine: the statements it runs while active and the conditions it is left on, by priority
+struct Step {
+    name: String,
+    actions: Vec<String>,
+    transitions: Vec<(String, String)>,
+}
+
+/// The variable a POU's state machine is kept in, marked with `{attribute 'sfc_state'}` above its declaration
+pub fn sfc_state_variable<'a>(sources: &LoadedSources, pou: &'a Pou) -> Option<&'a Variable> {
+    pou.variable_blocks.iter()
+        .flat_map(|it| &it.variables)
+        .find(|variable| preceding_attributes(sources, &variable.location).iter().any(|(name, _)| name.eq_ignore_ascii_case(SFC_STATE_ATTRIBUTE)))
+}
+
+/// Converts the body of a state machine into an SFC chart. The body has to be a single `CASE` over the state
+/// variable, e.g.
+/// ```text
+/// CASE state OF
+///     Idle:
+///         ready := TRUE;
+///         IF start THEN state := Running; END_IF
+///     Running: ...
+/// END_CASE
+/// ```
+/// Every branch becomes a step, named after its label. Its statements become an action, while `IF`s assigning the
+/// state become the transitions to the step assigned, their `ELSIF`s transitions of lower priority. The step the
+/// state variable is initialized with is the initial step, otherwise the first one.
+///
+/// Returns `None` for any other body, e.g. one assigning the state anywhere else, which only ST can express
+pub fn sfc_chart(sources: &LoadedSources, statements: &[AstNode], state_variable: &Variable) -> Option<SSfc> {
+    let mut statements = statements.iter().filter(|it| !it.is_empty_statement());
+    let (Some(case_statement), None) = (statements.next(), statements.next()) else {
+        return None;
+    };
+
+    let AstStatement::ControlStatement(AstControlStatement::Case(CaseStatement { selector, case_blocks, else_block, .. })) = &case_statement.stmt else {
+        return None;
+    };
+
+    if !is_state(selector, &state_variable.name) || !else_block.is_empty() {
+        return None;
+    }
+
+    let mut steps = Vec::new();
+    for case_block in case_blocks {
+        let mut step = Step { name: step_name(sources, &case_block.condition)?, actions: Vec::new(), transitions: Vec::new() };
+
+        for statement in case_block.body.iter().filter(|it| !it.is_empty_statement()) {
+            match transitions(sources, statement, &state_variable.name) {
+                Some(_) if !step.transitions.is_empty() => {
+                    return None //the priorities of separate IFs depend on their order
+                },
+                Some(transitions) => step.transitions = transitions?,
+                None => step.actions.push(statement_text(sources, statement)?),
+            }
+        }
+        steps.push(step);
+    }
+
+    //every transition leads to a step of the chart
+    let targets_known = steps.iter()
+        .flat_map(|it| &it.transitions)
+        .all(|(_, target)| steps.iter().any(|it| it.name.eq_ignore_ascii_case(target)));
+    if steps.is_empty() || !targets_known {
+        return None;
+    }
+
+    let initial_step = state_variable.initializer.as_ref()
+        .and_then(|initial| step_name(sources, initial))
+        .and_then(|initial| steps.iter().position(|it| it.name.eq_ignore_ascii_case(&initial)))
+        .unwrap_or_default();
+    Some(build_chart(steps, initial_step))
+}
+
+/// The transitions of a statement assigning the state, `Some(None)` if it assigns the state in any other way and
+/// `None` if it does not assign it at all
+fn transitions(sources: &LoadedSources, statement: &AstNode, state: &str) -> Option<Option<Vec<(String, String)>>> {
+    let mut writes = StateWrites { state, found: false };
+    writes.visit(statement);
+    if !writes.found {
+        return None;
+    }
+
+    if let Some(target) = state_target(sources, statement, state) {
+        return Some(Some(vec![(String::from("TRUE"), target)]));
+    }
+
+    let AstStatement::ControlStatement(AstControlStatement::If(IfStatement { blocks, else_block, .. })) = &statement.stmt else {
+        return Some(None);
+    };
+
+    let mut transitions = Vec::new();
+    for block in blocks {
+        let ([assignment], Some(condition)) = (block.body.as_slice(), source_text(sources, &block.condition.location)) else {
+            return Some(None);
+        };
+        let Some(target) = state_target(sources, assignment, state) else {
+            return Some(None);
+        };
+        transitions.push((condition, target));
+    }
+
+    match else_block.as_slice() {
+        [] => (),
+        [assignment] => match state_target(sources, assignment, state) {
+            Some(target) => transitions.push((String::from("TRUE"), target)),
+            None => return Some(None),
+        },
+        _ => return Some(None),
+    }
+    Some(Some(transitions))
+}
+
+/// The step a plain assignment of the state leads to
+fn state_target(sources: &LoadedSources, statement: &AstNode, state: &str) -> Option<String> {
+    match &statement.stmt {
+        AstStatement::Assignment(Assignment { left, right }) if is_state(left, state) => step_name(sources, right),
+        _ => None,
+    }
+}
+
+fn is_state(node: &AstNode, state: &str) -> bool {
+    node.get_flat_reference_name().is_some_and(|it| it.eq_ignore_ascii_case(state)) && node.get_parent_name_of_reference().is_none()
+}
+
+/// The name of the step of a case label or state value, e.g. `Running` for `State#Running` and `Step_2` for `2`
+fn step_name(sources: &LoadedSources, label: &AstNode) -> Option<String> {
+    let text = source_text(sources, &label.location)?;
+    let name = text.rsplit(['#', '.']).next()?.trim();
+
+    match name.chars().next()? {
+        first if first.is_ascii_digit() => Some(format!("Step_{name}")),
+        first if first.is_alphabetic() || first == '_' => Some(String::from(name)),
+        _ => None,
+    }
+}
+
+fn statement_text(sources: &LoadedSources, statement: &AstNode) -> Option<String> {
+    let text = source_text(sources, &statement.location)?;
+    match text.trim_end().ends_with(';') {
+        true => Some(text),
+        false => Some(format!("{text};")),
+    }
+}
+
+struct StateWrites<'a> {
+    state: &'a str,
+    found: bool,
+}
+
+impl AstVisitor for StateWrites<'_> {
+    fn visit_assignment(&mut self, stmt: &Assignment, _node: &AstNode) {
+        self.found |= is_state(&stmt.left, self.state);
+        self.visit(&stmt.right);
+    }
+}
+
+/// ST text as embedded in an SFC, e.g. as the condition of a transition
+fn inline_st(name: Option<String>, text: String) -> SInline {
+    SInline::new()
+        .maybe_attribute(String::from("name"), name)
+        .child(&SST::new().child(&SXhtml::new().attribute_str("xmlns", XHTML_NAMESPACE).content(text)))
+}
+
+fn connection_point_in(ref_local_id: i32, formal_parameter: Option<String>) -> SConnectionPointIn {
+    SConnectionPointIn::new().child(&SConnection::new()
+        .with_ref_id(ref_local_id)
+        .maybe_attribute(String::from("formalParameter"), formal_parameter)
+        .close())
+}
+
+/// Lays the steps out as a chart. A step with several transitions branches into them by a selection divergence, and
+/// every transition ends in a jump to the step it leads to
+fn build_chart(steps: Vec<Step>, initial_step: usize) -> SSfc {
+    let mut elements: Vec<Box<dyn IntoNode>> = Vec::new();
+    let mut last_id = 0;
+    let mut next_id = || {
+        last_id += 1;
+        last_id
+    };
+
+    for (index, step) in steps.into_iter().enumerate() {
+        let step_id = next_id();
+        let mut step_node = SStep::id(step_id).attribute(String::from("name"), step.name.clone());
+        if index == initial_step {
+            step_node = step_node.attribute_bool("initialStep", true);
+        }
+        elements.push(Box::new(step_node.child(&SConnectionPointOut::new().attribute_str("formalParameter", "sfc").close())));
+
+        if !step.actions.is_empty() {
+            let action = SAction::id(0).attribute_str("qualifier", "N").child(&inline_st(None, step.actions.join("\n")));
+            elements.push(Box::new(SActionBlock::id(next_id()).child(&connection_point_in(step_id, None)).child(&action)));
+        }
+
+        //the transitions start at the step, or at the branches of a divergence if there are several
+        let transition_count = step.transitions.len();
+        let sources: Vec<(i32, Option<String>)> = match transition_count {
+            0 => Vec::new(),
+            1 => vec![(step_id, None)],
+            _ => {
+                let divergence_id = next_id();
+                let branches: Vec<Box<dyn IntoNode>> = (0..transition_count)
+                    .map(|branch| Box::new(SConnectionPointOut::new().attribute(String::from("formalParameter"), branch.to_string()).close()) as Box<dyn IntoNode>)
+                    .collect();
+                elements.push(Box::new(SSelectionDivergence::id(divergence_id).child(&connection_point_in(step_id, None)).children(branches)));
+                (0..transition_count).map(|branch| (divergence_id, Some(branch.to_string()))).collect()
+            },
+        };
+
+        for (priority, ((condition, target), (source_id, formal_parameter))) in step.transitions.into_iter().zip(sources).enumerate() {
+            let transition_id = next_id();
+            let mut transition = STransition::id(transition_id).child(&connection_point_in(source_id, formal_parameter));
+            if transition_count > 1 {
+                transition = transition.attribute(String::from("priority"), (priority + 1).to_string());
+            }
+            let name = format!("{}_to_{target}", step.name);
+            elements.push(Box::new(transition.child(&SCondition::new().child(&inline_st(Some(name), condition)))));
+
+            elements.push(Box::new(SJumpStep::id(next_id())
+                .attribute(String::from("targetName"), target)
+                .child(&connection_point_in(transition_id, None))));
+        }
+    }
+    SSfc::new().children(elements)
+}
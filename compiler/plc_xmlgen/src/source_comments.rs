@@ -0,0 +1,356 @@
+use std::{ops::Range, path::Path, sync::Arc};
+
+use plc_diagnostics::diagnostics::Diagnostic;
+use plc_source::{
+    source_location::{CodeSpan, FileMarker, NewLines, SourceLocation, TextLocation},
+    SourceCode, SourceContainer,
+};
+use rustc_hash::FxHashMap;
+
+/// The text of the sources the compiler already loaded, by path. Comments, pragmas and bodies are read from here, so
+/// sources built in memory or decoded from another encoding need no file on disk
+#[derive(Debug, Clone, Default)]
+pub struct LoadedSources(FxHashMap<String, Arc<str>>);
+
+impl LoadedSources {
+    pub fn new<'a>(sources: impl IntoIterator<Item = &'a SourceCode>) -> Self {
+        let loaded = sources
+            .into_iter()
+            .filter_map(|source| {
+                let path = source.path.as_ref()?;
+                Some((path.to_string_lossy().into_owned(), Arc::from(source.source.as_str())))
+            })
+            .collect();
+        LoadedSources(loaded)
+    }
+
+    /// The text of the source at the given path, as loaded by the compiler or otherwise read from the file. The file
+    /// is decoded the way the compiler loads it, e.g. without a byte order mark, so the offsets of the AST point into
+    /// the text
+    fn read(&self, file_path: &str) -> Option<Arc<str>> {
+        if let Some(source) = self.0.get(file_path) {
+            return Some(source.clone());
+        }
+        Path::new(file_path).load_source(None).ok().map(|it| Arc::from(it.source))
+    }
+}
+
+/// The comment following a declaration on the same line, e.g. `speed : REAL; // in m/s` documents `speed`.
+///
+/// The lexer drops comments, so they are read back from the source file behind the declaration's span
+pub fn trailing_comment(sources: &LoadedSources, location: &SourceLocation) -> Option<String> {
+    let (FileMarker::File(file_path), CodeSpan::Range(range)) = (&location.file, &location.span) else {
+        return None; //internally generated declarations have no source to read from
+    };
+
+    let source = sources.read(file_path)?;
+    let rest_of_line = source.get(range.end.offset..)?.lines().next()?;
+    parse_comment(rest_of_line)
+}
+
+/// The comment lines right above a declaration, joined by line breaks, e.g.
+/// ```text
+/// // Drives the conveyor belt
+/// // and watches its motor current
+/// FUNCTION_BLOCK Conveyor
+/// ```
+/// Block comments may span several lines. Attribute pragmas between the comment and the declaration are skipped
+pub fn leading_comment(sources: &LoadedSources, location: &SourceLocation) -> Option<String> {
+    let (FileMarker::File(file_path), CodeSpan::Range(range)) = (&location.file, &location.span) else {
+        return None;
+    };
+
+    let source = sources.read(file_path)?;
+    let before = source.get(..range.start.offset)?;
+
+    let previous_lines = before.rfind('\n').map_or("", |end| &before[..end]);
+    let mut lines = Vec::new();
+    let mut in_block = false;
+    for line in previous_lines.lines().rev().map(str::trim) {
+        if in_block {
+            let (text, opened) = match line.find("(*") {
+                Some(start) => (&line[start + 2..], true),
+                None => (line, false),
+            };
+            lines.insert(0, String::from(text.trim_start_matches('*').trim()));
+            in_block = !opened;
+            continue;
+        }
+
+        if parse_attribute_pragma(line).is_some() && lines.is_empty() {
+            continue;
+        }
+
+        if line.ends_with("*)") && !line.contains("(*") { //the last line of a block comment spanning several lines
+            lines.insert(0, String::from(line.trim_end_matches("*)").trim_start_matches('*').trim()));
+            in_block = true;
+            continue;
+        }
+
+        if !(line.starts_with("//") || line.starts_with("(*")) {
+            break;
+        }
+        lines.insert(0, parse_comment(line).unwrap_or_default());
+    }
+
+    let text = lines.join("\n").trim().to_string();
+    if text.is_empty() {
+        return None;
+    }
+    Some(text)
+}
+
+/// The source text covered by a location, e.g. the body of an implementation. None if the location covers no text of
+/// its source, see [`checked_source_text`]
+pub fn source_text(sources: &LoadedSources, location: &SourceLocation) -> Option<String> {
+    checked_source_text(sources, location).ok().flatten()
+}
+
+/// The source text covered by a location, or why the location covers no text of its source, e.g. since the file
+/// changed after it was compiled, so the span ends within a multi-byte character
+pub fn checked_source_text(sources: &LoadedSources, location: &SourceLocation) -> Result<Option<String>, Diagnostic> {
+    let (FileMarker::File(file_path), CodeSpan::Range(range)) = (&location.file, &location.span) else {
+        return Ok(None);
+    };
+
+    let Some(source) = sources.read(file_path) else {
+        return Ok(None);
+    };
+    match source.get(range.start.offset..range.end.offset) {
+        Some(text) => Ok(Some(String::from(text))),
+        None => Err(Diagnostic::new(format!("bytes {}..{} of '{file_path}' are no span of its text, so the text is not exported", range.start.offset, range.end.offset))
+            .with_error_code("E127")
+            .with_location(location)),
+    }
+}
+
+/// The text of the first `//` or `(* *)` comment within the given line
+pub fn parse_comment(line: &str) -> Option<String> {
+    let line_comment = line.find("//");
+    let block_comment = line.find("(*");
+
+    let text = match (line_comment, block_comment) {
+        (Some(start), block) if block.is_none_or(|block| start < block) => line[start + 2..].trim_start_matches('/'), //also `///`
+        (_, Some(start)) => {
+            let inner = &line[start + 2..];
+            inner.find("*)").map_or(inner, |end| &inner[..end]).trim_start_matches('*') //also `(** *)`
+        },
+        _ => {
+            return None
+        }
+    };
+
+    let text = text.trim();
+    if text.is_empty() {
+        return None;
+    }
+    Some(String::from(text))
+}
+
+/// The attribute pragmas on the lines right above a declaration, e.g.
+/// ```text
+/// {attribute 'network_publish' := 'Output'}
+/// speed : REAL;
+/// ```
+/// yields `("network_publish", "Output")`. Attributes without a value yield an empty value.
+///
+/// Like comments, pragmas are dropped by the lexer and read back from the source file
+pub fn preceding_attributes(sources: &LoadedSources, location: &SourceLocation) -> Vec<(String, String)> {
+    preceding_attribute_pragmas(sources, location).into_iter().map(|(attribute, _)| attribute).collect()
+}
+
+/// The location of the attribute pragma with the given name right above a declaration, see [`preceding_attributes`]
+pub fn preceding_attribute_location(
+    sources: &LoadedSources,
+    location: &SourceLocation,
+    attribute_name: &str,
+) -> Option<SourceLocation> {
+    let (_, range) = preceding_attribute_pragmas(sources, location)
+        .into_iter()
+        .find(|((name, _), _)| name.eq_ignore_ascii_case(attribute_name))?;
+
+    let FileMarker::File(file_path) = &location.file else {
+        return None;
+    };
+    let newlines = NewLines::build(&sources.read(file_path)?);
+    Some(SourceLocation {
+        span: CodeSpan::Range(
+            TextLocation::from_offset(range.start, &newlines)..TextLocation::from_offset(range.end, &newlines),
+        ),
+        file: location.file,
+    })
+}
+
+/// The attribute pragmas right above a declaration, each with the bytes of the source it spans
+fn preceding_attribute_pragmas(
+    sources: &LoadedSources,
+    location: &SourceLocation,
+) -> Vec<((String, String), Range<usize>)> {
+    let (FileMarker::File(file_path), CodeSpan::Range(range)) = (&location.file, &location.span) else {
+        return Vec::new();
+    };
+
+    let Some(source) = sources.read(file_path) else {
+        return Vec::new();
+    };
+    let Some(before) = source.get(..range.start.offset) else {
+        return Vec::new();
+    };
+
+    //leave out the declaration's own line, then walk upwards as long as there are attribute pragmas
+    let mut attributes = Vec::new();
+    let mut end = before.rfind('\n').unwrap_or(0);
+    while end > 0 {
+        let start = before[..end].rfind('\n').map_or(0, |it| it + 1);
+        let line = &before[start..end];
+        let Some(attribute) = parse_attribute_pragma(line.trim()) else {
+            break;
+        };
+
+        let pragma_start = start + line.len() - line.trim_start().len();
+        attributes.insert(0, (attribute, pragma_start..pragma_start + line.trim().len()));
+        end = start.saturating_sub(1);
+    }
+    attributes
+}
+
+/// Parses `{attribute 'name'}` or `{attribute 'name' := 'value'}`
+pub fn parse_attribute_pragma(pragma: &str) -> Option<(String, String)> {
+    let inner = pragma.strip_prefix('{')?.strip_suffix('}')?.trim().strip_prefix("attribute")?.trim();
+
+    let (name, value) = match inner.split_once(":=") {
+        Some((name, value)) => (name.trim(), value.trim()),
+        None => (inner, ""),
+    };
+
+    let unquote = |text: &str| String::from(text.strip_prefix('\'').and_then(|it| it.strip_suffix('\'')).unwrap_or(text));
+    Some((unquote(name), unquote(value)))
+}
+
+/// How the comments inside exported ST bodies are written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BodyComments {
+    /// As in the source, including nested comments like `(* outer (* inner *) *)`
+    #[default]
+    Keep,
+
+    /// Left out, along with the lines holding nothing but a comment
+    Strip,
+
+    /// Rewritten in the comment syntax of the target IDE, see [`crate::xml_target::XmlTarget::format_body_comment`]
+    Convert,
+}
+
+/// The body with its `//`, `(* *)` and `/* */` comments stripped or rewritten by `convert`, which gets the text of
+/// each comment without its delimiters. Comment delimiters within string literals are left alone
+pub fn rewrite_body_comments(body: &str, handling: BodyComments, convert: &dyn Fn(&str) -> String) -> String {
+    if handling == BodyComments::Keep {
+        return String::from(body);
+    }
+
+    let bytes = body.as_bytes();
+    let mut rewritten = String::with_capacity(body.len());
+    let mut copied = 0;
+    let mut index = 0;
+    while index < bytes.len() {
+        let (text, end) = match bytes[index] {
+            quote @ (b'\'' | b'"') => {
+                index = string_end(bytes, index, quote);
+                continue;
+            },
+            b'/' if bytes[index + 1..].starts_with(b"/") => {
+                let end = body[index..].find('\n').map_or(body.len(), |it| index + it);
+                (&body[index + 2..end], end)
+            },
+            b'(' if bytes[index + 1..].starts_with(b"*") => block_comment(body, index),
+            b'/' if bytes[index + 1..].starts_with(b"*") => block_comment(body, index),
+            _ => {
+                index += 1;
+                continue;
+            },
+        };
+
+        rewritten.push_str(&body[copied..index]);
+        index = end;
+        match handling {
+            BodyComments::Convert => rewritten.push_str(&convert(text.trim())),
+            _ => {
+                //drop the whitespace around the comment, and its line if the comment was all there was on it
+                let line_start = rewritten.rfind('\n').map_or(0, |it| it + 1);
+                let rest_of_line = body[end..].find('\n').map_or(&body[end..], |it| &body[end..end + it]);
+                if rewritten[line_start..].trim().is_empty() && rest_of_line.trim().is_empty() && end + rest_of_line.len() < body.len() {
+                    rewritten.truncate(line_start);
+                    index = end + rest_of_line.len() + 1;
+                } else if rest_of_line.trim().is_empty() || rest_of_line.starts_with(char::is_whitespace) {
+                    rewritten.truncate(rewritten.trim_end_matches([' ', '\t']).len());
+                }
+            },
+        }
+        copied = index;
+    }
+    rewritten.push_str(&body[copied..]);
+    rewritten
+}
+
+/// The line breaks of exported ST bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEndings {
+    /// As in the source file
+    #[default]
+    Preserve,
+
+    /// `\n`
+    Lf,
+
+    /// `\r\n`, as Sysmac Studio and other Windows IDEs write them
+    Crlf,
+}
+
+/// The text with every `\r\n`, `\n` and lone `\r` line break written as the given line ending
+pub fn normalize_line_endings(text: &str, line_endings: LineEndings) -> String {
+    let line_break = match line_endings {
+        LineEndings::Preserve => {
+            return String::from(text)
+        },
+        LineEndings::Lf => "\n",
+        LineEndings::Crlf => "\r\n",
+    };
+    text.replace("\r\n", "\n").replace('\r', "\n").replace('\n', line_break)
+}
+
+/// The offset after the string literal starting at the given quote. `$` escapes the character following it
+pub(crate) fn string_end(bytes: &[u8], start: usize, quote: u8) -> usize {
+    let mut index = start + 1;
+    while index < bytes.len() && bytes[index] != quote {
+        index += if bytes[index] == b'$' { 2 } else { 1 };
+    }
+    (index + 1).min(bytes.len())
+}
+
+/// The text of the block comment starting at the given offset and the offset after it. Like the lexer, a comment
+/// nests the comments of its own kind, and an unterminated one lasts to the end of the body
+pub(crate) fn block_comment(body: &str, start: usize) -> (&str, usize) {
+    let bytes = body.as_bytes();
+    let (open, close): (&[u8], &[u8]) = match &bytes[start..start + 2] {
+        b"(*" => (b"(*", b"*)"),
+        _ => (b"/*", b"*/"),
+    };
+
+    let mut depth = 0;
+    let mut index = start;
+    while index + 1 < bytes.len() {
+        match &bytes[index..index + 2] {
+            pair if pair == open => depth += 1,
+            pair if pair == close => depth -= 1,
+            _ => {
+                index += 1;
+                continue;
+            },
+        }
+        index += 2;
+        if depth == 0 {
+            return (&body[start + 2..index - 2], index);
+        }
+    }
+    (&body[start + 2..], body.len())
+}
@@ -0,0 +1,119 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{CompilationUnit, LinkageType};
+use serde::Serialize;
+
+use super::identifier_rules::Rename;
+use super::serializer::*;
+use super::source_comments::{source_text, BodyComments};
+use super::xml_gen::GenerationParameters;
+
+pub const SOURCE_MAP_EXTENSION: &str = "sourcemap.json";
+
+/// A body of the document and the ST source it was exported from
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceMapEntry {
+    /// The POU the body belongs to as named in the document, e.g. `Motor.Start` for a method or action
+    pub pou: String,
+    pub file: String,
+    /// The line of the source file the body starts at, counted from 1
+    pub line: usize,
+    /// The line of the source file each line of the body was exported from. Empty if the lines of the body don't
+    /// correspond to the source, i.e. it was printed from the AST or its comments were stripped
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub lines: Vec<usize>,
+}
+
+/// The source map belonging to a generated document, e.g. `out.xml` comes with `out.sourcemap.json`
+pub fn source_map_path(document_path: &Path) -> PathBuf {
+    document_path.with_extension(SOURCE_MAP_EXTENSION)
+}
+
+/// Maps every body of the populated tree to the file and lines of the implementation it was exported from, in
+/// document order. Bodies without an implementation in the sources, like the instances of generic POUs, are left out
+pub fn collect_source_map(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, output_root: &Node) -> Vec<SourceMapEntry> {
+    let mut bodies: Vec<String> = Vec::new();
+    let elements = output_root.find(GLOBAL_NAMESPACE_PATH).map(|it| it.children.as_slice()).unwrap_or_default();
+    for element in elements {
+        collect_bodies(element, None, &mut bodies);
+    }
+
+    let exact = !generation_parameters.canonical_bodies && generation_parameters.body_comments != BodyComments::Strip;
+    let implementations: Vec<_> = units.iter()
+        .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+        .flat_map(|it| it.implementations.iter())
+        .filter(|it| it.linkage != LinkageType::External)
+        .collect();
+
+    bodies.into_iter()
+        .filter_map(|pou| {
+            let implementation = implementations.iter().find(|it| it.name.eq_ignore_ascii_case(&pou))?;
+            let text = source_text(&generation_parameters.sources, &implementation.location)?;
+            let line = implementation.location.get_line_plus_one();
+
+            Some(SourceMapEntry {
+                pou,
+                file: String::from(implementation.location.get_file_name()?),
+                line,
+                lines: match exact {
+                    true => (line..line + text.lines().count()).collect(),
+                    false => Vec::new(),
+                },
+            })
+        })
+        .collect()
+}
+
+/// The qualified names of the POUs, methods and actions with a body, e.g. `Motor.Start`
+fn collect_bodies(node: &Node, parent: Option<&str>, bodies: &mut Vec<String>) {
+    let Some(name) = node.attributes.get("name") else {
+        return;
+    };
+
+    let qualified = match (node.name.as_str(), parent) {
+        ("Program" | "Function" | "FunctionBlock" | "Class", None) => name.clone(),
+        ("Method" | "action", Some(parent)) => format!("{parent}.{name}"),
+        _ => {
+            return
+        }
+    };
+
+    if node.name != "Class" {
+        bodies.push(qualified.clone());
+    }
+
+    if parent.is_none() {
+        for child in &node.children {
+            collect_nested_bodies(child, &qualified, bodies);
+        }
+    }
+}
+
+/// Looks for the methods and actions of a POU within its containers, e.g. `<actions>`
+fn collect_nested_bodies(node: &Node, parent: &str, bodies: &mut Vec<String>) {
+    match node.name.as_str() {
+        "Method" | "action" => collect_bodies(node, Some(parent), bodies),
+        _ => {
+            for child in &node.children {
+                collect_nested_bodies(child, parent, bodies);
+            }
+        }
+    }
+}
+
+/// Names the entries the way the document does after the identifiers the target does not accept were renamed
+pub fn rename_source_map(entries: &mut [SourceMapEntry], renames: &[Rename]) {
+    for entry in entries.iter_mut() {
+        entry.pou = entry.pou.split('.')
+            .map(|segment| renames.iter().find(|it| it.original.eq_ignore_ascii_case(segment)).map_or(segment, |it| it.renamed.as_str()))
+            .collect::<Vec<_>>()
+            .join(".");
+    }
+}
+
+/// Writes the source map as JSON, so errors the IDE reports for a line of a body can be traced back to the ST source
+pub fn write_source_map(entries: &[SourceMapEntry], output_path: &Path) -> Result<(), Error> {
+    let json = serde_json::to_string_pretty(entries).map_err(Error::other)?;
+    fs::write(output_path, json)
+}
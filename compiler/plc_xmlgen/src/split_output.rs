@@ -0,0 +1,117 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use super::archive::entry_name;
+use super::serializer::*;
+use super::xml_gen::{read_xml_file, write_formatted_xml_file, write_xml_file};
+use super::xml_target::XmlTarget;
+
+pub const SPLIT_OUTPUT_EXTENSION: &str = "split";
+pub const SPLIT_INDEX_NAME: &str = "index.xml";
+pub const SPLIT_GLOBALS_NAME: &str = "globals.xml";
+
+/// The folder the split documents of a generated document are written to, e.g. `out.xml` is split into `out.split`
+pub fn split_output_path(document_path: &Path) -> PathBuf {
+    document_path.with_extension(SPLIT_OUTPUT_EXTENSION)
+}
+
+/// Writes every data type and POU of the populated tree as a document of its own, so each can be reviewed and imported
+/// by itself. Every part is post processed by the target and formatted like the whole document.
+///
+/// The folder has the following layout:
+/// ```text
+/// out.split
+/// ├── index.xml           lists the document, the target and every part with its kind and name
+/// ├── globals.xml         the configurations with the globals
+/// ├── types/Motor.xml     a document per data type
+/// └── pous/Main.xml       a document per POU, with its methods and actions
+/// ```
+pub fn write_split_output(target: &dyn XmlTarget, options: &SerializerOptions, output_root: &Node, document_path: &Path) -> Result<PathBuf, Error> {
+    let folder_path = split_output_path(document_path);
+    fs::create_dir_all(folder_path.join("types"))?;
+    fs::create_dir_all(folder_path.join("pous"))?;
+
+    let mut index = SManifest::new()
+        .attribute_str("schemaVersion", "1")
+        .attribute(String::from("target"), String::from(target.name()))
+        .attribute(String::from("document"), entry_name(document_path)?);
+
+    let elements = output_root.find(GLOBAL_NAMESPACE_PATH).map(|it| it.children.as_slice()).unwrap_or_default();
+    for element in elements {
+        let Some(name) = element.attributes.get("name") else {
+            continue;
+        };
+
+        let (kind, folder) = match element.name.as_str() {
+            "DataTypeDecl" => ("dataType", "types"),
+            _ => ("pou", "pous"),
+        };
+
+        let part_name = format!("{folder}/{name}.xml");
+        write_part(target, options, split_document(output_root, Some(element)), &folder_path.join(&part_name))?;
+
+        index = index.child(&SManifestFile::new()
+            .attribute(String::from("path"), part_name)
+            .attribute_str("kind", kind)
+            .attribute(String::from("name"), name.clone())
+            .close());
+    }
+
+    write_part(target, options, split_document(output_root, None), &folder_path.join(SPLIT_GLOBALS_NAME))?;
+    index = index.child(&SManifestFile::new()
+        .attribute_str("path", SPLIT_GLOBALS_NAME)
+        .attribute_str("kind", "globals")
+        .close());
+
+    let index_path = folder_path.join(SPLIT_INDEX_NAME);
+    write_formatted_xml_file(&index_path, &[], options, index.inner())?;
+    Ok(index_path)
+}
+
+/// The skeleton of the document holding only the given data type or POU, or only the globals if none is given
+fn split_document(output_root: &Node, element: Option<&Node>) -> Node {
+    let mut document = output_root.clone();
+
+    if let Some(namespace) = document.find_mut(GLOBAL_NAMESPACE_PATH) {
+        namespace.children = element.into_iter().cloned().collect();
+    }
+
+    if let Some(instances) = document.find_mut(INSTANCES) && element.is_some() {
+        instances.children.clear();
+    }
+    document
+}
+
+fn write_part(target: &dyn XmlTarget, options: &SerializerOptions, mut document: Node, part_path: &Path) -> Result<(), Error> {
+    target.post_process(&mut document);
+    write_formatted_xml_file(&part_path.to_path_buf(), &target.processing_instructions(), options, document)
+}
+
+/// Copies the folder the document was split into next to the copy of the document, naming the copy in the index
+pub fn copy_split_output(document_path: &Path, output_path: &Path) -> Result<(), Error> {
+    let folder_path = split_output_path(document_path);
+    if !folder_path.is_dir() {
+        return Ok(());
+    }
+
+    let copied_folder_path = split_output_path(output_path);
+    copy_folder(&folder_path, &copied_folder_path)?;
+
+    let index_path = copied_folder_path.join(SPLIT_INDEX_NAME);
+    let mut index = read_xml_file(&index_path)?;
+    index.attributes.insert(String::from("document"), entry_name(output_path)?);
+    write_xml_file(&index_path, index)
+}
+
+fn copy_folder(from: &Path, to: &Path) -> Result<(), Error> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let entry = entry?;
+        match entry.file_type()?.is_dir() {
+            true => copy_folder(&entry.path(), &to.join(entry.file_name()))?,
+            false => {
+                fs::copy(entry.path(), to.join(entry.file_name()))?;
+            },
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,274 @@
+use plc_ast::{
+    ast::{
+        Assignment, AstNode, AstStatement, BinaryExpression, CallStatement, DirectAccess, DirectAccessType, JumpStatement, MultipliedStatement,
+        Operator, RangeStatement, ReferenceAccess, ReferenceExpr, UnaryExpression,
+    },
+    control_statements::{AstControlStatement, CaseStatement, ConditionalBlock, ForLoopStatement, IfStatement, LoopStatement},
+    literals::AstLiteral,
+};
+use plc_source::source_location::SourceLocation;
+
+use super::literal_format::{format_iec_address, format_iec_literal, RealFormat};
+
+const INDENT: &str = "    ";
+
+/// The spelling of keywords like `IF` and `END_FOR` and of word operators like `AND` in printed bodies
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeywordCase {
+    #[default]
+    Upper,
+
+    Lower,
+}
+
+/// The spelling of the identifiers of printed bodies. ST ignores the case of identifiers, so each is the same name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IdentifierCase {
+    /// As written in the source
+    #[default]
+    AsWritten,
+
+    Upper,
+
+    Lower,
+}
+
+/// Writes statements back as ST from their AST, one statement per line and nested blocks indented by four spaces, e.g.
+/// ```text
+/// IF start THEN
+///     count := count + 1;
+/// END_IF
+/// ```
+/// Unlike the source text, the result does not depend on the file the statements were parsed from, so it is the
+/// same for in-memory or preprocessed sources. Comments and the spelling of the source, e.g. the case of keywords,
+/// are not kept. Literals are written in the IEC short form, reals in the given format
+pub struct StPrinter<'a> {
+    real_format: &'a RealFormat,
+
+    /// The name replacing the name at the given location, e.g. the instance called in place of a generic POU
+    rename: &'a dyn Fn(&SourceLocation) -> Option<String>,
+
+    /// The name a function called at the given location is written with, if it is called by another name, e.g. the
+    /// target's name of a standard function
+    function_name: &'a dyn Fn(&str, &SourceLocation) -> Option<String>,
+
+    keyword_case: KeywordCase,
+    identifier_case: IdentifierCase,
+}
+
+impl<'a> StPrinter<'a> {
+    pub fn new(
+        real_format: &'a RealFormat,
+        rename: &'a dyn Fn(&SourceLocation) -> Option<String>,
+        function_name: &'a dyn Fn(&str, &SourceLocation) -> Option<String>,
+    ) -> Self {
+        StPrinter { real_format, rename, function_name, keyword_case: KeywordCase::default(), identifier_case: IdentifierCase::default() }
+    }
+
+    /// Spells keywords and identifiers in the given case
+    pub fn with_case(self, keyword_case: KeywordCase, identifier_case: IdentifierCase) -> Self {
+        StPrinter { keyword_case, identifier_case, ..self }
+    }
+
+    fn keyword(&self, keyword: &str) -> String {
+        match self.keyword_case {
+            KeywordCase::Upper => keyword.to_uppercase(),
+            KeywordCase::Lower => keyword.to_lowercase(),
+        }
+    }
+
+    fn identifier(&self, name: &str) -> String {
+        match self.identifier_case {
+            IdentifierCase::AsWritten => String::from(name),
+            IdentifierCase::Upper => name.to_uppercase(),
+            IdentifierCase::Lower => name.to_lowercase(),
+        }
+    }
+
+    /// The statements as ST, without a trailing line break
+    pub fn print_statements(&self, statements: &[AstNode]) -> String {
+        let mut lines = Vec::new();
+        self.write_statements(statements, 0, &mut lines);
+        lines.join("\n")
+    }
+
+    fn write_statements(&self, statements: &[AstNode], depth: usize, lines: &mut Vec<String>) {
+        for statement in statements.iter().filter(|it| !it.is_empty_statement()) {
+            self.write_statement(statement, depth, lines);
+        }
+    }
+
+    fn write_statement(&self, statement: &AstNode, depth: usize, lines: &mut Vec<String>) {
+        let indent = INDENT.repeat(depth);
+        let mut line = |text: String| lines.push(format!("{indent}{text}"));
+
+        match &statement.stmt {
+            AstStatement::ControlStatement(control) => self.write_control_statement(control, depth, lines),
+            AstStatement::LabelStatement(label) => line(format!("{}:", self.identifier(&label.name))),
+            AstStatement::ExitStatement(_) => line(format!("{};", self.keyword("EXIT"))),
+            AstStatement::ContinueStatement(_) => line(format!("{};", self.keyword("CONTINUE"))),
+            AstStatement::ReturnStatement(_) => line(format!("{};", self.keyword("RETURN"))), //only CFC returns on a condition
+            AstStatement::JumpStatement(JumpStatement { condition, target }) => {
+                //jumps only come from CFC, which ST writes as a conditional jump to the label
+                line(format!("{} {} {}", self.keyword("IF"), self.print_expression(condition), self.keyword("THEN")));
+                lines.push(format!("{indent}{INDENT}{} {};", self.keyword("JMP"), self.print_expression(target)));
+                lines.push(format!("{indent}{}", self.keyword("END_IF")));
+            },
+            _ => line(format!("{};", self.print_expression(statement))),
+        }
+    }
+
+    fn write_control_statement(&self, control: &AstControlStatement, depth: usize, lines: &mut Vec<String>) {
+        let indent = INDENT.repeat(depth);
+
+        match control {
+            AstControlStatement::If(IfStatement { blocks, else_block, .. }) => {
+                for (index, ConditionalBlock { condition, body }) in blocks.iter().enumerate() {
+                    let keyword = if index == 0 { "IF" } else { "ELSIF" };
+                    lines.push(format!("{indent}{} {} {}", self.keyword(keyword), self.print_expression(condition), self.keyword("THEN")));
+                    self.write_statements(body, depth + 1, lines);
+                }
+                self.write_else(else_block, depth, lines);
+                lines.push(format!("{indent}{}", self.keyword("END_IF")));
+            },
+            AstControlStatement::ForLoop(ForLoopStatement { counter, start, end, by_step, body, .. }) => {
+                let by_step = by_step.as_ref().map(|it| format!(" {} {}", self.keyword("BY"), self.print_expression(it))).unwrap_or_default();
+                lines.push(format!(
+                    "{indent}{} {} := {} {} {}{by_step} {}",
+                    self.keyword("FOR"),
+                    self.print_expression(counter),
+                    self.print_expression(start),
+                    self.keyword("TO"),
+                    self.print_expression(end),
+                    self.keyword("DO")
+                ));
+                self.write_statements(body, depth + 1, lines);
+                lines.push(format!("{indent}{}", self.keyword("END_FOR")));
+            },
+            AstControlStatement::WhileLoop(LoopStatement { condition, body, .. }) => {
+                lines.push(format!("{indent}{} {} {}", self.keyword("WHILE"), self.print_expression(condition), self.keyword("DO")));
+                self.write_statements(body, depth + 1, lines);
+                lines.push(format!("{indent}{}", self.keyword("END_WHILE")));
+            },
+            AstControlStatement::RepeatLoop(LoopStatement { condition, body, .. }) => {
+                lines.push(format!("{indent}{}", self.keyword("REPEAT")));
+                self.write_statements(body, depth + 1, lines);
+                lines.push(format!("{indent}{} {}", self.keyword("UNTIL"), self.print_expression(condition)));
+                lines.push(format!("{indent}{}", self.keyword("END_REPEAT")));
+            },
+            AstControlStatement::Case(CaseStatement { selector, case_blocks, else_block, .. }) => {
+                lines.push(format!("{indent}{} {} {}", self.keyword("CASE"), self.print_expression(selector), self.keyword("OF")));
+                for ConditionalBlock { condition, body } in case_blocks {
+                    lines.push(format!("{indent}{}:", self.print_expression(condition)));
+                    self.write_statements(body, depth + 1, lines);
+                }
+                self.write_else(else_block, depth, lines);
+                lines.push(format!("{indent}{}", self.keyword("END_CASE")));
+            },
+        }
+    }
+
+    fn write_else(&self, else_block: &[AstNode], depth: usize, lines: &mut Vec<String>) {
+        if !else_block.is_empty() {
+            lines.push(format!("{}{}", INDENT.repeat(depth), self.keyword("ELSE")));
+            self.write_statements(else_block, depth + 1, lines);
+        }
+    }
+
+    /// An expression as ST. The parentheses of the source are part of the AST, so none are added
+    pub fn print_expression(&self, expression: &AstNode) -> String {
+        if let Some(name) = (self.rename)(&expression.location) {
+            return name;
+        }
+
+        match &expression.stmt {
+            AstStatement::Literal(AstLiteral::Integer(value)) => value.to_string(),
+            AstStatement::Literal(AstLiteral::Null) => self.keyword("NULL"),
+            AstStatement::Literal(AstLiteral::Array(array)) => {
+                format!("[{}]", array.elements.as_deref().map(|it| self.print_expression(it)).unwrap_or_default())
+            },
+            AstStatement::Literal(literal) => format_iec_literal(literal, self.real_format),
+            AstStatement::MultipliedStatement(MultipliedStatement { multiplier, element }) => {
+                format!("{multiplier}({})", self.print_expression(element))
+            },
+            AstStatement::Identifier(name) => self.identifier(name),
+            AstStatement::ReferenceExpr(reference) => self.print_reference(reference),
+            AstStatement::Super(deref) => match deref {
+                Some(_) => format!("{}^", self.keyword("SUPER")),
+                None => self.keyword("SUPER"),
+            },
+            AstStatement::This => self.keyword("THIS"),
+            AstStatement::DirectAccess(DirectAccess { access, index }) => {
+                format!("%{}{}", direct_access_prefix(access), self.print_expression(index))
+            },
+            AstStatement::HardwareAccess(hardware_access) => format_iec_address(hardware_access),
+            AstStatement::BinaryExpression(BinaryExpression { operator, left, right }) => {
+                format!("{} {} {}", self.print_expression(left), self.keyword(&operator.to_string()), self.print_expression(right))
+            },
+            AstStatement::UnaryExpression(UnaryExpression { operator: Operator::Not, value }) => {
+                format!("{} {}", self.keyword("NOT"), self.print_expression(value))
+            },
+            AstStatement::UnaryExpression(UnaryExpression { operator, value }) => {
+                format!("{operator}{}", self.print_expression(value))
+            },
+            AstStatement::ExpressionList(expressions) => {
+                expressions.iter().map(|it| self.print_expression(it)).collect::<Vec<_>>().join(", ")
+            },
+            AstStatement::ParenExpression(inner) => format!("({})", self.print_expression(inner)),
+            AstStatement::RangeStatement(RangeStatement { start, end }) => {
+                format!("{}..{}", self.print_expression(start), self.print_expression(end))
+            },
+            AstStatement::VlaRangeStatement => String::from("*"),
+            AstStatement::Assignment(assignment) => self.print_assignment(assignment, ":="),
+            AstStatement::OutputAssignment(assignment) => self.print_assignment(assignment, "=>"),
+            AstStatement::RefAssignment(assignment) => self.print_assignment(assignment, &self.keyword("REF=")),
+            AstStatement::CallStatement(CallStatement { operator, parameters }) => format!(
+                "{}({})",
+                self.print_callee(operator),
+                parameters.as_deref().map(|it| self.print_expression(it)).unwrap_or_default()
+            ),
+            AstStatement::CaseCondition(condition) => self.print_expression(condition),
+            //no ST of their own: defaults, allocations and the statements written by write_statement
+            _ => String::new(),
+        }
+    }
+
+    /// The called POU, by the name it is called with if the call of a plain function name is renamed
+    fn print_callee(&self, operator: &AstNode) -> String {
+        let function = operator.get_flat_reference_name().filter(|_| operator.get_parent_name_of_reference().is_none());
+
+        match (self.rename)(&operator.location).or_else(|| function.and_then(|it| (self.function_name)(it, &operator.location))) {
+            Some(name) => name,
+            None => self.print_expression(operator),
+        }
+    }
+
+    fn print_assignment(&self, Assignment { left, right }: &Assignment, operator: &str) -> String {
+        format!("{} {operator} {}", self.print_expression(left), self.print_expression(right))
+    }
+
+    fn print_reference(&self, ReferenceExpr { access, base }: &ReferenceExpr) -> String {
+        let base = base.as_deref().map(|it| self.print_expression(it));
+
+        match (access, base) {
+            (ReferenceAccess::Global(member), _) => format!(".{}", self.print_expression(member)),
+            (ReferenceAccess::Member(member), None) => self.print_expression(member),
+            (ReferenceAccess::Member(member), Some(base)) => format!("{base}.{}", self.print_expression(member)),
+            (ReferenceAccess::Index(index), base) => format!("{}[{}]", base.unwrap_or_default(), self.print_expression(index)),
+            (ReferenceAccess::Cast(member), base) => format!("{}#{}", base.unwrap_or_default(), self.print_expression(member)),
+            (ReferenceAccess::Deref, base) => format!("{}^", base.unwrap_or_default()),
+            (ReferenceAccess::Address, base) => format!("{}({})", self.keyword("REF"), base.unwrap_or_default()),
+        }
+    }
+}
+
+fn direct_access_prefix(access: &DirectAccessType) -> &'static str {
+    match access {
+        DirectAccessType::Bit => "X",
+        DirectAccessType::Byte => "B",
+        DirectAccessType::Word => "W",
+        DirectAccessType::DWord => "D",
+        DirectAccessType::LWord => "L",
+        DirectAccessType::Template => "*",
+    }
+}
@@ -0,0 +1,133 @@
+use std::{fs::File, io::{BufWriter, Error, Write}, path::Path};
+
+use plc_ast::ast::CompilationUnit;
+use plc_diagnostics::diagnostics::Diagnostic;
+
+use super::cross_reference::collect_referenced_globals;
+use super::emission_order::EmissionOrder;
+use super::identifier_rules::{apply_renames, collect_declared_names, plan_renames, IdentifierRules, Rename};
+use super::serializer::*;
+use super::source_map::{collect_source_map, rename_source_map, source_map_path, write_source_map};
+use super::xml_gen::{
+    add_provenance_comment, collect_unit_declared_names, generate_custom_types, generate_globals, generate_interfaces, generate_pous, recurse_write_xml, start_xml_document,
+    write_end_element, write_start_element, GenerationParameters, InheritedVariables, ProjectEnums,
+};
+use super::xml_target::XmlTarget;
+
+/// Why the document of the target has to be built as a whole before it is written, if it has to
+pub fn streaming_obstacle(generation_parameters: &GenerationParameters, target: &dyn XmlTarget) -> Option<String> {
+    if !target.supports_streaming() {
+        return Some(format!("the {} target rewrites the whole document", target.name()));
+    }
+
+    if generation_parameters.split_output {
+        return Some(String::from("splitting the document needs all of it"));
+    }
+
+    if generation_parameters.emission_order != EmissionOrder::Source {
+        return Some(String::from("reordering the declarations needs all of them"));
+    }
+    None
+}
+
+/// Writes the document of the target while generating it, one compilation unit at a time. Only the data types,
+/// interfaces and POUs of the current unit are held in memory, along with the configurations of all units since units
+/// may share one. The document is the same as the one built as a whole.
+///
+/// The identifiers the target does not accept are renamed across the whole document, so if the target has rules for
+/// identifiers, the names the units declare are collected from their declarations up front
+pub fn stream_project(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, units: &Vec<&CompilationUnit>, output_path: &Path, diagnostics: &mut Vec<Diagnostic>) -> Result<Vec<Rename>, Error> {
+    let enums = ProjectEnums::collect(units);
+    let inherited = match generation_parameters.flatten_inheritance && !target.supports_inheritance() {
+        true => InheritedVariables::collect(units),
+        false => InheritedVariables::default(),
+    };
+    let referenced_globals = generation_parameters.referenced_globals_only.then(|| collect_referenced_globals(units));
+
+    let source_units: Vec<&CompilationUnit> = units.iter()
+        .copied()
+        .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+        .collect();
+    let mut template = target.template();
+    add_provenance_comment(generation_parameters, units, &mut template);
+    let mut instances_root = Node::new_str("Project").children(template.children.iter()
+        .filter(|it| it.name == INSTANCES)
+        .map(|it| Box::new(it.clone()) as Box<dyn IntoNode>)
+        .collect());
+    for current_unit in &source_units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+        generate_globals(generation_parameters, target, &enums, current_unit, unit_name, referenced_globals.as_deref(), &mut instances_root)?;
+    }
+
+    let rules = IdentifierRules {
+        reserved_words: target.reserved_words(),
+        max_length: target.max_identifier_length(),
+        ascii_only: generation_parameters.ascii_identifiers,
+    };
+    let renames = match rules == IdentifierRules::default() {
+        true => Vec::new(),
+        false => {
+            //in document order, like the names collected from the whole tree
+            let mut declared_names = Vec::new();
+            for current_unit in &source_units {
+                collect_unit_declared_names(generation_parameters, target, &enums, &inherited, current_unit, &mut declared_names);
+            }
+            collect_declared_names(&instances_root, &mut declared_names);
+            plan_renames(&declared_names, &rules)
+        },
+    };
+    apply_renames(&mut instances_root, &renames);
+
+    let mut source_map = Vec::new();
+    let mut writer = start_xml_document(BufWriter::new(File::create(output_path)?), &target.processing_instructions(), &generation_parameters.serializer_options)?;
+    write_start_element(&mut writer, &template)?;
+
+    for element in std::mem::take(&mut template.children) {
+        match element.name.as_str() {
+            TYPES => {
+                write_start_element(&mut writer, &element)?;
+                for child in element.children {
+                    if child.name != GLOBAL_NAMESPACE {
+                        recurse_write_xml(&mut writer, child)?;
+                        continue;
+                    }
+
+                    write_start_element(&mut writer, &child)?;
+                    for current_unit in &source_units {
+                        let mut unit_root = Node::new_str("Project").child(&STypes::new().child(&SGlobalNamespace::new()));
+                        generate_custom_types(generation_parameters, target, &enums, current_unit, &mut unit_root)?;
+                        generate_interfaces(generation_parameters, target, &enums, current_unit, &mut unit_root)?;
+                        generate_pous(generation_parameters, target, &enums, &inherited, current_unit, &mut unit_root, diagnostics)?;
+                        if generation_parameters.source_map {
+                            source_map.extend(collect_source_map(generation_parameters, units, &unit_root));
+                        }
+                        apply_renames(&mut unit_root, &renames);
+
+                        let declarations = unit_root.children.into_iter()
+                            .flat_map(|it| it.children)
+                            .flat_map(|it| it.children);
+                        for declaration in declarations {
+                            recurse_write_xml(&mut writer, declaration)?;
+                        }
+                    }
+                    write_end_element(&mut writer)?;
+                }
+                write_end_element(&mut writer)?;
+            },
+            INSTANCES => {
+                for instances in std::mem::take(&mut instances_root.children) {
+                    recurse_write_xml(&mut writer, instances)?;
+                }
+            },
+            _ => recurse_write_xml(&mut writer, element)?,
+        }
+    }
+    write_end_element(&mut writer)?;
+    writer.into_inner().flush()?;
+
+    if generation_parameters.source_map {
+        rename_source_map(&mut source_map, &renames);
+        write_source_map(&source_map, &source_map_path(output_path))?;
+    }
+    Ok(renames)
+}
@@ -0,0 +1,106 @@
+use std::{fs, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{AstStatement, CompilationUnit, LinkageType, NetworkPublishMode, VariableBlockType};
+use plc_source::source_location::CodeSpan;
+
+use super::tag_list::declared_type_name;
+use super::xml_gen::{format_initial_value, variable_network_publish, GenerationParameters, ProjectEnums};
+use super::xml_target::{OmronTarget, XmlTarget, RETAIN_ATTRIBUTE};
+
+pub const SYSMAC_CSV_EXTENSION: &str = "csv";
+
+/// The columns of the global variable table as Sysmac Studio imports it
+pub const SYSMAC_CSV_HEADER: [&str; 8] = ["Name", "Data Type", "Initial Value", "AT", "Retain", "Constant", "Network Publish", "Comment"];
+
+/// The variable table belonging to a generated document, e.g. `out.xml` comes with `out.csv`
+pub fn sysmac_csv_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(SYSMAC_CSV_EXTENSION)
+}
+
+/// Writes the global variables of all units as a Sysmac Studio variable table
+pub fn generate_sysmac_csv(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, output_path: &Path) -> Result<(), Error> {
+    fs::write(output_path, format_sysmac_csv(generation_parameters, units))
+}
+
+pub fn format_sysmac_csv(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>) -> String {
+    let mut output = format_csv_row(SYSMAC_CSV_HEADER.iter().map(|it| String::from(*it)).collect());
+    let enums = ProjectEnums::collect(units);
+
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_global in &current_unit.global_vars {
+            if current_global.linkage == LinkageType::External || current_global.kind != VariableBlockType::Global {
+                continue; //same as the XML globals: only the users own globals
+            }
+
+            for current_variable in &current_global.variables {
+                if current_variable.location.span == CodeSpan::None {
+                    continue; //discard compiler interally generated variables
+                }
+
+                let Some(mut type_name) = declared_type_name(&current_variable.data_type_declaration) else {
+                    continue; //every variable must have a typename
+                };
+
+                let network_publish = variable_network_publish(&generation_parameters.sources, current_global, current_variable);
+                if network_publish != NetworkPublishMode::DoNotPublish && let Some(published_type) = generation_parameters.published_bit_string_type(&OmronTarget, &type_name) {
+                    type_name = published_type;
+                }
+
+                let initial_value = format_initial_value(current_variable, generation_parameters, &OmronTarget, &enums, None).unwrap_or_default();
+
+                let address = match current_variable.address.as_ref().map(|it| &it.stmt) {
+                    Some(AstStatement::Literal(literal)) => literal.to_string(),
+                    Some(AstStatement::HardwareAccess(hardware_access)) => OmronTarget.format_address(hardware_access).unwrap_or_default(),
+                    _ => String::new(),
+                };
+
+                output.push_str(&format_csv_row(vec![
+                    current_variable.name.clone(),
+                    OmronTarget.map_type_name(generation_parameters, &type_name),
+                    initial_value,
+                    address,
+                    format_sysmac_bool(OmronTarget.retention_attributes(current_global).contains(&RETAIN_ATTRIBUTE)),
+                    format_sysmac_bool(current_global.constant),
+                    format_sysmac_network_publish(network_publish),
+                    String::new(),
+                ]));
+            }
+        }
+    }
+    output
+}
+
+fn format_sysmac_network_publish(mode: NetworkPublishMode) -> String {
+    let sysmac_name = match mode {
+        NetworkPublishMode::DoNotPublish => "Do not publish",
+        NetworkPublishMode::PublishOnly => "Publish Only",
+        NetworkPublishMode::Input => "Input",
+        NetworkPublishMode::Output => "Output",
+    };
+    String::from(sysmac_name)
+}
+
+fn format_sysmac_bool(value: bool) -> String {
+    String::from(if value { "TRUE" } else { "FALSE" })
+}
+
+/// Joins the fields of a row, quoting those which contain separators, quotes or line breaks
+pub(crate) fn format_csv_row(fields: Vec<String>) -> String {
+    let quoted: Vec<String> = fields.into_iter().map(|field| {
+        if field.contains([',', '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        }
+
+        else {
+            field
+        }
+    })
+    .collect();
+    format!("{}\r\n", quoted.join(","))
+}
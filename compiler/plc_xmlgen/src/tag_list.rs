@@ -0,0 +1,209 @@
+use std::{fs::File, io::Error, path::{Path, PathBuf}};
+
+use plc_ast::ast::{AstStatement, CompilationUnit, DataType, DataTypeDeclaration, LinkageType, PouType, Variable, VariableBlock};
+use plc_source::source_location::CodeSpan;
+use zip::{write::SimpleFileOptions, ZipWriter};
+
+use super::serializer::*;
+use super::source_comments::{trailing_comment, LoadedSources};
+use super::xml_gen::write_xml_document;
+
+pub const TAG_LIST_EXTENSION: &str = "xlsx";
+pub const TAG_LIST_HEADER: [&str; 7] = ["Name", "Type", "Scope", "POU", "Initial Value", "Address", "Documentation"];
+
+const SPREADSHEET_NAMESPACE: &str = "http://schemas.openxmlformats.org/spreadsheetml/2006/main";
+const RELATIONSHIPS_NAMESPACE: &str = "http://schemas.openxmlformats.org/package/2006/relationships";
+const DOCUMENT_RELATIONSHIPS_NAMESPACE: &str = "http://schemas.openxmlformats.org/officeDocument/2006/relationships";
+
+/// A single row of the tag list
+#[derive(Debug, Clone, PartialEq)]
+pub struct Tag {
+    pub name: String,
+    pub type_name: String,
+    /// The variable block the tag is declared in, e.g. `global` or `input`
+    pub scope: String,
+    /// The POU declaring the tag, empty for globals
+    pub pou: String,
+    pub initial_value: String,
+    pub address: String,
+    pub documentation: String,
+}
+
+/// The tag list belonging to a generated document, e.g. `out.xml` comes with `out.xlsx`
+pub fn tag_list_path(output_path: &Path) -> PathBuf {
+    output_path.with_extension(TAG_LIST_EXTENSION)
+}
+
+/// The type name as written in the declaration. Sized strings are declared through an internal type, so their
+/// size is spelled out again, e.g. `STRING[80]`
+pub fn declared_type_name(declaration: &DataTypeDeclaration) -> Option<String> {
+    if let DataTypeDeclaration::Definition { data_type, .. } = declaration && let DataType::StringType { is_wide, size, .. } = data_type.as_ref() {
+        let keyword = if *is_wide { "WSTRING" } else { "STRING" };
+
+        return match size.as_ref().map(|it| &it.stmt) {
+            Some(AstStatement::Literal(literal)) => Some(format!("{keyword}[{}]", literal.to_string())),
+            _ => Some(String::from(keyword)),
+        };
+    }
+    declaration.get_name().map(String::from)
+}
+
+/// Collects the globals and the variables of every POU interface, in declaration order
+pub fn collect_tags(sources: &LoadedSources, units: &Vec<&CompilationUnit>) -> Vec<Tag> {
+    let mut tags = Vec::new();
+
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_global in &current_unit.global_vars {
+            if current_global.linkage != LinkageType::External {
+                collect_block_tags(sources, current_global, "", &mut tags);
+            }
+        }
+
+        for current_pou in &current_unit.pous {
+            if current_pou.linkage == LinkageType::External || !matches!(current_pou.kind, PouType::Program | PouType::Function | PouType::FunctionBlock) {
+                continue; //same POUs as the XML generation
+            }
+
+            for current_block in &current_pou.variable_blocks {
+                collect_block_tags(sources, current_block, &current_pou.name, &mut tags);
+            }
+        }
+    }
+    tags
+}
+
+fn collect_block_tags(sources: &LoadedSources, block: &VariableBlock, pou_name: &str, tags: &mut Vec<Tag>) {
+    for current_variable in &block.variables {
+        if current_variable.location.span == CodeSpan::None {
+            continue; //discard compiler interally generated variables
+        }
+
+        let Some(type_name) = declared_type_name(&current_variable.data_type_declaration) else {
+            continue;
+        };
+
+        tags.push(Tag {
+            name: current_variable.name.clone(),
+            type_name,
+            scope: block.kind.to_string(),
+            pou: String::from(pou_name),
+            initial_value: literal_text(current_variable.initializer.as_ref().map(|it| &it.stmt)),
+            address: literal_text(current_variable.address.as_ref().map(|it| &it.stmt)),
+            documentation: variable_documentation(sources, current_variable),
+        });
+    }
+}
+
+fn literal_text(statement: Option<&AstStatement>) -> String {
+    match statement {
+        Some(AstStatement::Literal(literal)) => literal.to_string(),
+        _ => String::new(),
+    }
+}
+
+fn variable_documentation(sources: &LoadedSources, variable: &Variable) -> String {
+    trailing_comment(sources, &variable.location).unwrap_or_default()
+}
+
+/// Writes the tags as a single sheet workbook with a header row
+pub fn write_tag_list(tags: &[Tag], output_path: &Path) -> Result<(), Error> {
+    let mut zip = ZipWriter::new(File::create(output_path)?);
+
+    let content_types = SXlsxTypes::new()
+        .attribute_str("xmlns", "http://schemas.openxmlformats.org/package/2006/content-types")
+        .child(&SXlsxDefault::new()
+            .attribute_str("Extension", "rels")
+            .attribute_str("ContentType", "application/vnd.openxmlformats-package.relationships+xml")
+            .close())
+        .child(&SXlsxDefault::new()
+            .attribute_str("Extension", "xml")
+            .attribute_str("ContentType", "application/xml")
+            .close())
+        .child(&SXlsxOverride::new()
+            .attribute_str("PartName", "/xl/workbook.xml")
+            .attribute_str("ContentType", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml")
+            .close())
+        .child(&SXlsxOverride::new()
+            .attribute_str("PartName", "/xl/worksheets/sheet1.xml")
+            .attribute_str("ContentType", "application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml")
+            .close());
+
+    let package_relationships = relationships("officeDocument", "xl/workbook.xml");
+    let workbook_relationships = relationships("worksheet", "worksheets/sheet1.xml");
+
+    let workbook = SXlsxWorkbook::new()
+        .attribute_str("xmlns", SPREADSHEET_NAMESPACE)
+        .attribute_str("xmlns:r", DOCUMENT_RELATIONSHIPS_NAMESPACE)
+        .child(&SXlsxSheets::new()
+            .child(&SXlsxSheet::new()
+                .attribute_str("name", "Tags")
+                .attribute_str("sheetId", "1")
+                .attribute_str("r:id", "rId1")
+                .close()));
+
+    let mut sheet_data = SXlsxSheetData::new().child(&spreadsheet_row(1, TAG_LIST_HEADER.iter().map(|it| String::from(*it)).collect()));
+    for (index, tag) in tags.iter().enumerate() {
+        sheet_data = sheet_data.child(&spreadsheet_row(index + 2, vec![
+            tag.name.clone(),
+            tag.type_name.clone(),
+            tag.scope.clone(),
+            tag.pou.clone(),
+            tag.initial_value.clone(),
+            tag.address.clone(),
+            tag.documentation.clone(),
+        ]));
+    }
+
+    let worksheet = SXlsxWorksheet::new()
+        .attribute_str("xmlns", SPREADSHEET_NAMESPACE)
+        .child(&sheet_data);
+
+    let parts: [(&str, Node); 5] = [
+        ("[Content_Types].xml", content_types.inner()),
+        ("_rels/.rels", package_relationships.inner()),
+        ("xl/workbook.xml", workbook.inner()),
+        ("xl/_rels/workbook.xml.rels", workbook_relationships.inner()),
+        ("xl/worksheets/sheet1.xml", worksheet.inner()),
+    ];
+
+    for (part_name, part) in parts {
+        zip.start_file(part_name, SimpleFileOptions::default()).map_err(Error::other)?;
+        write_xml_document(&mut zip, &[], &SerializerOptions::default(), part)?;
+    }
+    zip.finish().map_err(Error::other)?;
+    Ok(())
+}
+
+fn relationships(relationship_type: &str, target: &'static str) -> SXlsxRelationships {
+    SXlsxRelationships::new()
+        .attribute_str("xmlns", RELATIONSHIPS_NAMESPACE)
+        .child(&SXlsxRelationship::new()
+            .attribute_str("Id", "rId1")
+            .attribute(String::from("Type"), format!("http://schemas.openxmlformats.org/officeDocument/2006/relationships/{relationship_type}"))
+            .attribute_str("Target", target)
+            .close())
+}
+
+/// A row of inline string cells. The columns are addressed `A` to `Z`, which is plenty for the tag list
+fn spreadsheet_row(row_number: usize, values: Vec<String>) -> SXlsxRow {
+    let mut row = SXlsxRow::new().attribute_usize("r", row_number);
+
+    for (column, value) in values.into_iter().enumerate() {
+        if value.is_empty() {
+            continue; //empty cells are simply left out
+        }
+
+        let column_name = char::from(b'A' + column as u8);
+        row = row.child(&SXlsxCell::new()
+            .attribute(String::from("r"), format!("{column_name}{row_number}"))
+            .attribute_str("t", "inlineStr")
+            .child(&SXlsxInlineString::new().child(&SXlsxText::new().content(value))));
+    }
+    row
+}
@@ -7,16 +7,18 @@ mod xml_gen_tests {
 
     use crate::xml_gen::*;
     use crate::serializer::*;
+    use crate::node_diff::*;
+    use crate::source_comments::LoadedSources;
+    use crate::xml_target::*;
 
     use plc_ast::{
         literals::AstLiteral,
         ast::{
         AstFactory, AstNode, DataType,
-        DataTypeDeclaration, Implementation, LinkageType, Pou, PouType,
+        DataTypeDeclaration, Implementation, LinkageType, Operator, Pou, PouType,
         UserTypeDeclaration, Variable, VariableBlock, VariableBlockType,
     }};
     use plc_source::source_location::{CodeSpan, FileMarker, SourceLocation, TextLocation};
-    use std::collections::HashSet;
 
     /// Helper: Create a SourceLocation with a non-None CodeSpan::Range so variables are not skipped.
     fn make_source_location() -> SourceLocation {
@@ -178,7 +180,6 @@ mod xml_gen_tests {
     fn test_parse_project_into_nodetree() {
         let temp_dir = std::env::temp_dir();
         let output_path = temp_dir.join("test_parse_nodetree.xml");
-        let template = get_omron_template();
         let params = GenerationParameters::new();
 
         // Create a unit with a global variable
@@ -201,7 +202,7 @@ mod xml_gen_tests {
         });
 
         let units: Vec<&CompilationUnit> = vec![&unit];
-        let result = parse_project_into_nodetree(&params, &units, OMRON_SCHEMA, &output_path, template);
+        let result = parse_project_into_nodetree(&params, &OmronTarget, &units, &output_path, &mut Vec::new());
         assert!(result.is_ok());
         assert!(output_path.exists());
 
@@ -221,7 +222,6 @@ mod xml_gen_tests {
     fn test_generate_globals() {
         let params = GenerationParameters::new();
         let mut template = get_omron_template();
-        let mut order: HashSet<(String, usize)> = HashSet::new();
 
         // Unit with one normal global, one constant global, one retain global
         let mut unit = make_unit("globals.st");
@@ -244,9 +244,13 @@ mod xml_gen_tests {
         retain_block.retain = true;
         unit.global_vars.push(retain_block);
 
-        let result = generate_globals(&params, &unit, "globals.st", OMRON_SCHEMA, &mut order, &mut template);
+        let result = generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "globals.st", None, &mut template);
         assert!(result.is_ok());
 
+        // a document without instances has no place for the globals
+        let error = generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "globals.st", None, &mut Node::new_str("Project")).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+
         // Write the tree to verify the structure
         let temp_dir = std::env::temp_dir();
         let output_path = temp_dir.join("test_generate_globals_output.xml");
@@ -311,7 +315,7 @@ mod xml_gen_tests {
             linkage: LinkageType::Internal,
         });
 
-        let result = generate_custom_types(&params, &unit, &mut template);
+        let result = generate_custom_types(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut template);
         assert!(result.is_ok());
 
         // Write and verify
@@ -337,11 +341,353 @@ mod xml_gen_tests {
         let _ = std::fs::remove_file(&output_path);
     }
 
+    #[test]
+    fn test_generate_array_and_alias_types() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
+        let mut unit = make_unit("types.st");
+
+        // Add an ArrayType ARRAY[0..9] OF INT
+        let bounds = AstFactory::create_range_statement(
+            AstFactory::create_literal(AstLiteral::Integer(0), SourceLocation::internal(), 0),
+            AstFactory::create_literal(AstLiteral::Integer(9), SourceLocation::internal(), 0),
+            0,
+        );
+
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::ArrayType {
+                name: Some(String::from("Readings")),
+                bounds,
+                referenced_type: Box::new(DataTypeDeclaration::Reference {
+                    referenced_type: String::from("INT"),
+                    location: SourceLocation::internal(),
+                }),
+                is_variable_length: false,
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+
+        // Add an alias TYPE Speed : REAL; END_TYPE
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::SubRangeType {
+                name: Some(String::from("Speed")),
+                referenced_type: String::from("REAL"),
+                bounds: None,
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+
+        let result = generate_custom_types(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut template);
+        assert!(result.is_ok());
+
+        let output_path = std::env::temp_dir().join("test_generate_array_and_alias_types_output.xml");
+        write_xml_file(&output_path, template).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        // Array
+        assert!(contents.contains(r#"<DataTypeDecl name="Readings">"#));
+        assert!(contents.contains("ArrayTypeSpec"));
+        assert!(contents.contains("<BaseType>\n"));
+        assert!(contents.contains("<Dimension "));
+        assert!(contents.contains(r#"lower="0""#));
+        assert!(contents.contains(r#"upper="9""#));
+
+        // Alias
+        assert!(contents.contains(r#"<DataTypeDecl name="Speed">"#));
+        assert!(contents.contains("DerivedTypeSpec"));
+        assert!(contents.contains("<![CDATA[REAL]]>"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_enum_base_type_mapping() {
+        let mut unit = make_unit("enums.st");
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::EnumType {
+                name: Some(String::from("State")),
+                numeric_type: String::from("DINT"),
+                elements: make_enum_assignment("IDLE", 0),
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+
+        let base_type = |params: &GenerationParameters, target: &dyn XmlTarget| {
+            let mut template = get_omron_template();
+            generate_custom_types(params, target, &ProjectEnums::default(), &unit, &mut template).unwrap();
+            let types = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+            let spec = types.find_child("DataTypeDecl").unwrap().find_child("UserDefinedTypeSpec").unwrap();
+            spec.find_child("BaseType").unwrap().content.clone().unwrap()
+        };
+
+        // by default the declared storage size is kept, in the naming of the target
+        let mut params = GenerationParameters::new();
+        assert_eq!(base_type(&params, &OmronTarget), "DINT");
+        assert_eq!(base_type(&params, &TiaTarget), "DInt");
+
+        // a configured mapping applies to every target
+        params.enum_base_types = vec![(String::from("dint"), String::from("INT"))];
+        assert_eq!(base_type(&params, &OmronTarget), "INT");
+        assert_eq!(base_type(&params, &TiaTarget), "INT");
+    }
+
+    /// Helper: Create the bounds `0..upper[0], 0..upper[1], ...` of an array
+    fn make_array_bounds(upper: &[i128]) -> AstNode {
+        let ranges = upper.iter().map(|it| AstFactory::create_range_statement(
+            AstFactory::create_literal(AstLiteral::Integer(0), SourceLocation::internal(), 0),
+            AstFactory::create_literal(AstLiteral::Integer(*it), SourceLocation::internal(), 0),
+            0,
+        )).collect();
+        AstFactory::create_expression_list(ranges, SourceLocation::internal(), 0)
+    }
+
+    #[test]
+    fn test_generate_struct_initial_values() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
+        let literal = |value: AstLiteral| AstFactory::create_literal(value, SourceLocation::internal(), 0);
+        let member = |name: &str, value: AstNode| AstFactory::create_assignment(
+            AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0),
+            value,
+            0,
+        );
+        let struct_literal = |members: Vec<AstNode>| AstFactory::create_paren_expression(
+            AstFactory::create_expression_list(members, SourceLocation::internal(), 0),
+            SourceLocation::internal(),
+            0,
+        );
+
+        // VAR_GLOBAL axis : Axis := (speed := 100, limits := (low := 5.5), mode := other); END_VAR
+        let mut axis = make_variable("axis", "Axis");
+        axis.initializer = Some(struct_literal(vec![
+            member("speed", literal(AstLiteral::Integer(100))),
+            member("limits", struct_literal(vec![member("low", literal(AstLiteral::Real(String::from("5.5"))))])),
+            member("mode", AstFactory::create_member_reference(AstFactory::create_identifier("other", SourceLocation::internal(), 0), None, 0)),
+        ]));
+        let mut unit = make_unit("structs.st");
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![axis]));
+
+        generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "structs.st", None, &mut template).unwrap();
+
+        let output_path = std::env::temp_dir().join("test_generate_struct_initial_values_output.xml");
+        write_xml_file(&output_path, template).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+
+        assert!(contents.contains("<InitialValue>\n"));
+        assert_eq!(contents.matches("<StructValue>").count(), 2);
+        assert!(contents.contains(r#"<Value member="speed">"#));
+        assert!(contents.contains(r#"<SimpleValue value="100" />"#));
+        assert!(contents.contains(r#"<Value member="limits">"#));
+        assert!(contents.contains(r#"<Value member="low">"#));
+        assert!(contents.contains(r#"<SimpleValue value="5.5" />"#));
+
+        // members initialized with anything else keep the default of their type
+        assert!(!contents.contains(r#"member="mode""#));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generate_folded_initial_values() {
+        let mut params = GenerationParameters::new();
+        params.folded_initial_values = vec![(String::from("gCircumference"), AstLiteral::Real(String::from("6.283185")))];
+        let mut template = get_omron_template();
+
+        let expression = |left: &str| AstFactory::create_binary_expression(
+            AstFactory::create_member_reference(AstFactory::create_identifier(left, SourceLocation::internal(), 0), None, 0),
+            Operator::Multiplication,
+            AstFactory::create_literal(AstLiteral::Integer(2), SourceLocation::internal(), 0),
+            0,
+        );
+
+        // VAR_GLOBAL gCircumference : REAL := PI * 2; gUnknown : INT := count * 2; END_VAR
+        let mut circumference = make_variable("gCircumference", "REAL");
+        circumference.initializer = Some(expression("PI"));
+        let mut unknown = make_variable("gUnknown", "INT");
+        unknown.initializer = Some(expression("count"));
+        let mut unit = make_unit("folded.st");
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![circumference, unknown]));
+
+        generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "folded.st", None, &mut template).unwrap();
+
+        let output_path = std::env::temp_dir().join("test_generate_folded_initial_values_output.xml");
+        write_xml_file(&output_path, template).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+
+        assert!(contents.contains(r#"<SimpleValue value="6.283185" />"#));
+
+        // expressions the driver could not fold keep the default of their type
+        assert_eq!(contents.matches("<InitialValue>").count(), 1);
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generate_prefixed_literal_initial_values() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
+        let literal = |value: AstLiteral| AstFactory::create_literal(value, SourceLocation::internal(), 0);
+        let prefixed = |operator: Operator, value: AstNode| AstFactory::create_unary_expression(operator, value, SourceLocation::internal(), 0);
+        let initialized = |name: &str, type_name: &str, initializer: AstNode| {
+            let mut variable = make_variable(name, type_name);
+            variable.initializer = Some(initializer);
+            variable
+        };
+
+        // VAR_GLOBAL gOffset : REAL := -5.5; gEnabled : BOOL := NOT TRUE; gTwice : LREAL := -(-2.5); END_VAR
+        let mut unit = make_unit("prefixed.st");
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![
+            initialized("gOffset", "REAL", prefixed(Operator::Minus, literal(AstLiteral::Real(String::from("5.5"))))),
+            initialized("gEnabled", "BOOL", prefixed(Operator::Not, literal(AstLiteral::Bool(true)))),
+            initialized("gTwice", "LREAL", prefixed(Operator::Minus, AstFactory::create_paren_expression(
+                prefixed(Operator::Minus, literal(AstLiteral::Real(String::from("2.5")))),
+                SourceLocation::internal(),
+                0,
+            ))),
+            initialized("gMask", "WORD", prefixed(Operator::Not, literal(AstLiteral::Integer(0)))),
+        ]));
+
+        generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "prefixed.st", None, &mut template).unwrap();
+
+        let output_path = std::env::temp_dir().join("test_generate_prefixed_literal_initial_values_output.xml");
+        write_xml_file(&output_path, template).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+
+        assert!(contents.contains(r#"<SimpleValue value="-5.5" />"#));
+        assert!(contents.contains(r#"<SimpleValue value="FALSE" />"#));
+        assert!(contents.contains(r#"<SimpleValue value="2.5" />"#));
+
+        // NOT on a bit string depends on the width of the type and keeps the default
+        assert_eq!(contents.matches("<InitialValue>").count(), 3);
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generate_multi_dimensional_arrays() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
+        let mut unit = make_unit("arrays.st");
+
+        // TYPE Grid : ARRAY[0..3, 0..7] OF REAL; END_TYPE
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::ArrayType {
+                name: Some(String::from("Grid")),
+                bounds: make_array_bounds(&[3, 7]),
+                referenced_type: Box::new(DataTypeDeclaration::Reference {
+                    referenced_type: String::from("REAL"),
+                    location: SourceLocation::internal(),
+                }),
+                is_variable_length: false,
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+
+        // VAR_GLOBAL matrix : ARRAY[0..1, 0..2, 0..5] OF INT; END_VAR
+        let mut matrix = make_variable("matrix", "INT");
+        matrix.data_type_declaration = DataTypeDeclaration::Definition {
+            data_type: Box::new(DataType::ArrayType {
+                name: Some(String::from("__global_matrix")),
+                bounds: make_array_bounds(&[1, 2, 5]),
+                referenced_type: Box::new(DataTypeDeclaration::Reference {
+                    referenced_type: String::from("INT"),
+                    location: SourceLocation::internal(),
+                }),
+                is_variable_length: false,
+            }),
+            location: SourceLocation::internal(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        };
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![matrix]));
+
+        generate_custom_types(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+        generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "arrays.st", None, &mut template).unwrap();
+
+        let output_path = std::env::temp_dir().join("test_generate_multi_dimensional_arrays_output.xml");
+        write_xml_file(&output_path, template).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+
+        // one <Dimension> per range, in declaration order
+        assert_eq!(contents.matches("<Dimension ").count(), 5);
+        assert!(contents.contains(r#"upper="7""#));
+        assert!(contents.contains(r#"upper="5""#));
+
+        // the inline array is described in the <Type> of the variable instead of naming its generated type
+        assert!(contents.contains("<ArrayType>"));
+        assert!(contents.contains("<![CDATA[REAL]]>"));
+        assert!(contents.contains("<![CDATA[INT]]>"));
+        assert!(!contents.contains("__global_matrix"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generate_arrays_with_constant_bounds() {
+        use plc_ast::ast::Operator;
+
+        let mut params = GenerationParameters::new();
+        let mut unit = make_unit("arrays.st");
+
+        // TYPE Buffer : ARRAY[0..MAX - 1] OF BYTE; END_TYPE
+        let upper = AstFactory::create_binary_expression(
+            AstFactory::create_member_reference(AstFactory::create_identifier("MAX", SourceLocation::internal(), 0), None, 0),
+            Operator::Minus,
+            AstFactory::create_literal(AstLiteral::Integer(1), SourceLocation::internal(), 0),
+            0,
+        );
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::ArrayType {
+                name: Some(String::from("Buffer")),
+                bounds: AstFactory::create_range_statement(AstFactory::create_literal(AstLiteral::Integer(0), SourceLocation::internal(), 0), upper, 0),
+                referenced_type: Box::new(DataTypeDeclaration::Reference {
+                    referenced_type: String::from("BYTE"),
+                    location: SourceLocation::internal(),
+                }),
+                is_variable_length: false,
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+
+        // without evaluated bounds an expression cannot be exported
+        let mut template = get_omron_template();
+        generate_custom_types(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+        let types = &template.find(GLOBAL_NAMESPACE_PATH).unwrap().children;
+        assert!(types.is_empty());
+
+        // the driver evaluates MAX - 1
+        params.array_dimensions = vec![(String::from("BUFFER"), vec![(0, 15)])];
+        let mut template = get_omron_template();
+        generate_custom_types(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+        let types = &template.find(GLOBAL_NAMESPACE_PATH).unwrap().children;
+        let dimension = types[0].find_child("UserDefinedTypeSpec").unwrap().find_child("Dimension").unwrap();
+        assert_eq!(dimension.attributes.get("lower").map(String::as_str), Some("0"));
+        assert_eq!(dimension.attributes.get("upper").map(String::as_str), Some("15"));
+    }
+
     #[test]
     fn test_generate_pous() {
         let params = GenerationParameters::new();
         let mut template = get_omron_template();
-        let mut order: HashSet<(String, usize)> = HashSet::new();
 
         // Create a temp .st file for grab_file_statement_from_span to read
         let temp_dir = std::env::temp_dir();
@@ -398,7 +744,7 @@ mod xml_gen_tests {
             access: None,
         });
 
-        let result = generate_pous(&params, &unit, OMRON_SCHEMA, &mut order, &mut template);
+        let result = generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new());
         assert!(result.is_ok());
 
         // Write and verify
@@ -417,335 +763,5400 @@ mod xml_gen_tests {
     }
 
     #[test]
-    fn test_write_xml_file_creates_file() {
+    fn test_parameter_order_follows_declaration() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
         let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_write_xml_output.xml");
+        let st_path = temp_dir.join("test_parameter_order.st");
+        std::fs::write(&st_path, "q := a;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
 
-        let node = Node::new_str("TestRoot")
-            .attribute_str("version", "1.0");
+        // FUNCTION_BLOCK Valve VAR_INPUT a, b END_VAR VAR_OUTPUT q END_VAR VAR_INPUT c END_VAR VAR t END_VAR
+        let block = |kind, names: &[&str]| VariableBlock::default()
+            .with_block_type(kind)
+            .with_variables(names.iter().map(|it| make_variable(it, "BOOL")).collect());
 
-        let result = write_xml_file(&output_path, node);
-        assert!(result.is_ok());
-        assert!(output_path.exists());
+        let mut unit = make_unit("test_parameter_order.st");
+        unit.pous.push(Pou {
+            id: 1,
+            name: String::from("Valve"),
+            kind: PouType::FunctionBlock,
+            variable_blocks: vec![
+                block(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal), &["a", "b"]),
+                block(VariableBlockType::Output, &["q"]),
+                block(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal), &["c"]),
+                block(VariableBlockType::Local, &["t"]),
+            ],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("Valve"),
+            type_name: String::from("Valve"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::FunctionBlock,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 7, 7)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
 
-        // Clean up
-        let _ = std::fs::remove_file(&output_path);
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+
+        fn collect_orders(node: &Node, orders: &mut Vec<(String, Option<String>)>) {
+            if node.name == "Variable" {
+                orders.push((node.attributes["name"].clone(), node.attributes.get("orderWithinParamSet").cloned()));
+            }
+            node.children.iter().for_each(|it| collect_orders(it, orders));
+        }
+        let mut orders = Vec::new();
+        collect_orders(&template, &mut orders);
+        orders.sort();
+
+        // inputs and outputs are numbered in source order, local variables are no parameters
+        let order = |it: &str| Some(String::from(it));
+        assert_eq!(orders, vec![
+            (String::from("a"), order("0")),
+            (String::from("b"), order("1")),
+            (String::from("c"), order("3")),
+            (String::from("q"), order("2")),
+            (String::from("t"), None),
+        ]);
+
+        let _ = std::fs::remove_file(&st_path);
     }
 
     #[test]
-    fn test_write_xml_file_with_children() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_write_xml_children.xml");
+    fn test_classes_and_methods() {
+        use plc_ast::ast::{AccessModifier, DeclarationKind};
 
-        let child1 = STypeName::new().content(String::from("INT"));
-        let child2 = STypeName::new().content(String::from("BOOL"));
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
 
-        let node = Node::new_str("Root")
-            .child(&child1)
-            .child(&child2);
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_classes_and_methods.st");
+        std::fs::write(&st_path, "THIS^.speed := 5;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
 
-        let result = write_xml_file(&output_path, node);
-        assert!(result.is_ok());
+        let pou = |name: &str, kind: PouType, variable_blocks: Vec<VariableBlock>, return_type: Option<&str>| Pou {
+            id: 1,
+            name: String::from(name),
+            kind,
+            variable_blocks,
+            return_type: return_type.map(|it| DataTypeDeclaration::Reference { referenced_type: String::from(it), location: SourceLocation::internal() }),
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, pou_type: PouType, access: Option<AccessModifier>, overriding: bool| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 17, 17)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding,
+            generic: false,
+            access,
+        };
+        let method = |parent: &str| PouType::Method { parent: String::from(parent), property: None, declaration_kind: DeclarationKind::Concrete };
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.contains("Root"));
-        assert!(contents.contains("INT"));
-        assert!(contents.contains("BOOL"));
+        // CLASS Motor VAR speed : INT; END_VAR METHOD PUBLIC start : BOOL VAR_INPUT target : INT; END_VAR ... END_METHOD END_CLASS
+        // FUNCTION_BLOCK Valve METHOD PRIVATE OVERRIDE open VAR RETAIN opening : BOOL; END_VAR VAR CONSTANT limit : INT; END_VAR
+        // VAR_TEMP step : INT; END_VAR ... END_METHOD ... END_FUNCTION_BLOCK
+        let mut unit = make_unit("test_classes_and_methods.st");
+        unit.pous = vec![
+            pou("Motor", PouType::Class, vec![VariableBlock::default().with_variables(vec![make_variable("speed", "INT")])], None),
+            pou("Motor.start", method("Motor"), vec![VariableBlock::default()
+                .with_block_type(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal))
+                .with_variables(vec![make_variable("target", "INT")])], Some("BOOL")),
+            Pou {
+                interfaces: vec![plc_ast::ast::Identifier { name: String::from("IValve"), location: SourceLocation::internal() }],
+                ..pou("Valve", PouType::FunctionBlock, vec![], None)
+            },
+            pou("Valve.open", method("Valve"), vec![
+                VariableBlock { retain: true, ..VariableBlock::default().with_variables(vec![make_variable("opening", "BOOL")]) },
+                VariableBlock { constant: true, ..VariableBlock::default().with_variables(vec![make_variable("limit", "INT")]) },
+                VariableBlock::default().with_block_type(VariableBlockType::Temp).with_variables(vec![make_variable("step", "INT")]),
+            ], None),
+        ];
+        // methods are parsed before the POU they belong to, classes have no body of their own
+        unit.implementations = vec![
+            implementation("Motor.start", method("Motor"), Some(AccessModifier::Public), false),
+            Implementation { location: SourceLocation::internal(), ..implementation("Motor", PouType::Class, None, false) },
+            implementation("Valve.open", method("Valve"), Some(AccessModifier::Private), true),
+            implementation("Valve", PouType::FunctionBlock, None, false),
+        ];
 
-        let _ = std::fs::remove_file(&output_path);
-    }
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
 
-    #[test]
-    fn test_write_xml_file_with_attributes() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_write_xml_attrs.xml");
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        let names: Vec<(&str, &str)> = namespace.children.iter().map(|it| (it.name.as_str(), it.attributes["name"].as_str())).collect();
+        assert_eq!(names, vec![("Class", "Motor"), ("FunctionBlock", "Valve")]);
 
-        let node = Node::new_str("Element")
-            .attribute_str("key1", "value1")
-            .attribute_str("key2", "value2");
+        let class = &namespace.children[0];
+        assert_eq!(class.find_child("Vars").unwrap().children[0].attributes["name"], "speed");
+        let start = class.find_child("Method").unwrap();
+        assert_eq!(start.attributes["name"], "start");
+        assert_eq!(start.attributes["accessSpecifier"], "public");
+        assert!(!start.attributes.contains_key("override"));
+        assert_eq!(start.find_child("ResultType").unwrap().find_child("TypeName").unwrap().content.as_deref(), Some("BOOL"));
+        let inputs = start.find_child("Parameters").unwrap().find_child("InputVars").unwrap();
+        assert_eq!(inputs.children[0].attributes["name"], "target");
+        let body = start.find("MainBody/BodyContent/ST").unwrap();
+        assert_eq!(body.content.as_deref(), Some("THIS^.speed := 5;"));
 
-        let result = write_xml_file(&output_path, node);
-        assert!(result.is_ok());
+        // methods follow the body of a function block, without a result unless they return something
+        let valve = &namespace.children[1];
+        assert_eq!(valve.children.last().unwrap().name, "Method");
+        let open = valve.find_child("Method").unwrap();
+        assert_eq!(open.attributes["accessSpecifier"], "private");
+        assert_eq!(open.attributes["override"], "true");
+        assert!(open.find_child("ResultType").is_none());
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.contains("key1=\"value1\""));
-        assert!(contents.contains("key2=\"value2\""));
+        // the locals of a method are declared with it, without retention
+        let locals: Vec<(&str, Option<&String>, &str)> = open.children.iter()
+            .filter(|it| matches!(it.name.as_str(), "Vars" | "TempVars"))
+            .flat_map(|block| block.children.iter().map(move |it| (block.name.as_str(), block.attributes.get("constant"), it.attributes["name"].as_str())))
+            .collect();
+        let constant = String::from("true");
+        assert_eq!(locals, vec![("Vars", None, "opening"), ("Vars", Some(&constant), "limit"), ("TempVars", None, "step")]);
+        assert_eq!(valve.find_child("Implements").unwrap().attributes["name"], "IValve");
 
-        let _ = std::fs::remove_file(&output_path);
-    }
+        // targets without interfaces drop the list of implemented ones, since the interfaces are left out as well
+        struct NoInterfacesTarget;
+        impl XmlTarget for NoInterfacesTarget {
+            fn name(&self) -> &'static str {
+                "no_interfaces"
+            }
+
+            fn template(&self) -> Node {
+                get_omron_template()
+            }
+
+            fn supports_interfaces(&self) -> bool {
+                false
+            }
+        }
+
+        let mut template = get_omron_template();
+        generate_pous(&params, &NoInterfacesTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        assert!(namespace.children[1].find_child("Implements").is_none());
+
+        let _ = std::fs::remove_file(&st_path);
+    }
+
+    #[test]
+    fn test_interfaces() {
+        use plc_ast::ast::{DeclarationKind, Identifier, Interface, PropertyKind};
+
+        let params = GenerationParameters::new();
+        let identifier = |name: &str| Identifier { name: String::from(name), location: SourceLocation::internal() };
+        let method = |name: &str, property: Option<(String, PropertyKind)>, variable_blocks: Vec<VariableBlock>, return_type: Option<&str>| Pou {
+            id: 1,
+            name: format!("IMotor.{name}"),
+            kind: PouType::Method { parent: String::from("IMotor"), property, declaration_kind: DeclarationKind::Abstract },
+            variable_blocks,
+            return_type: return_type.map(|it| DataTypeDeclaration::Reference { referenced_type: String::from(it), location: SourceLocation::internal() }),
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+
+        // INTERFACE IMotor EXTENDS IDevice METHOD start : BOOL VAR_INPUT speed : INT; END_VAR VAR temp : INT; END_VAR END_METHOD
+        let mut unit = make_unit("test_interfaces.st");
+        unit.interfaces.push(Interface {
+            id: 0,
+            ident: identifier("IMotor"),
+            location: make_source_location(),
+            methods: vec![
+                method("start", None, vec![
+                    VariableBlock::default()
+                        .with_block_type(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal))
+                        .with_variables(vec![make_variable("speed", "INT")]),
+                    VariableBlock::default().with_variables(vec![make_variable("temp", "INT")]),
+                ], Some("BOOL")),
+                method("__get_speed", Some((String::from("speed"), PropertyKind::Get)), vec![], Some("INT")),
+            ],
+            extensions: vec![identifier("IDevice")],
+            properties: vec![],
+        });
+
+        let mut template = get_omron_template();
+        generate_interfaces(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        let interface = namespace.find_child("Interface").unwrap();
+        assert_eq!(interface.attributes["name"], "IMotor");
+        assert_eq!(interface.find_child("Extends").unwrap().attributes["name"], "IDevice");
+
+        // only the prototypes of the methods: their results and parameters
+        let methods: Vec<&Node> = interface.children.iter().filter(|it| it.name == "Method").collect();
+        assert_eq!(methods.len(), 1);
+        assert_eq!(methods[0].attributes["name"], "start");
+        assert_eq!(methods[0].find_child("ResultType").unwrap().find_child("TypeName").unwrap().content.as_deref(), Some("BOOL"));
+        let parameters = methods[0].find_child("Parameters").unwrap();
+        assert_eq!(parameters.find_child("InputVars").unwrap().children[0].attributes["name"], "speed");
+        assert!(methods[0].find_child("Vars").is_none());
+        assert!(methods[0].find_child("MainBody").is_none());
+
+        // targets without interfaces leave them out
+        let mut template = get_omron_template();
+        generate_interfaces(&params, &TiaTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+        assert!(template.find(GLOBAL_NAMESPACE_PATH).unwrap().find_child("Interface").is_none());
+    }
+
+    #[test]
+    fn test_properties() {
+        use plc_ast::ast::{Identifier, Interface, PropertyBlock, PropertyImplementation, PropertyKind};
+
+        let params = GenerationParameters::new();
+
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_properties.st");
+        std::fs::write(&st_path, "speed := tmp;\ntmp := speed;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
+        let statement = |start: usize, end: usize| AstFactory::create_empty_statement(SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, start, start)..TextLocation::new(0, end, end)),
+            file: FileMarker::File(st_path_str),
+        }, 0);
+
+        let accessor = |kind: PropertyKind, variable_blocks: Vec<VariableBlock>, body: Vec<AstNode>| PropertyImplementation {
+            kind,
+            location: SourceLocation::internal(),
+            variable_blocks,
+            body,
+            end_location: SourceLocation::internal(),
+        };
+        let property = |implementations: Vec<PropertyImplementation>| PropertyBlock {
+            ident: Identifier { name: String::from("speed"), location: SourceLocation::internal() },
+            datatype: DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() },
+            implementations,
+        };
+
+        // FUNCTION_BLOCK Motor PROPERTY speed : INT GET VAR tmp : INT; END_VAR speed := tmp; END_GET SET tmp := speed; END_SET END_PROPERTY
+        let mut unit = make_unit("test_properties.st");
+        unit.pous.push(Pou {
+            id: 1,
+            name: String::from("Motor"),
+            kind: PouType::FunctionBlock,
+            variable_blocks: vec![],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![property(vec![
+                accessor(PropertyKind::Get, vec![VariableBlock::default().with_variables(vec![make_variable("tmp", "INT")])], vec![statement(0, 13)]),
+                accessor(PropertyKind::Set, vec![], vec![statement(14, 27)]),
+            ])],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("Motor"),
+            type_name: String::from("Motor"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::FunctionBlock,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 0, 0)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+        // INTERFACE IMotor PROPERTY speed : INT GET END_GET END_PROPERTY
+        unit.interfaces.push(Interface {
+            id: 2,
+            ident: Identifier { name: String::from("IMotor"), location: SourceLocation::internal() },
+            location: make_source_location(),
+            methods: vec![],
+            extensions: vec![],
+            properties: vec![property(vec![accessor(PropertyKind::Get, vec![], vec![])])],
+        });
+
+        let body = |element: &Node| element.find("MainBody/BodyContent/ST").unwrap().content.clone();
+
+        // CODESYS has properties
+        let mut template = get_iec_template();
+        generate_interfaces(&params, &CodesysTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+        generate_pous(&params, &CodesysTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+
+        let property_node = namespace.find_child("FunctionBlock").unwrap().find_child("Property").unwrap();
+        assert_eq!(property_node.attributes["name"], "speed");
+        assert_eq!(property_node.find_child("Type").unwrap().find_child("TypeName").unwrap().content.as_deref(), Some("INT"));
+        let getter = property_node.find_child("Get").unwrap();
+        assert_eq!(getter.find_child("Vars").unwrap().children[0].attributes["name"], "tmp");
+        assert_eq!(body(getter).as_deref(), Some("speed := tmp;"));
+        assert_eq!(body(property_node.find_child("Set").unwrap()).as_deref(), Some("tmp := speed;"));
+
+        let prototype = namespace.find_child("Interface").unwrap().find_child("Property").unwrap();
+        assert!(prototype.find_child("Get").unwrap().children.is_empty());
+        assert!(prototype.find_child("Set").is_none());
+
+        // the other targets get the methods the compiler lowers the property to
+        let mut template = get_iec_template();
+        generate_interfaces(&params, &IecTarget, &ProjectEnums::default(), &unit, &mut template).unwrap();
+        generate_pous(&params, &IecTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+
+        let motor = namespace.find_child("FunctionBlock").unwrap();
+        assert!(motor.find_child("Property").is_none());
+        let methods: Vec<&Node> = motor.children.iter().filter(|it| it.name == "Method").collect();
+        assert_eq!(methods.len(), 2);
+
+        assert_eq!(methods[0].attributes["name"], "__get_speed");
+        assert_eq!(methods[0].find_child("ResultType").unwrap().find_child("TypeName").unwrap().content.as_deref(), Some("INT"));
+        let locals: Vec<&str> = methods[0].children.iter().filter(|it| it.name == "Vars").map(|it| it.children[0].attributes["name"].as_str()).collect();
+        assert_eq!(locals, vec!["speed", "tmp"]);
+        assert_eq!(body(methods[0]).as_deref(), Some("speed := tmp;\n__get_speed := speed;"));
+
+        assert_eq!(methods[1].attributes["name"], "__set_speed");
+        let input = &methods[1].find_child("Parameters").unwrap().find_child("InputVars").unwrap().children[0];
+        assert_eq!(input.attributes["name"], "speed");
+        assert_eq!(body(methods[1]).as_deref(), Some("tmp := speed;"));
+
+        let prototype = namespace.find_child("Interface").unwrap().find_child("Method").unwrap();
+        assert_eq!(prototype.attributes["name"], "__get_speed");
+        assert!(prototype.find_child("MainBody").is_none());
+    }
+
+    #[test]
+    fn test_declared_names_follow_the_generated_document() {
+        use crate::identifier_rules::collect_declared_names;
+        use plc_ast::ast::{ArgumentProperty, DeclarationKind, Identifier, Interface, PropertyBlock, PropertyImplementation, PropertyKind};
+        use plc_source::SourceCode;
+
+        let source_path = "in_memory/declared_names.st";
+        let mut params = GenerationParameters::new();
+        params.sources = LoadedSources::new(&[SourceCode::new("x := 1;", source_path)]);
+
+        let identifier = |name: &str| Identifier { name: String::from(name), location: SourceLocation::internal() };
+        let block = |kind: VariableBlockType, names: &[&str]| VariableBlock::default()
+            .with_block_type(kind)
+            .with_variables(names.iter().map(|it| make_variable(it, "INT")).collect());
+        let pou = |name: &str, kind: PouType, variable_blocks: Vec<VariableBlock>| Pou {
+            id: 1,
+            name: String::from(name),
+            kind,
+            variable_blocks,
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, pou_type: PouType| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 7, 7)),
+                file: FileMarker::File(source_path),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+        let method = |parent: &str| PouType::Method { parent: String::from(parent), property: None, declaration_kind: DeclarationKind::Concrete };
+        let accessor = |kind: PropertyKind, variable_blocks: Vec<VariableBlock>| PropertyImplementation {
+            kind,
+            location: SourceLocation::internal(),
+            variable_blocks,
+            body: vec![],
+            end_location: SourceLocation::internal(),
+        };
+        let property = |implementations: Vec<PropertyImplementation>| PropertyBlock {
+            ident: identifier("position"),
+            datatype: DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() },
+            implementations,
+        };
+        let input = VariableBlockType::Input(ArgumentProperty::ByVal);
+
+        let mut unit = make_unit("declared_names.st");
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::StructType { name: Some(String::from("Point")), variables: vec![make_variable("x", "INT"), make_variable("y", "INT")] },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::EnumType {
+                name: Some(String::from("Mode")),
+                numeric_type: String::from("INT"),
+                elements: AstFactory::create_expression_list(vec![make_enum_assignment("OFF", 0), make_enum_assignment("ON", 1)], SourceLocation::internal(), 0),
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+        unit.interfaces.push(Interface {
+            id: 0,
+            ident: identifier("IValve"),
+            location: make_source_location(),
+            methods: vec![pou("IValve.open", method("IValve"), vec![block(input, &["force"]), block(VariableBlockType::Local, &["hidden"])])],
+            extensions: vec![],
+            properties: vec![property(vec![accessor(PropertyKind::Get, vec![])])],
+        });
+        // the sections of the document do not follow the order of declaration, and some are left out per kind of POU
+        unit.pous = vec![
+            pou("Valve.open", method("Valve"), vec![block(VariableBlockType::Temp, &["step"]), block(input, &["force"]), block(VariableBlockType::Local, &["count"])]),
+            Pou {
+                properties: vec![property(vec![accessor(PropertyKind::Get, vec![block(VariableBlockType::Local, &["tmp"])]), accessor(PropertyKind::Set, vec![])])],
+                ..pou("Valve", PouType::FunctionBlock, vec![
+                    VariableBlock { retain: true, ..block(VariableBlockType::Local, &["memory"]) },
+                    block(VariableBlockType::Output, &["done"]),
+                    block(input, &["enable"]),
+                    VariableBlock { persistent: true, ..block(VariableBlockType::Local, &["kept"]) },
+                    block(VariableBlockType::Local, &["state"]),
+                    block(VariableBlockType::Temp, &["scratch"]),
+                    block(VariableBlockType::External, &["gLimit"]),
+                ])
+            },
+            pou("Scale", PouType::Function, vec![block(VariableBlockType::Temp, &["t"]), block(VariableBlockType::Local, &["unused"]), block(input, &["value"])]),
+            pou("Main", PouType::Program, vec![
+                block(VariableBlockType::Temp, &["cycle"]),
+                VariableBlock { constant: true, ..block(VariableBlockType::Local, &["limit"]) },
+                block(VariableBlockType::Local, &["counter"]),
+            ]),
+        ];
+        unit.implementations = vec![
+            implementation("Valve.open", method("Valve")),
+            implementation("Valve", PouType::FunctionBlock),
+            implementation("Scale", PouType::Function),
+            implementation("Main", PouType::Program),
+        ];
+
+        let targets: [&dyn XmlTarget; 3] = [&IecTarget, &OmronTarget, &CodesysTarget];
+        for target in targets {
+            let mut template = target.template();
+            generate_custom_types(&params, target, &ProjectEnums::default(), &unit, &mut template).unwrap();
+            generate_interfaces(&params, target, &ProjectEnums::default(), &unit, &mut template).unwrap();
+            generate_pous(&params, target, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+
+            let mut generated = Vec::new();
+            collect_declared_names(&template, &mut generated);
+            let mut collected = Vec::new();
+            collect_unit_declared_names(&params, target, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut collected);
+            assert_eq!(collected, generated, "{}", target.name());
+        }
+    }
+
+    #[test]
+    fn test_actions() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_actions.st");
+        std::fs::write(&st_path, "count := 0;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
+
+        let pou = |name: &str, kind: PouType| Pou {
+            id: 1,
+            name: String::from(name),
+            kind,
+            variable_blocks: vec![],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, type_name: &str, pou_type: PouType| Implementation {
+            name: String::from(name),
+            type_name: String::from(type_name),
+            linkage: LinkageType::Internal,
+            pou_type,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 11, 11)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        // PROGRAM Main ... END_PROGRAM ACTIONS Main ACTION reset count := 0; END_ACTION END_ACTIONS
+        // FUNCTION_BLOCK Valve ... ACTION close ... END_ACTION END_FUNCTION_BLOCK
+        let mut unit = make_unit("test_actions.st");
+        unit.pous = vec![
+            pou("Main", PouType::Program),
+            pou("Main.reset", PouType::Action),
+            pou("Valve", PouType::FunctionBlock),
+            pou("Valve.close", PouType::Action),
+        ];
+        // actions may be parsed before or after the POU they belong to
+        unit.implementations = vec![
+            implementation("Main", "Main", PouType::Program),
+            implementation("Main.reset", "Main", PouType::Action),
+            implementation("Valve.close", "Valve", PouType::Action),
+            implementation("Valve", "Valve", PouType::FunctionBlock),
+        ];
+
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        let names: Vec<&str> = namespace.children.iter().map(|it| it.attributes["name"].as_str()).collect();
+        assert_eq!(names, vec!["Main", "Valve"]);
+
+        for (owner, action_name) in [(&namespace.children[0], "reset"), (&namespace.children[1], "close")] {
+            let actions = owner.find_child("actions").unwrap();
+            assert_eq!(actions.children.len(), 1);
+            assert_eq!(actions.children[0].attributes["name"], action_name);
+
+            let st = actions.children[0].find("MainBody/BodyContent/ST").unwrap();
+            assert_eq!(st.content.as_deref(), Some("count := 0;"));
+        }
+
+        // printed bodies are written for actions as well, even without a source to copy them from
+        let mut params = params;
+        params.canonical_bodies = true;
+        let mut generated = implementation("Main.reset", "Main", PouType::Action);
+        generated.location = SourceLocation::internal();
+        generated.statements = vec![AstFactory::create_assignment(
+            AstFactory::create_member_reference(AstFactory::create_identifier("count", SourceLocation::internal(), 0), None, 0),
+            AstFactory::create_literal(AstLiteral::Integer(1), SourceLocation::internal(), 0),
+            0,
+        )];
+        unit.implementations[1] = generated;
+
+        let mut template = get_omron_template();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let main = &template.find(GLOBAL_NAMESPACE_PATH).unwrap().children[0];
+        let st = main.find_child("actions").unwrap().children[0].find("MainBody/BodyContent/ST").unwrap();
+        assert_eq!(st.content.as_deref(), Some("count := 1;"));
+    }
+
+    #[test]
+    fn test_monomorphize_generics() {
+        use plc_ast::ast::{GenericBinding, TypeNature};
+
+        let mut params = GenerationParameters::new();
+
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_monomorphize_generics.st");
+        std::fs::write(&st_path, "MAX_OF := a;\nx := MAX_OF(i, j);\ny := MAX_OF(r, s);").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
+        let location = |start: usize, end: usize| SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, start, start)..TextLocation::new(0, end, end)),
+            file: FileMarker::File(st_path_str),
+        };
+
+        let pou = |name: &str, kind: PouType, variable_blocks: Vec<VariableBlock>, return_type: Option<&str>, generics: Vec<GenericBinding>| Pou {
+            id: 1,
+            name: String::from(name),
+            kind,
+            variable_blocks,
+            return_type: return_type.map(|it| DataTypeDeclaration::Reference { referenced_type: String::from(it), location: SourceLocation::internal() }),
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics,
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, pou_type: PouType, location: SourceLocation| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type,
+            statements: vec![],
+            location,
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        // FUNCTION MAX_OF<T : ANY_NUM> : T VAR_INPUT a, b : T; END_VAR MAX_OF := a; END_FUNCTION
+        // PROGRAM Main ... x := MAX_OF(i, j); y := MAX_OF(r, s); END_PROGRAM
+        let mut unit = make_unit("test_monomorphize_generics.st");
+        unit.pous = vec![
+            pou("MAX_OF", PouType::Function, vec![VariableBlock::default()
+                .with_block_type(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal))
+                .with_variables(vec![make_variable("a", "T"), make_variable("b", "T")])], Some("T"), vec![GenericBinding { name: String::from("T"), nature: TypeNature::Num }]),
+            pou("Main", PouType::Program, vec![], None, vec![]),
+        ];
+        unit.implementations = vec![
+            implementation("MAX_OF", PouType::Function, location(0, 12)),
+            implementation("Main", PouType::Program, location(13, 50)),
+        ];
+
+        // the driver resolves the calls, the second instantiation of INT is exported only once
+        let call = |types: &str, start: usize| GenericCall { generic: String::from("MAX_OF"), types: vec![String::from(types)], location: location(start, start + 6) };
+        params.generic_calls = vec![call("INT", 18), call("REAL", 37), call("INT", 18)];
+
+        // without the option the generic POU is exported as declared
+        let mut template = get_omron_template();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        let names: Vec<&str> = namespace.children.iter().map(|it| it.attributes["name"].as_str()).collect();
+        assert_eq!(names, vec!["MAX_OF", "Main"]);
+
+        params.monomorphize_generics = true;
+        let mut template = get_omron_template();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        let names: Vec<&str> = namespace.children.iter().map(|it| it.attributes["name"].as_str()).collect();
+        assert_eq!(names, vec!["Main", "MAX_OF_INT", "MAX_OF_REAL"]);
+
+        let body = |element: &Node| element.find("MainBody/BodyContent/ST").unwrap().content.clone();
+        assert_eq!(body(&namespace.children[0]).as_deref(), Some("x := MAX_OF_INT(i, j);\ny := MAX_OF_REAL(r, s);"));
+
+        let instance = &namespace.children[2];
+        assert_eq!(instance.find_child("ResultType").unwrap().find_child("TypeName").unwrap().content.as_deref(), Some("REAL"));
+        let inputs = instance.find_child("Parameters").unwrap().find_child("InputVars").unwrap();
+        for input in &inputs.children {
+            assert_eq!(input.find_child("Type").unwrap().find_child("TypeName").unwrap().content.as_deref(), Some("REAL"));
+        }
+        assert_eq!(body(instance).as_deref(), Some("MAX_OF := a;"));
+    }
+
+    #[test]
+    fn test_inheritance() {
+        use plc_ast::ast::Identifier;
+
+        let mut params = GenerationParameters::new();
+
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_inheritance.st");
+        std::fs::write(&st_path, "count := count + 1;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
+
+        let pou = |name: &str, variable_blocks: Vec<VariableBlock>, super_class: Option<&str>| Pou {
+            id: 1,
+            name: String::from(name),
+            kind: PouType::FunctionBlock,
+            variable_blocks,
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: super_class.map(|it| Identifier { name: String::from(it), location: SourceLocation::internal() }),
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::FunctionBlock,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 19, 19)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        // FUNCTION_BLOCK Base VAR_INPUT start : BOOL; END_VAR ... END_FUNCTION_BLOCK
+        // FUNCTION_BLOCK Counter EXTENDS Base VAR count : INT; END_VAR ... END_FUNCTION_BLOCK
+        let mut unit = make_unit("test_inheritance.st");
+        unit.pous = vec![
+            pou("Base", vec![VariableBlock::default()
+                .with_block_type(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal))
+                .with_variables(vec![make_variable("start", "BOOL")])], None),
+            pou("Counter", vec![VariableBlock::default().with_variables(vec![make_variable("count", "INT")])], Some("Base")),
+        ];
+        unit.implementations = vec![implementation("Base"), implementation("Counter")];
+
+        let counter = |template: &Node| template.find(GLOBAL_NAMESPACE_PATH).unwrap().children[1].clone();
+        let inputs = |counter: &Node| counter.find_child("Parameters").unwrap().find_child("InputVars").unwrap().children.len();
+
+        // targets with inheritance keep the base
+        let mut template = get_iec_template();
+        generate_pous(&params, &IecTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let derived = counter(&template);
+        assert_eq!(derived.find_child("Extends").unwrap().attributes["name"], "Base");
+        assert_eq!(inputs(&derived), 0);
+
+        // the others leave it out...
+        let mut template = get_omron_template();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+        let derived = counter(&template);
+        assert!(derived.find_child("Extends").is_none());
+        assert_eq!(inputs(&derived), 0);
+
+        // ...or flatten its variables into the derived function block
+        params.flatten_inheritance = true;
+        let mut template = get_omron_template();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::collect(&vec![&unit]), &unit, &mut template, &mut Vec::new()).unwrap();
+        let derived = counter(&template);
+        assert!(derived.find_child("Extends").is_none());
+        let parameters = derived.find_child("Parameters").unwrap();
+        assert_eq!(parameters.find_child("InputVars").unwrap().children[0].attributes["name"], "start");
+        assert_eq!(derived.find_child("Vars").unwrap().children[0].attributes["name"], "count");
+    }
+
+    #[test]
+    fn test_external_stubs() {
+        let mut params = GenerationParameters::new();
+
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_external_stubs.st");
+        std::fs::write(&st_path, "Scale := x * 2;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
+
+        // {external} FUNCTION Scale : INT VAR_INPUT x : INT; END_VAR END_FUNCTION
+        let mut unit = make_unit("test_external_stubs.st");
+        unit.pous.push(Pou {
+            id: 1,
+            name: String::from("Scale"),
+            kind: PouType::Function,
+            variable_blocks: vec![VariableBlock::default()
+                .with_block_type(VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal))
+                .with_variables(vec![make_variable("x", "INT")])],
+            return_type: Some(DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() }),
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::External,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("Scale"),
+            type_name: String::from("Scale"),
+            linkage: LinkageType::External,
+            pou_type: PouType::Function,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 15, 15)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        let pous = |params: &GenerationParameters| {
+            let mut template = get_omron_template();
+            generate_pous(params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+            template.find(GLOBAL_NAMESPACE_PATH).unwrap().children.clone()
+        };
+
+        // external POUs are left out...
+        assert!(pous(&params).is_empty());
+
+        // ...or exported with their declaration only
+        params.external_stubs = true;
+        let stubs = pous(&params);
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(stubs[0].name, "Function");
+        assert_eq!(stubs[0].find_child("ResultType").unwrap().children[0].content.as_deref(), Some("INT"));
+        assert_eq!(stubs[0].find_child("Parameters").unwrap().find_child("InputVars").unwrap().children[0].attributes["name"], "x");
+
+        let body = stubs[0].find("MainBody/BodyContent/ST").unwrap();
+        assert_eq!(body.content, None);
+
+        let _ = std::fs::remove_file(&st_path);
+    }
+
+    #[test]
+    fn test_program_temp_vars() {
+        let params = GenerationParameters::new();
+        let mut template = get_omron_template();
+
+        let temp_dir = std::env::temp_dir();
+        let st_path = temp_dir.join("test_program_temp_vars.st");
+        std::fs::write(&st_path, "scratch := 1;").unwrap();
+        let st_path_str: &'static str = Box::leak(st_path.to_string_lossy().into_owned().into_boxed_str());
+
+        // PROGRAM Main VAR_TEMP scratch : INT; END_VAR scratch := 1; END_PROGRAM
+        let mut unit = make_unit("test_program_temp_vars.st");
+        unit.pous.push(Pou {
+            id: 1,
+            name: String::from("Main"),
+            kind: PouType::Program,
+            variable_blocks: vec![VariableBlock::default()
+                .with_block_type(VariableBlockType::Temp)
+                .with_variables(vec![make_variable("scratch", "INT")])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("Main"),
+            type_name: String::from("Main"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 13, 13)),
+                file: FileMarker::File(st_path_str),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+
+        let program = &template.find(GLOBAL_NAMESPACE_PATH).unwrap().children[0];
+        let temp_vars = program.find_child("TempVars").unwrap();
+        assert_eq!(temp_vars.children[0].attributes["name"], "scratch");
+
+        // declared ahead of the body
+        let position = |name: &str| program.children.iter().position(|it| it.name == name).unwrap();
+        assert!(position("TempVars") < position("MainBody"));
+
+        let _ = std::fs::remove_file(&st_path);
+    }
+
+    #[test]
+    fn test_emission_order() {
+        use crate::emission_order::{order_declarations, EmissionOrder};
+
+        let name = |node: Node, name: &str| node.attribute(String::from("name"), String::from(name));
+        let uses_type = |node: Node, type_name: &str| {
+            node.child(&SVars::new().child(&SGenVariable::new().child(&SType::new().child(&STypeName::new().content(String::from(type_name))))))
+        };
+        let calls = |node: Node, body: &str| node.child(&SMainBody::new().child(&SBodyContent::new().child(&SST::new().content(String::from(body)))));
+
+        let mut template = get_omron_template();
+        let namespace = template.children.iter_mut().find(|it| it.name == TYPES).unwrap()
+            .children.iter_mut().find(|it| it.name == GLOBAL_NAMESPACE).unwrap();
+        namespace.children = vec![
+            calls(name(SProgram::new().inner(), "main"), "ping(); motor();"),
+            uses_type(name(SFunctionBlock::new().inner(), "Motor"), "ARRAY[0..1] OF Settings"),
+            name(SDataTypeDecl::new().inner(), "Settings"),
+            calls(name(SFunction::new().inner(), "Ping"), "Pong();"),
+            calls(name(SFunction::new().inner(), "Pong"), "Ping();"),
+            name(SFunctionBlock::new().inner(), "Runner").child(&SImplements::new().attribute(String::from("name"), String::from("IRun"))),
+            name(SInterfaceDecl::new().inner(), "IRun"),
+        ];
+
+        let ordered = |order: EmissionOrder| {
+            let mut root = template.clone();
+            order_declarations(&mut root, order);
+            let namespace = root.find(GLOBAL_NAMESPACE_PATH).unwrap();
+            namespace.children.iter().map(|it| it.attributes["name"].clone()).collect::<Vec<_>>()
+        };
+
+        assert_eq!(ordered(EmissionOrder::Source), vec!["main", "Motor", "Settings", "Ping", "Pong", "Runner", "IRun"]);
+
+        // data types first, then interfaces and POUs, each by name ignoring case
+        assert_eq!(ordered(EmissionOrder::Alphabetical), vec!["Settings", "IRun", "main", "Motor", "Ping", "Pong", "Runner"]);
+
+        // every declaration after the ones it uses, the functions calling each other in the order they are reached
+        assert_eq!(ordered(EmissionOrder::Dependency), vec!["Settings", "Motor", "Pong", "Ping", "main", "IRun", "Runner"]);
+    }
+
+    #[test]
+    fn test_fbd_bodies() {
+        let temp_dir = std::env::temp_dir().join("test_fbd_bodies");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("fbd.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "total := left_in + right_in;\ntimer(IN := start, PT := delay, Q => done);\nlog(total);\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let at = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+        let reference = |name: &str| AstFactory::create_member_reference(AstFactory::create_identifier(name, at(name), 0), None, 0);
+        let call = |operator: &str, parameters: Vec<AstNode>| {
+            let parameters = AstFactory::create_expression_list(parameters, SourceLocation::internal(), 0);
+            AstFactory::create_call_statement(reference(operator), Some(parameters), 0, SourceLocation::internal())
+        };
+
+        // total := left_in + right_in; timer(IN := start, PT := delay, Q => done);
+        let network_statements = vec![
+            AstFactory::create_assignment(reference("total"), AstFactory::create_binary_expression(reference("left_in"), Operator::Plus, reference("right_in"), 0), 0),
+            call("timer", vec![
+                AstFactory::create_assignment(reference("IN"), reference("start"), 0),
+                AstFactory::create_assignment(reference("PT"), reference("delay"), 0),
+                AstFactory::create_output_assignment(reference("Q"), reference("done"), 0),
+            ]),
+        ];
+        // log(total); calls a function, which only ST can express
+        let mut text_statements = network_statements.clone();
+        text_statements.push(call("log", vec![reference("total")]));
+
+        let pou = |name: &str| Pou {
+            id: 0,
+            name: String::from(name),
+            kind: PouType::FunctionBlock,
+            variable_blocks: vec![VariableBlock::default().with_variables(vec![make_variable("timer", "TON")])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, statements: Vec<AstNode>| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::FunctionBlock,
+            statements,
+            location: at(source.trim_end()),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        let mut unit = make_unit("fbd.st");
+        unit.pous = vec![pou("Network"), pou("Text")];
+        unit.implementations = vec![implementation("Network", network_statements), implementation("Text", text_statements)];
+
+        let bodies = |params: &GenerationParameters, target: &dyn XmlTarget| {
+            let mut template = target.template();
+            generate_pous(params, target, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+            let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+            namespace.children.iter().map(|it| it.find_child("MainBody").unwrap().find_child("BodyContent").unwrap().clone()).collect::<Vec<_>>()
+        };
+        let body_types = |bodies: &[Node]| bodies.iter().map(|it| it.attributes["xsi:type"].clone()).collect::<Vec<_>>();
+
+        let mut params = GenerationParameters::new();
+        assert_eq!(body_types(&bodies(&params, &IecTarget)), vec!["ST", "ST"]);
+
+        // only bodies of assignments and function block calls become networks, and not for targets without FBD
+        params.fbd_bodies = true;
+        assert_eq!(body_types(&bodies(&params, &OmronTarget)), vec!["ST", "ST"]);
+        let iec_bodies = bodies(&params, &IecTarget);
+        assert_eq!(body_types(&iec_bodies), vec!["FBD", "ST"]);
+
+        let elements: Vec<(String, String)> = iec_bodies[0].find_child("FBD").unwrap().children.iter()
+            .map(|it| {
+                let label = it.attributes.get("typeName")
+                    .cloned()
+                    .or_else(|| it.find_child("expression").and_then(|it| it.content.clone()))
+                    .unwrap_or_default();
+                (it.name.clone(), label)
+            })
+            .collect();
+        let element = |kind: &str, label: &str| (String::from(kind), String::from(label));
+        assert_eq!(elements, vec![
+            element("inVariable", "left_in"),
+            element("inVariable", "right_in"),
+            element("block", "ADD"),
+            element("outVariable", "total"),
+            element("inVariable", "start"),
+            element("inVariable", "delay"),
+            element("block", "TON"),
+            element("outVariable", "done"),
+        ]);
+
+        // the blocks are fed by their inputs and feed the variables written through their outputs
+        let network = iec_bodies[0].find_child("FBD").unwrap();
+        let timer = &network.children[6];
+        assert_eq!(timer.attributes["instanceName"], "timer");
+        let inputs: Vec<(&str, &str)> = timer.find_child("inputVariables").unwrap().children.iter()
+            .map(|it| (it.attributes["formalParameter"].as_str(), it.find_child("connectionPointIn").unwrap().find_child("connection").unwrap().attributes["refLocalId"].as_str()))
+            .collect();
+        assert_eq!(inputs, vec![("IN", "5"), ("PT", "6")]);
+        let done = network.children[7].find_child("connectionPointIn").unwrap().find_child("connection").unwrap();
+        assert_eq!((done.attributes["refLocalId"].as_str(), done.attributes["formalParameter"].as_str()), ("7", "Q"));
+
+        // the elements are laid out in columns fed from left to right, the timer below the addition
+        let positions: Vec<(&str, &str)> = network.children.iter()
+            .map(|it| it.find_child("position").unwrap())
+            .map(|it| (it.attributes["x"].as_str(), it.attributes["y"].as_str()))
+            .collect();
+        assert_eq!(positions, vec![("20", "20"), ("20", "60"), ("144", "20"), ("264", "20"), ("20", "100"), ("20", "140"), ("144", "100"), ("264", "100")]);
+        assert_eq!((timer.attributes["width"].as_str(), timer.attributes["height"].as_str()), ("80", "60"));
+        let rel_position = |pin: &Node, point: &str| {
+            let it = pin.find_child(point).unwrap().find_child("relPosition").unwrap();
+            (it.attributes["x"].clone(), it.attributes["y"].clone())
+        };
+        let delay_pin = &timer.find_child("inputVariables").unwrap().children[1];
+        assert_eq!(rel_position(delay_pin, "connectionPointIn"), (String::from("0"), String::from("50")));
+        let done_pin = &timer.find_child("outputVariables").unwrap().children[0];
+        assert_eq!(rel_position(done_pin, "connectionPointOut"), (String::from("80"), String::from("30")));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_sfc_bodies() {
+        use plc_ast::control_statements::{CaseStatement, ConditionalBlock, IfStatement};
+
+        let temp_dir = std::env::temp_dir().join("test_sfc_bodies");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("sfc.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'sfc_state'}\nstate : Mode := Idle;\nCASE state OF\nIdle:\nready := TRUE;\n\
+            IF start THEN state := Running; ELSIF abort THEN state := Failed; END_IF\nRunning:\nIF done THEN state := Idle; END_IF\n\
+            Failed:\nstate := Idle;\nEND_CASE\n";
+        std::fs::write(source_path, source).unwrap();
+
+        // the location of the nth occurrence of the text
+        let at = |text: &str, nth: usize| {
+            let (offset, _) = source.match_indices(text).nth(nth).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+        let reference = |name: &str, nth: usize| AstFactory::create_member_reference(AstFactory::create_identifier(name, at(name, nth), 0), None, 0);
+        let set_state = |target: &str, nth: usize| AstFactory::create_assignment(reference("state", 0), reference(target, nth), 0);
+        let conditional = |condition: AstNode, body: Vec<AstNode>| ConditionalBlock { condition: Box::new(condition), body };
+        let if_statement = |blocks: Vec<ConditionalBlock>| AstFactory::create_if_statement(
+            IfStatement { blocks, else_block: vec![], end_location: SourceLocation::internal() },
+            SourceLocation::internal(),
+            0,
+        );
+        let ready = AstFactory::create_assignment(reference("ready", 0), AstFactory::create_literal(AstLiteral::Bool(true), at("TRUE", 0), 0), 0);
+
+        let case_statement = AstFactory::create_case_statement(CaseStatement {
+            selector: Box::new(reference("state", 0)),
+            case_blocks: vec![
+                conditional(reference("Idle", 1), vec![
+                    ready.clone(),
+                    if_statement(vec![
+                        conditional(reference("start", 0), vec![set_state("Running", 0)]),
+                        conditional(reference("abort", 0), vec![set_state("Failed", 0)]),
+                    ]),
+                ]),
+                conditional(reference("Running", 1), vec![if_statement(vec![conditional(reference("done", 0), vec![set_state("Idle", 2)])])]),
+                conditional(reference("Failed", 1), vec![set_state("Idle", 3)]),
+            ],
+            else_block: vec![],
+            end_location: SourceLocation::internal(),
+        }, SourceLocation::internal(), 0);
+
+        let state = Variable {
+            initializer: Some(reference("Idle", 0)),
+            location: at("state : Mode", 0),
+            ..make_variable("state", "Mode")
+        };
+        let pou = |name: &str| Pou {
+            id: 0,
+            name: String::from(name),
+            kind: PouType::Program,
+            variable_blocks: vec![VariableBlock::default().with_variables(vec![state.clone()])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, statements: Vec<AstNode>| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements,
+            location: at("CASE", 0),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        // a body which is no single CASE over the state stays ST
+        let mut unit = make_unit("sfc.st");
+        unit.pous = vec![pou("Machine"), pou("Partial")];
+        unit.implementations = vec![implementation("Machine", vec![case_statement]), implementation("Partial", vec![ready])];
+
+        let bodies = |target: &dyn XmlTarget| {
+            let mut template = target.template();
+            generate_pous(&GenerationParameters::new(), target, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+            let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+            namespace.children.iter().map(|it| it.find_child("MainBody").unwrap().find_child("BodyContent").unwrap().clone()).collect::<Vec<_>>()
+        };
+        let body_types = |bodies: &[Node]| bodies.iter().map(|it| it.attributes["xsi:type"].clone()).collect::<Vec<_>>();
+
+        assert_eq!(body_types(&bodies(&OmronTarget)), vec!["ST", "ST"]);
+        let iec_bodies = bodies(&IecTarget);
+        assert_eq!(body_types(&iec_bodies), vec!["SFC", "ST"]);
+
+        let chart = iec_bodies[0].find_child("SFC").unwrap();
+        let inline_text = |node: &Node| node.find_child("inline").unwrap().find_child("ST").unwrap().find_child("xhtml").unwrap().content.clone().unwrap();
+        let elements: Vec<(&str, String)> = chart.children.iter()
+            .map(|it| {
+                let label = match it.name.as_str() {
+                    "step" => it.attributes["name"].clone(),
+                    "jumpStep" => it.attributes["targetName"].clone(),
+                    "transition" => inline_text(it.find_child("condition").unwrap()),
+                    "actionBlock" => inline_text(it.find_child("action").unwrap()),
+                    _ => String::new(),
+                };
+                (it.name.as_str(), label)
+            })
+            .collect();
+        let element = |kind: &'static str, label: &str| (kind, String::from(label));
+        assert_eq!(elements, vec![
+            element("step", "Idle"),
+            element("actionBlock", "ready := TRUE;"),
+            element("selectionDivergence", ""),
+            element("transition", "start"),
+            element("jumpStep", "Running"),
+            element("transition", "abort"),
+            element("jumpStep", "Failed"),
+            element("step", "Running"),
+            element("transition", "done"),
+            element("jumpStep", "Idle"),
+            element("step", "Failed"),
+            element("transition", "TRUE"),
+            element("jumpStep", "Idle"),
+        ]);
+
+        // the state is initialized with the initial step, the ELSIF is the transition of lower priority
+        assert_eq!(chart.children[0].attributes["initialStep"], "true");
+        assert!(!chart.children[7].attributes.contains_key("initialStep"));
+        assert_eq!((chart.children[3].attributes["priority"].as_str(), chart.children[5].attributes["priority"].as_str()), ("1", "2"));
+        let abort = chart.children[5].find_child("connectionPointIn").unwrap().find_child("connection").unwrap();
+        assert_eq!((abort.attributes["refLocalId"].as_str(), abort.attributes["formalParameter"].as_str()), ("3", "1"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_state_machine_exported_as_st_is_reported() {
+        let temp_dir = std::env::temp_dir().join("test_state_machine_exported_as_st_is_reported");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("sfc.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "PROGRAM Partial\n{attribute 'sfc_state'}\nstate : INT;\nready := TRUE;\nEND_PROGRAM\n";
+        std::fs::write(source_path, source).unwrap();
+        let at = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+
+        // the body is no CASE over the state at all
+        let mut unit = make_unit("sfc.st");
+        unit.pous = vec![Pou {
+            id: 0,
+            name: String::from("Partial"),
+            kind: PouType::Program,
+            variable_blocks: vec![VariableBlock::default().with_variables(vec![Variable { location: at("state : INT"), ..make_variable("state", "INT") }])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: at("Partial"),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        }];
+        unit.implementations = vec![Implementation {
+            name: String::from("Partial"),
+            type_name: String::from("Partial"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![AstFactory::create_assignment(
+                AstFactory::create_member_reference(AstFactory::create_identifier("ready", at("ready"), 0), None, 0),
+                AstFactory::create_literal(AstLiteral::Bool(true), at("TRUE"), 0),
+                0,
+            )],
+            location: at("ready := TRUE;"),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        }];
+
+        let mut template = IecTarget.template();
+        let mut diagnostics = Vec::new();
+        generate_pous(&GenerationParameters::new(), &IecTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut diagnostics).unwrap();
+
+        let [warning] = diagnostics.as_slice() else { panic!("{diagnostics:?}") };
+        assert_eq!(warning.get_error_code(), "E128");
+        assert_eq!(warning.get_message(), "The state machine of 'Partial' is exported as ST, since its body is no single CASE over 'state' assigning it only in IFs");
+        assert_eq!(warning.get_location(), at("Partial"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_canonical_bodies() {
+        use plc_ast::control_statements::{ConditionalBlock, ForLoopStatement, IfStatement};
+        use crate::source_comments::LineEndings;
+        use crate::st_printer::{IdentifierCase, KeywordCase};
+
+        // built in memory, so there is no source file to copy the body from
+        let reference = |name: &str| AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0);
+        let literal = |value: AstLiteral| AstFactory::create_literal(value, SourceLocation::internal(), 0);
+        let assign = |left: AstNode, right: AstNode| AstFactory::create_assignment(left, right, 0);
+
+        let parameters = AstFactory::create_expression_list(vec![
+            assign(reference("IN"), literal(AstLiteral::Bool(true))),
+            AstFactory::create_output_assignment(reference("Q"), reference("done"), 0),
+        ], SourceLocation::internal(), 0);
+        let if_statement = AstFactory::create_if_statement(IfStatement {
+            blocks: vec![
+                ConditionalBlock {
+                    condition: Box::new(AstFactory::create_binary_expression(reference("count"), Operator::Greater, literal(AstLiteral::Integer(10)), 0)),
+                    body: vec![assign(reference("count"), literal(AstLiteral::Integer(0)))],
+                },
+                ConditionalBlock {
+                    condition: Box::new(AstFactory::create_not_expression(reference("running"), SourceLocation::internal(), 0)),
+                    body: vec![AstFactory::create_call_statement(reference("timer"), Some(parameters), 0, SourceLocation::internal())],
+                },
+            ],
+            else_block: vec![],
+            end_location: SourceLocation::internal(),
+        }, SourceLocation::internal(), 0);
+        let for_loop = AstFactory::create_for_loop(ForLoopStatement {
+            counter: Box::new(reference("i")),
+            start: Box::new(literal(AstLiteral::Integer(1))),
+            end: Box::new(literal(AstLiteral::Integer(3))),
+            by_step: None,
+            body: vec![assign(
+                AstFactory::create_index_reference(reference("i"), Some(reference("values")), 0, SourceLocation::internal()),
+                literal(AstLiteral::Real(String::from("1.5E3"))),
+            )],
+            end_location: SourceLocation::internal(),
+        }, SourceLocation::internal(), 0);
+        let sum = AstFactory::create_paren_expression(
+            AstFactory::create_binary_expression(reference("count"), Operator::Plus, literal(AstLiteral::Integer(1)), 0),
+            SourceLocation::internal(),
+            0,
+        );
+        let statements = vec![
+            assign(reference("count"), AstFactory::create_binary_expression(sum, Operator::Multiplication, literal(AstLiteral::Integer(2)), 0)),
+            AstFactory::create_empty_statement(SourceLocation::internal(), 0),
+            if_statement,
+            for_loop,
+        ];
+
+        let mut unit = make_unit("canonical.st");
+        unit.pous = vec![Pou {
+            id: 0,
+            name: String::from("Counter"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        }];
+        unit.implementations = vec![Implementation {
+            name: String::from("Counter"),
+            type_name: String::from("Counter"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        }];
+
+        let bodies = |params: &GenerationParameters| {
+            let mut template = IecTarget.template();
+            generate_pous(params, &IecTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+            let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+            namespace.children.iter()
+                .map(|it| it.find("MainBody/BodyContent/ST").unwrap().content.clone().unwrap())
+                .collect::<Vec<_>>()
+        };
+
+        // without the source text the POU has no body to copy
+        let mut params = GenerationParameters::new();
+        assert!(bodies(&params).is_empty());
+
+        params.canonical_bodies = true;
+        assert_eq!(bodies(&params), vec![String::from(
+            "count := (count + 1) * 2;\n\
+            IF count > 10 THEN\n    count := 0;\n\
+            ELSIF NOT running THEN\n    timer(IN := TRUE, Q => done);\n\
+            END_IF\n\
+            FOR i := 1 TO 3 DO\n    values[i] := 1500.0;\n\
+            END_FOR"
+        )]);
+
+        // the printed lines end as configured
+        params.line_endings = LineEndings::Crlf;
+        assert!(bodies(&params)[0].starts_with("count := (count + 1) * 2;\r\nIF count > 10 THEN\r\n"));
+
+        // keywords and identifiers in the style of the IDE
+        params.line_endings = LineEndings::Preserve;
+        params.keyword_case = KeywordCase::Lower;
+        params.identifier_case = IdentifierCase::Upper;
+        assert_eq!(bodies(&params), vec![String::from(
+            "COUNT := (COUNT + 1) * 2;\n\
+            if COUNT > 10 then\n    COUNT := 0;\n\
+            elsif not RUNNING then\n    TIMER(IN := TRUE, Q => DONE);\n\
+            end_if\n\
+            for I := 1 to 3 do\n    VALUES[I] := 1500.0;\n\
+            end_for"
+        )]);
+    }
+
+    #[test]
+    fn test_function_names() {
+        let reference = |name: &str| AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0);
+        let call = |function: &str, arguments: Vec<AstNode>| AstFactory::create_call_statement(
+            reference(function),
+            Some(AstFactory::create_expression_list(arguments, SourceLocation::internal(), 0)),
+            0,
+            SourceLocation::internal(),
+        );
+        let one = AstFactory::create_literal(AstLiteral::Integer(1), SourceLocation::internal(), 0);
+        let statements = vec![
+            AstFactory::create_assignment(reference("last"), call("UPPER_BOUND", vec![reference("values"), one]), 0),
+            AstFactory::create_assignment(reference("pick"), call("SEL", vec![reference("flag"), reference("low"), reference("high")]), 0),
+            AstFactory::create_assignment(reference("address"), call("ADR", vec![reference("values")]), 0),
+        ];
+
+        let mut unit = make_unit("calls.st");
+        unit.pous = vec![Pou {
+            id: 0,
+            name: String::from("Picker"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        }];
+        unit.implementations = vec![Implementation {
+            name: String::from("Picker"),
+            type_name: String::from("Picker"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        }];
+
+        let body = |params: &GenerationParameters, target: &dyn XmlTarget| {
+            let mut template = target.template();
+            generate_pous(params, target, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut Vec::new()).unwrap();
+            let namespace = template.find(GLOBAL_NAMESPACE_PATH).unwrap();
+            let body_content = namespace.children[0].find_child("MainBody").unwrap().find_child("BodyContent").unwrap();
+            body_content.find_child("ST").unwrap().content.clone().unwrap()
+        };
+
+        let mut params = GenerationParameters::new();
+        params.canonical_bodies = true;
+        assert_eq!(body(&params, &IecTarget), "last := UPPER_BOUND(values, 1);\npick := SEL(flag, low, high);\naddress := ADR(values);");
+
+        // calls without a counterpart are kept, configured names extend and override those of the target
+        assert_eq!(body(&params, &OmronTarget), "last := UpperBound(values, 1);\npick := SEL(flag, low, high);\naddress := ADR(values);");
+        params.function_names = vec![(String::from("sel"), String::from("MUX2")), (String::from("UPPER_BOUND"), String::from("UB"))];
+        assert_eq!(body(&params, &OmronTarget), "last := UB(values, 1);\npick := MUX2(flag, low, high);\naddress := ADR(values);");
+    }
+
+    #[test]
+    fn test_call_without_counterpart_is_reported() {
+        // address := ADR(values);
+        let at = |start: usize, end: usize| SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, start, start)..TextLocation::new(0, end, end)),
+            file: FileMarker::File("calls.st"),
+        };
+        let reference = |name: &str, location: SourceLocation| AstFactory::create_member_reference(AstFactory::create_identifier(name, location, 0), None, 0);
+        let call = AstFactory::create_call_statement(
+            reference("ADR", at(11, 14)),
+            Some(AstFactory::create_expression_list(vec![reference("values", at(15, 21))], SourceLocation::internal(), 0)),
+            0,
+            at(11, 22),
+        );
+
+        let mut unit = make_unit("calls.st");
+        unit.pous = vec![Pou {
+            id: 0,
+            name: String::from("Picker"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        }];
+        unit.implementations = vec![Implementation {
+            name: String::from("Picker"),
+            type_name: String::from("Picker"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![AstFactory::create_assignment(reference("address", at(0, 7)), call, 0)],
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        }];
+
+        let mut params = GenerationParameters::new();
+        params.canonical_bodies = true;
+        let mut template = OmronTarget.template();
+        let mut diagnostics = Vec::new();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut template, &mut diagnostics).unwrap();
+
+        let [warning] = diagnostics.as_slice() else { panic!("{diagnostics:?}") };
+        assert_eq!(warning.get_error_code(), "E126");
+        assert_eq!(warning.get_message(), "'Picker' calls 'ADR', which the omron target has no counterpart of, so the call is exported unchanged");
+        assert_eq!(warning.get_location(), at(11, 14));
+    }
+
+    #[test]
+    fn test_write_xml_file_creates_file() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_write_xml_output.xml");
+
+        let node = Node::new_str("TestRoot")
+            .attribute_str("version", "1.0");
+
+        let result = write_xml_file(&output_path, node);
+        assert!(result.is_ok());
+        assert!(output_path.exists());
+
+        // Clean up
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_file_with_children() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_write_xml_children.xml");
+
+        let child1 = STypeName::new().content(String::from("INT"));
+        let child2 = STypeName::new().content(String::from("BOOL"));
+
+        let node = Node::new_str("Root")
+            .child(&child1)
+            .child(&child2);
+
+        let result = write_xml_file(&output_path, node);
+        assert!(result.is_ok());
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("Root"));
+        assert!(contents.contains("INT"));
+        assert!(contents.contains("BOOL"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_file_with_attributes() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_write_xml_attrs.xml");
+
+        let node = Node::new_str("Element")
+            .attribute_str("key1", "value1")
+            .attribute_str("key2", "value2");
+
+        let result = write_xml_file(&output_path, node);
+        assert!(result.is_ok());
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("key1=\"value1\""));
+        assert!(contents.contains("key2=\"value2\""));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_file_full_template() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_write_xml_full_template.xml");
+
+        let template = get_omron_template();
+        let result = write_xml_file(&output_path, template);
+        assert!(result.is_ok());
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("Project"));
+        assert!(contents.contains(FILE_HEADER));
+        assert!(contents.contains(CONTENT_HEADER));
+        assert!(contents.contains(TYPES));
+        assert!(contents.contains(INSTANCES));
+        assert!(contents.contains(GLOBAL_NAMESPACE));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_file_invalid_path() {
+        let output_path = PathBuf::from("/nonexistent/directory/file.xml");
+        let node = Node::new_str("Root");
+        let result = write_xml_file(&output_path, node);
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_empty_paths() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_copy_empty.xml");
+        let result = copy_xmlfile_to_output(vec![], output_path.clone(), &[]);
+        assert!(result.is_ok());
+        assert_eq!(result.unwrap(), output_path);
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_with_xml_file() {
+        let temp_dir = std::env::temp_dir();
+
+        // Create a source XML file
+        let source_path = temp_dir.join("test_source_copy.XML");
+        std::fs::write(&source_path, "<Root/>").unwrap();
+
+        let output_path = temp_dir.join("test_dest_copy.xml");
+
+        let result = copy_xmlfile_to_output(vec![source_path.as_path()], output_path.clone(), &[]);
+        assert!(result.is_ok());
+        assert!(output_path.exists());
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert_eq!(contents, "<Root/>");
+
+        let _ = std::fs::remove_file(&source_path);
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_format_enum_initials_no_conflicts() {
+        let variants = vec![
+            NameAndInitialValue {
+                name: String::from("A"),
+                initial_value: String::from("0"),
+            },
+            NameAndInitialValue {
+                name: String::from("B"),
+                initial_value: String::from("1"),
+            },
+            NameAndInitialValue {
+                name: String::from("C"),
+                initial_value: String::from("2"),
+            },
+        ];
+
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn test_format_enum_initials_with_conflicts() {
+        let variants = vec![
+            NameAndInitialValue {
+                name: String::from("A"),
+                initial_value: String::from("0"),
+            },
+            NameAndInitialValue {
+                name: String::from("B"),
+                initial_value: String::from("0"), // conflict with A
+            },
+            NameAndInitialValue {
+                name: String::from("C"),
+                initial_value: String::from("1"), // conflict with auto-incremented B
+            },
+        ];
+
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 3);
+        // After resolution: A=0, B=1 (incremented), C=2 (incremented since 1 is taken)
+    }
+
+    #[test]
+    fn test_format_enum_initials_empty() {
+        let variants: Vec<NameAndInitialValue> = vec![];
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 0);
+    }
+
+    #[test]
+    fn test_format_enum_initials_single_element() {
+        let variants = vec![NameAndInitialValue {
+            name: String::from("ONLY"),
+            initial_value: String::from("42"),
+        }];
+
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 1);
+    }
+
+    #[test]
+    fn test_format_enum_initials_all_same_value() {
+        let variants = vec![
+            NameAndInitialValue {
+                name: String::from("X"),
+                initial_value: String::from("5"),
+            },
+            NameAndInitialValue {
+                name: String::from("Y"),
+                initial_value: String::from("5"),
+            },
+            NameAndInitialValue {
+                name: String::from("Z"),
+                initial_value: String::from("5"),
+            },
+        ];
+
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 3);
+        // Should auto-increment: X=5, Y=6, Z=7
+    }
+
+    #[test]
+    fn test_parse_project_empty_units() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_parse_empty_units.xml");
+        let params = GenerationParameters::new();
+        let units: Vec<&CompilationUnit> = vec![];
+
+        let result =
+            parse_project_into_nodetree(&params, &OmronTarget, &units, &output_path, &mut Vec::new());
+        assert!(result.is_ok());
+        assert!(output_path.exists());
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_nested_structure() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_nested_structure.xml");
+
+        let inner_child = STypeName::new().content(String::from("DINT"));
+        let type_node = SType::new().child(&inner_child);
+        let member = SMember::new()
+            .attribute_str("name", "field1")
+            .child(&type_node);
+        let root = Node::new_str("DataType").child(&member);
+
+        let result = write_xml_file(&output_path, root);
+        assert!(result.is_ok());
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("DataType"));
+        assert!(contents.contains("Member"));
+        assert!(contents.contains("field1"));
+        assert!(contents.contains("DINT"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generation_parameters_omron_flag() {
+        let mut params = GenerationParameters::new();
+        assert!(!params.output_xml_omron);
+
+        params.output_xml_omron = true;
+        assert!(params.output_xml_omron);
+    }
+
+    #[test]
+    fn test_write_xml_file_produces_valid_xml_header() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_xml_header.xml");
+
+        let node = Node::new_str("Root");
+        write_xml_file(&output_path, node).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_empty_node() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_empty_node.xml");
+
+        let node = Node::new_str("Empty");
+        write_xml_file(&output_path, node).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("Empty"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_cdata_content() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_cdata_content.xml");
+
+        let node = Node::new_str("Code");
+        // Node with content should produce CDATA
+        let mut node_with_content = node;
+        node_with_content.content = Some(String::from("x := 1 + 2;"));
+
+        write_xml_file(&output_path, node_with_content).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("x := 1 + 2;"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_write_xml_cdata_terminator_in_content() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_cdata_terminator_in_content.xml");
+
+        let mut node = Node::new_str("Code");
+        let body = "x := a[b[1]]>0; // ]]> in a comment\ny := ']]>';";
+        node.content = Some(String::from(body));
+
+        write_xml_file(&output_path, node).unwrap();
+
+        // the content reads back as written, split across sections
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<![CDATA[x := a[b[1]]]]><![CDATA[>0;"));
+        let text: String = xml::reader::EventReader::new(contents.as_bytes())
+            .into_iter()
+            .filter_map(|event| match event.unwrap() {
+                xml::reader::XmlEvent::CData(text) | xml::reader::XmlEvent::Characters(text) => Some(text),
+                _ => None,
+            })
+            .collect();
+        assert_eq!(text, body);
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_find_nodes_by_path() {
+        let mut root = get_iec_template();
+        let namespace = root.find_mut(GLOBAL_NAMESPACE_PATH).unwrap();
+        namespace.child_borrowed(&SDataTypeDecl::new().attribute_str("name", "Motor"));
+        namespace.child_borrowed(&SDataTypeDecl::new().attribute_str("name", "a/b").attribute_str("kind", "struct"));
+        namespace.child_borrowed(&SFunctionBlock::new().attribute_str("name", "Motor"));
+
+        let names = |nodes: Vec<&Node>| nodes.iter().map(|it| format!("{}:{}", it.name, it.attributes["name"])).collect::<Vec<_>>();
+        assert_eq!(names(root.find_all("Types/GlobalNamespace/DataTypeDecl")), vec!["DataTypeDecl:Motor", "DataTypeDecl:a/b"]);
+        assert_eq!(names(root.find_all("Types/GlobalNamespace/*[@name='Motor']")), vec!["DataTypeDecl:Motor", "FunctionBlock:Motor"]);
+        assert_eq!(names(root.find_all("Types/*/DataTypeDecl[@name=\"a/b\"][@kind='struct']")), vec!["DataTypeDecl:a/b"]);
+        assert!(root.find("Types/GlobalNamespace/DataTypeDecl[@name='Valve']").is_none());
+        assert!(root.find("Instances").is_some());
+
+        root.find_mut("Types/GlobalNamespace/FunctionBlock[@name='Motor']").unwrap().attributes.insert(String::from("name"), String::from("Drive"));
+        assert_eq!(names(root.find_all("Types/GlobalNamespace/FunctionBlock")), vec!["FunctionBlock:Drive"]);
+    }
+
+    #[test]
+    fn test_find_nodes_by_malformed_path() {
+        let mut template = get_iec_template();
+        let malformed = [
+            "Types/GlobalNamespace/DataTypeDecl[@name=Motor]",
+            "Types/GlobalNamespace/DataTypeDecl[@name]",
+            "Types/GlobalNamespace/DataTypeDecl[@name='Motor]",
+            "Types/GlobalNamespace/DataTypeDecl[@name='Motor'",
+            "Types//GlobalNamespace",
+            "Types[@name='a']GlobalNamespace",
+        ];
+
+        // a malformed path finds nothing instead of panicking
+        for path in malformed {
+            assert!(template.find(path).is_none(), "{path}");
+            assert!(template.find_all(path).is_empty(), "{path}");
+            assert!(template.find_mut(path).is_none(), "{path}");
+        }
+        assert!(template.find("Types/GlobalNamespace").is_some());
+    }
+
+    #[test]
+    fn test_comment_nodes() {
+        let comment = Node::comment("a -- b ---");
+        assert_eq!(comment.kind, NodeKind::Comment);
+        assert_eq!(comment.content.as_deref(), Some("a - - b - - - "));
+
+        let root = Node::new_str("Project").child(&comment).child(&STypes::new());
+        assert_eq!(root.serialize(0), "<Project >\n    <!-- a - - b - - - -->\n    <Types >\n    </Types>\n</Project>\n");
+        assert_eq!(root.find_all("*").len(), 1);
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_comment_nodes.xml");
+        write_xml_file(&output_path, root).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        let _ = std::fs::remove_file(&output_path);
+        assert!(contents.contains("<Project>\n  <!-- a - - b - - - -->\n  <Types />"), "{contents}");
+    }
+
+    #[test]
+    fn test_processing_instruction_nodes() {
+        let instruction = Node::processing_instruction("importer", "mode=\"a?>b\"");
+        assert_eq!(instruction.kind, NodeKind::ProcessingInstruction);
+        assert_eq!(instruction.serialize(0), "<?importer mode=\"a? >b\"?>\n");
+        assert_eq!(Node::processing_instruction("importer", "").serialize(1), "    <?importer?>\n");
+
+        let root = Node::new_str("Project").child(&Node::processing_instruction("inline", "x")).child(&STypes::new());
+        assert_eq!(root.find_all("*").len(), 1);
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_processing_instruction_nodes.xml");
+        write_formatted_xml_file(&output_path, &[instruction, Node::comment("prolog")], &SerializerOptions::default(), root).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        let _ = std::fs::remove_file(&output_path);
+
+        let mut events = Vec::new();
+        for event in xml::ParserConfig::new().ignore_comments(false).create_reader(contents.as_bytes()) {
+            match event.unwrap() {
+                xml::reader::XmlEvent::ProcessingInstruction { name, data } => events.push(format!("?{name} {}", data.unwrap_or_default())),
+                xml::reader::XmlEvent::Comment(text) => events.push(format!("!{text}")),
+                xml::reader::XmlEvent::StartElement { name, .. } => events.push(name.local_name),
+                _ => {}
+            }
+        }
+        assert_eq!(events, vec!["?importer mode=\"a? >b\"", "! prolog ", "Project", "?inline x", "Types"]);
+    }
+
+    #[test]
+    fn test_parse_written_document() {
+        let mut root = get_omron_template();
+        root.find_mut(GLOBAL_NAMESPACE_PATH).unwrap().child_borrowed(&SFunctionBlock::new()
+            .attribute_str("name", "Motor")
+            .child(&SExpression::new().content(String::from("  IF x < 0 THEN (* ]]> *) END_IF  ")))
+            .child(&Node::comment("generated")));
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_parse_written_document.xml");
+        write_formatted_xml_file(&output_path, &[Node::processing_instruction("importer", "v=\"1\"")], &SerializerOptions::default(), root.clone()).unwrap();
+        let read = read_xml_file(&output_path).unwrap();
+        let (prolog, _) = Node::parse_with_prolog(std::fs::File::open(&output_path).unwrap()).unwrap();
+        let _ = std::fs::remove_file(&output_path);
+
+        assert_eq!(diff_nodes(&root, &read), vec![]);
+        assert_eq!(read.attributes, root.attributes, "the namespaces are declared as attributes");
+        let block = read.find("Types/GlobalNamespace/FunctionBlock[@name='Motor']").unwrap();
+        assert_eq!(block.children[1].kind, NodeKind::Comment);
+        assert!(read.find(FILE_HEADER).unwrap().closed);
+
+        assert_eq!(prolog.len(), 1);
+        assert_eq!(prolog[0].serialize(0), "<?importer v=\"1\"?>\n");
+
+        // the escaped text written by serialize reads the same
+        let reread = Node::parse(read.serialize(0).as_bytes()).unwrap();
+        assert_eq!(diff_nodes(&read, &reread), vec![]);
+    }
+
+    #[test]
+    fn test_parse_malformed_document() {
+        assert!(Node::parse("<Project><Types></Project>".as_bytes()).is_err());
+        assert!(Node::parse("<?xml version=\"1.0\"?><!-- only a comment -->".as_bytes()).is_err());
+    }
+
+    #[test]
+    fn test_typed_attributes() {
+        let node = SDimension::new()
+            .attribute_bool("constant", true)
+            .attribute_bool("retain", false)
+            .attribute_int("lower", -5)
+            .attribute_usize("order", 3)
+            .with_id(7)
+            .inner();
+
+        let value = |key: &str| node.attributes.get(key).map(String::as_str);
+        assert_eq!(value("constant"), Some("true"));
+        assert_eq!(value("retain"), Some("false"));
+        assert_eq!(value("lower"), Some("-5"));
+        assert_eq!(value("order"), Some("3"));
+        assert_eq!(value("localId"), Some("7"));
+        assert_eq!(SInVariable::new().inner().attributes.get("negated").map(String::as_str), Some("false"));
+    }
+
+    #[test]
+    fn test_write_xml_document_into_any_sink() {
+        let mut root = get_iec_template();
+        root.find_mut(GLOBAL_NAMESPACE_PATH).unwrap().child_borrowed(&SDataTypeDecl::new().attribute_str("name", "Motor"));
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_write_xml_document_into_any_sink.xml");
+        write_xml_file(&output_path, root.clone()).unwrap();
+        let written = std::fs::read(&output_path).unwrap();
+        let _ = std::fs::remove_file(&output_path);
+
+        let mut buffer = Vec::new();
+        write_xml_document(&mut buffer, &[], &SerializerOptions::default(), root.clone()).unwrap();
+        assert_eq!(diff_nodes(&Node::parse(buffer.as_slice()).unwrap(), &Node::parse(written.as_slice()).unwrap()), vec![]);
+
+        // writing node by node into a compressing stream
+        let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+        zip.start_file("document.xml", zip::write::SimpleFileOptions::default()).unwrap();
+        let mut writer = start_xml_document(&mut zip, &[Node::comment("zipped")], &SerializerOptions::default()).unwrap();
+        recurse_write_xml(&mut writer, root.clone()).unwrap();
+        let mut archive = zip::ZipArchive::new(zip.finish().unwrap()).unwrap();
+        let zipped = Node::parse(archive.by_name("document.xml").unwrap()).unwrap();
+        assert_eq!(diff_nodes(&zipped, &root), vec![]);
+    }
+
+    #[test]
+    fn test_to_xml_string_matches_written_document() {
+        let mut root = get_iec_template();
+        root.find_mut(GLOBAL_NAMESPACE_PATH).unwrap().child_borrowed(&SFunctionBlock::new()
+            .attribute_str("name", "Motor")
+            .attribute_str("comment", "a & b")
+            .child(&SExpression::new().content(String::from("x < 0 (* ]]> *)")))
+            .child(&Node::comment("body")));
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_to_xml_string_matches_written_document.xml");
+        write_xml_file(&output_path, root.clone()).unwrap();
+        let written = std::fs::read_to_string(&output_path).unwrap();
+        let _ = std::fs::remove_file(&output_path);
+
+        let declaration = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n";
+        assert_eq!(format!("{declaration}{}", root.to_xml_string().unwrap()), written);
+        assert_eq!(
+            SExpression::new().content(String::from("a ]]> b")).to_xml_string().unwrap(),
+            "<expression><![CDATA[a ]]]]><![CDATA[> b]]></expression>"
+        );
+        assert_eq!(SDataTypeDecl::new().to_xml_string().unwrap(), "<DataTypeDecl />");
+    }
+
+    #[test]
+    fn test_mixed_content() {
+        let paragraph = Node::new_str("p")
+            .child(&Node::text("Press "))
+            .child(&Node::new_str("b").content_borrowed(String::from("F5")))
+            .child(&Node::text(" to start & "))
+            .child(&Node::new_str("i").child(&Node::new_str("br")));
+        assert!(paragraph.is_mixed());
+        let root = Node::new_str("xhtml").child(&paragraph).child(&Node::new_str("hr"));
+
+        let written = root.to_xml_string().unwrap();
+        assert_eq!(written, "<xhtml>\n  <p>Press <b><![CDATA[F5]]></b> to start &amp; <i>\n      <br />\n    </i></p>\n  <hr />\n</xhtml>");
+        assert_eq!(paragraph.serialize(0), "<p >Press <b>F5</b> to start &amp; <i >\n    <br >\n    </br>\n</i></p>\n");
+
+        let read = Node::parse(written.as_bytes()).unwrap();
+        assert_eq!(diff_nodes(&root, &read), vec![]);
+        let read_paragraph = read.find("p").unwrap();
+        assert_eq!(read_paragraph.children.iter().map(|it| it.kind).collect::<Vec<_>>(), vec![NodeKind::Text, NodeKind::Element, NodeKind::Text, NodeKind::Element]);
+        assert_eq!(read_paragraph.find("b").unwrap().content.as_deref(), Some("F5"));
+
+        // content followed by children is no longer dropped
+        let labelled = Node::new_str("label").content_borrowed(String::from("Speed: ")).child(&Node::new_str("value").content_borrowed(String::from("10")));
+        assert_eq!(labelled.to_xml_string().unwrap(), "<label><![CDATA[Speed: ]]><value><![CDATA[10]]></value></label>");
+        let changed = Node::new_str("label").child(&Node::text("Speed:")).child(&Node::new_str("value").content_borrowed(String::from("10")));
+        assert_eq!(diff_report(&diff_nodes(&labelled, &changed)), "~ label content: 'Speed: ' -> 'Speed:'\n");
+    }
+
+    #[test]
+    fn test_serializer_options() {
+        let root = Node::new_str("Project").child(&STypes::new().child(&SDataTypeDecl::new()));
+        assert_eq!(root.to_xml_string().unwrap(), "<Project>\n  <Types>\n    <DataTypeDecl />\n  </Types>\n</Project>");
+
+        let options = SerializerOptions { indent: Some(String::from("\t")), line_separator: String::from("\r\n"), pad_self_closing: false };
+        assert_eq!(root.to_formatted_xml_string(&options).unwrap(), "<Project>\r\n\t<Types>\r\n\t\t<DataTypeDecl/>\r\n\t</Types>\r\n</Project>");
+
+        let options = SerializerOptions { indent: None, ..SerializerOptions::default() };
+        assert_eq!(root.to_formatted_xml_string(&options).unwrap(), "<Project><Types><DataTypeDecl /></Types></Project>");
+    }
+
+    #[test]
+    fn test_serialize_escapes_attributes_and_content() {
+        let node = SInVariable::id(1)
+            .attribute_str("comment", "say \"a & b\"")
+            .with_expression_str("x < 0 AND y > 0");
+        let serialized = node.serialize();
+
+        assert!(serialized.contains("comment=\"say &quot;a &amp; b&quot;\""));
+        assert!(serialized.contains("<expression>x &lt; 0 AND y &gt; 0</expression>"));
+
+        // reads back as written, like the document written by write_xml_file
+        let mut attributes = Vec::new();
+        let mut text = String::new();
+        for event in xml::reader::EventReader::new(serialized.as_bytes()) {
+            match event.unwrap() {
+                xml::reader::XmlEvent::StartElement { attributes: read, .. } => attributes.extend(read.into_iter().map(|it| (it.name.local_name, it.value))),
+                xml::reader::XmlEvent::Characters(read) => text.push_str(&read),
+                _ => {}
+            }
+        }
+        assert!(attributes.contains(&(String::from("comment"), String::from("say \"a & b\""))));
+        assert_eq!(text, "x < 0 AND y > 0");
+    }
+
+    #[test]
+    fn test_diff_nodes() {
+        let mut old = get_iec_template();
+        let namespace = old.find_mut(GLOBAL_NAMESPACE_PATH).unwrap();
+        namespace.child_borrowed(&SDataTypeDecl::new().attribute_str("name", "Motor").attribute_str("kind", "struct"));
+        namespace.child_borrowed(&SDataTypeDecl::new().attribute_str("name", "Valve"));
+        namespace.child_borrowed(&SFunctionBlock::new().attribute_str("name", "Drive").child(&SExpression::new().content(String::from("a + b"))));
+
+        let mut new = old.clone(); // the same creation time
+        let namespace = new.find_mut(GLOBAL_NAMESPACE_PATH).unwrap();
+        namespace.children.clear();
+        namespace.child_borrowed(&SFunctionBlock::new().attribute_str("name", "Drive").child(&SExpression::new().content(String::from("a - b"))));
+        namespace.child_borrowed(&SDataTypeDecl::new().attribute_str("name", "Motor").attribute_str("kind", "union").attribute_str("comment", "new"));
+        namespace.child_borrowed(&SDataTypeDecl::new().attribute_str("name", "Pump"));
+
+        assert!(diff_nodes(&old, &old.clone()).is_empty());
+        assert_eq!(diff_report(&diff_nodes(&old, &old)), "no changes\n");
+
+        let namespace = format!("{}/{GLOBAL_NAMESPACE_PATH}", old.name);
+        assert_eq!(diff_nodes(&old, &new), vec![
+            NodeChange::AttributeAdded { path: format!("{namespace}/DataTypeDecl[@name='Motor']"), key: String::from("comment"), value: String::from("new") },
+            NodeChange::AttributeChanged { path: format!("{namespace}/DataTypeDecl[@name='Motor']"), key: String::from("kind"), old: String::from("struct"), new: String::from("union") },
+            NodeChange::ElementRemoved { path: format!("{namespace}/DataTypeDecl[@name='Valve']") },
+            NodeChange::ContentChanged { path: format!("{namespace}/FunctionBlock[@name='Drive']/expression"), old: Some(String::from("a + b")), new: Some(String::from("a - b")) },
+            NodeChange::ElementAdded { path: format!("{namespace}/DataTypeDecl[@name='Pump']") },
+        ]);
+        assert_eq!(diff_report(&diff_nodes(&old, &new)), format!("\
++ {namespace}/DataTypeDecl[@name='Motor'] @comment='new'
+~ {namespace}/DataTypeDecl[@name='Motor'] @kind: 'struct' -> 'union'
+- {namespace}/DataTypeDecl[@name='Valve']
+~ {namespace}/FunctionBlock[@name='Drive']/expression content: 'a + b' -> 'a - b'
++ {namespace}/DataTypeDecl[@name='Pump']
+"));
+    }
+
+    #[test]
+    fn test_diff_nodes_numbers_unnamed_siblings() {
+        let old = SInterface::new().child(&SLocalVars::new()).child(&SLocalVars::new().attribute_str("constant", "true"));
+        let new = SInterface::new().child(&SLocalVars::new());
+
+        assert_eq!(diff_report(&diff_nodes(&old.inner(), &new.inner())), "\
+- interface/localVars[2]
+");
+    }
+
+    #[test]
+    fn test_format_enum_initials_negative_values() {
+        let variants = vec![
+            NameAndInitialValue {
+                name: String::from("NEG"),
+                initial_value: String::from("-1"),
+            },
+            NameAndInitialValue {
+                name: String::from("NEG2"),
+                initial_value: String::from("-1"), // conflict
+            },
+        ];
+
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 2);
+        // NEG=-1, NEG2=0 (incremented from -1)
+    }
+
+    #[test]
+    fn test_format_enum_initials_consecutive_conflicts() {
+        let variants = vec![
+            NameAndInitialValue {
+                name: String::from("A"),
+                initial_value: String::from("0"),
+            },
+            NameAndInitialValue {
+                name: String::from("B"),
+                initial_value: String::from("1"),
+            },
+            NameAndInitialValue {
+                name: String::from("C"),
+                initial_value: String::from("0"), // conflicts with A, tries 1 (taken by B), settles on 2
+            },
+        ];
+
+        let result = format_enum_initials(variants);
+        assert_eq!(result.len(), 3);
+    }
+
+    #[test]
+    fn test_project_model_exports_types_globals_and_pous() {
+        use crate::project_model::collect_project_model;
+
+        let temp_dir = std::env::temp_dir().join("test_project_model_exports_types_globals_and_pous");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("line.st").to_string_lossy().into_owned().into_boxed_str());
+        let program_body = "counter := counter + 1;";
+        std::fs::write(source_path, program_body).unwrap();
+
+        let mut unit = make_unit(source_path);
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![make_variable("gSpeed", "REAL")]));
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::EnumType {
+                name: Some(String::from("Mode")),
+                numeric_type: String::from("INT"),
+                elements: AstFactory::create_expression_list(vec![make_enum_assignment("AUTO", 0), make_enum_assignment("MANUAL", 1)], SourceLocation::internal(), 0),
+            },
+            initializer: None,
+            location: make_source_location(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+        unit.pous.push(Pou {
+            id: 1,
+            name: String::from("Line"),
+            kind: PouType::Program,
+            variable_blocks: vec![VariableBlock::default().with_block_type(VariableBlockType::Local).with_variables(vec![make_variable("counter", "DINT")])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("Line"),
+            type_name: String::from("Line"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, program_body.len(), program_body.len())),
+                file: FileMarker::File(source_path),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        let model = collect_project_model(&LoadedSources::default(), &vec![&unit]);
+        let json: serde_json::Value = serde_json::to_value(&model).unwrap();
+
+        assert_eq!(json["dataTypes"][0]["name"], "Mode");
+        assert_eq!(json["dataTypes"][0]["kind"], "enum");
+        assert_eq!(json["dataTypes"][0]["values"][1]["name"], "MANUAL");
+        assert_eq!(json["globals"][0]["name"], "gSpeed");
+        assert_eq!(json["globals"][0]["scope"], "global");
+        assert_eq!(json["pous"][0]["kind"], "Program");
+        assert_eq!(json["pous"][0]["variables"][0]["typeName"], "DINT");
+        assert_eq!(json["pous"][0]["body"], program_body);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_call_graph_follows_instances_and_calls() {
+        use crate::call_graph::{collect_call_graph, format_call_graph};
+
+        let make_pou = |name: &str, kind: PouType, variables: Vec<Variable>| Pou {
+            id: 0,
+            name: String::from(name),
+            kind,
+            variable_blocks: vec![VariableBlock::default().with_block_type(VariableBlockType::Local).with_variables(variables)],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let make_implementation = |name: &str, kind: PouType, callees: Vec<&str>| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type: kind,
+            statements: callees.into_iter().map(|callee| {
+                let operator = AstFactory::create_member_reference(AstFactory::create_identifier(callee, SourceLocation::internal(), 0), None, 0);
+                AstFactory::create_call_statement(operator, None, 0, SourceLocation::internal())
+            })
+            .collect(),
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        let mut unit = make_unit("plant.st");
+        unit.pous.push(make_pou("Main", PouType::Program, vec![make_variable("conveyor", "Motor"), make_variable("count", "INT")]));
+        unit.pous.push(make_pou("Motor", PouType::FunctionBlock, vec![]));
+        unit.pous.push(make_pou("Scale", PouType::Function, vec![]));
+        unit.implementations.push(make_implementation("Main", PouType::Program, vec!["conveyor", "scale", "Scale"]));
+        unit.implementations.push(make_implementation("Motor", PouType::FunctionBlock, vec!["Scale", "Unknown"]));
+
+        let graph = collect_call_graph(&vec![&unit]);
+        let edges: Vec<(&str, &str, Option<&str>)> = graph.edges.iter().map(|it| (it.caller.as_str(), it.callee.as_str(), it.instance.as_deref())).collect();
+        assert_eq!(edges, vec![("Main", "Motor", Some("conveyor")), ("Main", "Scale", None), ("Motor", "Scale", None)]);
+
+        let dot = format_call_graph(&graph);
+        assert!(dot.starts_with("digraph CallGraph {"));
+        assert!(dot.contains("\"Main\" [shape=box, style=bold];"));
+        assert!(dot.contains("\"Motor\" [shape=component];"));
+        assert!(dot.contains("\"Main\" -> \"Motor\" [label=\"conveyor\"];"));
+        assert!(dot.contains("\"Motor\" -> \"Scale\";"));
+    }
+
+    #[test]
+    fn test_cross_references_count_reads_and_writes() {
+        use crate::cross_reference::{collect_cross_references, format_cross_references, CrossReferenceFormat};
+        use plc_ast::ast::Operator;
+
+        let reference = |name: &str| AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0);
+
+        let mut unit = make_unit("plant.st");
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![
+            make_variable("gSpeed", "REAL"),
+            make_variable("gLimit", "REAL"),
+            make_variable("gRun", "BOOL"),
+            make_variable("gDone", "BOOL"),
+        ]));
+        unit.pous.push(Pou {
+            id: 1,
+            name: String::from("Main"),
+            kind: PouType::Program,
+            variable_blocks: vec![VariableBlock::default().with_block_type(VariableBlockType::Local).with_variables(vec![make_variable("counter", "INT"), make_variable("timer", "TON")])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+
+        let increment = AstFactory::create_binary_expression(reference("counter"), Operator::Plus, AstFactory::create_literal(AstLiteral::Integer(1), SourceLocation::internal(), 0), 0);
+        let parameters = AstFactory::create_expression_list(vec![
+            AstFactory::create_assignment(reference("IN"), reference("gRun"), 0),
+            AstFactory::create_output_assignment(reference("Q"), reference("gDone"), 0),
+        ], SourceLocation::internal(), 0);
+        unit.implementations.push(Implementation {
+            name: String::from("Main"),
+            type_name: String::from("Main"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![
+                AstFactory::create_assignment(reference("counter"), increment, 0),
+                AstFactory::create_assignment(reference("gSpeed"), reference("gLimit"), 0),
+                AstFactory::create_call_statement(reference("timer"), Some(parameters), 0, SourceLocation::internal()),
+            ],
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        let references = collect_cross_references(&vec![&unit]);
+        let summary: Vec<(&str, &str, &str, u32, u32)> = references.iter().map(|it| (it.variable.as_str(), it.declared_in.as_str(), it.pou.as_str(), it.reads, it.writes)).collect();
+        assert_eq!(summary, vec![
+            ("gDone", "", "Main", 0, 1),
+            ("gLimit", "", "Main", 1, 0),
+            ("gRun", "", "Main", 1, 0),
+            ("gSpeed", "", "Main", 0, 1),
+            ("counter", "Main", "Main", 1, 1),
+            ("timer", "Main", "Main", 1, 0),
+        ]);
+
+        let csv = format_cross_references(&references, CrossReferenceFormat::Csv).unwrap();
+        assert!(csv.starts_with("Variable,Declared In,POU,Reads,Writes\r\ngDone,,Main,0,1\r\n"));
+        let json: serde_json::Value = serde_json::from_str(&format_cross_references(&references, CrossReferenceFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[4]["declaredIn"], "Main");
+    }
+}
+#[cfg(test)]
+mod xml_target_tests {
+    use std::{path::PathBuf, sync::Arc};
+
+    use plc_ast::ast::{AstStatement, CompilationUnit, DataTypeDeclaration, DirectAccessType, HardwareAccessType, LinkageType, Pou, PouType, Variable, VariableBlock};
+    use plc_source::source_location::{CodeSpan, FileMarker, SourceLocation, TextLocation};
+
+    use crate::serializer::*;
+    use crate::source_comments::LoadedSources;
+    use crate::xml_gen::*;
+    use crate::xml_target::*;
+
+    /// Helper: Create a unit containing a single global variable
+    fn unit_with_global(file_name: &'static str, name: &str, type_name: &str) -> CompilationUnit {
+        let mut unit = CompilationUnit::new(file_name);
+        unit.global_vars.push(VariableBlock::global().with_variables(vec![Variable {
+            name: String::from(name),
+            data_type_declaration: DataTypeDeclaration::Reference {
+                referenced_type: String::from(type_name),
+                location: SourceLocation::internal(),
+            },
+            initializer: None,
+            address: None,
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 10, 10)),
+                file: FileMarker::Internal("<test>"),
+            },
+        }]));
+        unit
+    }
+
+    struct RenamingTarget;
+
+    impl XmlTarget for RenamingTarget {
+        fn name(&self) -> &'static str {
+            "renaming"
+        }
+
+        fn template(&self) -> Node {
+            get_omron_template()
+        }
+
+        fn map_type_name(&self, _generation_parameters: &GenerationParameters, type_name: &str) -> String {
+            format!("My{type_name}")
+        }
+
+        fn supports_streaming(&self) -> bool {
+            false
+        }
+
+        fn processing_instructions(&self) -> Vec<Node> {
+            vec![Node::processing_instruction("renaming-importer", "version=\"2\"")]
+        }
+
+        fn post_process(&self, output_root: &mut Node) {
+            output_root.attributes.insert(String::from("postProcessed"), String::from("true"));
+        }
+    }
+
+    #[test]
+    fn test_default_registry_contains_omron() {
+        let registry = TargetRegistry::default();
+        assert_eq!(registry.names(), vec!["iec", "omron", "codesys", "tia", "opcua", "aml"]);
+        assert!(registry.get("OMRON").is_some());
+        assert!(registry.get("unknown").is_none());
+    }
+
+    #[test]
+    fn test_registering_a_target_replaces_same_name() {
+        let mut registry = TargetRegistry::default();
+        registry.register(Arc::new(RenamingTarget));
+        registry.register(Arc::new(RenamingTarget));
+        assert_eq!(registry.names(), vec!["iec", "omron", "codesys", "tia", "opcua", "aml", "renaming"]);
+    }
+
+    #[test]
+    fn test_xml_omron_flag_selects_omron_target() {
+        let mut params = GenerationParameters::new();
+        assert!(params.target_names().is_empty());
+
+        params.output_xml_omron = true;
+        assert_eq!(params.target_names(), vec!["omron"]);
+
+        params.xml_targets = vec![String::from("renaming"), String::from("iec"), String::from("RENAMING")];
+        assert_eq!(params.target_names(), vec!["renaming", "iec"]);
+    }
+
+    #[test]
+    fn test_target_output_path() {
+        assert_eq!(target_output_path(&PathBuf::from("build/out.xml"), "omron"), PathBuf::from("build/out.omron.xml"));
+        assert_eq!(target_output_path(&PathBuf::from("out"), "iec"), PathBuf::from("out.iec"));
+    }
+
+    #[test]
+    fn test_generate_xml_targets_writes_one_file_per_target() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_multi_target.xml");
+        let mut params = GenerationParameters::new();
+        params.targets.register(Arc::new(RenamingTarget));
+        params.xml_targets = vec![String::from("omron"), String::from("renaming")];
+
+        let unit = unit_with_global("multi.st", "gValue", "STRING");
+        let units = vec![&unit];
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        assert_eq!(written, vec![temp_dir.join("test_multi_target.omron.xml"), temp_dir.join("test_multi_target.renaming.xml")]);
+
+        let omron = std::fs::read_to_string(&written[0]).unwrap();
+        assert!(omron.contains("String[1986]"));
+        let renamed = std::fs::read_to_string(&written[1]).unwrap();
+        assert!(renamed.contains("MySTRING"));
+
+        written.iter().for_each(|it| { let _ = std::fs::remove_file(it); });
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_copies_every_target() {
+        let temp_dir = std::env::temp_dir().join("test_copy_every_target");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let build_path = temp_dir.join("build.xml");
+        std::fs::write(temp_dir.join("build.iec.xml"), "<Iec/>").unwrap();
+        std::fs::write(temp_dir.join("build.omron.xml"), "<Omron/>").unwrap();
+
+        let output_path = temp_dir.join("out.xml");
+        let targets = vec![String::from("iec"), String::from("omron")];
+        copy_xmlfile_to_output(vec![build_path.as_path()], output_path, &targets).unwrap();
+
+        assert_eq!(std::fs::read_to_string(temp_dir.join("out.iec.xml")).unwrap(), "<Iec/>");
+        assert_eq!(std::fs::read_to_string(temp_dir.join("out.omron.xml")).unwrap(), "<Omron/>");
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_generate_xml_targets_single_target_uses_output_path() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_single_target.xml");
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+
+        let units: Vec<&CompilationUnit> = vec![];
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        assert_eq!(written, vec![output_path.clone()]);
+
+        params.xml_targets = vec![String::from("unknown")];
+        assert!(generate_xml_targets(&params, &units, &output_path).is_err());
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generate_xml_targets_reads_loaded_sources() {
+        use plc_ast::ast::Implementation;
+        use plc_source::SourceCode;
+
+        // the source only exists in memory, as if built by a tool embedding the compiler
+        let source_path = "in_memory/pump.st";
+        let source = "// Pumps until the tank is full\nPROGRAM pump\nlevel := level + 1;\nEND_PROGRAM\n";
+        let at = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+
+        let mut unit = CompilationUnit::new(source_path);
+        unit.pous.push(Pou {
+            id: 0,
+            name: String::from("pump"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: at("PROGRAM pump"),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("pump"),
+            type_name: String::from("pump"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: at("level := level + 1;"),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_loaded_sources.xml");
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        params.sources = LoadedSources::new(&[SourceCode::new(source, source_path)]);
+
+        let units = vec![&unit];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<![CDATA[level := level + 1;]]>"));
+        assert!(contents.contains("Pumps until the tank is full"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_generate_xml_targets_writes_source_map() {
+        use plc_ast::ast::Implementation;
+        use plc_source::SourceCode;
+        use crate::source_map::source_map_path;
+
+        let source_path = "in_memory/mixer.st";
+        let source = "PROGRAM mixer\nVAR level : INT; END_VAR\nlevel := level + 1;\nlevel := level * 2;\nEND_PROGRAM\n";
+        let at = |text: &str, line: usize| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(line, 0, offset)..TextLocation::new(line, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+
+        let mut unit = CompilationUnit::new(source_path);
+        unit.pous.push(Pou {
+            id: 0,
+            name: String::from("mixer"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: at("PROGRAM mixer", 0),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(Implementation {
+            name: String::from("mixer"),
+            type_name: String::from("mixer"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: at("level := level + 1;\nlevel := level * 2;", 2),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_source_map.xml");
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        params.sources = LoadedSources::new(&[SourceCode::new(source, source_path)]);
+        params.source_map = true;
+
+        let units = vec![&unit];
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        let map_path = source_map_path(&output_path);
+        assert!(written.contains(&map_path));
+
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&map_path).unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!([{ "pou": "mixer", "file": source_path, "line": 3, "lines": [3, 4] }]));
+
+        // printed bodies don't keep the lines of the source
+        params.canonical_bodies = true;
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(&map_path).unwrap()).unwrap();
+        assert_eq!(json, serde_json::json!([{ "pou": "mixer", "file": source_path, "line": 3 }]));
+
+        let _ = std::fs::remove_file(&output_path);
+        let _ = std::fs::remove_file(&map_path);
+    }
+
+    #[test]
+    fn test_streamed_documents_match_built_documents() {
+        use plc_ast::ast::Implementation;
+        use plc_source::SourceCode;
+
+        let source_path = "in_memory/stream.st";
+        let source = "PROGRAM pump\nPriority := Priority + 1;\nEND_PROGRAM\n";
+        let at = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+
+        let mut program_unit = unit_with_global("in_memory/stream.st", "gValue", "STRING");
+        program_unit.pous.push(Pou {
+            id: 0,
+            name: String::from("pump"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: at("PROGRAM pump"),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        program_unit.implementations.push(Implementation {
+            name: String::from("pump"),
+            type_name: String::from("pump"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: at("Priority := Priority + 1;"),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+        // the global of another unit is a reserved word of Sysmac Studio, renamed in the body of the first one as well
+        let global_unit = unit_with_global("stream_globals.st", "Priority", "INT");
+        let units = vec![&program_unit, &global_unit];
+
+        let temp_dir = std::env::temp_dir();
+        let document = |target: &str, streaming: bool| {
+            let output_path = temp_dir.join(format!("test_streaming_{target}_{streaming}.xml"));
+            let mut params = GenerationParameters::new();
+            params.targets.register(Arc::new(RenamingTarget));
+            params.xml_targets = vec![String::from(target)];
+            params.sources = LoadedSources::new(&[SourceCode::new(source, source_path)]);
+            params.streaming = streaming;
+            generate_xml_targets(&params, &units, &output_path).unwrap();
+
+            let contents = std::fs::read_to_string(&output_path).unwrap();
+            let _ = std::fs::remove_file(&output_path);
+            let _ = std::fs::remove_file(output_path.with_extension("renames.csv"));
+            contents.lines().filter(|it| !it.contains("creationDateTime")).collect::<Vec<_>>().join("\n")
+        };
+
+        for target in ["iec", "omron", "tia"] {
+            assert_eq!(document(target, true), document(target, false), "{target}");
+        }
+        let omron = document("omron", true);
+        assert!(omron.contains("<![CDATA[Priority_1 := Priority_1 + 1;]]>"));
+        assert!(omron.contains("name=\"Priority_1\""));
+
+        // targets rewriting the whole document build it as a whole
+        assert!(document("renaming", true).contains("postProcessed=\"true\""));
+    }
+
+    #[test]
+    fn test_generate_xml_targets_writes_provenance_comment() {
+        let units = unit_with_global("provenance.st", "gSpeed", "INT");
+        let units = vec![&units];
+
+        let temp_dir = std::env::temp_dir();
+        for (target, streaming) in [("iec", false), ("iec", true), ("tia", false)] {
+            let output_path = temp_dir.join(format!("test_provenance_{target}_{streaming}.xml"));
+            let mut params = GenerationParameters::new();
+            params.xml_targets = vec![String::from(target)];
+            params.provenance_comment = true;
+            params.streaming = streaming;
+            generate_xml_targets(&params, &units, &output_path).unwrap();
+
+            let contents = std::fs::read_to_string(&output_path).unwrap();
+            let _ = std::fs::remove_file(&output_path);
+            let comments: Vec<String> = xml::ParserConfig::new().ignore_comments(false).create_reader(contents.as_bytes())
+                .into_iter()
+                .filter_map(|it| match it.unwrap() {
+                    xml::reader::XmlEvent::Comment(text) => Some(text),
+                    _ => None,
+                })
+                .collect();
+
+            assert_eq!(comments.len(), 1, "{target}");
+            assert!(comments[0].starts_with(&format!(" Generated by rusty {} from provenance.st on ", env!("CARGO_PKG_VERSION"))), "{target}");
+        }
+    }
+
+    #[test]
+    fn test_generate_xml_targets_formats_documents() {
+        let unit = unit_with_global("formatted.st", "gSpeed", "INT");
+        let units = vec![&unit];
+
+        let temp_dir = std::env::temp_dir();
+        for streaming in [false, true] {
+            let output_path = temp_dir.join(format!("test_formatted_{streaming}.xml"));
+            let mut params = GenerationParameters::new();
+            params.xml_targets = vec![String::from("iec")];
+            params.serializer_options.indent = Some(String::from("\t"));
+            params.streaming = streaming;
+            generate_xml_targets(&params, &units, &output_path).unwrap();
+
+            let contents = std::fs::read_to_string(&output_path).unwrap();
+            let _ = std::fs::remove_file(&output_path);
+            assert!(contents.contains("\n\t<FileHeader"), "{streaming}");
+            assert!(contents.contains("\n\t\t<GlobalNamespace"), "{streaming}");
+            assert!(!contents.contains("\n  <"), "{streaming}");
+        }
+    }
+
+    #[test]
+    fn test_custom_target_hooks_are_used() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_custom_target_hooks.xml");
+        let params = GenerationParameters::new();
+
+        let unit = unit_with_global("custom.st", "gValue", "INT");
+
+        let units = vec![&unit];
+        parse_project_into_nodetree(&params, &RenamingTarget, &units, &output_path, &mut Vec::new()).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("MyINT"));
+        assert!(contents.contains("postProcessed=\"true\""));
+        assert!(contents.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<?renaming-importer version=\"2\"?>\n<Project"));
+        // the default target hooks add no vendor specific data
+        assert!(!contents.contains("GlobalVariableAdditionalProperties"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_iec_template_has_no_vendor_namespace() {
+        let template = IecTarget.template();
+        assert_eq!(template.attributes.get("xmlns").map(String::as_str), Some(IEC_NAMESPACE));
+        assert!(!template.attributes.contains_key("xmlns:smcext"));
+        assert!(!template.attributes.contains_key("xsi:schemaLocation"));
+        assert_eq!(template.children.len(), 4);
+    }
+
+    #[test]
+    fn test_iec_target_omits_add_data() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_iec_target_omits_add_data.xml");
+        let params = GenerationParameters::new();
+
+        let unit = unit_with_global("iec.st", "gText", "STRING");
+
+        let units = vec![&unit];
+        parse_project_into_nodetree(&params, &IecTarget, &units, &output_path, &mut Vec::new()).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("gText"));
+        assert!(contents.contains("STRING"));
+        assert!(!contents.contains("AddData"));
+        assert!(!contents.contains("smcext"));
+        assert!(!contents.contains("String[1986]"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+    #[test]
+    fn test_targets_without_an_archive_keep_the_bare_document() {
+        let temp_dir = std::env::temp_dir().join("test_targets_without_an_archive_keep_the_bare_document");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("project.xml");
+
+        // Sysmac Studio imports no archive of ours, so omron keeps the bare document like iec
+        for target in ["iec", "omron"] {
+            let mut params = GenerationParameters::new();
+            params.xml_targets = vec![String::from(target)];
+            params.package_archive = true;
+
+            let units: Vec<&CompilationUnit> = vec![];
+            generate_xml_targets(&params, &units, &output_path).unwrap();
+            assert!(output_path.exists());
+            assert!(!temp_dir.join("project.zip").exists());
+        }
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_copies_archive() {
+        let temp_dir = std::env::temp_dir().join("test_copy_xmlfile_to_output_copies_archive");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let build_path = temp_dir.join("build.xml");
+        std::fs::write(&build_path, "<Omron/>").unwrap();
+        std::fs::write(temp_dir.join("build.zip"), "archive").unwrap();
+
+        let output_path = temp_dir.join("out.xml");
+        copy_xmlfile_to_output(vec![build_path.as_path()], output_path, &[]).unwrap();
+
+        assert_eq!(std::fs::read_to_string(temp_dir.join("out.zip")).unwrap(), "archive");
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+    #[test]
+    fn test_codesys_target_packages_library() {
+        let temp_dir = std::env::temp_dir().join("test_codesys_target_packages_library");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("utilities.xml");
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("codesys")];
+        params.package_archive = true;
+        params.library.version = String::from("1.2.0.0");
+        params.library.namespace = Some(String::from("util"));
+
+        let unit = unit_with_global("library.st", "gValue", "INT");
+        let units = vec![&unit];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+
+        let library = std::fs::File::open(temp_dir.join("utilities.zip")).unwrap();
+        let mut library = zip::ZipArchive::new(library).unwrap();
+        let mut names: Vec<&str> = library.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["projectinformation.xml", "utilities.xml"]);
+
+        let mut information = String::new();
+        std::io::Read::read_to_string(&mut library.by_name("projectinformation.xml").unwrap(), &mut information).unwrap();
+        assert!(information.contains("value=\"utilities\""));
+        assert!(information.contains("value=\"1.2.0.0\""));
+        assert!(information.contains("value=\"util\""));
+        assert!(information.contains("document=\"utilities.xml\""));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_library_lists_pous_and_data_types() {
+        let temp_dir = std::env::temp_dir().join("test_library_lists_pous_and_data_types");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let document_path = temp_dir.join("lib.xml");
+        std::fs::write(&document_path, r#"<Project><Types><GlobalNamespace>
+            <DataTypeDecl name="Color"/>
+            <FunctionBlock name="Counter"/>
+            <Function name="Add"/>
+            <Program name="Main"/>
+        </GlobalNamespace></Types></Project>"#).unwrap();
+
+        let library_path = crate::library::package_library(&document_path, &Default::default(), &[]).unwrap();
+        assert_eq!(library_path, temp_dir.join("lib.zip"));
+
+        let mut library = zip::ZipArchive::new(std::fs::File::open(&library_path).unwrap()).unwrap();
+        let mut information = String::new();
+        std::io::Read::read_to_string(&mut library.by_name("projectinformation.xml").unwrap(), &mut information).unwrap();
+
+        let objects: Vec<&str> = information.lines().filter(|it| it.contains("<Object")).collect();
+        assert_eq!(objects.len(), 4);
+        assert!(objects[0].contains("kind=\"dataType\"") && objects[0].contains("name=\"Color\""));
+        assert!(objects[1].contains("kind=\"functionBlock\"") && objects[1].contains("name=\"Counter\""));
+        assert!(objects[2].contains("kind=\"function\"") && objects[2].contains("name=\"Add\""));
+        assert!(objects[3].contains("kind=\"program\"") && objects[3].contains("name=\"Main\""));
+        // the namespace defaults to the title, which defaults to the document name
+        assert!(information.contains("value=\"lib\""));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+    #[test]
+    fn test_tia_target_maps_type_names() {
+        let params = GenerationParameters::new();
+        assert_eq!(TiaTarget.map_type_name(&params, "INT"), "Int");
+        assert_eq!(TiaTarget.map_type_name(&params, "lreal"), "LReal");
+        assert_eq!(TiaTarget.map_type_name(&params, "__global_textString"), "String");
+        assert_eq!(TiaTarget.map_type_name(&params, "STRING[20]"), "String[20]");
+        assert_eq!(TiaTarget.map_type_name(&params, "WSTRING[12]"), "WString[12]");
+        assert_eq!(TiaTarget.map_type_name(&params, "MyStruct"), "\"MyStruct\"");
+    }
+
+    #[test]
+    fn test_tia_target_emits_function_block() {
+        let pou = PouElements {
+            name: String::from("Counter"),
+            kind: PouType::FunctionBlock,
+            access: None,
+            overriding: false,
+            extends: None,
+            implements: Vec::new(),
+            en_eno: true,
+            add_data: None,
+            documentation: None,
+            result_type: SResultType::new(),
+            parameters: SParameters::new()
+                .child(&SInputVars::new().child(&SGenVariable::new()
+                    .attribute(String::from("name"), String::from("enable"))
+                    .child(&SType::new().child(&STypeName::new().content(String::from("Bool"))))))
+                .child(&SInoutVars::new())
+                .child(&SOutputVars::new()),
+            externals: SExternalVars::new(),
+            constant_externals: SExternalVars::new(),
+            vars: SVars::new().child(&SGenVariable::new()
+                .attribute(String::from("name"), String::from("count"))
+                .child(&SType::new().child(&STypeName::new().content(String::from("Int"))))
+                .child(&SInitialValue::new().child(&SSimpleValue::new().attribute(String::from("value"), String::from("5")).close()))),
+            constant_vars: SVars::new(),
+            retain_vars: SVars::new(),
+            constant_retain_vars: SVars::new(),
+            retention_vars: Vec::new(),
+            temp_vars: STempVars::new(),
+            constant_temp_vars: STempVars::new(),
+            main_body: SMainBody::new().child(&SBodyContent::new().child(&SST::new().content(String::from("count := count + 1;")))),
+            methods: Vec::new(),
+            actions: Vec::new(),
+        };
+
+        let block = TiaTarget.emit_pou(pou).unwrap();
+        assert_eq!(block.name, "SW.Blocks.FB");
+
+        let attributes = block.find_child("AttributeList").unwrap();
+        assert_eq!(attributes.find_child("Name").unwrap().content.as_deref(), Some("Counter"));
+        assert_eq!(attributes.find_child("SetENOAutomatically").unwrap().content.as_deref(), Some("true"));
+
+        let sections = attributes.find_child("Interface").unwrap().find_child("Sections").unwrap();
+        let section_names: Vec<&str> = sections.children.iter().map(|it| it.attributes["Name"].as_str()).collect();
+        assert_eq!(section_names, vec!["Input", "Output", "InOut", "Static", "Temp", "Constant"]);
+
+        let input = &sections.children[0].children[0];
+        assert_eq!(input.attributes["Name"], "enable");
+        assert_eq!(input.attributes["Datatype"], "Bool");
+
+        let count = &sections.children[3].children[0];
+        assert_eq!(count.attributes["Name"], "count");
+        assert_eq!(count.find_child("StartValue").unwrap().content.as_deref(), Some("5"));
+
+        let source = block.find_child("ObjectList").unwrap()
+            .find_child("SW.Blocks.CompileUnit").unwrap()
+            .find_child("AttributeList").unwrap()
+            .find_child("NetworkSource").unwrap()
+            .find_child("StructuredText").unwrap();
+        assert_eq!(source.content.as_deref(), Some("count := count + 1;"));
+    }
+
+    #[test]
+    fn test_tia_target_writes_openness_document() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_tia_target_writes_openness_document.xml");
+        let params = GenerationParameters::new();
+
+        let unit = unit_with_global("plant.st", "gSpeed", "REAL");
+        let units = vec![&unit];
+        parse_project_into_nodetree(&params, &TiaTarget, &units, &output_path, &mut Vec::new()).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<Document>"));
+        assert!(contents.contains("<Engineering version=\"V17\" />"));
+        assert!(contents.contains("SW.Blocks.GlobalDB"));
+        assert!(contents.contains("plant_Globals"));
+        assert!(contents.contains("Name=\"gSpeed\""));
+        assert!(contents.contains("Datatype=\"Real\""));
+        assert!(!contents.contains("<Project"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+    #[test]
+    fn test_opcua_target_exports_globals() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_opcua_target_exports_globals.xml");
+        let params = GenerationParameters::new();
+
+        let unit = unit_with_global("plant.st", "gSpeed", "LREAL");
+        let units = vec![&unit];
+        parse_project_into_nodetree(&params, &OpcUaTarget, &units, &output_path, &mut Vec::new()).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<UANodeSet"));
+        assert!(contents.contains("urn:rusty:plc"));
+        assert!(contents.contains("NodeId=\"ns=1;s=plant\""));
+        assert!(contents.contains("NodeId=\"ns=1;s=plant.gSpeed\""));
+        assert!(contents.contains("DataType=\"Double\""));
+        assert!(contents.contains("AccessLevel=\"3\""));
+        assert!(!contents.contains("<Project"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_opcua_target_exports_user_types() {
+        let params = GenerationParameters::new();
+        assert_eq!(OpcUaTarget.map_type_name(&params, "Color"), "ns=1;s=DataType.Color");
+
+        let mut root = OpcUaTarget.template();
+        let namespace = root.children.iter_mut().find(|it| it.name == TYPES).unwrap().children.first_mut().unwrap();
+        namespace.children.push(SDataTypeDecl::new()
+            .attribute(String::from("name"), String::from("Point"))
+            .child(&SUserDefinedTypeSpec::new()
+                .attribute_str("xsi:type", "StructTypeSpec")
+                .child(&SMember::new()
+                    .attribute(String::from("name"), String::from("x"))
+                    .child(&SType::new().child(&STypeName::new().content(String::from("Int16"))))))
+            .inner());
+        namespace.children.push(SDataTypeDecl::new()
+            .attribute(String::from("name"), String::from("Color"))
+            .child(&SUserDefinedTypeSpec::new()
+                .attribute_str("xsi:type", "EnumTypeWithNamedValueSpec")
+                .children(format_enum_initials(vec![NameAndInitialValue { name: String::from("Red"), initial_value: String::from("4") }]))
+                .child(&SEnumerator::new().attribute(String::from("name"), String::from("Green"))))
+            .inner());
+
+        OpcUaTarget.post_process(&mut root);
+        let data_types: Vec<&Node> = root.children.iter().filter(|it| it.name == "UADataType").collect();
+        assert_eq!(data_types.len(), 2);
+
+        let point = data_types[0];
+        assert_eq!(point.attributes["NodeId"], "ns=1;s=DataType.Point");
+        assert_eq!(point.find_child("References").unwrap().children[0].content.as_deref(), Some("i=22"));
+        let field = &point.find_child("Definition").unwrap().children[0];
+        assert_eq!(field.attributes["Name"], "x");
+        assert_eq!(field.attributes["DataType"], "Int16");
+
+        let color = data_types[1];
+        assert_eq!(color.find_child("References").unwrap().children[0].content.as_deref(), Some("i=29"));
+        let fields: Vec<(&str, &str)> = color.find_child("Definition").unwrap().children.iter()
+            .map(|it| (it.attributes["Name"].as_str(), it.attributes["Value"].as_str()))
+            .collect();
+        // an enumerator without a value follows the one before it
+        assert_eq!(fields, vec![("Red", "4"), ("Green", "5")]);
+    }
+    #[test]
+    fn test_aml_target_exports_project_structure() {
+        let temp_dir = std::env::temp_dir();
+        let output_path = temp_dir.join("test_aml_target_exports_project_structure.aml");
+        let params = GenerationParameters::new();
+
+        let unit = unit_with_global("plant.st", "gSpeed", "LREAL");
+        let units = vec![&unit];
+        parse_project_into_nodetree(&params, &AutomationMlTarget, &units, &output_path, &mut Vec::new()).unwrap();
+
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<CAEXFile"));
+        assert!(contents.contains("RefBaseSystemUnitPath=\"IEC61131_3/Configuration\""));
+        assert!(contents.contains("Name=\"plant.st_Configuration\""));
+        assert!(contents.contains("RefBaseSystemUnitPath=\"IEC61131_3/Resource\""));
+        assert!(contents.contains("Name=\"gSpeed\""));
+        assert!(contents.contains("AttributeDataType=\"xs:double\""));
+        assert!(contents.contains("ID=\"00000000-0000-0000-0000-000000000001\""));
+        assert!(!contents.contains("<Project"));
+
+        let _ = std::fs::remove_file(&output_path);
+    }
+
+    #[test]
+    fn test_aml_target_emits_pou_variables() {
+        let pou = PouElements {
+            name: String::from("Scale"),
+            kind: PouType::Function,
+            access: None,
+            overriding: false,
+            extends: None,
+            implements: Vec::new(),
+            en_eno: false,
+            add_data: None,
+            documentation: None,
+            result_type: SResultType::new().child(&STypeName::new().content(String::from("REAL"))),
+            parameters: SParameters::new()
+                .child(&SInputVars::new().child(&SGenVariable::new()
+                    .attribute(String::from("name"), String::from("raw"))
+                    .child(&SType::new().child(&STypeName::new().content(String::from("Point"))))))
+                .child(&SInoutVars::new())
+                .child(&SOutputVars::new()),
+            externals: SExternalVars::new(),
+            constant_externals: SExternalVars::new(),
+            vars: SVars::new(),
+            constant_vars: SVars::new(),
+            retain_vars: SVars::new(),
+            constant_retain_vars: SVars::new(),
+            retention_vars: Vec::new(),
+            temp_vars: STempVars::new(),
+            constant_temp_vars: STempVars::new().attribute_str("constant", "true").child(&SGenVariable::new()
+                .attribute(String::from("name"), String::from("factor"))
+                .child(&SType::new().child(&STypeName::new().content(String::from("REAL"))))),
+            main_body: SMainBody::new(),
+            methods: Vec::new(),
+            actions: Vec::new(),
+        };
+
+        let element = AutomationMlTarget.emit_pou(pou).unwrap();
+        assert_eq!(element.attributes["RefBaseSystemUnitPath"], "IEC61131_3/Function");
+        let attributes: Vec<&str> = element.children.iter().map(|it| it.attributes["Name"].as_str()).collect();
+        assert_eq!(attributes, vec!["raw", "factor", "ReturnType"]);
+
+        assert_eq!(element.children[0].attributes["RefAttributeType"], "UserTypes/Point");
+        assert_eq!(element.children[1].attributes["AttributeDataType"], "xs:float");
+
+        let nested: Vec<&str> = element.children[1].children.iter().map(|it| it.attributes["Name"].as_str()).collect();
+        assert_eq!(nested, vec!["IecType", "Kind", "Constant"]);
+    }
+
+    #[test]
+    fn test_instance_initial_values_per_member() {
+        use plc_ast::{ast::AstFactory, literals::{AstLiteral, Time}};
+
+        let temp_dir = std::env::temp_dir().join("test_instance_initial_values_per_member");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+
+        let literal = |value: AstLiteral| AstFactory::create_literal(value, SourceLocation::internal(), 0);
+        let member = |name: &str, value| AstFactory::create_assignment(
+            AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0),
+            value,
+            0,
+        );
+        let five_seconds = Time { day: 0.0, hour: 0.0, min: 0.0, sec: 5.0, milli: 0.0, micro: 0.0, nano: 0, negative: false };
+
+        // VAR_GLOBAL timer : TON := (PT := T#5s, IN := TRUE); END_VAR
+        let mut unit = unit_with_global("instances.st", "timer", "TON");
+        unit.global_vars[0].variables[0].initializer = Some(AstFactory::create_paren_expression(
+            AstFactory::create_expression_list(vec![
+                member("PT", literal(AstLiteral::Time(five_seconds))),
+                member("IN", literal(AstLiteral::Bool(true))),
+            ], SourceLocation::internal(), 0),
+            SourceLocation::internal(),
+            0,
+        ));
+        let units = vec![&unit];
+
+        let export = |target: &str| {
+            let mut params = GenerationParameters::new();
+            params.xml_targets = vec![String::from(target)];
+            let output_path = temp_dir.join(format!("{target}.xml"));
+            generate_xml_targets(&params, &units, &output_path).unwrap();
+            std::fs::read_to_string(&output_path).unwrap()
+        };
+
+        // the IEC document keeps the struct value, the flat targets set every member on its own
+        let codesys = export("codesys");
+        assert!(codesys.contains(r#"<Value member="PT">"#));
+        assert!(codesys.contains(r#"<Value member="IN">"#));
+
+        let tia = export("tia");
+        assert!(tia.contains(r#"<Subelement Path="PT">"#));
+        assert!(tia.contains("<StartValue><![CDATA[T#5s]]></StartValue>"));
+        assert!(tia.contains(r#"<Subelement Path="IN">"#));
+        assert!(tia.contains("<StartValue><![CDATA[true]]></StartValue>"));
+
+        let aml = export("aml");
+        assert!(aml.contains(r#"<Attribute Name="PT">"#));
+        assert!(aml.contains(r#"<Attribute Name="IN">"#));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_pou_documentation() {
+        let pou = || PouElements {
+            name: String::from("Conveyor"),
+            kind: PouType::Program,
+            access: None,
+            overriding: false,
+            extends: None,
+            implements: Vec::new(),
+            en_eno: false,
+            add_data: None,
+            documentation: Some(String::from("Drives the conveyor belt")),
+            result_type: SResultType::new(),
+            parameters: SParameters::new(),
+            externals: SExternalVars::new(),
+            constant_externals: SExternalVars::new(),
+            vars: SVars::new(),
+            constant_vars: SVars::new(),
+            retain_vars: SVars::new(),
+            constant_retain_vars: SVars::new(),
+            retention_vars: Vec::new(),
+            temp_vars: STempVars::new(),
+            constant_temp_vars: STempVars::new(),
+            main_body: SMainBody::new(),
+            methods: Vec::new(),
+            actions: Vec::new(),
+        };
+
+        let program = IecTarget.emit_pou(pou()).unwrap();
+        assert_eq!(program.find_child("Documentation").and_then(|it| it.content.as_deref()), Some("Drives the conveyor belt"));
+
+        let element = AutomationMlTarget.emit_pou(pou()).unwrap();
+        assert_eq!(element.children[0].name, "Description");
+        assert_eq!(element.children[0].content.as_deref(), Some("Drives the conveyor belt"));
+    }
+
+    #[test]
+    fn test_sysmac_csv_lists_globals() {
+        let params = GenerationParameters::new();
+        let mut unit = unit_with_global("plant.st", "gText", "STRING");
+        unit.global_vars[0].retain = true;
+        let mut quoted = unit_with_global("quoted.st", "gSpeed", "LREAL");
+        quoted.global_vars[0].constant = true;
+        quoted.global_vars[0].variables[0].name = String::from("odd,name");
+        let internal = unit_with_global("<internal>", "gHidden", "INT");
+
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &vec![&unit, &quoted, &internal]);
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(rows, vec![
+            "Name,Data Type,Initial Value,AT,Retain,Constant,Network Publish,Comment",
+            "gText,String[1986],,,TRUE,FALSE,Do not publish,",
+            "\"odd,name\",LREAL,,,FALSE,TRUE,Do not publish,",
+            "",
+        ]);
+    }
+
+    #[test]
+    fn test_omron_string_length() {
+        let mut params = GenerationParameters::new();
+
+        // STRING[256] keeps its declared length
+        let mut unit = unit_with_global("plant.st", "gText", "STRING");
+        unit.global_vars[0].variables[0].data_type_declaration = DataTypeDeclaration::Definition {
+            data_type: Box::new(plc_ast::ast::DataType::StringType {
+                name: Some(String::from("__global_gText")),
+                is_wide: false,
+                size: Some(plc_ast::ast::AstFactory::create_literal(plc_ast::literals::AstLiteral::Integer(256), SourceLocation::internal(), 0)),
+            }),
+            location: SourceLocation::internal(),
+            scope: None,
+            linkage: plc_ast::ast::LinkageType::Internal,
+        };
+        let unsized_string = unit_with_global("plant.st", "gName", "STRING");
+
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &vec![&unit, &unsized_string]);
+        assert!(csv.contains("gText,String[256],"));
+        assert!(csv.contains("gName,String[1986],"));
+
+        // strings of unknown length fall back to the configured default
+        params.default_string_length = 80;
+        assert_eq!(OmronTarget.map_type_name(&params, "STRING"), "String[80]");
+        assert_eq!(OmronTarget.map_type_name(&params, "STRING[MAX_LENGTH]"), "String[80]");
+        assert_eq!(OmronTarget.map_type_name(&params, "WSTRING[12]"), "String[12]");
+        assert_eq!(OmronTarget.map_type_name(&params, "INT"), "INT");
+    }
+
+    #[test]
+    fn test_array_of_struct_per_target() {
+        use plc_ast::ast::{AstFactory, DataType, LinkageType, UserTypeDeclaration};
+        use plc_ast::literals::AstLiteral;
+
+        let temp_dir = std::env::temp_dir().join("test_array_of_struct_per_target");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // TYPE Motor : STRUCT speed : INT; END_STRUCT END_TYPE
+        // VAR_GLOBAL motors : ARRAY[0..9] OF Motor; END_VAR
+        let mut unit = unit_with_global("plant.st", "motors", "Motor");
+        let location = unit.global_vars[0].variables[0].location.clone();
+        let mut speed = unit.global_vars[0].variables[0].clone();
+        speed.name = String::from("speed");
+        speed.data_type_declaration = DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() };
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::StructType { name: Some(String::from("Motor")), variables: vec![speed] },
+            initializer: None,
+            location,
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+        unit.global_vars[0].variables[0].data_type_declaration = DataTypeDeclaration::Definition {
+            data_type: Box::new(DataType::ArrayType {
+                name: Some(String::from("__global_motors")),
+                bounds: AstFactory::create_range_statement(
+                    AstFactory::create_literal(AstLiteral::Integer(0), SourceLocation::internal(), 0),
+                    AstFactory::create_literal(AstLiteral::Integer(9), SourceLocation::internal(), 0),
+                    0,
+                ),
+                referenced_type: Box::new(DataTypeDeclaration::Reference { referenced_type: String::from("Motor"), location: SourceLocation::internal() }),
+                is_variable_length: false,
+            }),
+            location: SourceLocation::internal(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        };
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = ["omron", "tia", "opcua", "aml"].iter().map(|it| String::from(*it)).collect();
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        let documents: Vec<String> = written.iter().map(|it| std::fs::read_to_string(it).unwrap()).collect();
+        assert!(documents.iter().all(|it| !it.contains("__global_motors")));
+
+        assert!(documents[0].contains("<ArrayType>"));
+        assert!(documents[0].contains("<![CDATA[Motor]]>"));
+        assert!(documents[1].contains(r#"Datatype="Array[0..9] of &quot;Motor&quot;""#));
+        assert!(documents[2].contains(r#"ValueRank="1""#));
+        assert!(documents[2].contains(r#"ArrayDimensions="10""#));
+        assert!(documents[2].contains(r#"DataType="ns=1;s=DataType.Motor""#));
+        assert!(documents[3].contains("ARRAY[0..9] OF Motor"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_wide_strings_depend_on_target() {
+        let temp_dir = std::env::temp_dir().join("test_wide_strings_depend_on_target");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        let mut unit = unit_with_global("plant.st", "gText", "WSTRING");
+        unit.global_vars[0].variables[0].data_type_declaration = DataTypeDeclaration::Definition {
+            data_type: Box::new(plc_ast::ast::DataType::StringType {
+                name: Some(String::from("__global_gText")),
+                is_wide: true,
+                size: Some(plc_ast::ast::AstFactory::create_literal(plc_ast::literals::AstLiteral::Integer(12), SourceLocation::internal(), 0)),
+            }),
+            location: SourceLocation::internal(),
+            scope: None,
+            linkage: plc_ast::ast::LinkageType::Internal,
+        };
+        let units = vec![&unit];
+
+        // targets with wide strings keep the declared type instead of the generated one
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("WSTRING[12]"));
+        assert!(!contents.contains("__global_gText"));
+
+        // Sysmac Studio has none, the export is rejected naming the variable
+        params.xml_targets = vec![String::from("omron")];
+        let error = generate_xml_targets(&params, &units, &output_path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        assert!(error.to_string().contains("'gText' in plant.st is declared as WSTRING[12]"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_sysmac_addresses() {
+        let sysmac = |direction, access, components: Vec<i128>| match hardware_access(direction, access, components).stmt {
+            AstStatement::HardwareAccess(hardware_access) => OmronTarget.format_address(&hardware_access),
+            _ => unreachable!(),
+        };
+
+        assert_eq!(sysmac(HardwareAccessType::Input, DirectAccessType::Bit, vec![0, 5]), Ok(String::from("CIO 0.05")));
+        assert_eq!(sysmac(HardwareAccessType::Input, DirectAccessType::Bit, vec![3, 2]), Ok(String::from("CIO 1.10")));
+        assert_eq!(sysmac(HardwareAccessType::Output, DirectAccessType::Word, vec![7]), Ok(String::from("CIO 7")));
+        assert_eq!(sysmac(HardwareAccessType::Output, DirectAccessType::DWord, vec![5]), Ok(String::from("CIO 10")));
+        assert_eq!(sysmac(HardwareAccessType::Memory, DirectAccessType::Word, vec![10]), Ok(String::from("W10")));
+        assert_eq!(sysmac(HardwareAccessType::Memory, DirectAccessType::LWord, vec![2]), Ok(String::from("W8")));
+
+        assert!(sysmac(HardwareAccessType::Input, DirectAccessType::Byte, vec![1]).is_err());
+        assert!(sysmac(HardwareAccessType::Global, DirectAccessType::Word, vec![1]).is_err());
+        assert!(sysmac(HardwareAccessType::Input, DirectAccessType::Bit, vec![1, 2, 3]).is_err());
+
+        // the other targets keep the IEC notation
+        let iec = match hardware_access(HardwareAccessType::Input, DirectAccessType::Byte, vec![1]).stmt {
+            AstStatement::HardwareAccess(hardware_access) => IecTarget.format_address(&hardware_access),
+            _ => unreachable!(),
+        };
+        assert_eq!(iec, Ok(String::from("%IB1")));
+    }
+
+    #[test]
+    fn test_sysmac_bit_address_range() {
+        let sysmac = |components: Vec<i128>| match hardware_access(HardwareAccessType::Input, DirectAccessType::Bit, components).stmt {
+            AstStatement::HardwareAccess(hardware_access) => OmronTarget.format_address(&hardware_access),
+            _ => unreachable!(),
+        };
+
+        // the last bit of an odd byte is the last bit of its word
+        assert_eq!(sysmac(vec![1, 7]), Ok(String::from("CIO 0.15")));
+        assert_eq!(sysmac(vec![1, 8]), Err(String::from("bit 8 is out of range, a bit address names one of the 8 bits of a byte")));
+        assert_eq!(sysmac(vec![0, 15]), Err(String::from("bit 15 is out of range, a bit address names one of the 8 bits of a byte")));
+    }
+
+    #[test]
+    fn test_retention_qualifiers_per_target() {
+        let temp_dir = std::env::temp_dir().join("test_retention_qualifiers_per_target");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // VAR_GLOBAL RETAIN PERSISTENT gCounter : DINT; END_VAR VAR_GLOBAL NON_RETAIN gScratch : DINT; END_VAR
+        let mut unit = unit_with_global("plant.st", "gCounter", "DINT");
+        unit.global_vars[0].retain = true;
+        unit.global_vars[0].persistent = true;
+        let mut scratch = unit_with_global("plant.st", "gScratch", "DINT").global_vars.remove(0);
+        scratch.non_retain = true;
+        unit.global_vars.push(scratch);
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains(r#"<GlobalVars persistent="true" retain="true">"#));
+        assert!(contents.contains(r#"<GlobalVars nonretain="true">"#));
+
+        // Sysmac Studio only knows retained variables, so persistent ones are retained and non-retained ones are plain
+        params.xml_targets = vec![String::from("omron")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(!contents.contains("persistent"));
+        assert!(!contents.contains("nonretain"));
+        assert!(contents.contains(r#"<GlobalVars retain="true">"#));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_unmappable_addresses_depend_on_target() {
+        let temp_dir = std::env::temp_dir().join("test_unmappable_addresses_depend_on_target");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // VAR_GLOBAL gSensor AT %IX3.2 : BOOL; gStatus AT %IB1 : BYTE; END_VAR
+        let mut unit = unit_with_global("plant.st", "gSensor", "BOOL");
+        unit.global_vars[0].variables[0].address = Some(hardware_access(HardwareAccessType::Input, DirectAccessType::Bit, vec![3, 2]));
+        let mut status = unit.global_vars[0].variables[0].clone();
+        status.name = String::from("gStatus");
+        status.address = Some(hardware_access(HardwareAccessType::Input, DirectAccessType::Byte, vec![1]));
+        unit.global_vars[0].variables.push(status);
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains(r#"<Address address="%IX3.2" />"#));
+        assert!(contents.contains(r#"<Address address="%IB1" />"#));
+
+        // Sysmac Studio has no byte addresses, the export is rejected naming the variable
+        params.xml_targets = vec![String::from("omron")];
+        let error = generate_xml_targets(&params, &units, &output_path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::Unsupported);
+        assert!(error.to_string().contains("cannot place 'gStatus' in plant.st at %IB1"));
+
+        unit.global_vars[0].variables.pop();
+        let units = vec![&unit];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains(r#"<Address address="CIO 1.10" />"#));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_var_config_export() {
+        let temp_dir = std::env::temp_dir().join("test_var_config_export");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // VAR_CONFIG main.fb.start AT %IX3.2 : BOOL; END_VAR
+        let reference = ["main", "fb", "start"].iter().fold(None, |base, name| Some(plc_ast::ast::AstFactory::create_member_reference(
+            plc_ast::ast::AstFactory::create_identifier(*name, SourceLocation::internal(), 0),
+            base,
+            0,
+        )));
+        let mut unit = unit_with_global("plant.st", "gSpeed", "INT");
+        unit.var_config.push(plc_ast::ast::ConfigVariable::new(
+            reference.unwrap(),
+            DataTypeDeclaration::Reference { referenced_type: String::from("BOOL"), location: SourceLocation::internal() },
+            hardware_access(HardwareAccessType::Input, DirectAccessType::Bit, vec![3, 2]),
+            SourceLocation::internal(),
+        ));
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<ConfigVars>"));
+        assert!(contents.contains(r#"<ConfigVariable instancePathAndName="main.fb.start" address="%IX3.2">"#));
+        assert!(contents.contains("<TypeName><![CDATA[BOOL]]></TypeName>"));
+
+        // the address is written the way the target IDE expects it
+        params.xml_targets = vec![String::from("omron")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains(r#"<ConfigVariable instancePathAndName="main.fb.start" address="CIO 1.10">"#));
+
+        // units without VAR_CONFIG have no ConfigVars
+        unit.var_config.clear();
+        let units = vec![&unit];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(!contents.contains("<ConfigVars"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_var_access_export() {
+        use plc_ast::ast::{AccessDirection, AccessVariable};
+
+        let temp_dir = std::env::temp_dir().join("test_var_access_export");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // VAR_ACCESS speed : main.motor.speed : REAL READ_WRITE; sensor : %IX3.2 : BOOL; END_VAR
+        let reference = ["main", "motor", "speed"].iter().fold(None, |base, name| Some(plc_ast::ast::AstFactory::create_member_reference(
+            plc_ast::ast::AstFactory::create_identifier(*name, SourceLocation::internal(), 0),
+            base,
+            0,
+        )));
+        let access_variable = |name: &str, path, type_name: &str, direction| AccessVariable {
+            name: String::from(name),
+            path,
+            data_type: DataTypeDeclaration::Reference { referenced_type: String::from(type_name), location: SourceLocation::internal() },
+            direction,
+            location: SourceLocation::internal(),
+        };
+        let mut unit = unit_with_global("plant.st", "gSpeed", "INT");
+        unit.var_access.push(access_variable("speed", reference.unwrap(), "REAL", AccessDirection::ReadWrite));
+        unit.var_access.push(access_variable("sensor", hardware_access(HardwareAccessType::Input, DirectAccessType::Bit, vec![3, 2]), "BOOL", AccessDirection::ReadOnly));
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains("<AccessPaths>"));
+        assert!(contents.contains(r#"<AccessVariable instancePathAndName="main.motor.speed" direction="readWrite" alias="speed">"#));
+        assert!(contents.contains(r#"<AccessVariable instancePathAndName="%IX3.2" direction="readOnly" alias="sensor">"#));
+        assert!(contents.contains("<TypeName><![CDATA[REAL]]></TypeName>"));
+
+        // direct addresses are written the way the target IDE expects them
+        params.xml_targets = vec![String::from("omron")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(contents.contains(r#"<AccessVariable instancePathAndName="CIO 1.10" direction="readOnly" alias="sensor">"#));
+
+        // units without VAR_ACCESS have no AccessPaths
+        unit.var_access.clear();
+        let units = vec![&unit];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+        assert!(!contents.contains("<AccessPaths"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_referenced_globals_only() {
+        use plc_ast::{ast::AstFactory, literals::AstLiteral};
+
+        let temp_dir = std::env::temp_dir().join("test_referenced_globals_only");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        let reference = |name: &str| AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0);
+        let variable = |name: &str, type_name: &str| Variable {
+            name: String::from(name),
+            data_type_declaration: DataTypeDeclaration::Reference { referenced_type: String::from(type_name), location: SourceLocation::internal() },
+            initializer: None,
+            address: None,
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 10, 10)),
+                file: FileMarker::Internal("<test>"),
+            },
+        };
+
+        // VAR_GLOBAL gUsed : INT := gBase; gBase : INT; gExternal : BOOL; gUnused : REAL; END_VAR
+        let mut unit = unit_with_global("plant.st", "gUsed", "INT");
+        unit.global_vars[0].variables[0].initializer = Some(reference("gBase"));
+        unit.global_vars[0].variables.extend([variable("gBase", "INT"), variable("gExternal", "BOOL"), variable("gUnused", "REAL")]);
+
+        // PROGRAM main VAR_EXTERNAL gExternal : BOOL; END_VAR gUsed := 1; END_PROGRAM
+        unit.pous.push(plc_ast::ast::Pou {
+            id: 0,
+            name: String::from("main"),
+            kind: PouType::Program,
+            variable_blocks: vec![VariableBlock::default()
+                .with_block_type(plc_ast::ast::VariableBlockType::External)
+                .with_variables(vec![variable("gExternal", "BOOL")])],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: plc_ast::ast::LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(plc_ast::ast::Implementation {
+            name: String::from("main"),
+            type_name: String::from("main"),
+            linkage: plc_ast::ast::LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![AstFactory::create_assignment(
+                reference("gUsed"),
+                AstFactory::create_literal(AstLiteral::Integer(1), SourceLocation::internal(), 0),
+                0,
+            )],
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+        let units = vec![&unit];
+
+        let exported_globals = |params: &GenerationParameters| {
+            generate_xml_targets(params, &units, &output_path).unwrap();
+            let contents = std::fs::read_to_string(&output_path).unwrap();
+            ["gUsed", "gBase", "gExternal", "gUnused"].into_iter()
+                .filter(|it| contents.contains(&format!(r#"<Variable name="{it}">"#)))
+                .collect::<Vec<_>>()
+        };
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        assert_eq!(exported_globals(&params), vec!["gUsed", "gBase", "gExternal", "gUnused"]);
+
+        // used in a body, in the initializer of a used global or as an external
+        params.referenced_globals_only = true;
+        assert_eq!(exported_globals(&params), vec!["gUsed", "gBase", "gExternal"]);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_global_groups() {
+        let temp_dir = std::env::temp_dir().join("test_global_groups");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("groups.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "VAR_GLOBAL\n gA : INT;\nEND_VAR\n{attribute 'group' := 'Motion'}\nVAR_GLOBAL\n gSpeed : REAL;\nEND_VAR\n\
+            VAR_GLOBAL CONSTANT\n gLimit : INT;\nEND_VAR\n{attribute 'group' := 'motion'}\nVAR_GLOBAL\n gAccel : REAL;\nEND_VAR\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let template = unit_with_global(source_path, "gA", "INT").global_vars[0].variables[0].clone();
+        let mut unit = CompilationUnit::new(source_path);
+        let mut offsets = source.match_indices("VAR_GLOBAL").map(|(offset, _)| offset);
+        for (name, constant) in [("gA", false), ("gSpeed", false), ("gLimit", true), ("gAccel", false)] {
+            let offset = offsets.next().unwrap();
+            let mut block = VariableBlock::global().with_variables(vec![Variable { name: String::from(name), ..template.clone() }]);
+            block.constant = constant;
+            block.location = SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + 10)),
+                file: FileMarker::File(source_path),
+            };
+            unit.global_vars.push(block);
+        }
+
+        fn collect_groups(node: &Node, groups: &mut Vec<(Option<String>, bool, Vec<String>)>) {
+            if node.name == "GlobalVars" && !node.children.is_empty() {
+                let variables = node.children.iter().map(|it| it.attributes["name"].clone()).collect();
+                groups.push((node.attributes.get("name").cloned(), node.attributes.contains_key("constant"), variables));
+            }
+            node.children.iter().for_each(|it| collect_groups(it, groups));
+        }
+        let groups = |params: &GenerationParameters| {
+            let mut root = OmronTarget.template();
+            generate_globals(params, &OmronTarget, &ProjectEnums::default(), &unit, "groups.st", None, &mut root).unwrap();
+            let mut groups = Vec::new();
+            collect_groups(&root, &mut groups);
+            groups
+        };
+        let group = |name: Option<&str>, constant: bool, variables: &[&str]| (name.map(String::from), constant, variables.iter().map(|it| String::from(*it)).collect::<Vec<_>>());
+
+        // blocks named alike are grouped, the others are merged by their qualifiers
+        let mut params = GenerationParameters::new();
+        assert_eq!(groups(&params), vec![
+            group(None, true, &["gLimit"]),
+            group(None, false, &["gA"]),
+            group(Some("Motion"), false, &["gSpeed", "gAccel"]),
+        ]);
+
+        // or every other block is a group of its own
+        params.global_groups = true;
+        assert_eq!(groups(&params), vec![
+            group(Some("groups_1"), false, &["gA"]),
+            group(Some("Motion"), false, &["gSpeed", "gAccel"]),
+            group(Some("groups_2"), true, &["gLimit"]),
+        ]);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_merged_configuration() {
+        let line = unit_with_global("line.st", "gSpeed", "REAL");
+        let mut cell = unit_with_global("cell.st", "gCount", "INT");
+        cell.global_vars.push(VariableBlock::global().with_variables(vec![Variable { name: String::from("gLimit"), ..cell.global_vars[0].variables[0].clone() }]));
+        cell.global_vars[1].constant = true;
+
+        let configurations = |params: &GenerationParameters| {
+            let mut root = OmronTarget.template();
+            for unit in [&line, &cell] {
+                let unit_name = unit.file.get_name().unwrap();
+                generate_globals(params, &OmronTarget, &ProjectEnums::default(), unit, unit_name, None, &mut root).unwrap();
+            }
+            root.find_child(INSTANCES).unwrap().children.clone()
+        };
+        let names = |nodes: &[Node]| nodes.iter().map(|it| it.attributes["name"].clone()).collect::<Vec<_>>();
+
+        // every file is a controller of its own...
+        let mut params = GenerationParameters::new();
+        assert_eq!(names(&configurations(&params)), vec!["line.st_Configuration", "cell.st_Configuration"]);
+
+        // ...or all globals share one
+        params.configuration_name = Some(String::from("Controller"));
+        let merged = configurations(&params);
+        assert_eq!(names(&merged), vec!["Controller"]);
+        let resources = &merged[0].children;
+        assert_eq!(names(resources), vec!["Controller_Resource"]);
+
+        let lists: Vec<(bool, Vec<String>)> = resources[0].children.iter()
+            .filter(|it| !it.children.is_empty())
+            .map(|it| (it.attributes.contains_key("constant"), names(&it.children)))
+            .collect();
+        assert_eq!(lists, vec![(true, vec![String::from("gLimit")]), (false, vec![String::from("gSpeed"), String::from("gCount")])]);
+    }
+
+    #[test]
+    fn test_program_tasks() {
+        let temp_dir = std::env::temp_dir().join("test_program_tasks");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("tasks.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'task' := 'Fast'}\n{attribute 'task_interval' := 'T#10ms'}\n{attribute 'task_priority' := '1'}\n\
+            PROGRAM motion\nEND_PROGRAM\n{attribute 'task' := 'fast'}\nPROGRAM safety\nEND_PROGRAM\nPROGRAM logging\nEND_PROGRAM\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let mut unit = CompilationUnit::new(source_path);
+        for (index, (offset, _)) in source.match_indices("PROGRAM ").enumerate() {
+            let name = source[offset + 8..].split('\n').next().unwrap();
+            unit.pous.push(Pou {
+                id: index,
+                name: String::from(name),
+                kind: PouType::Program,
+                variable_blocks: vec![],
+                return_type: None,
+                location: SourceLocation {
+                    span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + 7)),
+                    file: FileMarker::File(source_path),
+                },
+                name_location: SourceLocation::internal(),
+                poly_mode: None,
+                generics: vec![],
+                linkage: LinkageType::Internal,
+                super_class: None,
+                is_const: false,
+                interfaces: vec![],
+                properties: vec![],
+            });
+        }
+
+        fn collect_scheduling(node: &Node, elements: &mut Vec<(String, Vec<(String, String)>)>) {
+            if node.name == "Task" || node.name == "ProgramInstance" {
+                let mut attributes: Vec<(String, String)> = node.attributes.iter().map(|(name, value)| (name.clone(), value.clone())).collect();
+                attributes.sort();
+                elements.push((node.name.clone(), attributes));
+            }
+            node.children.iter().for_each(|it| collect_scheduling(it, elements));
+        }
+        let scheduling = |params: &GenerationParameters| {
+            let mut root = OmronTarget.template();
+            generate_globals(params, &OmronTarget, &ProjectEnums::default(), &unit, "tasks.st", None, &mut root).unwrap();
+            let mut elements = Vec::new();
+            collect_scheduling(&root, &mut elements);
+            elements
+        };
+        let element = |name: &str, attributes: &[(&str, &str)]| (String::from(name), attributes.iter().map(|(name, value)| (String::from(*name), String::from(*value))).collect::<Vec<_>>());
+        let instance = |program: &str, task: &str| element("ProgramInstance", &[("associatedTaskName", task), ("name", program), ("typeName", program)]);
+
+        // programs of the same task share it, unassigned programs are not instantiated
+        let mut params = GenerationParameters::new();
+        assert_eq!(scheduling(&params), vec![
+            element("Task", &[("interval", "T#10ms"), ("name", "Fast"), ("priority", "1")]),
+            instance("motion", "Fast"),
+            instance("safety", "Fast"),
+        ]);
+
+        // or run in the default task
+        params.default_task = Some(String::from("Background"));
+        assert_eq!(scheduling(&params), vec![
+            element("Task", &[("interval", "T#10ms"), ("name", "Fast"), ("priority", "1")]),
+            element("Task", &[("name", "Background"), ("priority", "0")]),
+            instance("motion", "Fast"),
+            instance("safety", "Fast"),
+            instance("logging", "Background"),
+        ]);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_export_pragmas() {
+        use plc_ast::ast::{DataType, Implementation, UserTypeDeclaration, VariableBlockType};
+
+        let temp_dir = std::env::temp_dir().join("test_export_pragmas");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("library.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'no_xml_export'}\nTYPE Scratch : STRUCT\nlevel : INT;\nEND_STRUCT\nEND_TYPE\nTYPE Settings : STRUCT\nlimit : INT;\nEND_STRUCT\nEND_TYPE\n\
+            {attribute 'xml_export'}\nFUNCTION_BLOCK Motor\nVAR\nspeed : INT;\nEND_VAR\n{attribute 'no_xml_export'}\nVAR\ndebug : INT;\nEND_VAR\n\
+            speed := 1;\nEND_FUNCTION_BLOCK\nFUNCTION_BLOCK Helper\nhelp := 2;\nEND_FUNCTION_BLOCK\n\
+            VAR_GLOBAL\ngScratch : INT;\nEND_VAR\n{attribute 'xml_export'}\nVAR_GLOBAL\ngSpeed : INT;\nEND_VAR\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let at = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+        let variable = |name: &str| Variable {
+            name: String::from(name),
+            data_type_declaration: DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() },
+            initializer: None,
+            address: None,
+            location: at(&format!("{name} :")),
+        };
+        let block = |kind: VariableBlockType, name: &str| VariableBlock {
+            location: at(&format!("VAR{}\n{name}", if kind == VariableBlockType::Global { "_GLOBAL" } else { "" })),
+            ..VariableBlock::default().with_block_type(kind).with_variables(vec![variable(name)])
+        };
+        let user_type = |name: &str, field: &str| UserTypeDeclaration {
+            data_type: DataType::StructType { name: Some(String::from(name)), variables: vec![variable(field)] },
+            initializer: None,
+            location: at(name),
+            scope: None,
+            linkage: LinkageType::Internal,
+        };
+        let pou = |name: &str, variable_blocks: Vec<VariableBlock>| Pou {
+            id: 0,
+            name: String::from(name),
+            kind: PouType::FunctionBlock,
+            variable_blocks,
+            return_type: None,
+            location: at(&format!("FUNCTION_BLOCK {name}")),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        let implementation = |name: &str, body: &str| Implementation {
+            name: String::from(name),
+            type_name: String::from(name),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::FunctionBlock,
+            statements: vec![],
+            location: at(body),
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        };
+
+        let mut unit = CompilationUnit::new(source_path);
+        unit.user_types = vec![user_type("Scratch", "level"), user_type("Settings", "limit")];
+        unit.pous = vec![
+            pou("Motor", vec![block(VariableBlockType::Local, "speed"), block(VariableBlockType::Local, "debug")]),
+            pou("Helper", vec![]),
+        ];
+        unit.implementations = vec![implementation("Motor", "speed := 1;"), implementation("Helper", "help := 2;")];
+        unit.global_vars = vec![block(VariableBlockType::Global, "gScratch"), block(VariableBlockType::Global, "gSpeed")];
+
+        let params = GenerationParameters::new();
+        let mut root = OmronTarget.template();
+        generate_custom_types(&params, &OmronTarget, &ProjectEnums::default(), &unit, &mut root).unwrap();
+        generate_pous(&params, &OmronTarget, &ProjectEnums::default(), &InheritedVariables::default(), &unit, &mut root, &mut Vec::new()).unwrap();
+        generate_globals(&params, &OmronTarget, &ProjectEnums::default(), &unit, "library.st", None, &mut root).unwrap();
+
+        fn variable_names(node: &Node, names: &mut Vec<String>) {
+            if node.name == "Variable" {
+                names.push(node.attributes["name"].clone());
+            }
+            node.children.iter().for_each(|it| variable_names(it, names));
+        }
+
+        // types are left out one by one, while marking a POU or a global block exports only the marked ones
+        let namespace = root.find(GLOBAL_NAMESPACE_PATH).unwrap();
+        let declarations: Vec<&str> = namespace.children.iter().map(|it| it.attributes["name"].as_str()).collect();
+        assert_eq!(declarations, vec!["Settings", "Motor"]);
+
+        let mut motor_variables = Vec::new();
+        variable_names(&namespace.children[1], &mut motor_variables);
+        assert_eq!(motor_variables, vec!["speed"]);
+
+        let mut global_variables = Vec::new();
+        variable_names(root.find_child(INSTANCES).unwrap(), &mut global_variables);
+        assert_eq!(global_variables, vec!["gSpeed"]);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_function_block_instances() {
+        use plc_ast::ast::AstFactory;
+        use plc_ast::literals::AstLiteral;
+
+        let temp_dir = std::env::temp_dir().join("test_function_block_instances");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("counters.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'network_publish' := 'Input'}\nVAR_GLOBAL\ngCounter : CTU := (PV := 10);\ngLimit : INT;\nEND_VAR\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let location = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+
+        // gCounter : CTU := (PV := 10);
+        let mut unit = unit_with_global(source_path, "gCounter", "CTU");
+        unit.global_vars[0].location = location("VAR_GLOBAL");
+        let counter = &mut unit.global_vars[0].variables[0];
+        counter.location = location("gCounter");
+        counter.initializer = Some(AstFactory::create_assignment(
+            AstFactory::create_member_reference(AstFactory::create_identifier("PV", SourceLocation::internal(), 0), None, 0),
+            AstFactory::create_literal(AstLiteral::Integer(10), SourceLocation::internal(), 0),
+            0,
+        ));
+        let limit = Variable {
+            name: String::from("gLimit"),
+            data_type_declaration: DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() },
+            initializer: None,
+            location: location("gLimit"),
+            ..counter.clone()
+        };
+        unit.global_vars[0].variables.push(limit);
+
+        let globals = |params: &GenerationParameters| {
+            let mut root = OmronTarget.template();
+            generate_globals(params, &OmronTarget, &ProjectEnums::default(), &unit, "counters.st", None, &mut root).unwrap();
+            let resource = root.find_child(INSTANCES).unwrap().find_child(CONFIGURATION).unwrap().find_child(RESOURCE).unwrap().clone();
+            resource.children.into_iter().flat_map(|it| it.children).collect::<Vec<Node>>()
+        };
+        let network_publish = |variable: &Node| variable.find_child("AddData").unwrap().find_child("Data").unwrap()
+            .find_child("GlobalVariableAdditionalProperties").unwrap().attributes["networkPublish"].clone();
+
+        // instances of function blocks are never published, but keep the values of their inputs
+        let mut params = GenerationParameters::new();
+        params.function_blocks = vec![String::from("CTU")];
+        let variables = globals(&params);
+        assert_eq!(variables.len(), 2);
+        assert_eq!(network_publish(&variables[0]), "DoNotPublish");
+        assert_eq!(VariableType::of(&variables[0]).unwrap().type_name, "CTU");
+        let value = variables[0].find_child("InitialValue").unwrap().find_child("StructValue").unwrap().find_child("Value").unwrap();
+        assert_eq!(value.attributes["member"], "PV");
+        assert_eq!(network_publish(&variables[1]), "Input");
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_externals_must_match_globals() {
+        let temp_dir = std::env::temp_dir().join("test_externals_must_match_globals");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // PROGRAM main VAR_EXTERNAL gSpeed : INT; END_VAR END_PROGRAM
+        let external_location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(2, 4, 30)..TextLocation::new(2, 10, 36)),
+            file: FileMarker::File("plant.st"),
+        };
+        let external = |name: &str, type_name: &str| {
+            let mut unit = unit_with_global("plant.st", "gSpeed", "INT");
+            let mut block = VariableBlock::default().with_block_type(plc_ast::ast::VariableBlockType::External);
+            block.variables.push(Variable {
+                name: String::from(name),
+                data_type_declaration: DataTypeDeclaration::Reference { referenced_type: String::from(type_name), location: SourceLocation::internal() },
+                initializer: None,
+                address: None,
+                location: external_location.clone(),
+            });
+            unit.pous.push(plc_ast::ast::Pou {
+                id: 0,
+                name: String::from("main"),
+                kind: PouType::Program,
+                variable_blocks: vec![block],
+                return_type: None,
+                location: SourceLocation::internal(),
+                name_location: SourceLocation::internal(),
+                poly_mode: None,
+                generics: vec![],
+                linkage: plc_ast::ast::LinkageType::Internal,
+                super_class: None,
+                is_const: false,
+                interfaces: vec![],
+                properties: vec![],
+            });
+            unit
+        };
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+
+        let unit = external("GSPEED", "int");
+        generate_xml_targets(&params, &vec![&unit], &output_path).unwrap();
+
+        // a mismatching type or a missing global is reported at the external declaration
+        let unit = external("gSpeed", "REAL");
+        let error = generate_xml_targets(&params, &vec![&unit], &output_path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "'main' declares the external 'gSpeed' as REAL but the global is INT");
+        assert_eq!(DeclarationError::location_of(&error), Some(&external_location));
+
+        let unit = external("gMissing", "INT");
+        let error = generate_xml_targets(&params, &vec![&unit], &output_path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "'main' declares the external 'gMissing' but there is no global of that name");
+        assert_eq!(DeclarationError::location_of(&error), Some(&external_location));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_split_output() {
+        use crate::split_output::split_output_path;
+
+        let temp_dir = std::env::temp_dir().join("test_split_output");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+        let source_path: &'static str = Box::leak(temp_dir.join("plant.st").to_string_lossy().into_owned().into_boxed_str());
+        std::fs::write(source_path, "gSpeed := 1.5;").unwrap();
+
+        // PROGRAM Main gSpeed := 1.5; END_PROGRAM
+        let mut unit = unit_with_global(source_path, "gSpeed", "REAL");
+        unit.pous.push(Pou {
+            id: 0,
+            name: String::from("Main"),
+            kind: PouType::Program,
+            variable_blocks: vec![],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+        unit.implementations.push(plc_ast::ast::Implementation {
+            name: String::from("Main"),
+            type_name: String::from("Main"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 14, 14)),
+                file: FileMarker::File(source_path),
+            },
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        params.split_output = true;
+        generate_xml_targets(&params, &vec![&unit], &output_path).unwrap();
+
+        let split_path = split_output_path(&output_path);
+        let index = std::fs::read_to_string(split_path.join("index.xml")).unwrap();
+        assert!(index.contains("path=\"pous/Main.xml\""));
+        assert!(index.contains("path=\"globals.xml\""));
+
+        // each part holds only its own declaration
+        let program = std::fs::read_to_string(split_path.join("pous").join("Main.xml")).unwrap();
+        assert!(program.contains("gSpeed := 1.5;"));
+        assert!(!program.contains("GlobalVars"));
+
+        let globals = std::fs::read_to_string(split_path.join("globals.xml")).unwrap();
+        assert!(globals.contains("name=\"gSpeed\""));
+        assert!(!globals.contains("Program"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
 
     #[test]
-    fn test_write_xml_file_full_template() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_write_xml_full_template.xml");
+    fn test_document_built_despite_streaming_is_reported() {
+        let temp_dir = std::env::temp_dir().join("test_document_built_despite_streaming_is_reported");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
 
-        let template = get_omron_template();
-        let result = write_xml_file(&output_path, template);
-        assert!(result.is_ok());
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        params.streaming = true;
+        params.split_output = true;
+        let unit = unit_with_global("plant.st", "gSpeed", "REAL");
+        let generated = generate_xml_targets(&params, &vec![&unit], &output_path).unwrap();
+
+        let [note] = generated.diagnostics.as_slice() else { panic!("{:?}", generated.diagnostics) };
+        assert_eq!(note.get_error_code(), "E129");
+        assert_eq!(note.get_message(), "The document of the iec target is built as a whole, since splitting the document needs all of it");
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_copies_split_output() {
+        use crate::split_output::split_output_path;
+
+        let temp_dir = std::env::temp_dir().join("test_copy_xmlfile_to_output_copies_split_output");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let build_path = temp_dir.join("build.xml");
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        params.split_output = true;
+        let unit = unit_with_global("plant.st", "gSpeed", "REAL");
+        generate_xml_targets(&params, &vec![&unit], &build_path).unwrap();
+
+        let output_path = temp_dir.join("out.xml");
+        copy_xmlfile_to_output(vec![build_path.as_path()], output_path.clone(), &[]).unwrap();
+
+        // the parts are copied next to the copied document, whose index names the copy
+        let split_path = split_output_path(&output_path);
+        let index = std::fs::read_to_string(split_path.join("index.xml")).unwrap();
+        assert!(index.contains("document=\"out.xml\""));
+        assert!(std::fs::read_to_string(split_path.join("globals.xml")).unwrap().contains("name=\"gSpeed\""));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_copies_source_maps() {
+        use crate::source_map::source_map_path;
+
+        let temp_dir = std::env::temp_dir().join("test_copy_xmlfile_to_output_copies_source_maps");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let build_path = temp_dir.join("build.xml");
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec"), String::from("omron")];
+        params.source_map = true;
+        let unit = unit_with_global("plant.st", "gSpeed", "REAL");
+        generate_xml_targets(&params, &vec![&unit], &build_path).unwrap();
+
+        let output_path = temp_dir.join("out.xml");
+        copy_xmlfile_to_output(vec![build_path.as_path()], output_path.clone(), &params.xml_targets).unwrap();
+
+        // every target has a source map of its own
+        for name in &params.xml_targets {
+            assert!(source_map_path(&target_output_path(&output_path, name)).exists());
+        }
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_strict_return_types() {
+        let temp_dir = std::env::temp_dir().join("test_strict_return_types");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // FUNCTION Scale VAR_INPUT x : INT; END_VAR END_FUNCTION
+        let name_location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 9, 9)..TextLocation::new(0, 14, 14)),
+            file: FileMarker::File("plant.st"),
+        };
+        let mut unit = CompilationUnit::new("plant.st");
+        unit.pous.push(Pou {
+            id: 0,
+            name: String::from("Scale"),
+            kind: PouType::Function,
+            variable_blocks: vec![],
+            return_type: None,
+            location: name_location.clone(),
+            name_location: name_location.clone(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+
+        // only warned about by default...
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &vec![&unit], &output_path).unwrap();
+
+        // ...but reported at the function's name in strict mode
+        params.strict_return_types = true;
+        let error = generate_xml_targets(&params, &vec![&unit], &output_path).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::InvalidInput);
+        assert_eq!(error.to_string(), "The function 'Scale' in plant.st has no return type the export can name");
+        assert_eq!(DeclarationError::location_of(&error), Some(&name_location));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_missing_return_type_is_reported() {
+        let temp_dir = std::env::temp_dir().join("test_missing_return_type_is_reported");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // FUNCTION Scale END_FUNCTION
+        let name_location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 9, 9)..TextLocation::new(0, 14, 14)),
+            file: FileMarker::File("plant.st"),
+        };
+        let mut unit = CompilationUnit::new("plant.st");
+        unit.pous.push(Pou {
+            id: 0,
+            name: String::from("Scale"),
+            kind: PouType::Function,
+            variable_blocks: vec![],
+            return_type: None,
+            location: name_location.clone(),
+            name_location: name_location.clone(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        });
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("iec")];
+        let generated = generate_xml_targets(&params, &vec![&unit], &output_path).unwrap();
+
+        let [warning] = generated.diagnostics.as_slice() else { panic!("{:?}", generated.diagnostics) };
+        assert_eq!(warning.get_error_code(), "E125");
+        assert_eq!(warning.get_message(), "The function 'Scale' in plant.st has no return type the export can name, it is exported as returning BOOL");
+        assert_eq!(warning.get_location(), name_location);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_rename_reserved_identifiers() {
+        use crate::identifier_rules::{rename_identifiers, rename_invalid_identifiers, IdentifierRules, Rename};
+
+        // TYPE Step : STRUCT on : BOOL; END_STRUCT END_TYPE, a global `task : Step` and a global `Task_1 : INT`
+        let variable = |name: &str, type_name: &str| SGenVariable::new()
+            .attribute(String::from("name"), String::from(name))
+            .child(&SType::new().child(&STypeName::new().content(String::from(type_name))))
+            .inner();
+        let mut root = Node::new_str("Project")
+            .child(&SDataTypeDecl::new().attribute_str("name", "Step").child(&SMember::new().attribute_str("name", "on")))
+            .child(&variable("task", "Step"))
+            .child(&variable("Task_1", "INT"))
+            .child(&SST::new().content(String::from("task.on := TRUE; // task is on\nmsg := 'on';")))
+            .child(&SConfigVariable::new().attribute_str("instancePathAndName", "main.task.on"));
+
+        let rules = IdentifierRules { reserved_words: OmronTarget.reserved_words(), ..IdentifierRules::default() };
+        let renames = rename_invalid_identifiers(&mut root, &rules);
+        let rename = |original: &str, renamed: &str| Rename { original: String::from(original), renamed: String::from(renamed) };
+        assert_eq!(renames, vec![rename("Step", "Step_1"), rename("on", "on_1"), rename("task", "task_2")]);
+
+        assert_eq!(root.children[0].attributes["name"], "Step_1");
+        assert_eq!(root.children[0].children[0].attributes["name"], "on_1");
+        assert_eq!(root.children[1].attributes["name"], "task_2");
+        assert_eq!(root.children[1].children[0].children[0].content.as_deref(), Some("Step_1"));
+        assert_eq!(root.children[2].attributes["name"], "Task_1");
+
+        // comments and strings keep their text
+        assert_eq!(root.children[3].content.as_deref(), Some("task_2.on_1 := TRUE; // task is on\nmsg := 'on';"));
+        assert_eq!(root.children[4].attributes["instancePathAndName"], "main.task_2.on_1");
+
+        // numbers and typed literals are no identifiers
+        assert_eq!(rename_identifiers("t := T#1s + on * 16#0F;", &renames), "t := T#1s + on_1 * 16#0F;");
+
+        // an escaped quote does not end a string, nor does a nested comment end the one around it
+        assert_eq!(rename_identifiers("msg := 'don$'t on'; on := TRUE;", &renames), "msg := 'don$'t on'; on_1 := TRUE;");
+        assert_eq!(rename_identifiers("msg := \"say $\"on$\"\"; on := TRUE;", &renames), "msg := \"say $\"on$\"\"; on_1 := TRUE;");
+        assert_eq!(rename_identifiers("(* a (* on *) on *) on := TRUE;", &renames), "(* a (* on *) on *) on_1 := TRUE;");
+        assert_eq!(rename_identifiers("/* a /* on */ on */ on := TRUE;", &renames), "/* a /* on */ on */ on_1 := TRUE;");
+
+        // targets without reserved words keep all names
+        let rules = IdentifierRules { reserved_words: IecTarget.reserved_words(), max_length: IecTarget.max_identifier_length(), ascii_only: false };
+        assert!(rename_invalid_identifiers(&mut root, &rules).is_empty());
+    }
+
+    #[test]
+    fn test_shorten_long_identifiers() {
+        use crate::identifier_rules::{rename_invalid_identifiers, IdentifierRules, Rename};
+
+        // two names which only differ after the maximum length, a short reserved one and one with a multi-byte character
+        let mut root = Node::new_str("Project")
+            .child(&SGenVariable::new().attribute_str("name", "conveyorSpeedLeft"))
+            .child(&SGenVariable::new().attribute_str("name", "conveyorSpeedRight"))
+            .child(&SGenVariable::new().attribute_str("name", "on"))
+            .child(&SGenVariable::new().attribute_str("name", "conveyorGrößeX"))
+            .child(&SST::new().content(String::from("conveyorSpeedRight := conveyorSpeedLeft;")));
+
+        let rules = IdentifierRules { reserved_words: &["ON"], max_length: Some(12), ascii_only: false };
+        let renames = rename_invalid_identifiers(&mut root, &rules);
+        let rename = |original: &str, renamed: &str| Rename { original: String::from(original), renamed: String::from(renamed) };
+        assert_eq!(renames, vec![
+            rename("conveyorSpeedLeft", "conveyorSpee"),
+            rename("conveyorSpeedRight", "conveyorSp_1"),
+            rename("on", "on_1"),
+            rename("conveyorGrößeX", "conveyorGrö"), //the two bytes of ß would not fit
+        ]);
+        assert_eq!(root.children[4].content.as_deref(), Some("conveyorSp_1 := conveyorSpee;"));
+        assert!(renames.iter().all(|it| it.renamed.len() <= 12));
+    }
+
+    #[test]
+    fn test_transliterate_identifiers() {
+        use crate::identifier_rules::{rename_invalid_identifiers, transliterate, IdentifierRules};
+
+        assert_eq!(transliterate("Größe"), "Groesse");
+        assert_eq!(transliterate("vitesse_réelle"), "vitesse_reelle");
+        assert_eq!(transliterate("温度"), "U6E29U5EA6");
+
+        // a transliteration colliding with a declared name gets a suffix, ASCII names are kept
+        let mut root = Node::new_str("Project")
+            .child(&SDataTypeDecl::new().attribute_str("name", "Maß"))
+            .child(&SGenVariable::new().attribute_str("name", "Mass"))
+            .child(&SGenVariable::new().attribute_str("name", "größe")
+                .child(&SType::new().child(&STypeName::new().content(String::from("Maß")))))
+            .child(&SST::new().content(String::from("größe := Mass; // größe in kg")));
+
+        let rules = IdentifierRules { ascii_only: true, ..IdentifierRules::default() };
+        let renames: Vec<(String, String)> = rename_invalid_identifiers(&mut root, &rules).into_iter().map(|it| (it.original, it.renamed)).collect();
+        assert_eq!(renames, vec![(String::from("Maß"), String::from("Mass_1")), (String::from("größe"), String::from("groesse"))]);
+        assert_eq!(root.children[2].children[0].children[0].content.as_deref(), Some("Mass_1"));
+        assert_eq!(root.children[3].content.as_deref(), Some("groesse := Mass; // größe in kg"));
+
+        // without the option non-ASCII names are exported as declared
+        let mut root = Node::new_str("Project").child(&SGenVariable::new().attribute_str("name", "größe"));
+        assert!(rename_invalid_identifiers(&mut root, &IdentifierRules::default()).is_empty());
+    }
+
+    #[test]
+    fn test_rename_report() {
+        let temp_dir = std::env::temp_dir().join("test_rename_report");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        let unit = unit_with_global("plant.st", "Priority", "INT");
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("omron")];
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        let report_path = temp_dir.join("plant.renames.csv");
+        assert_eq!(written, vec![output_path.clone(), report_path.clone()]);
 
         let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.contains("Project"));
-        assert!(contents.contains(FILE_HEADER));
-        assert!(contents.contains(CONTENT_HEADER));
-        assert!(contents.contains(TYPES));
-        assert!(contents.contains(INSTANCES));
-        assert!(contents.contains(GLOBAL_NAMESPACE));
+        assert!(contents.contains(r#"<Variable name="Priority_1">"#));
+        let report = std::fs::read_to_string(&report_path).unwrap();
+        assert!(report.contains("Priority,Priority_1"), "{report}");
+
+        // the IEC target has no reserved words beyond the standard ones
+        std::fs::remove_file(&report_path).unwrap();
+        params.xml_targets = vec![String::from("iec")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        assert!(!report_path.exists());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_copy_xmlfile_to_output_copies_rename_report() {
+        let temp_dir = std::env::temp_dir().join("test_copy_xmlfile_to_output_copies_rename_report");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let build_path = temp_dir.join("build.xml");
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("omron")];
+        let unit = unit_with_global("plant.st", "Priority", "INT");
+        generate_xml_targets(&params, &vec![&unit], &build_path).unwrap();
+
+        copy_xmlfile_to_output(vec![build_path.as_path()], temp_dir.join("out.xml"), &params.xml_targets).unwrap();
+        let report = std::fs::read_to_string(temp_dir.join("out.renames.csv")).unwrap();
+        assert!(report.contains("Priority,Priority_1"), "{report}");
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_sysmac_csv_without_xml_targets() {
+        let temp_dir = std::env::temp_dir().join("test_sysmac_csv_without_xml_targets");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        let mut params = GenerationParameters::new();
+        params.sysmac_csv = true;
+
+        let unit = unit_with_global("plant.st", "gSpeed", "REAL");
+        let units = vec![&unit];
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        assert_eq!(written, vec![temp_dir.join("plant.csv")]);
+        assert!(!output_path.exists());
+        assert!(std::fs::read_to_string(&written[0]).unwrap().contains("gSpeed,REAL"));
+
+        let copied_path = temp_dir.join("out.xml");
+        copy_xmlfile_to_output(vec![output_path.as_path()], copied_path.clone(), &[]).unwrap();
+        assert!(temp_dir.join("out.csv").exists());
+        assert!(!copied_path.exists());
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+    #[test]
+    fn test_parse_comment() {
+        use crate::source_comments::parse_comment;
+        assert_eq!(parse_comment(" : REAL; // in m/s"), Some(String::from("in m/s")));
+        assert_eq!(parse_comment(" : REAL; (* in m/s *) // ignored"), Some(String::from("in m/s")));
+        assert_eq!(parse_comment(" : REAL; (* unterminated"), Some(String::from("unterminated")));
+        assert_eq!(parse_comment(" : REAL; //   "), None);
+        assert_eq!(parse_comment(" : REAL;"), None);
+        assert_eq!(parse_comment("/// in m/s"), Some(String::from("in m/s")));
+        assert_eq!(parse_comment("(** in m/s *)"), Some(String::from("in m/s")));
+    }
+
+    #[test]
+    fn test_source_text_of_multi_byte_characters() {
+        use crate::source_comments::source_text;
+
+        // the offsets point into the decoded text, which starts after the byte order mark
+        let source_path = std::env::temp_dir().join("test_source_text_utf8.st");
+        let source = "PROGRAM Größe\nmaß := 'µ';\nEND_PROGRAM\n";
+        std::fs::write(&source_path, format!("\u{feff}{source}")).unwrap();
+        let file_path: &'static str = Box::leak(source_path.to_string_lossy().into_owned().into_boxed_str());
+        let at = |start: usize, end: usize| SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 0, start)..TextLocation::new(0, 0, end)),
+            file: FileMarker::File(file_path),
+        };
+
+        let body = source.find("maß").unwrap()..source.find("\nEND_PROGRAM").unwrap();
+        assert_eq!(source_text(&LoadedSources::default(), &at(body.start, body.end)).as_deref(), Some("maß := 'µ';"));
+
+        // a span ending within a character is reported instead of cut
+        let within_sharp_s = source.find('ß').unwrap() + 1;
+        assert_eq!(source_text(&LoadedSources::default(), &at(body.start, within_sharp_s)), None);
+
+        let _ = std::fs::remove_file(&source_path);
+    }
+
+    #[test]
+    fn test_loaded_sources_are_read_in_place_of_the_file() {
+        use plc_source::SourceCode;
+        use crate::source_comments::source_text;
+
+        let source_path = std::env::temp_dir().join("test_loaded_sources_in_place.st");
+        std::fs::write(&source_path, "PROGRAM OnDisk\nEND_PROGRAM\n").unwrap();
+        let file_path: &'static str = Box::leak(source_path.to_string_lossy().into_owned().into_boxed_str());
+        let name = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 8, 8)..TextLocation::new(0, 14, 14)),
+            file: FileMarker::File(file_path),
+        };
+
+        let loaded = LoadedSources::new(&[SourceCode::new("PROGRAM Loaded\nEND_PROGRAM\n", file_path)]);
+        assert_eq!(source_text(&loaded, &name).as_deref(), Some("Loaded"));
+
+        // the sources of one export are not read by another
+        assert_eq!(source_text(&LoadedSources::default(), &name).as_deref(), Some("OnDisk"));
+
+        let _ = std::fs::remove_file(&source_path);
+    }
+
+    #[test]
+    fn test_source_text_outside_the_text_is_reported() {
+        use plc_ast::ast::{Implementation, LinkageType, PouType};
+        use crate::source_comments::{checked_source_text, source_text};
+
+        let source_path = std::env::temp_dir().join("test_source_text_outside_the_text.st");
+        std::fs::write(&source_path, "PROGRAM Größe\nEND_PROGRAM\n").unwrap();
+        let file_path: &'static str = Box::leak(source_path.to_string_lossy().into_owned().into_boxed_str());
+        let within_sharp_s = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 8, 8)..TextLocation::new(0, 13, 13)),
+            file: FileMarker::File(file_path),
+        };
+
+        let diagnostic = checked_source_text(&LoadedSources::default(), &within_sharp_s).unwrap_err();
+        assert_eq!(diagnostic.get_error_code(), "E127");
+        assert_eq!(diagnostic.get_message(), format!("bytes 8..13 of '{file_path}' are no span of its text, so the text is not exported"));
+        assert_eq!(diagnostic.get_location(), within_sharp_s);
+
+        // the text is left out, and the export returns the diagnostic of the body
+        assert_eq!(source_text(&LoadedSources::default(), &within_sharp_s), None);
+        let mut unit = CompilationUnit::new("test_source_text_outside_the_text.st");
+        unit.implementations.push(Implementation {
+            name: String::from("Groesse"),
+            type_name: String::from("Groesse"),
+            linkage: LinkageType::Internal,
+            pou_type: PouType::Program,
+            statements: vec![],
+            location: within_sharp_s,
+            name_location: SourceLocation::internal(),
+            end_location: SourceLocation::internal(),
+            overriding: false,
+            generic: false,
+            access: None,
+        });
+        let output_path = std::env::temp_dir().join("test_source_text_outside_the_text.xml");
+        let generated = generate_xml_targets(&GenerationParameters::new(), &vec![&unit], &output_path).unwrap();
+        assert_eq!(generated.diagnostics.iter().map(|it| it.get_error_code()).collect::<Vec<_>>(), vec!["E127"]);
+
+        let _ = std::fs::remove_file(&source_path);
+    }
+
+    #[test]
+    fn test_normalize_line_endings() {
+        use crate::source_comments::{normalize_line_endings, LineEndings};
+        let body = "a := 1;\r\nb := 2;\nc := 3;\rd := 4;";
+
+        assert_eq!(normalize_line_endings(body, LineEndings::Preserve), body);
+        assert_eq!(normalize_line_endings(body, LineEndings::Lf), "a := 1;\nb := 2;\nc := 3;\nd := 4;");
+        assert_eq!(normalize_line_endings(body, LineEndings::Crlf), "a := 1;\r\nb := 2;\r\nc := 3;\r\nd := 4;");
+    }
+
+    #[test]
+    fn test_rewrite_body_comments() {
+        use crate::source_comments::{rewrite_body_comments, BodyComments};
+        let body = "// count the parts\ncount := count + 1; (* outer (* nested *) *)\nname := 'no (* comment *)';\n  /* below */  \ndone := TRUE;";
+        let convert = |text: &str| IecTarget.format_body_comment(text);
+
+        assert_eq!(rewrite_body_comments(body, BodyComments::Keep, &convert), body);
+        assert_eq!(
+            rewrite_body_comments(body, BodyComments::Strip, &convert),
+            "count := count + 1;\nname := 'no (* comment *)';\ndone := TRUE;"
+        );
+        assert_eq!(
+            rewrite_body_comments(body, BodyComments::Convert, &convert),
+            "(* count the parts *)\ncount := count + 1; (* outer ( nested ) *)\nname := 'no (* comment *)';\n  (* below *)  \ndone := TRUE;"
+        );
+    }
+
+    #[test]
+    fn test_tag_list_collects_globals_and_pou_interfaces() {
+        let temp_dir = std::env::temp_dir().join("test_tag_list_collects_globals_and_pou_interfaces");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source = "gSpeed : REAL; // conveyor speed in m/s\nenable : BOOL;\n";
+        let source_path: &'static str = Box::leak(temp_dir.join("plant.st").to_string_lossy().into_owned().into_boxed_str());
+        std::fs::write(source_path, source).unwrap();
+
+        let mut unit = unit_with_global(source_path, "gSpeed", "REAL");
+        unit.global_vars[0].variables[0].location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 6, 6)),
+            file: FileMarker::File(source_path),
+        };
+
+        let mut pou = plc_ast::ast::Pou {
+            id: 0,
+            name: String::from("Conveyor"),
+            kind: PouType::FunctionBlock,
+            variable_blocks: vec![VariableBlock::default().with_block_type(plc_ast::ast::VariableBlockType::Input(plc_ast::ast::ArgumentProperty::ByVal))],
+            return_type: None,
+            location: SourceLocation::internal(),
+            name_location: SourceLocation::internal(),
+            poly_mode: None,
+            generics: vec![],
+            linkage: plc_ast::ast::LinkageType::Internal,
+            super_class: None,
+            is_const: false,
+            interfaces: vec![],
+            properties: vec![],
+        };
+        pou.variable_blocks[0].variables.push(Variable {
+            name: String::from("enable"),
+            data_type_declaration: DataTypeDeclaration::Reference { referenced_type: String::from("BOOL"), location: SourceLocation::internal() },
+            initializer: None,
+            address: None,
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(1, 0, 41)..TextLocation::new(1, 6, 47)),
+                file: FileMarker::File(source_path),
+            },
+        });
+        unit.pous.push(pou);
+
+        let tags = crate::tag_list::collect_tags(&LoadedSources::default(), &vec![&unit]);
+        assert_eq!(tags.len(), 2);
+        assert_eq!((tags[0].name.as_str(), tags[0].scope.as_str(), tags[0].pou.as_str()), ("gSpeed", "global", ""));
+        assert_eq!(tags[0].documentation, "conveyor speed in m/s");
+        assert_eq!((tags[1].name.as_str(), tags[1].scope.as_str(), tags[1].pou.as_str()), ("enable", "input", "Conveyor"));
+        assert_eq!(tags[1].type_name, "BOOL");
+        assert_eq!(tags[1].documentation, "");
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_tag_list_writes_workbook() {
+        let temp_dir = std::env::temp_dir().join("test_tag_list_writes_workbook");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        let mut params = GenerationParameters::new();
+        params.tag_list = true;
+
+        let unit = unit_with_global("plant.st", "gSpeed", "REAL");
+        let units = vec![&unit];
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        assert_eq!(written, vec![temp_dir.join("plant.xlsx")]);
+
+        let mut workbook = zip::ZipArchive::new(std::fs::File::open(&written[0]).unwrap()).unwrap();
+        let mut names: Vec<&str> = workbook.file_names().collect();
+        names.sort();
+        assert_eq!(names, vec!["[Content_Types].xml", "_rels/.rels", "xl/_rels/workbook.xml.rels", "xl/workbook.xml", "xl/worksheets/sheet1.xml"]);
+
+        let mut sheet = String::new();
+        std::io::Read::read_to_string(&mut workbook.by_name("xl/worksheets/sheet1.xml").unwrap(), &mut sheet).unwrap();
+        assert!(sheet.contains("r=\"A1\""));
+        assert!(sheet.contains("Documentation"));
+        assert!(sheet.contains("r=\"A2\""));
+        assert!(sheet.contains("gSpeed"));
+        assert!(sheet.contains("global"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+    fn hardware_access(direction: plc_ast::ast::HardwareAccessType, access: plc_ast::ast::DirectAccessType, address: Vec<i128>) -> plc_ast::ast::AstNode {
+        let address = address.into_iter()
+            .map(|it| plc_ast::ast::AstFactory::create_literal(plc_ast::literals::AstLiteral::Integer(it), SourceLocation::internal(), 0))
+            .collect();
+        plc_ast::ast::AstFactory::create_hardware_access(access, direction, address, SourceLocation::internal(), 0)
+    }
+
+    #[test]
+    fn test_modbus_register_locations() {
+        use crate::modbus_map::{locate_register, ModbusTable};
+        use plc_ast::ast::{AstStatement, DirectAccessType, HardwareAccessType};
+
+        let cases = vec![
+            (HardwareAccessType::Input, DirectAccessType::Bit, vec![2, 3], (ModbusTable::DiscreteInput, 19, None)),
+            (HardwareAccessType::Output, DirectAccessType::Bit, vec![0, 1], (ModbusTable::Coil, 1, None)),
+            (HardwareAccessType::Memory, DirectAccessType::Bit, vec![3, 2], (ModbusTable::HoldingRegister, 1, Some(10))),
+            (HardwareAccessType::Input, DirectAccessType::Word, vec![4], (ModbusTable::InputRegister, 4, None)),
+            (HardwareAccessType::Memory, DirectAccessType::DWord, vec![5], (ModbusTable::HoldingRegister, 10, None)),
+            (HardwareAccessType::Output, DirectAccessType::LWord, vec![1], (ModbusTable::HoldingRegister, 4, None)),
+        ];
+
+        for (direction, access, address, expected) in cases {
+            let node = hardware_access(direction, access, address);
+            let AstStatement::HardwareAccess(hardware_access) = &node.stmt else { unreachable!() };
+            assert_eq!(locate_register(hardware_access), Some(expected));
+        }
+
+        let node = hardware_access(HardwareAccessType::Input, DirectAccessType::Template, vec![]);
+        let AstStatement::HardwareAccess(hardware_access) = &node.stmt else { unreachable!() };
+        assert_eq!(locate_register(hardware_access), None);
+    }
+
+    #[test]
+    fn test_modbus_scaling_from_documentation() {
+        use crate::modbus_map::parse_scaling;
+        assert_eq!(parse_scaling("tank temperature scale=0.1 offset=-40"), (0.1, -40.0));
+        assert_eq!(parse_scaling("scale=2, in bar"), (2.0, 0.0));
+        assert_eq!(parse_scaling("no scaling"), (1.0, 0.0));
+    }
+
+    #[test]
+    fn test_modbus_map_lists_located_variables() {
+        use crate::modbus_map::{collect_modbus_registers, format_modbus_map, ModbusMapFormat};
+        use plc_ast::ast::{DirectAccessType, HardwareAccessType};
+
+        let temp_dir = std::env::temp_dir().join("test_modbus_map_lists_located_variables");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("plant.st").to_string_lossy().into_owned().into_boxed_str());
+        std::fs::write(source_path, "gLevel AT %MD5 : REAL; // scale=0.5\n").unwrap();
+
+        let mut unit = unit_with_global(source_path, "gLevel", "REAL");
+        unit.global_vars[0].variables[0].address = Some(hardware_access(HardwareAccessType::Memory, DirectAccessType::DWord, vec![5]));
+        unit.global_vars[0].variables[0].location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(0, 0, 0)..TextLocation::new(0, 6, 6)),
+            file: FileMarker::File(source_path),
+        };
+        let unlocated = Variable { name: String::from("gUnlocated"), address: None, ..unit.global_vars[0].variables[0].clone() };
+        unit.global_vars[0].variables.push(unlocated);
+
+        let registers = collect_modbus_registers(&LoadedSources::default(), &vec![&unit]);
+        assert_eq!(registers.len(), 1);
+
+        let csv = format_modbus_map(&registers, ModbusMapFormat::Csv).unwrap();
+        let rows: Vec<&str> = csv.split("\r\n").collect();
+        assert_eq!(rows[0], "Name,POU,Address,Table,Register,Bit,Data Type,Length,Scale,Offset");
+        assert_eq!(rows[1], "gLevel,,%MD5,holdingRegister,10,,REAL,2,0.5,0");
+
+        let json: serde_json::Value = serde_json::from_str(&format_modbus_map(&registers, ModbusMapFormat::Json).unwrap()).unwrap();
+        assert_eq!(json[0]["table"], "holdingRegister");
+        assert_eq!(json[0]["register"], 10);
+        assert_eq!(json[0]["dataType"], "REAL");
+        assert_eq!(json[0]["scale"], 0.5);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
+    }
+
+    #[test]
+    fn test_parse_attribute_pragma() {
+        use crate::source_comments::parse_attribute_pragma;
+        assert_eq!(parse_attribute_pragma("{attribute 'network_publish' := 'Output'}"), Some((String::from("network_publish"), String::from("Output"))));
+        assert_eq!(parse_attribute_pragma("{attribute 'hide'}"), Some((String::from("hide"), String::new())));
+        assert_eq!(parse_attribute_pragma("gSpeed : REAL;"), None);
+    }
+
+    #[test]
+    fn test_network_publish_of_blocks() {
+        use crate::xml_gen::variable_network_publish;
+        use plc_ast::ast::NetworkPublishMode;
+
+        let temp_dir = std::env::temp_dir().join("test_network_publish_of_blocks");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("cell.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'network_publish' := 'PublicationOnly'}\nVAR_GLOBAL\ngSpeed : REAL;\n{attribute 'network_publish' := 'Input'}\ngSetpoint : REAL;\n{attribute 'network_publish' := 'DoNotPublish'}\ngHidden : INT;\nEND_VAR\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let location = |text: &str| {
+            let offset = source.find(text).unwrap();
+            SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + text.len())),
+                file: FileMarker::File(source_path),
+            }
+        };
+
+        let mut unit = unit_with_global(source_path, "gSpeed", "REAL");
+        let template = unit.global_vars[0].variables[0].clone();
+        unit.global_vars[0].location = location("VAR_GLOBAL");
+        unit.global_vars[0].variables = ["gSpeed", "gSetpoint", "gHidden"].into_iter()
+            .map(|name| Variable { name: String::from(name), location: location(name), ..template.clone() })
+            .collect();
 
-        let _ = std::fs::remove_file(&output_path);
-    }
+        // the attribute above the block is the default, the attribute of a variable overrides it
+        let block = &unit.global_vars[0];
+        let modes: Vec<NetworkPublishMode> = block.variables.iter().map(|it| variable_network_publish(&LoadedSources::default(), block, it)).collect();
+        assert_eq!(modes, vec![NetworkPublishMode::PublishOnly, NetworkPublishMode::Input, NetworkPublishMode::DoNotPublish]);
 
-    #[test]
-    fn test_write_xml_file_invalid_path() {
-        let output_path = PathBuf::from("/nonexistent/directory/file.xml");
-        let node = Node::new_str("Root");
-        let result = write_xml_file(&output_path, node);
-        assert!(result.is_err());
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_copy_xmlfile_to_output_empty_paths() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_copy_empty.xml");
-        let result = copy_xmlfile_to_output(vec![], output_path.clone());
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), output_path);
+    fn test_unknown_network_publish_mode_is_reported() {
+        use crate::xml_gen::variable_network_publish;
+        use plc_ast::ast::NetworkPublishMode;
+        use plc_source::SourceCode;
+
+        let source_path = "test_unknown_network_publish_mode.st";
+        let source = "VAR_GLOBAL\n    {attribute 'network_publish' := 'Outptu'}\n    gStatus : DINT;\nEND_VAR\n";
+        let mut params = GenerationParameters::new();
+        params.sources = LoadedSources::new(&[SourceCode::new(source, source_path)]);
+
+        let offset = source.find("gStatus").unwrap();
+        let mut unit = unit_with_global(source_path, "gStatus", "DINT");
+        unit.global_vars[0].variables[0].location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(2, 4, offset)..TextLocation::new(2, 11, offset + 7)),
+            file: FileMarker::File(source_path),
+        };
+
+        // the variable is left unpublished, and the export points at the attribute
+        let block = &unit.global_vars[0];
+        assert_eq!(variable_network_publish(&params.sources, block, &block.variables[0]), NetworkPublishMode::DoNotPublish);
+
+        let output_path = std::env::temp_dir().join("test_unknown_network_publish_mode.xml");
+        let generated = generate_xml_targets(&params, &vec![&unit], &output_path).unwrap();
+        assert_eq!(generated.diagnostics.len(), 1);
+        let warning = &generated.diagnostics[0];
+        assert_eq!(warning.get_error_code(), "E130");
+        assert_eq!(warning.get_message(), "'Outptu' is no network publish mode, so 'gStatus' is not published. Accepted are PublishOnly, Input, Output and DoNotPublish");
+        let attribute_start = source.find("{attribute").unwrap();
+        let attribute_end = source.find("}\n").unwrap() + 1;
+        assert_eq!(warning.get_location(), SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(1, 4, attribute_start)..TextLocation::new(1, attribute_end - attribute_start + 4, attribute_end)),
+            file: FileMarker::File(source_path),
+        });
+
+        let _ = std::fs::remove_file(&output_path);
     }
 
     #[test]
-    fn test_copy_xmlfile_to_output_with_xml_file() {
-        let temp_dir = std::env::temp_dir();
+    fn test_eip_tags_list_published_globals() {
+        use crate::eip_tags::{collect_published_tags, format_eip_tags};
+        use crate::xml_gen::variable_network_publish;
+        use plc_ast::ast::NetworkPublishMode;
 
-        // Create a source XML file
-        let source_path = temp_dir.join("test_source_copy.XML");
-        std::fs::write(&source_path, "<Root/>").unwrap();
+        let temp_dir = std::env::temp_dir().join("test_eip_tags_list_published_globals");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("cell.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'network_publish' := 'Input'}\ngSetpoint : REAL;\n{attribute 'network_publish' := 'output'}\ngStatus : DINT;\ngHidden : INT;\n";
+        std::fs::write(source_path, source).unwrap();
 
-        let output_path = temp_dir.join("test_dest_copy.xml");
+        let mut unit = unit_with_global(source_path, "gSetpoint", "REAL");
+        let template = unit.global_vars[0].variables[0].clone();
+        unit.global_vars[0].variables = [("gSetpoint", "REAL"), ("gStatus", "DINT"), ("gHidden", "INT")].into_iter().map(|(name, type_name)| {
+            let offset = source.find(name).unwrap();
+            Variable {
+                name: String::from(name),
+                data_type_declaration: DataTypeDeclaration::Reference { referenced_type: String::from(type_name), location: SourceLocation::internal() },
+                location: SourceLocation {
+                    span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + name.len())),
+                    file: FileMarker::File(source_path),
+                },
+                ..template.clone()
+            }
+        })
+        .collect();
 
-        let result = copy_xmlfile_to_output(vec![source_path.as_path()], output_path.clone());
-        assert!(result.is_ok());
-        assert!(output_path.exists());
+        let block = &unit.global_vars[0];
+        let modes: Vec<NetworkPublishMode> = block.variables.iter().map(|it| variable_network_publish(&LoadedSources::default(), block, it)).collect();
+        assert_eq!(modes, vec![NetworkPublishMode::Input, NetworkPublishMode::Output, NetworkPublishMode::DoNotPublish]);
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert_eq!(contents, "<Root/>");
+        let tags = collect_published_tags(&LoadedSources::default(), &vec![&unit]);
+        assert_eq!(tags.iter().map(|it| it.name.as_str()).collect::<Vec<_>>(), vec!["gSetpoint", "gStatus"]);
+        assert_eq!(tags[0].cip_type, 0xCA);
+        assert_eq!(tags[1].size, Some(4));
 
-        let _ = std::fs::remove_file(&source_path);
-        let _ = std::fs::remove_file(&output_path);
+        let document = format_eip_tags(&tags);
+        let assemblies = document.find_child("Assemblies").unwrap();
+        let input = &assemblies.children[0];
+        assert_eq!(input.attributes.get("instance").map(String::as_str), Some("100"));
+        assert_eq!(input.children[0].attributes.get("tag").map(String::as_str), Some("gSetpoint"));
+        let output = &assemblies.children[1];
+        assert_eq!(output.children[0].attributes.get("tag").map(String::as_str), Some("gStatus"));
+        assert_eq!(output.attributes.get("size").map(String::as_str), Some("4"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_format_enum_initials_no_conflicts() {
-        let variants = vec![
-            NameAndInitialValue {
-                name: String::from("A"),
-                initial_value: String::from("0"),
-            },
-            NameAndInitialValue {
-                name: String::from("B"),
-                initial_value: String::from("1"),
-            },
-            NameAndInitialValue {
-                name: String::from("C"),
-                initial_value: String::from("2"),
-            },
-        ];
+    fn test_yaml_rendering() {
+        use crate::yaml::to_yaml;
 
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 3);
+        let value = serde_json::json!({
+            "name": "Line",
+            "kind": "Program",
+            "variables": [
+                { "name": "counter", "typeName": "DINT" },
+                { "name": "limit", "initialValue": "10" }
+            ],
+            "empty": [],
+            "body": "IF start THEN\n    counter := counter + 1;\nEND_IF",
+            "documentation": "speed: in m/s"
+        });
+
+        let expected = "\
+body: |-
+  IF start THEN
+      counter := counter + 1;
+  END_IF
+documentation: \"speed: in m/s\"
+empty: []
+kind: Program
+name: Line
+variables:
+- name: counter
+  typeName: DINT
+- initialValue: \"10\"
+  name: limit
+";
+        assert_eq!(to_yaml(&value), expected);
     }
 
     #[test]
-    fn test_format_enum_initials_with_conflicts() {
-        let variants = vec![
-            NameAndInitialValue {
-                name: String::from("A"),
-                initial_value: String::from("0"),
-            },
-            NameAndInitialValue {
-                name: String::from("B"),
-                initial_value: String::from("0"), // conflict with A
-            },
-            NameAndInitialValue {
-                name: String::from("C"),
-                initial_value: String::from("1"), // conflict with auto-incremented B
-            },
-        ];
+    fn test_leading_comment() {
+        use crate::source_comments::leading_comment;
 
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 3);
-        // After resolution: A=0, B=1 (incremented), C=2 (incremented since 1 is taken)
+        let temp_dir = std::env::temp_dir().join("test_leading_comment");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("conveyor.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "END_VAR\n// Drives the conveyor belt\n(* and watches its motor *)\n{attribute 'hide'}\nFUNCTION_BLOCK Conveyor\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let offset = source.find("FUNCTION_BLOCK").unwrap();
+        let location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(4, 0, offset)..TextLocation::new(4, 14, offset + 14)),
+            file: FileMarker::File(source_path),
+        };
+        assert_eq!(leading_comment(&LoadedSources::default(), &location).as_deref(), Some("Drives the conveyor belt\nand watches its motor"));
+
+        // block comments spanning several lines
+        let source = "END_VAR\n(* Drives the conveyor belt\n * and watches its motor\n *)\nFUNCTION_BLOCK Conveyor\n";
+        std::fs::write(source_path, source).unwrap();
+        let offset = source.find("FUNCTION_BLOCK").unwrap();
+        let location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(4, 0, offset)..TextLocation::new(4, 14, offset + 14)),
+            file: FileMarker::File(source_path),
+        };
+        assert_eq!(leading_comment(&LoadedSources::default(), &location).as_deref(), Some("Drives the conveyor belt\nand watches its motor"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_format_enum_initials_empty() {
-        let variants: Vec<NameAndInitialValue> = vec![];
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 0);
+    fn test_variable_documentation() {
+        let temp_dir = std::env::temp_dir().join("test_variable_documentation");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("cell.st").to_string_lossy().into_owned().into_boxed_str());
+        let output_path = temp_dir.join("cell.xml");
+        let source = "VAR_GLOBAL\n/// Belt speed\n/// in m/s\ngSpeed : REAL;\ngCount : INT; (* parts & pieces *)\ngPlain : INT;\nEND_VAR\n";
+        std::fs::write(source_path, source).unwrap();
+
+        let mut unit = unit_with_global(source_path, "gSpeed", "REAL");
+        let template = unit.global_vars[0].variables[0].clone();
+        unit.global_vars[0].variables = ["gSpeed", "gCount", "gPlain"].into_iter().map(|name| {
+            let offset = source.find(name).unwrap();
+            Variable {
+                name: String::from(name),
+                location: SourceLocation {
+                    span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + name.len())),
+                    file: FileMarker::File(source_path),
+                },
+                ..template.clone()
+            }
+        })
+        .collect();
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = vec![String::from("omron")];
+        generate_xml_targets(&params, &units, &output_path).unwrap();
+        let contents = std::fs::read_to_string(&output_path).unwrap();
+
+        // the comment above a variable documents it, otherwise the one behind it
+        assert!(contents.contains("<Documentation><![CDATA[Belt speed\nin m/s]]></Documentation>"));
+        assert!(contents.contains("<Documentation><![CDATA[parts & pieces]]></Documentation>"));
+        assert_eq!(contents.matches("<Documentation>").count(), 2);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_format_enum_initials_single_element() {
-        let variants = vec![NameAndInitialValue {
-            name: String::from("ONLY"),
-            initial_value: String::from("42"),
-        }];
+    fn test_markdown_docs_document_types_and_pous() {
+        use crate::markdown_docs::format_markdown_docs;
+        use crate::project_model::{DataTypeModel, EnumValueModel, PouModel, ProjectModel, VariableModel};
 
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 1);
+        let variable = |name: &str, scope: &str, type_name: &str, documentation: Option<&str>| VariableModel {
+            name: String::from(name),
+            type_name: String::from(type_name),
+            scope: String::from(scope),
+            initial_value: None,
+            address: None,
+            constant: false,
+            retain: false,
+            documentation: documentation.map(String::from),
+        };
+
+        let model = ProjectModel {
+            data_types: vec![DataTypeModel {
+                name: String::from("Mode"),
+                kind: String::from("enum"),
+                base_type: Some(String::from("INT")),
+                members: vec![],
+                values: vec![EnumValueModel { name: String::from("AUTO"), value: String::from("0") }],
+                documentation: Some(String::from("Operating mode")),
+            }],
+            globals: vec![variable("gSpeed", "global", "REAL", Some("in m/s | km/h"))],
+            pous: vec![PouModel {
+                name: String::from("Conveyor"),
+                kind: String::from("FunctionBlock"),
+                return_type: None,
+                variables: vec![variable("enable", "input", "BOOL", None), variable("timer", "local", "TON", None)],
+                documentation: Some(String::from("Drives the belt")),
+                body: None,
+            }],
+        };
+
+        let markdown = format_markdown_docs(&model, "plant");
+        assert!(markdown.starts_with("# plant\n"));
+        assert!(markdown.contains("### Mode\n\nOperating mode\n\nEnumeration of `INT`"));
+        assert!(markdown.contains("| AUTO | 0 |"));
+        assert!(markdown.contains("### Conveyor\n\nDrives the belt\n\nFunctionBlock\n"));
+        assert!(markdown.contains("#### Interface\n\n| Name | Scope | Type | Initial value | Documentation |\n| --- | --- | --- | --- | --- |\n| enable | input | `BOOL` |  |  |"));
+        assert!(markdown.contains("#### Variables\n\n| Name | Scope | Type | Initial value | Documentation |\n| --- | --- | --- | --- | --- |\n| timer | local | `TON` |  |  |"));
+        assert!(markdown.contains("| gSpeed | `REAL` |  | in m/s \\| km/h |"));
     }
 
     #[test]
-    fn test_format_enum_initials_all_same_value() {
-        let variants = vec![
-            NameAndInitialValue {
-                name: String::from("X"),
-                initial_value: String::from("5"),
-            },
-            NameAndInitialValue {
-                name: String::from("Y"),
-                initial_value: String::from("5"),
-            },
-            NameAndInitialValue {
-                name: String::from("Z"),
-                initial_value: String::from("5"),
-            },
-        ];
+    fn test_html_docs_link_types_and_highlight_bodies() {
+        use crate::html_docs::format_html_docs;
+        use crate::project_model::{DataTypeModel, PouModel, ProjectModel, VariableModel};
 
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 3);
-        // Should auto-increment: X=5, Y=6, Z=7
+        let variable = |name: &str, type_name: &str| VariableModel {
+            name: String::from(name),
+            type_name: String::from(type_name),
+            scope: String::from("local"),
+            initial_value: None,
+            address: None,
+            constant: false,
+            retain: false,
+            documentation: None,
+        };
+
+        let model = ProjectModel {
+            data_types: vec![DataTypeModel {
+                name: String::from("Motor"),
+                kind: String::from("struct"),
+                base_type: None,
+                members: vec![variable("speed", "REAL")],
+                values: vec![],
+                documentation: None,
+            }],
+            globals: vec![],
+            pous: vec![PouModel {
+                name: String::from("Main"),
+                kind: String::from("Program"),
+                return_type: None,
+                variables: vec![variable("drive", "Motor")],
+                documentation: Some(String::from("Runs <everything>")),
+                body: Some(String::from("IF drive.speed > 1.5 THEN // fast\n    Main();\nEND_IF")),
+            }],
+        };
+
+        let html = format_html_docs(&model, "plant");
+        assert!(html.contains("<li><a href=\"#doc-motor\">Motor</a></li>"));
+        assert!(html.contains("<section id=\"doc-main\">"));
+        assert!(html.contains("<p>Runs &lt;everything&gt;</p>"));
+        assert!(html.contains("<td><code><a href=\"#doc-motor\">Motor</a></code></td>"));
+        assert!(html.contains("<span class=\"kw\">IF</span> drive.speed &gt; <span class=\"nu\">1.5</span> <span class=\"kw\">THEN</span> <span class=\"cm\">// fast</span>"));
+        assert!(html.contains("<a href=\"#doc-main\">Main</a>();"));
+        assert!(!html.contains("globals"));
     }
 
     #[test]
-    fn test_parse_project_empty_units() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_parse_empty_units.xml");
-        let template = get_omron_template();
-        let params = GenerationParameters::new();
-        let units: Vec<&CompilationUnit> = vec![];
+    fn test_ignition_tags_of_published_globals() {
+        use crate::ignition_tags::{collect_ignition_tags, DEFAULT_IGNITION_OPC_SERVER};
 
-        let result =
-            parse_project_into_nodetree(&params, &units, OMRON_SCHEMA, &output_path, template);
-        assert!(result.is_ok());
-        assert!(output_path.exists());
+        let temp_dir = std::env::temp_dir().join("test_ignition_tags_of_published_globals");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("plant.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'network_publish' := 'Output'}\ngSpeed : REAL;\ngHidden : INT;\n";
+        std::fs::write(source_path, source).unwrap();
 
-        let _ = std::fs::remove_file(&output_path);
+        let mut unit = unit_with_global(source_path, "gSpeed", "REAL");
+        let published = unit.global_vars[0].variables[0].clone();
+        let offset = source.find("gSpeed").unwrap();
+        unit.global_vars[0].variables[0].location = SourceLocation {
+            span: CodeSpan::Range(TextLocation::new(1, 0, offset)..TextLocation::new(1, 6, offset + 6)),
+            file: FileMarker::File(source_path),
+        };
+        let offset = source.find("gHidden").unwrap();
+        unit.global_vars[0].variables.push(Variable {
+            name: String::from("gHidden"),
+            location: SourceLocation {
+                span: CodeSpan::Range(TextLocation::new(2, 0, offset)..TextLocation::new(2, 7, offset + 7)),
+                file: FileMarker::File(source_path),
+            },
+            ..published
+        });
+
+        let provider = serde_json::to_value(collect_ignition_tags(&LoadedSources::default(), &vec![&unit], DEFAULT_IGNITION_OPC_SERVER)).unwrap();
+        assert_eq!(provider["tagType"], "Provider");
+        assert_eq!(provider["tags"][0]["name"], "plant");
+        assert_eq!(provider["tags"][0]["tagType"], "Folder");
+
+        let tags = provider["tags"][0]["tags"].as_array().unwrap();
+        assert_eq!(tags.len(), 1);
+        assert_eq!(tags[0]["name"], "gSpeed");
+        assert_eq!(tags[0]["tagType"], "AtomicTag");
+        assert_eq!(tags[0]["valueSource"], "opc");
+        assert_eq!(tags[0]["dataType"], "Float4");
+        assert_eq!(tags[0]["opcServer"], "Ignition OPC UA Server");
+        assert_eq!(tags[0]["opcItemPath"], "ns=1;s=plant.gSpeed");
+        assert_eq!(tags[0]["readOnly"], true);
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_write_xml_nested_structure() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_nested_structure.xml");
+    fn test_real_literal_format() {
+        use crate::literal_format::{RealFormat, RealNotation};
+        use plc_ast::literals::AstLiteral;
 
-        let inner_child = STypeName::new().content(String::from("DINT"));
-        let type_node = SType::new().child(&inner_child);
-        let member = SMember::new()
-            .attribute_str("name", "field1")
-            .child(&type_node);
-        let root = Node::new_str("DataType").child(&member);
+        let mut params = GenerationParameters::new();
+        let real = |value: &str| AstLiteral::Real(String::from(value));
 
-        let result = write_xml_file(&output_path, root);
-        assert!(result.is_ok());
+        // normalized to plain decimals by default
+        assert_eq!(OmronTarget.format_literal(&params, &real("1_000.5")), "1000.5");
+        assert_eq!(OmronTarget.format_literal(&params, &real("1e3")), "1000.0");
+        assert_eq!(OmronTarget.format_literal(&params, &real("2.5E-3")), "0.0025");
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.contains("DataType"));
-        assert!(contents.contains("Member"));
-        assert!(contents.contains("field1"));
-        assert!(contents.contains("DINT"));
+        params.real_format = RealFormat { digits: Some(4), notation: RealNotation::Decimal };
+        assert_eq!(OmronTarget.format_literal(&params, &real("3.14159265")), "3.142");
+        assert_eq!(OmronTarget.format_literal(&params, &real("123456.0")), "123500.0");
 
-        let _ = std::fs::remove_file(&output_path);
+        params.real_format = RealFormat { digits: None, notation: RealNotation::Scientific };
+        assert_eq!(OmronTarget.format_literal(&params, &real("1500.0")), "1.5E3");
+        assert_eq!(TiaTarget.format_literal(&params, &real("1.0")), "1.0E0");
+
+        params.real_format.digits = Some(2);
+        assert_eq!(AutomationMlTarget.format_literal(&params, &real("0.000123456")), "1.2E-4");
     }
 
     #[test]
-    fn test_generation_parameters_omron_flag() {
+    fn test_bool_literals_per_target() {
+        use crate::literal_format::BoolCase;
+        use plc_ast::literals::AstLiteral;
+
         let mut params = GenerationParameters::new();
-        assert!(!params.output_xml_omron);
+        assert_eq!(OmronTarget.format_literal(&params, &AstLiteral::Bool(true)), "TRUE");
+        assert_eq!(CodesysTarget.format_literal(&params, &AstLiteral::Bool(false)), "FALSE");
+        assert_eq!(TiaTarget.format_literal(&params, &AstLiteral::Bool(true)), "true");
+        assert_eq!(AutomationMlTarget.format_literal(&params, &AstLiteral::Bool(false)), "false");
 
-        params.output_xml_omron = true;
-        assert!(params.output_xml_omron);
+        // a configured spelling applies to every target
+        params.bool_case = Some(BoolCase::Lower);
+        assert_eq!(OmronTarget.format_literal(&params, &AstLiteral::Bool(true)), "true");
+        params.bool_case = Some(BoolCase::Upper);
+        assert_eq!(AutomationMlTarget.format_literal(&params, &AstLiteral::Bool(false)), "FALSE");
+
+        let mut unit = unit_with_global("plant.st", "gRunning", "BOOL");
+        unit.global_vars[0].variables[0].initializer = Some(plc_ast::ast::AstFactory::create_literal(AstLiteral::Bool(true), SourceLocation::internal(), 0));
+        let csv = crate::sysmac_csv::format_sysmac_csv(&GenerationParameters::new(), &vec![&unit]);
+        assert!(csv.contains("gRunning,BOOL,TRUE,"));
     }
 
     #[test]
-    fn test_write_xml_file_produces_valid_xml_header() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_xml_header.xml");
+    fn test_string_literals_per_target() {
+        use plc_ast::literals::AstLiteral;
 
-        let node = Node::new_str("Root");
-        write_xml_file(&output_path, node).unwrap();
+        let params = GenerationParameters::new();
+        let string = |value: &str| AstLiteral::new_string(String::from(value), false);
+        let wide = |value: &str| AstLiteral::new_string(String::from(value), true);
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.starts_with("<?xml version=\"1.0\" encoding=\"UTF-8\"?>"));
+        // the delimiter, `$` and control characters are escaped the IEC way
+        assert_eq!(OmronTarget.format_literal(&params, &string("it's 5$")), "'it$'s 5$$'");
+        assert_eq!(CodesysTarget.format_literal(&params, &string("say \"hi\"\r\n")), "'say \"hi\"$R$N'");
+        assert_eq!(TiaTarget.format_literal(&params, &wide("say \"hi\"\t")), "\"say $\"hi$\"$T\"");
 
-        let _ = std::fs::remove_file(&output_path);
+        // characters outside of ASCII by their bytes or UTF-16 units
+        assert_eq!(OmronTarget.format_literal(&params, &string("Grüße")), "'Gr$C3$BC$C3$9Fe'");
+        assert_eq!(OmronTarget.format_literal(&params, &wide("Grüße")), "\"Gr$00FC$00DFe\"");
+        assert_eq!(OmronTarget.format_literal(&params, &wide("\u{1F600}")), "\"$D83D$DE00\"");
+
+        // xs:string values are written as they are
+        assert_eq!(AutomationMlTarget.format_literal(&params, &string("it's 5$")), "it's 5$");
     }
 
     #[test]
-    fn test_write_xml_empty_node() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_empty_node.xml");
+    fn test_date_time_literals_per_target() {
+        use plc_ast::literals::{AstLiteral, Time};
 
-        let node = Node::new_str("Empty");
-        write_xml_file(&output_path, node).unwrap();
+        let params = GenerationParameters::new();
+        let time = |sec: f64, milli: f64, negative: bool| AstLiteral::Time(Time { day: 0.0, hour: 0.0, min: 0.0, sec, milli, micro: 0.0, nano: 0, negative });
+        let date = AstLiteral::new_date(2024, 1, 5);
+        let tod = AstLiteral::new_time_of_day(7, 30, 15, 250_000_000);
+        let dt = AstLiteral::new_date_and_time(2024, 1, 5, 12, 0, 0, 0);
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.contains("Empty"));
+        assert_eq!(OmronTarget.format_literal(&params, &time(1.0, 500.0, false)), "T#1s500ms");
+        assert_eq!(OmronTarget.format_literal(&params, &time(0.0, 0.0, false)), "T#0s");
+        assert_eq!(OmronTarget.format_literal(&params, &time(90.0, 0.0, true)), "T#-1m30s");
 
-        let _ = std::fs::remove_file(&output_path);
+        // Sysmac Studio has no units below milliseconds
+        let fine = |milli: f64, micro: f64, nano: u32| AstLiteral::Time(Time { day: 0.0, hour: 0.0, min: 0.0, sec: 1.0, milli, micro, nano, negative: false });
+        assert_eq!(OmronTarget.format_literal(&params, &fine(500.0, 250.0, 0)), "T#1s500.25ms");
+        assert_eq!(OmronTarget.format_literal(&params, &fine(0.0, 2.0, 5)), "T#1s0.002005ms");
+        assert_eq!(CodesysTarget.format_literal(&params, &fine(500.0, 250.0, 0)), "T#1s500ms250us");
+        assert_eq!(OmronTarget.format_literal(&params, &date), "D#2024-01-05");
+        assert_eq!(OmronTarget.format_literal(&params, &tod), "TOD#07:30:15.25");
+        assert_eq!(OmronTarget.format_literal(&params, &dt), "DT#2024-01-05-12:00:00");
+        assert_eq!(OmronTarget.format_literal(&params, &AstLiteral::new_integer(5)), "5");
+
+        assert_eq!(TiaTarget.format_literal(&params, &time(1.0, 500.0, false)), "T#1s_500ms");
+        assert_eq!(TiaTarget.format_literal(&params, &date), "D#2024-01-05");
+
+        assert_eq!(AutomationMlTarget.format_literal(&params, &time(1.0, 500.0, false)), "PT1.5S");
+        assert_eq!(AutomationMlTarget.format_literal(&params, &time(0.0, 0.0, false)), "PT0S");
+        assert_eq!(AutomationMlTarget.format_literal(&params, &time(90.0, 0.0, true)), "-PT1M30S");
+        assert_eq!(AutomationMlTarget.format_literal(&params, &date), "2024-01-05");
+        assert_eq!(AutomationMlTarget.format_literal(&params, &tod), "07:30:15.25");
+        assert_eq!(AutomationMlTarget.format_literal(&params, &dt), "2024-01-05T12:00:00");
+
+        // the Sysmac variable table writes initial values like the omron target
+        let mut unit = unit_with_global("plant.st", "gDelay", "TIME");
+        unit.global_vars[0].variables[0].initializer = Some(plc_ast::ast::AstFactory::create_literal(time(2.0, 0.0, false), SourceLocation::internal(), 0));
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &vec![&unit]);
+        assert!(csv.contains("gDelay,TIME,T#2s,"));
     }
 
     #[test]
-    fn test_write_xml_cdata_content() {
-        let temp_dir = std::env::temp_dir();
-        let output_path = temp_dir.join("test_cdata_content.xml");
+    fn test_published_bit_string_types() {
+        let temp_dir = std::env::temp_dir().join("test_published_bit_string_types");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let source_path: &'static str = Box::leak(temp_dir.join("cell.st").to_string_lossy().into_owned().into_boxed_str());
+        let source = "{attribute 'network_publish' := 'Output'}\ngStatus : WORD;\ngFlags : WORD;\n";
+        std::fs::write(source_path, source).unwrap();
 
-        let node = Node::new_str("Code");
-        // Node with content should produce CDATA
-        let mut node_with_content = node;
-        node_with_content.content = Some(String::from("x := 1 + 2;"));
+        let mut unit = unit_with_global(source_path, "gStatus", "WORD");
+        let template = unit.global_vars[0].variables[0].clone();
+        unit.global_vars[0].variables = ["gStatus", "gFlags"].into_iter().map(|name| {
+            let offset = source.find(name).unwrap();
+            Variable {
+                name: String::from(name),
+                location: SourceLocation {
+                    span: CodeSpan::Range(TextLocation::new(0, 0, offset)..TextLocation::new(0, 0, offset + name.len())),
+                    file: FileMarker::File(source_path),
+                },
+                ..template.clone()
+            }
+        })
+        .collect();
 
-        write_xml_file(&output_path, node_with_content).unwrap();
+        // only published variables are mapped, by the table of the target
+        let mut params = GenerationParameters::new();
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &vec![&unit]);
+        assert!(csv.contains("gStatus,UINT,"));
+        assert!(csv.contains("gFlags,WORD,"));
+        assert_eq!(params.published_bit_string_type(&CodesysTarget, "WORD"), None);
 
-        let contents = std::fs::read_to_string(&output_path).unwrap();
-        assert!(contents.contains("x := 1 + 2;"));
+        // configured entries override and extend the table
+        params.bit_string_types = vec![(String::from("word"), String::from("INT")), (String::from("BYTE"), String::from("SINT"))];
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &vec![&unit]);
+        assert!(csv.contains("gStatus,INT,"));
+        assert_eq!(params.published_bit_string_type(&OmronTarget, "DWORD").as_deref(), Some("UDINT"));
+        assert_eq!(params.published_bit_string_type(&CodesysTarget, "byte").as_deref(), Some("SINT"));
 
-        let _ = std::fs::remove_file(&output_path);
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_format_enum_initials_negative_values() {
-        let variants = vec![
-            NameAndInitialValue {
-                name: String::from("NEG"),
-                initial_value: String::from("-1"),
-            },
-            NameAndInitialValue {
-                name: String::from("NEG2"),
-                initial_value: String::from("-1"), // conflict
-            },
-        ];
+    fn test_struct_member_initial_values() {
+        use plc_ast::ast::{AstFactory, DataType, LinkageType, UserTypeDeclaration};
+        use plc_ast::literals::AstLiteral;
 
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 2);
-        // NEG=-1, NEG2=0 (incremented from -1)
+        let temp_dir = std::env::temp_dir().join("test_struct_member_initial_values");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        // TYPE Motor : STRUCT speed : INT := 100; running : BOOL; END_STRUCT END_TYPE
+        let mut unit = unit_with_global("plant.st", "motor", "Motor");
+        let location = unit.global_vars[0].variables[0].location.clone();
+        let mut speed = unit.global_vars[0].variables[0].clone();
+        speed.name = String::from("speed");
+        speed.data_type_declaration = DataTypeDeclaration::Reference { referenced_type: String::from("INT"), location: SourceLocation::internal() };
+        speed.initializer = Some(AstFactory::create_literal(AstLiteral::Integer(100), SourceLocation::internal(), 0));
+        let mut running = speed.clone();
+        running.name = String::from("running");
+        running.data_type_declaration = DataTypeDeclaration::Reference { referenced_type: String::from("BOOL"), location: SourceLocation::internal() };
+        running.initializer = None;
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::StructType { name: Some(String::from("Motor")), variables: vec![speed, running] },
+            initializer: None,
+            location,
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+        let units = vec![&unit];
+
+        let mut params = GenerationParameters::new();
+        params.xml_targets = ["omron", "tia", "aml"].iter().map(|it| String::from(*it)).collect();
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        let documents: Vec<String> = written.iter().map(|it| std::fs::read_to_string(it).unwrap()).collect();
+
+        let members = documents[0].split("<Member ").skip(1).collect::<Vec<_>>();
+        assert_eq!(members.len(), 2);
+        assert!(members[0].contains(r#"<SimpleValue value="100" />"#));
+        assert!(!members[1].contains("<InitialValue>"));
+        assert!(documents[1].contains("<StartValue><![CDATA[100]]></StartValue>"));
+        assert!(documents[2].contains("<DefaultValue><![CDATA[100]]></DefaultValue>"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
 
     #[test]
-    fn test_format_enum_initials_consecutive_conflicts() {
-        let variants = vec![
-            NameAndInitialValue {
-                name: String::from("A"),
-                initial_value: String::from("0"),
-            },
-            NameAndInitialValue {
-                name: String::from("B"),
-                initial_value: String::from("1"),
-            },
-            NameAndInitialValue {
-                name: String::from("C"),
-                initial_value: String::from("0"), // conflicts with A, tries 1 (taken by B), settles on 2
+    fn test_enum_initial_values_per_target() {
+        use plc_ast::ast::{AstFactory, DataType, LinkageType, UserTypeDeclaration};
+        use plc_ast::literals::AstLiteral;
+
+        let temp_dir = std::env::temp_dir().join("test_enum_initial_values_per_target");
+        std::fs::create_dir_all(&temp_dir).unwrap();
+        let output_path = temp_dir.join("plant.xml");
+
+        let reference = |name: &str| AstFactory::create_member_reference(AstFactory::create_identifier(name, SourceLocation::internal(), 0), None, 0);
+        let variant = |name: &str, value: i128| AstFactory::create_assignment(reference(name), AstFactory::create_literal(AstLiteral::Integer(value), SourceLocation::internal(), 0), 0);
+
+        // TYPE State : (IDLE := 0, RUN := 5); END_TYPE
+        // VAR_GLOBAL state : State := RUN; qualified : State := State#IDLE; END_VAR
+        let mut unit = unit_with_global("plant.st", "state", "State");
+        unit.user_types.push(UserTypeDeclaration {
+            data_type: DataType::EnumType {
+                name: Some(String::from("State")),
+                numeric_type: String::from("INT"),
+                elements: AstFactory::create_expression_list(vec![variant("IDLE", 0), variant("RUN", 5)], SourceLocation::internal(), 0),
             },
-        ];
+            initializer: None,
+            location: unit.global_vars[0].variables[0].location.clone(),
+            scope: None,
+            linkage: LinkageType::Internal,
+        });
+        let mut qualified = unit.global_vars[0].variables[0].clone();
+        qualified.name = String::from("qualified");
+        qualified.initializer = Some(AstFactory::create_cast_statement(reference("State"), reference("IDLE"), &SourceLocation::internal(), 0));
+        unit.global_vars[0].variables[0].initializer = Some(reference("RUN"));
+        unit.global_vars[0].variables.push(qualified);
+        let units = vec![&unit];
 
-        let result = format_enum_initials(variants);
-        assert_eq!(result.len(), 3);
+        let mut params = GenerationParameters::new();
+        params.xml_targets = ["omron", "tia"].iter().map(|it| String::from(*it)).collect();
+        let written = generate_xml_targets(&params, &units, &output_path).unwrap().written_paths;
+        let documents: Vec<String> = written.iter().map(|it| std::fs::read_to_string(it).unwrap()).collect();
+
+        // targets with enums keep the variant name, TIA only knows the number
+        assert!(documents[0].contains(r#"<SimpleValue value="RUN" />"#));
+        assert!(documents[0].contains(r#"<SimpleValue value="IDLE" />"#));
+        assert!(documents[1].contains("<StartValue><![CDATA[5]]></StartValue>"));
+        assert!(documents[1].contains("<StartValue><![CDATA[0]]></StartValue>"));
+
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &units);
+        assert!(csv.contains("state,State,RUN,"));
+        assert!(csv.contains("qualified,State,IDLE,"));
+
+        // references which are no variant of the declared enum are not exported
+        unit.global_vars[0].variables[0].initializer = Some(reference("MAX_SPEED"));
+        let csv = crate::sysmac_csv::format_sysmac_csv(&params, &vec![&unit]);
+        assert!(csv.contains("state,State,,"));
+
+        let _ = std::fs::remove_dir_all(&temp_dir);
     }
-}
\ No newline at end of file
+}
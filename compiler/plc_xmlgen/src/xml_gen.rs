@@ -1,23 +1,322 @@
-use std::{borrow::Cow, collections::{HashSet}, fs::{File, copy}, io::{Error, Read, Seek, SeekFrom}, ops::Range, path::{Path, PathBuf}};
-
+use std::{borrow::Cow, cell::RefCell, collections::{HashSet}, fs::{File, copy}, io::{BufReader, Error, ErrorKind, Write}, ops::Range, path::{Path, PathBuf}};
+
+use super::archive::archive_path;
+use super::call_graph::{call_graph_path, generate_call_graph};
+use super::cross_reference::{collect_referenced_globals, cross_reference_path, generate_cross_references, CrossReferenceFormat};
+use super::eip_tags::{eip_tags_path, generate_eip_tags};
+use super::emission_order::{order_declarations, EmissionOrder};
+use super::fbd_body::fbd_network;
+use super::library::LibraryProperties;
+use super::literal_format::{format_iec_address, BoolCase, RealFormat};
 use super::serializer::*;
+use super::sfc_body::{sfc_chart, sfc_state_variable};
+use super::st_printer::{IdentifierCase, KeywordCase, StPrinter};
+use super::project_model::{generate_project_json, generate_project_yaml, project_json_path, project_yaml_path};
+use super::ignition_tags::{generate_ignition_tags, ignition_tags_path};
+use super::html_docs::{generate_html_docs, html_docs_path};
+use super::identifier_rules::{collect_declared_names, rename_invalid_identifiers, rename_report_path, write_rename_report, IdentifierRules, Rename};
+use super::markdown_docs::{generate_markdown_docs, markdown_docs_path};
+use super::modbus_map::{generate_modbus_map, modbus_map_path, ModbusMapFormat};
+use super::source_comments::{checked_source_text, leading_comment, preceding_attribute_location, preceding_attributes, normalize_line_endings, rewrite_body_comments, source_text, trailing_comment, BodyComments, LineEndings, LoadedSources};
+use super::source_map::{collect_source_map, rename_source_map, source_map_path, write_source_map};
+use super::split_output::{copy_split_output, write_split_output};
+use super::streaming::{stream_project, streaming_obstacle};
+use super::sysmac_csv::{generate_sysmac_csv, sysmac_csv_path};
+use super::tag_list::{collect_tags, declared_type_name, tag_list_path, write_tag_list};
+use super::xml_target::{omron::DEFAULT_OMRON_STRING_LENGTH, TargetRegistry, XmlTarget, RETAIN_ATTRIBUTE};
 
 use plc_ast::ast::*;
+use plc_ast::literals::{AstLiteral, Time};
 
-use plc_source::source_location::{CodeSpan, TextLocation};
+use plc_diagnostics::diagnostics::Diagnostic;
+use plc_source::source_location::{CodeSpan, SourceLocation};
 use xml::{attribute::Attribute, common::XmlVersion, name::Name, namespace::Namespace, writer::XmlEvent, EmitterConfig, EventWriter};
 use chrono::Local;
 
-#[derive(Debug)]
+#[derive(Debug, Clone)]
 pub struct GenerationParameters {
-    pub output_xml_omron: bool    
+    pub output_xml_omron: bool,
+
+    /// The names of the registered [`XmlTarget`]s to generate for
+    pub xml_targets: Vec<String>,
+
+    /// All targets which can be chosen by name
+    pub targets: TargetRegistry,
+
+    /// Whether to package the generated document into an archive together with the archive files, for targets which
+    /// package their documents
+    pub package_archive: bool,
+
+    /// Additional files to be packaged next to the generated document
+    pub archive_files: Vec<PathBuf>,
+
+    /// The properties of the packaged library, for targets which package into a library
+    pub library: LibraryProperties,
+
+    /// Whether to write the globals as a Sysmac Studio variable table next to the generated documents
+    pub sysmac_csv: bool,
+
+    /// Whether to write all variables as a spreadsheet tag list next to the generated documents
+    pub tag_list: bool,
+
+    /// The format of the Modbus register map of the located variables, if one should be written
+    pub modbus_map: Option<ModbusMapFormat>,
+
+    /// Whether to write the EtherNet/IP tag and assembly definitions of the published globals
+    pub eip_tags: bool,
+
+    /// Whether to write the project model (types, globals, POUs and their bodies) as JSON
+    pub project_json: bool,
+
+    /// Whether to write the project model as YAML, for human friendly diffing of the exports
+    pub project_yaml: bool,
+
+    /// Whether to write the POU call graph as a Graphviz DOT file
+    pub call_graph: bool,
+
+    /// Whether to write Markdown documentation of the user types, POUs and globals
+    pub markdown_docs: bool,
+
+    /// Whether to write cross-linked HTML documentation of the user types, POUs and globals
+    pub html_docs: bool,
+
+    /// The OPC server the Ignition tags of the published globals are read through, if they should be written
+    pub ignition_tags: Option<String>,
+
+    /// The format of the cross-reference report of all variables, if one should be written
+    pub cross_reference: Option<CrossReferenceFormat>,
+
+    /// Whether to additionally write every data type and POU as a document of its own, listed in an index next to the
+    /// document
+    pub split_output: bool,
+
+    /// Whether to additionally write a source map next to each document, mapping every line of its bodies back to the
+    /// file and line of the ST source
+    pub source_map: bool,
+
+    /// Whether to write each document while it is generated instead of building it as a whole first, so large projects
+    /// need less memory. Documents which need the whole tree, e.g. for a target rewriting it, are still built as a whole
+    pub streaming: bool,
+
+    /// Whether to start each document with a comment naming the version of the compiler, the source files and the time
+    /// it was generated at
+    pub provenance_comment: bool,
+
+    /// How the documents are indented and their lines are ended
+    pub serializer_options: SerializerOptions,
+
+    /// The length of strings whose declared length is unknown, for targets which require a length
+    pub default_string_length: u32,
+
+    /// Declared enum base types paired with the type to export them as, e.g. `("DINT", "INT")`.
+    /// Base types without an entry are mapped by the target
+    pub enum_base_types: Vec<(String, String)>,
+
+    /// Bit-string types paired with the type to export network-published variables of that type as, e.g.
+    /// `("WORD", "UINT")`. Extends and overrides the table of the target
+    pub bit_string_types: Vec<(String, String)>,
+
+    /// Functions paired with the name calls of them are exported with, e.g. `("SEL", "MUX_2")`. Extends and overrides the
+    /// table of the target, see [`XmlTarget::function_names`]
+    pub function_names: Vec<(String, String)>,
+
+    /// The lower and upper bound of every dimension of the array types, by type name. Filled in by the driver with
+    /// the evaluated constant expressions, so bounds like `ARRAY[0..MAX-1]` are exported as numbers
+    pub array_dimensions: Vec<(String, Vec<(i64, i64)>)>,
+
+    /// How REAL and LREAL literals like initial values are written
+    pub real_format: RealFormat,
+
+    /// The spelling of boolean literals like initial values for every target. Each target chooses its own if not set
+    pub bool_case: Option<BoolCase>,
+
+    /// Whether to transliterate identifiers with non-ASCII characters for every target, e.g. `Größe` to `Groesse`
+    pub ascii_identifiers: bool,
+
+    /// Whether to export only the globals the exported POUs use, instead of every global of the project
+    pub referenced_globals_only: bool,
+
+    /// Whether to export every `VAR_GLOBAL` block as a `<GlobalVars>` group of its own instead of merging the blocks by
+    /// their qualifiers. Blocks named with `{attribute 'group' := 'Motion'}` are grouped either way
+    pub global_groups: bool,
+
+    /// The task the programs without a `{attribute 'task' := 'Fast'}` pragma run in. These programs are not
+    /// instantiated if not set
+    pub default_task: Option<String>,
+
+    /// The name of the single configuration holding the globals of every file. Each file gets a configuration of its
+    /// own if not set, which Sysmac Studio imports as separate controllers
+    pub configuration_name: Option<String>,
+
+    /// Whether to export externally linked POUs as stubs with their declaration but without a body, for targets whose
+    /// libraries do not provide them
+    pub external_stubs: bool,
+
+    /// Whether functions and function blocks have the implicit `EN` input and `ENO` output, for targets which add them
+    /// to every POU
+    pub implicit_en_eno: bool,
+
+    /// Whether a function without a return type the export can name fails the export, instead of being exported as
+    /// returning `BOOL` with a warning
+    pub strict_return_types: bool,
+
+    /// Whether to export bodies made of assignments and function block calls only as FBD networks instead of ST, for
+    /// targets with graphical bodies
+    pub fbd_bodies: bool,
+
+    /// How the comments inside ST bodies are written, since some importers reject nested comments
+    pub body_comments: BodyComments,
+
+    /// The line breaks of ST bodies, to match the IDE instead of the source files
+    pub line_endings: LineEndings,
+
+    /// Whether to write ST bodies from their AST instead of copying them from the source file, so they are laid out the
+    /// same way throughout and do not depend on the file the POU was parsed from
+    pub canonical_bodies: bool,
+
+    /// The case of the keywords of bodies written with [`GenerationParameters::canonical_bodies`]
+    pub keyword_case: KeywordCase,
+
+    /// The case of the identifiers of bodies written with [`GenerationParameters::canonical_bodies`], to match the style
+    /// of the target IDE
+    pub identifier_case: IdentifierCase,
+
+    /// The order the data types, interfaces and POUs are written in, since some importers need a type declared before
+    /// its use
+    pub emission_order: EmissionOrder,
+
+    /// Whether to export one concrete POU per instantiation of a generic POU, e.g. `MAX_OF_INT` for a call of
+    /// `MAX_OF<T>` with `INT` arguments, instead of the generic POU itself
+    pub monomorphize_generics: bool,
+
+    /// Whether to copy the variables of the base into a function block or class extending it, for targets without
+    /// inheritance
+    pub flatten_inheritance: bool,
+
+    /// The names of the function blocks of the project and its libraries, e.g. `TON`. Filled in by the driver from the
+    /// index, so globals declared as an instance of one are told apart from those of a structure
+    pub function_blocks: Vec<String>,
+
+    /// The sources of the project as the driver loaded them. Their text is read in place of the files on disk
+    pub sources: LoadedSources,
+
+    /// The calls of the project's generic POUs. Filled in by the driver with the types the calls were resolved to
+    pub generic_calls: Vec<GenericCall>,
+
+    /// The initial values of variables initialized with a constant expression like `2 * PI`, by qualified name, e.g.
+    /// `gLimit` or `Pou.count`. Filled in by the driver with the folded literals
+    pub folded_initial_values: Vec<(String, AstLiteral)>,
 }
 
 impl GenerationParameters {
     pub fn new() -> Self {
         GenerationParameters { 
-            output_xml_omron: false 
+            output_xml_omron: false,
+            xml_targets: Vec::new(),
+            targets: TargetRegistry::default(),
+            package_archive: false,
+            archive_files: Vec::new(),
+            library: LibraryProperties::default(),
+            sysmac_csv: false,
+            tag_list: false,
+            modbus_map: None,
+            eip_tags: false,
+            project_json: false,
+            project_yaml: false,
+            call_graph: false,
+            markdown_docs: false,
+            html_docs: false,
+            ignition_tags: None,
+            cross_reference: None,
+            split_output: false,
+            source_map: false,
+            streaming: false,
+            provenance_comment: false,
+            serializer_options: SerializerOptions::default(),
+            default_string_length: DEFAULT_OMRON_STRING_LENGTH,
+            enum_base_types: Vec::new(),
+            bit_string_types: Vec::new(),
+            function_names: Vec::new(),
+            array_dimensions: Vec::new(),
+            real_format: RealFormat::default(),
+            bool_case: None,
+            ascii_identifiers: false,
+            referenced_globals_only: false,
+            global_groups: false,
+            default_task: None,
+            configuration_name: None,
+            external_stubs: false,
+            implicit_en_eno: true,
+            strict_return_types: false,
+            fbd_bodies: false,
+            body_comments: BodyComments::Keep,
+            line_endings: LineEndings::Preserve,
+            canonical_bodies: false,
+            keyword_case: KeywordCase::Upper,
+            identifier_case: IdentifierCase::AsWritten,
+            emission_order: EmissionOrder::Source,
+            monomorphize_generics: false,
+            flatten_inheritance: false,
+            function_blocks: Vec::new(),
+            sources: LoadedSources::default(),
+            generic_calls: Vec::new(),
+            folded_initial_values: Vec::new(),
+        }
+    }
+
+    /// The configured export type of the given enum base type, if any
+    pub fn enum_base_type(&self, numeric_type: &str) -> Option<&str> {
+        self.enum_base_types.iter().find(|(declared, _)| declared.eq_ignore_ascii_case(numeric_type)).map(|(_, exported)| exported.as_str())
+    }
+
+    /// The type a network-published variable of the given bit-string type is exported as, if it is mapped
+    pub fn published_bit_string_type(&self, target: &dyn XmlTarget, type_name: &str) -> Option<String> {
+        let configured = self.bit_string_types.iter().find(|(declared, _)| declared.eq_ignore_ascii_case(type_name)).map(|(_, exported)| exported.as_str());
+        let targeted = || target.bit_string_types().iter().find(|(declared, _)| declared.eq_ignore_ascii_case(type_name)).map(|(_, exported)| *exported);
+        configured.or_else(targeted).map(String::from)
+    }
+
+    /// The name a call of the given function is exported with: `None` if it keeps its name and `Some(None)` if the
+    /// target has no counterpart of it
+    pub fn exported_function_name(&self, target: &dyn XmlTarget, function: &str) -> Option<Option<String>> {
+        let configured = self.function_names.iter().find(|(declared, _)| declared.eq_ignore_ascii_case(function)).map(|(_, exported)| Some(exported.clone()));
+        let targeted = || target.function_names().iter().find(|(declared, _)| declared.eq_ignore_ascii_case(function)).map(|(_, exported)| exported.map(String::from));
+        configured.or_else(targeted)
+    }
+
+    /// The evaluated bounds of the given array type, if the driver could evaluate them
+    pub fn array_dimensions(&self, type_name: &str) -> Option<&[(i64, i64)]> {
+        self.array_dimensions.iter().find(|(name, _)| name.eq_ignore_ascii_case(type_name)).map(|(_, dimensions)| dimensions.as_slice())
+    }
+
+    /// Whether the given type is a function block, i.e. variables of the type are instances of it
+    pub fn is_function_block(&self, type_name: &str) -> bool {
+        self.function_blocks.iter().any(|it| it.eq_ignore_ascii_case(type_name))
+    }
+
+    /// The folded initial value of the variable with the given qualified name, if the driver could fold it
+    pub fn folded_initial_value(&self, qualified_name: &str) -> Option<&AstLiteral> {
+        self.folded_initial_values.iter().find(|(name, _)| name.eq_ignore_ascii_case(qualified_name)).map(|(_, literal)| literal)
+    }
+
+    /// Whether any output besides the target documents was chosen. These can be generated without a target
+    pub fn writes_side_outputs(&self) -> bool {
+        self.sysmac_csv || self.tag_list || self.modbus_map.is_some() || self.eip_tags || self.project_json || self.project_yaml || self.call_graph || self.markdown_docs || self.html_docs || self.ignition_tags.is_some() || self.cross_reference.is_some()
+    }
+
+    /// The names of the chosen targets, `--xml-omron` being a shorthand for the omron target
+    pub fn target_names(&self) -> Vec<&str> {
+        if self.xml_targets.is_empty() && self.output_xml_omron {
+            return vec!["omron"];
         }
+
+        let mut names: Vec<&str> = Vec::with_capacity(self.xml_targets.len());
+        for name in &self.xml_targets {
+            if !names.iter().any(|it| it.eq_ignore_ascii_case(name)) {
+                names.push(name.as_str());
+            }
+        }
+        names
     }
 }
 
@@ -39,7 +338,7 @@ pub fn get_omron_template() -> Node {
         .attribute_str("xmlns:smcext", "https://www.ia.omron.com/Smc")
         .attribute_str("xsi:schemaLocation", OMRON_SCHEMA)
         .attribute_str("schemaVersion", "1")
-        .attribute_str("xmlns", "www.iec.ch/public/TC65SC65BWG7TF10")
+        .attribute_str("xmlns", IEC_NAMESPACE)
             .child(&SFileHeader::new()
                 .attribute_str("companyName", "OMRON Corporation")
                 .attribute_str("productName", "Sysmac Studio")
@@ -52,607 +351,2442 @@ pub fn get_omron_template() -> Node {
             .child(&SInstances::new())
 }
 
-pub const OMRON_SCHEMA: &'static str = "https://www.ia.omron.com/Smc IEC61131_10_Ed1_0_SmcExt1_0_Spc1_0.xsd";
+pub const OMRON_SCHEMA: &str = "https://www.ia.omron.com/Smc IEC61131_10_Ed1_0_SmcExt1_0_Spc1_0.xsd";
 
-pub fn parse_project_into_nodetree(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, schema_path: &'static str, output_path: &PathBuf, mut output_root: Node) -> Result<(), Error> {
-    let mut param_order: HashSet<(String, usize)> = HashSet::new(); //the unique combination of (ParameterName, orderWithinParamSet) for the entire generation.
-    let borrowed_order = &mut param_order;
+/// <?xml version=\"1.0\"?>
+/// <Project xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\" schemaVersion=\"1\" xmlns=\"www.iec.ch/public/TC65SC65BWG7TF10\">
+///     <FileHeader companyName=\"\" productName=\"rusty\" productVersion=\"\" />
+///     <ContentHeader name=\"Sample\" creationDateTime="">
+///     </ContentHeader>
+///     <Types>
+///         <GlobalNamespace>
+///         </GlobalNamespace>
+///     </Types>
+///     <Instances>
+///     </Instances>
+/// </Project>
+pub fn get_iec_template() -> Node {
+    Node::new_str("Project")
+        .attribute_str("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance")
+        .attribute_str("schemaVersion", "1")
+        .attribute_str("xmlns", IEC_NAMESPACE)
+            .child(&SFileHeader::new()
+                .attribute_str("companyName", "")
+                .attribute_str("productName", "rusty")
+                .attribute_str("productVersion", env!("CARGO_PKG_VERSION")))
+            .child(&SContentHeader::new()
+                .attribute_str("name", "Sample")
+                .attribute(String::from("creationDateTime"), Local::now().to_rfc3339()))
+            .child(&STypes::new()
+                .child(&SGlobalNamespace::new()))
+            .child(&SInstances::new())
+}
 
-    for a in 0..units.len() {
-        let current_unit = units[a];
-        let unit_name = current_unit.file.get_name().unwrap_or("");
+pub const IEC_NAMESPACE: &str = "www.iec.ch/public/TC65SC65BWG7TF10";
 
-        if unit_name.to_lowercase().ends_with(".st") == false {
-            continue; //skip this unit since it is an internally generated file, not the users source code
-        }
-        let borrowed_root = &mut output_root;
+/// The files written by [`generate_xml_targets`] and the warnings about declarations it could not export as declared
+#[derive(Debug)]
+pub struct GeneratedXml {
+    pub written_paths: Vec<PathBuf>,
+    pub diagnostics: Vec<Diagnostic>,
+}
 
-        let _ = generate_globals(generation_parameters, current_unit, unit_name, schema_path, borrowed_order, borrowed_root);
-        let _ = generate_custom_types(generation_parameters, current_unit, borrowed_root);
-        let _ = generate_pous(generation_parameters, current_unit, schema_path, borrowed_order, borrowed_root);
-    }
-    write_xml_file(output_path, output_root)?;
-    Ok(())
+/// Generates the document of every selected target from the same compilation units.
+///
+/// A single target is written to `output_path`. When several targets are selected, each is written to its
+/// own [`target_output_path`] so the outputs don't overwrite each other. The side outputs, like the Sysmac
+/// variable table, are written next to `output_path`. Returns the written files together with the diagnostics
+/// reported while exporting.
+pub fn generate_xml_targets(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, output_path: &PathBuf) -> Result<GeneratedXml, Error> {
+    let mut diagnostics = Vec::new();
+    let written_paths = write_xml_targets(generation_parameters, units, output_path, &mut diagnostics)?;
+    Ok(GeneratedXml { written_paths, diagnostics })
 }
 
-pub fn generate_globals(generation_parameters: &GenerationParameters, current_unit: &CompilationUnit, unit_name: &str, schema_path: &'static str, preused_order: &mut HashSet<(String, usize)>, output_root: &mut Node) -> Result<(), ()> {
-    let maybe_globals_root: Option<&mut Node> = output_root.children.iter_mut().find(|a| a.name == INSTANCES);
-    let globals_root = maybe_globals_root.ok_or(())?;
+fn write_xml_targets(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, output_path: &PathBuf, diagnostics: &mut Vec<Diagnostic>) -> Result<Vec<PathBuf>, Error> {
+    check_return_types(generation_parameters, units, diagnostics)?;
+    check_body_locations(&generation_parameters.sources, units, diagnostics);
+    check_network_publish_attributes(&generation_parameters.sources, units, diagnostics);
 
-    //create the 4 destinations for <GlobalVars>
-    let mut constant_retain_globals = SGlobalVars::new()
-        .attribute_str("constant", "true")
-        .attribute_str("retain", "true");
+    let target_names = generation_parameters.target_names();
+    let mut written_paths = Vec::with_capacity(target_names.len());
 
-    let mut constant_globals = SGlobalVars::new()
-        .attribute_str("constant", "true");
+    for name in &target_names {
+        let target = generation_parameters.targets.get(name).ok_or_else(|| {
+            Error::new(ErrorKind::NotFound, format!("Unknown XML target '{name}'. Registered targets are: {}", generation_parameters.targets.names().join(", ")))
+        })?;
 
-    let mut retain_globals = SGlobalVars::new()
-        .attribute_str("retain", "true");
+        let target_path = if target_names.len() == 1 {
+            output_path.clone()
+        }
 
-    let mut normal_globals = SGlobalVars::new();
+        else {
+            target_output_path(output_path, name)
+        };
 
-    //parse the unit into nodes
-    for a in 0..current_unit.global_vars.len() {
-        let current_global = &current_unit.global_vars[a];
-        let mut parsed_variables: Vec<Box<dyn IntoNode>> = Vec::with_capacity(current_global.variables.len());
+        let renames = parse_project_into_nodetree(generation_parameters, target.as_ref(), units, &target_path, diagnostics)?;
 
-        if current_global.linkage == LinkageType::External { //Don't include globals that are external
-            continue;
+        if generation_parameters.package_archive {
+            target.package(generation_parameters, &target_path)?;
         }
 
-        for b in 0..current_global.variables.len() {
-            let current_variable = &current_global.variables[b];
+        let report_path = rename_report_path(&target_path);
+        let map_path = source_map_path(&target_path);
+        written_paths.push(target_path);
 
-            if current_variable.location.span == CodeSpan::None {
-                continue; //discard compiler interally generated variables
-            }
+        if !renames.is_empty() {
+            write_rename_report(&renames, &report_path)?;
+            written_paths.push(report_path);
+        }
 
-            let network_publish = match current_global.kind {
-                VariableBlockType::Global => String::from("DoNotPublish"),
-                _ => {
-                    continue; //skip non global variables
-                }
-            };            
+        if generation_parameters.source_map {
+            written_paths.push(map_path);
+        }
+    }
 
-            let cloned_unitname = String::from(unit_name);
+    if generation_parameters.sysmac_csv {
+        let csv_path = sysmac_csv_path(output_path);
+        generate_sysmac_csv(generation_parameters, units, &csv_path)?;
+        written_paths.push(csv_path);
+    }
 
-            let maybe_newvar = generate_variable_element(current_variable, generation_parameters, &cloned_unitname, schema_path, network_publish, preused_order, b, false);
+    if generation_parameters.tag_list {
+        let xlsx_path = tag_list_path(output_path);
+        write_tag_list(&collect_tags(&generation_parameters.sources, units), &xlsx_path)?;
+        written_paths.push(xlsx_path);
+    }
 
-            let new_var = match maybe_newvar {
-                Some(a) => a,
-                None => { continue; }, //no variable element created so skip it
-            };
-            parsed_variables.push(Box::new(new_var));
-        }
+    if let Some(format) = generation_parameters.modbus_map {
+        let map_path = modbus_map_path(output_path, format);
+        generate_modbus_map(&generation_parameters.sources, units, &map_path, format)?;
+        written_paths.push(map_path);
+    }
 
-        //add globals to the correct element
-        if current_global.constant && current_global.retain {
-            constant_retain_globals = constant_retain_globals.children(parsed_variables);
-        }
+    if generation_parameters.eip_tags {
+        let eip_path = eip_tags_path(output_path);
+        generate_eip_tags(&generation_parameters.sources, units, &eip_path)?;
+        written_paths.push(eip_path);
+    }
 
-        else if current_global.constant {
-            constant_globals = constant_globals.children(parsed_variables);
-        }
+    if generation_parameters.project_json {
+        let json_path = project_json_path(output_path);
+        generate_project_json(&generation_parameters.sources, units, &json_path)?;
+        written_paths.push(json_path);
+    }
 
-        else if current_global.retain {
-            retain_globals = retain_globals.children(parsed_variables);
-        }
+    if generation_parameters.project_yaml {
+        let yaml_path = project_yaml_path(output_path);
+        generate_project_yaml(&generation_parameters.sources, units, &yaml_path)?;
+        written_paths.push(yaml_path);
+    }
 
-        else {
-            normal_globals = normal_globals.children(parsed_variables);
-        }
+    if generation_parameters.call_graph {
+        let dot_path = call_graph_path(output_path);
+        generate_call_graph(units, &dot_path)?;
+        written_paths.push(dot_path);
     }
-    
-    //relinquish copies of the nodes into the tree
-    let name_label = String::from("name");
-    let resources_name = format!("{}_{}", unit_name, RESOURCE);
-    
-    let resource_node = SResource::new()
-        .attribute(name_label.clone(), resources_name)
-        .attribute_str("resourceTypeName", "")
-        .child(&constant_retain_globals)
-        .child(&constant_globals)
-        .child(&retain_globals)
-        .child(&normal_globals);
 
-    let config_name = format!("{}_{}", unit_name, CONFIGURATION);
+    if generation_parameters.markdown_docs {
+        let markdown_path = markdown_docs_path(output_path);
+        generate_markdown_docs(&generation_parameters.sources, units, &markdown_path)?;
+        written_paths.push(markdown_path);
+    }
 
-    let configuration_node = SConfiguration::new()
-        .attribute(name_label, config_name)
-        .child(&resource_node);
+    if generation_parameters.html_docs {
+        let html_path = html_docs_path(output_path);
+        generate_html_docs(&generation_parameters.sources, units, &html_path)?;
+        written_paths.push(html_path);
+    }
 
-    globals_root.child_borrowed(&configuration_node); //need to borrow a mut Node so I don't break the root nodes reference to the globals node
-    return Ok(());
+    if let Some(opc_server) = &generation_parameters.ignition_tags {
+        let tags_path = ignition_tags_path(output_path);
+        generate_ignition_tags(&generation_parameters.sources, units, &tags_path, opc_server)?;
+        written_paths.push(tags_path);
+    }
+
+    if let Some(format) = generation_parameters.cross_reference {
+        let xref_path = cross_reference_path(output_path, format);
+        generate_cross_references(units, &xref_path, format)?;
+        written_paths.push(xref_path);
+    }
+    Ok(written_paths)
 }
 
-pub fn generate_custom_types(generation_parameters: &GenerationParameters, current_unit: &CompilationUnit, output_root: &mut Node) -> Result<(), ()> {
-    let maybe_types_root: Option<&mut Node> = output_root.children.iter_mut().find(|a| a.name == TYPES);
-    let types_root: &mut Node = maybe_types_root.ok_or(())?;    
-    let maybe_global_root: Option<&mut Node> = types_root.children.iter_mut().find(|a| a.name == GLOBAL_NAMESPACE);
-    let global_root: &mut Node = maybe_global_root.ok_or(())?;
+/// Every side output which may be written next to the base path
+fn side_output_paths(output_path: &Path) -> Vec<PathBuf> {
+    vec![
+        sysmac_csv_path(output_path),
+        tag_list_path(output_path),
+        modbus_map_path(output_path, ModbusMapFormat::Csv),
+        modbus_map_path(output_path, ModbusMapFormat::Json),
+        eip_tags_path(output_path),
+        project_json_path(output_path),
+        project_yaml_path(output_path),
+        call_graph_path(output_path),
+        markdown_docs_path(output_path),
+        html_docs_path(output_path),
+        ignition_tags_path(output_path),
+        cross_reference_path(output_path, CrossReferenceFormat::Csv),
+        cross_reference_path(output_path, CrossReferenceFormat::Json),
+    ]
+}
 
-    for a in 0..current_unit.user_types.len() {
-        let current_usertype = &current_unit.user_types[a];
+/// The file of a single target when several targets are generated at once, e.g. `out.xml` becomes `out.omron.xml`
+pub fn target_output_path(output_path: &Path, target_name: &str) -> PathBuf {
+    let stem = output_path.file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
 
-        if current_usertype.location.span == CodeSpan::None {
-            continue; //discard internally generated types
-        }
+    let file_name = match output_path.extension() {
+        Some(extension) => format!("{stem}.{target_name}.{}", extension.to_string_lossy()),
+        None => format!("{stem}.{target_name}"),
+    };
+    output_path.with_file_name(file_name)
+}
 
-        if current_usertype.linkage == LinkageType::External {
-            continue; //discard externally defined types; same as externally defined functions
+/// Writes the document of the target, adding the warnings about what it could not export as declared to the given
+/// diagnostics. Returns the identifiers which had to be renamed since the target IDE does not accept them
+pub fn parse_project_into_nodetree(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, units: &Vec<&CompilationUnit>, output_path: &PathBuf, diagnostics: &mut Vec<Diagnostic>) -> Result<Vec<Rename>, Error> {
+    check_externals(units)?;
+    check_target_compatibility(target, units)?;
+
+    if generation_parameters.streaming {
+        match streaming_obstacle(generation_parameters, target) {
+            Some(reason) => diagnostics.push(Diagnostic::new(format!("The document of the {} target is built as a whole, since {reason}", target.name())).with_error_code("E129")),
+            None => {
+                return stream_project(generation_parameters, target, units, output_path, diagnostics);
+            }
         }
+    }
 
-        let customtype_maybe: Option<SDataTypeDecl> = match &current_usertype.data_type {
-            DataType::StructType { name, variables } => { //STRUCT
-                let unwrapped_name = match name {
-                    Some(a) => a.clone(),
-                    None => { continue; }, //every structure must have a name
-                };
+    let mut output_root = target.template();
+    let enums = ProjectEnums::collect(units);
+    let inherited = match generation_parameters.flatten_inheritance && !target.supports_inheritance() {
+        true => InheritedVariables::collect(units),
+        false => InheritedVariables::default(),
+    };
+    let referenced_globals = generation_parameters.referenced_globals_only.then(|| collect_referenced_globals(units));
 
-                let mut spec_node = SUserDefinedTypeSpec::new()
-                    .attribute_str("xsi:type", "StructTypeSpec");
+    for a in 0..units.len() {
+        let current_unit = units[a];
+        let unit_name = current_unit.file.get_name().unwrap_or("");
 
-                for b in 0..variables.len() {
-                    let current_variable = &variables[b];
-                    let maybe_typename = current_variable.data_type_declaration.get_name();                    
+        if unit_name.to_lowercase().ends_with(".st") == false {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+        let borrowed_root = &mut output_root;
 
-                    let mut typename = match maybe_typename {
-                        Some(a) => a,
-                        None => { continue; }, //every variable must have a type
-                    };
+        generate_globals(generation_parameters, target, &enums, current_unit, unit_name, referenced_globals.as_deref(), borrowed_root)?;
+        generate_custom_types(generation_parameters, target, &enums, current_unit, borrowed_root)?;
+        generate_interfaces(generation_parameters, target, &enums, current_unit, borrowed_root)?;
+        generate_pous(generation_parameters, target, &enums, &inherited, current_unit, borrowed_root, diagnostics)?;
+    }
+    let rules = IdentifierRules {
+        reserved_words: target.reserved_words(),
+        max_length: target.max_identifier_length(),
+        ascii_only: generation_parameters.ascii_identifiers,
+    };
+    let mut source_map = generation_parameters.source_map.then(|| collect_source_map(generation_parameters, units, &output_root));
+    let renames = rename_invalid_identifiers(&mut output_root, &rules);
+    order_declarations(&mut output_root, generation_parameters.emission_order);
 
-                    if typename.to_lowercase().contains("string") && generation_parameters.output_xml_omron { //string[256] produces a type of __global_testString. This is not a valid type for Omron Sysmac Studio
-                        typename = "String[1986]";
-                    }
+    if generation_parameters.split_output {
+        write_split_output(target, &generation_parameters.serializer_options, &output_root, output_path)?;
+    }
+    if let Some(entries) = &mut source_map {
+        rename_source_map(entries, &renames);
+        write_source_map(entries, &source_map_path(output_path))?;
+    }
+    target.post_process(&mut output_root);
+    add_provenance_comment(generation_parameters, units, &mut output_root);
+    write_formatted_xml_file(output_path, &target.processing_instructions(), &generation_parameters.serializer_options, output_root)?;
+    Ok(renames)
+}
 
-                    let typename_node = STypeName::new()
-                        .content(String::from(typename));
+/// Puts a comment naming where the document came from before the other children of the root, if the parameters ask
+/// for one, e.g. `<!-- Generated by rusty 0.2.0 from main.st, motor.st on 2024-05-02T10:00:00+02:00 -->`
+pub(crate) fn add_provenance_comment(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, root: &mut Node) {
+    if !generation_parameters.provenance_comment {
+        return;
+    }
 
-                    let type_node = SType::new()
-                        .child(&typename_node);
+    let files: Vec<&str> = units.iter()
+        .filter_map(|it| it.file.get_name())
+        .filter(|it| it.to_lowercase().ends_with(".st")) //skip internally generated units
+        .collect();
+    let text = format!(" Generated by rusty {} from {} on {} ", env!("CARGO_PKG_VERSION"), files.join(", "), Local::now().to_rfc3339());
+    root.children.insert(0, Node::comment(&text));
+}
 
-                    let member_node = SMember::new()
-                        .attribute(String::from("name"), current_variable.name.clone())
-                        .child(&type_node);
+/// A call of a generic POU with the types its generic parameters were resolved to
+#[derive(Debug, Clone, PartialEq)]
+pub struct GenericCall {
+    /// The called generic POU, e.g. `MAX_OF`
+    pub generic: String,
+    /// The types in the order of the generic parameters, e.g. `[INT]`
+    pub types: Vec<String>,
+    /// The name of the generic POU at the call, replaced by the name of the instance
+    pub location: SourceLocation,
+}
 
-                    spec_node = spec_node.child(&member_node);
-                }
+/// An export error caused by a declaration, carrying its location so it can be reported at the declaration
+#[derive(Debug)]
+pub struct DeclarationError {
+    pub message: String,
+    pub location: SourceLocation,
+}
 
-                if spec_node.inner().children.len() == 0 { //structs must have <Member> elements, otherwise delete it
-                    None
-                }
+impl DeclarationError {
+    pub fn new_error(kind: ErrorKind, message: String, location: &SourceLocation) -> Error {
+        Error::new(kind, DeclarationError { message, location: location.clone() })
+    }
 
-                else {
-                    let decl_node1 = SDataTypeDecl::new()
-                        .attribute(String::from("name"), unwrapped_name)
-                        .child(&spec_node);
+    /// The location of the declaration an export error was caused by, if it was caused by one
+    pub fn location_of(error: &Error) -> Option<&SourceLocation> {
+        error.get_ref()?.downcast_ref::<DeclarationError>().map(|it| &it.location)
+    }
+}
 
-                    Some(decl_node1)
-                }
-            },
-            DataType::EnumType { name, numeric_type, elements } => { //ENUM
-                let unwrapped_enum_type = match name {
-                    Some(a) => a.clone(),
-                    None => { continue; }, //every structure must have a name
-                };
+impl std::fmt::Display for DeclarationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
 
-                let enumerators = match &elements.stmt {
-                    AstStatement::ExpressionList(ast_nodes) => ast_nodes.iter().map(|a| {
-                        match &a.stmt {
-                            AstStatement::Assignment(assignment) => parse_enum_expression(assignment),
-                            other => panic!("Expected Assignment. Instead got: {:?}", other)
-                        }
-                    }).collect(),
+impl std::error::Error for DeclarationError {}
 
-                    AstStatement::Assignment(assignment) => vec![parse_enum_expression(assignment)],
-                    other => panic!("Expected ExpressionList or Assignment. Instead got: {:?}", other)
-                };
+/// Rejects `VAR_EXTERNAL` declarations without a global of the same name and type, since the target IDE only notices
+/// them when importing the document
+fn check_externals(units: &Vec<&CompilationUnit>) -> Result<(), Error> {
+    //globals declared elsewhere, e.g. in a library, are globals all the same
+    let globals: Vec<&Variable> = units.iter()
+        .flat_map(|it| &it.global_vars)
+        .filter(|it| it.kind == VariableBlockType::Global)
+        .flat_map(|it| &it.variables)
+        .collect();
 
-                let base_node = SBaseType::new()
-                    .content(numeric_type.clone());
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
 
-                let formatted = format_enum_initials(enumerators);
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
 
-                let spec_node = SUserDefinedTypeSpec::new()
-                    .attribute_str("xsi:type", "EnumTypeWithNamedValueSpec")                    
-                    .children(formatted)
-                    .child(&base_node); //<BaseType> element must be declared below all the <Member> elements, apparently
+        for current_pou in current_unit.pous.iter().filter(|it| it.linkage != LinkageType::External) {
+            let externals = current_pou.variable_blocks.iter().filter(|it| it.kind == VariableBlockType::External).flat_map(|it| &it.variables);
 
-                let decl_node2 = SDataTypeDecl::new()
-                    .attribute(String::from("name"), unwrapped_enum_type)
-                    .child(&spec_node);
+            for current_external in externals {
+                if current_external.location.span == CodeSpan::None {
+                    continue; //discard compiler interally generated variables
+                }
 
-                Some(decl_node2)
-            },
-            _ => None                                
-        };
+                let Some(global) = globals.iter().find(|it| it.name.eq_ignore_ascii_case(&current_external.name)) else {
+                    return Err(DeclarationError::new_error(ErrorKind::NotFound, format!(
+                        "'{}' declares the external '{}' but there is no global of that name", current_pou.name, current_external.name
+                    ), &current_external.location));
+                };
 
-        if let Some(unwrapped_ready) = customtype_maybe {
-            global_root.child_borrowed(&unwrapped_ready);
-        }        
+                //inline types like arrays have no name to compare
+                let external_type = declared_type_name(&current_external.data_type_declaration);
+                let global_type = declared_type_name(&global.data_type_declaration);
+
+                if let (Some(external_type), Some(global_type)) = (external_type, global_type) && !external_type.eq_ignore_ascii_case(&global_type) {
+                    return Err(DeclarationError::new_error(ErrorKind::InvalidInput, format!(
+                        "'{}' declares the external '{}' as {external_type} but the global is {global_type}", current_pou.name, current_external.name
+                    ), &current_external.location));
+                }
+            }
+        }
     }
     Ok(())
 }
 
-fn parse_enum_expression(input: &Assignment) -> NameAndInitialValue {
-    let enum_variant_name = match &input.left.stmt {
-        AstStatement::ReferenceExpr(reference_exp) => {
-            match &reference_exp.access {
-                ReferenceAccess::Member(member_exp) => {
-                    match &member_exp.stmt {
+/// Reports the functions without a return type the export can name, since they are exported as returning `BOOL`. With
+/// [`GenerationParameters::strict_return_types`] the first one fails the export at its name, otherwise each is warned about
+fn check_return_types(generation_parameters: &GenerationParameters, units: &Vec<&CompilationUnit>, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        let allow_list = has_pou_allow_list(&generation_parameters.sources, current_unit);
+        let functions = current_unit.pous.iter()
+            .filter(|it| it.kind == PouType::Function && it.location.span != CodeSpan::None)
+            .filter(|it| it.linkage != LinkageType::External || generation_parameters.external_stubs)
+            .filter(|it| is_pou_exported(&generation_parameters.sources, it, current_unit, allow_list));
+
+        for current_function in functions {
+            if current_function.return_type.as_ref().and_then(declared_type_name).is_some() {
+                continue;
+            }
+
+            let message = format!("The function '{}' in {unit_name} has no return type the export can name", current_function.name);
+            if generation_parameters.strict_return_types {
+                return Err(DeclarationError::new_error(ErrorKind::InvalidInput, message, &current_function.name_location));
+            }
+            diagnostics.push(Diagnostic::new(format!("{message}, it is exported as returning BOOL"))
+                .with_error_code("E125")
+                .with_location(&current_function.name_location));
+        }
+    }
+    Ok(())
+}
+
+/// Reports the bodies whose location covers no text of their source, e.g. since the file changed after it was compiled.
+/// Every output reading the text leaves it out, so each body is reported once here instead of by each of them
+fn check_body_locations(sources: &LoadedSources, units: &Vec<&CompilationUnit>, diagnostics: &mut Vec<Diagnostic>) {
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_impl in current_unit.implementations.iter().filter(|it| it.linkage != LinkageType::External) {
+            if let Err(diagnostic) = checked_source_text(sources, &current_impl.location) {
+                diagnostics.push(diagnostic);
+            }
+        }
+    }
+}
+
+/// Warns about `network_publish` attributes with a value that is no network publish mode, since those globals are not published
+fn check_network_publish_attributes(sources: &LoadedSources, units: &Vec<&CompilationUnit>, diagnostics: &mut Vec<Diagnostic>) {
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        for current_block in current_unit.global_vars.iter().filter(|it| it.linkage != LinkageType::External) {
+            let declarations = std::iter::once((&current_block.location, "VAR_GLOBAL"))
+                .chain(current_block.variables.iter().map(|it| (&it.location, it.name.as_str())));
+
+            for (location, name) in declarations {
+                let Some(value) = preceding_attributes(sources, location).into_iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(NETWORK_PUBLISH_ATTRIBUTE))
+                    .map(|(_, value)| value) else {
+                    continue;
+                };
+
+                if parse_network_publish_mode(&value).is_some() {
+                    continue;
+                }
+
+                let attribute_location = preceding_attribute_location(sources, location, NETWORK_PUBLISH_ATTRIBUTE).unwrap_or_else(|| location.clone());
+                diagnostics.push(
+                    Diagnostic::new(format!("'{value}' is no network publish mode, so '{name}' is not published. Accepted are PublishOnly, Input, Output and DoNotPublish"))
+                        .with_error_code("E130")
+                        .with_location(&attribute_location),
+                );
+            }
+        }
+    }
+}
+
+/// Rejects declarations the target IDE has no equivalent for, instead of exporting a document it cannot import
+fn check_target_compatibility(target: &dyn XmlTarget, units: &Vec<&CompilationUnit>) -> Result<(), Error> {
+    for current_unit in units {
+        let unit_name = current_unit.file.get_name().unwrap_or("");
+
+        if !unit_name.to_lowercase().ends_with(".st") {
+            continue; //skip this unit since it is an internally generated file, not the users source code
+        }
+
+        //external declarations are not exported, so they may use anything
+        let members = current_unit.user_types.iter().filter(|it| it.linkage != LinkageType::External).flat_map(|it| match &it.data_type {
+            DataType::StructType { variables, .. } => variables.as_slice(),
+            _ => &[],
+        });
+
+        let variables = current_unit.global_vars.iter()
+            .filter(|it| it.linkage != LinkageType::External)
+            .chain(current_unit.pous.iter().filter(|it| it.linkage != LinkageType::External).flat_map(|it| &it.variable_blocks))
+            .flat_map(|it| &it.variables)
+            .chain(members);
+
+        for current_variable in variables {
+            if current_variable.location.span == CodeSpan::None {
+                continue; //discard compiler interally generated variables
+            }
+
+            if let Some(type_name) = declared_type_name(&current_variable.data_type_declaration) && type_name.to_uppercase().starts_with("WSTRING") && !target.supports_wide_strings() {
+                return Err(Error::new(ErrorKind::Unsupported, format!(
+                    "The {} target has no wide strings, but '{}' in {unit_name} is declared as {type_name}. Declare it as STRING instead",
+                    target.name(), current_variable.name
+                )));
+            }
+
+            if let Some(AstStatement::HardwareAccess(hardware_access)) = current_variable.address.as_ref().map(|it| &it.stmt) {
+                check_address(target, &current_variable.name, unit_name, hardware_access)?;
+            }
+        }
+
+        for config_variable in &current_unit.var_config {
+            if let AstStatement::HardwareAccess(hardware_access) = &config_variable.address.stmt {
+                check_address(target, &config_variable.reference.as_string(), unit_name, hardware_access)?;
+            }
+        }
+
+        for access_variable in &current_unit.var_access {
+            if let AstStatement::HardwareAccess(hardware_access) = &access_variable.path.stmt {
+                check_address(target, &access_variable.name, unit_name, hardware_access)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn check_address(target: &dyn XmlTarget, variable_name: &str, unit_name: &str, hardware_access: &HardwareAccess) -> Result<(), Error> {
+    target.format_address(hardware_access).map(|_| ()).map_err(|reason| Error::new(ErrorKind::Unsupported, format!(
+        "The {} target cannot place '{variable_name}' in {unit_name} at {}: {reason}",
+        target.name(), format_iec_address(hardware_access)
+    )))
+}
+
+/// The element of the document the declarations of a unit are generated into, e.g. [`INSTANCES`] for the globals
+fn document_element<'a>(output_root: &'a mut Node, path: &str) -> Result<&'a mut Node, Error> {
+    output_root.find_mut(path).ok_or_else(|| Error::new(ErrorKind::NotFound, format!("The XML document has no '{path}' element to generate the declarations into")))
+}
+
+/// referenced_globals - the only globals to export, all of them if not given
+pub fn generate_globals(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, current_unit: &CompilationUnit, unit_name: &str, referenced_globals: Option<&[String]>, output_root: &mut Node) -> Result<(), Error> {
+    let globals_root = document_element(output_root, INSTANCES)?;
+
+    //create the destinations for <GlobalVars>, the usual 4 always and one more for every other combination of
+    //retention attributes the target writes, e.g. persistent
+    let mut destinations: Vec<BlockVariables> = vec![
+        (true, vec![RETAIN_ATTRIBUTE], Vec::new()),
+        (true, Vec::new(), Vec::new()),
+        (false, vec![RETAIN_ATTRIBUTE], Vec::new()),
+        (false, Vec::new(), Vec::new()),
+    ];
+
+    //the named <GlobalVars> groups, each with the same destinations
+    let mut groups: Vec<(String, Vec<BlockVariables>)> = Vec::new();
+    let mut unnamed_groups = 0;
+
+    let allow_list = has_export_allow_list(&generation_parameters.sources, current_unit.global_vars.iter().map(|it| &it.location));
+
+    //parse the unit into nodes
+    for a in 0..current_unit.global_vars.len() {
+        let current_global = &current_unit.global_vars[a];
+        let mut parsed_variables: Vec<Box<dyn IntoNode>> = Vec::with_capacity(current_global.variables.len());
+
+        if current_global.linkage == LinkageType::External { //Don't include globals that are external
+            continue;
+        }
+
+        if !is_exported(&generation_parameters.sources, &current_global.location, allow_list) {
+            continue;
+        }
+
+        for b in 0..current_global.variables.len() {
+            let current_variable = &current_global.variables[b];
+
+            if current_variable.location.span == CodeSpan::None {
+                continue; //discard compiler interally generated variables
+            }
+
+            if referenced_globals.is_some_and(|it| !it.contains(&current_variable.name)) {
+                continue; //no exported POU uses it
+            }
+
+            let network_publish = match current_global.kind {
+                VariableBlockType::Global => variable_network_publish(&generation_parameters.sources, current_global, current_variable).to_string(),
+                _ => {
+                    continue; //skip non global variables
+                }
+            };            
+
+            let is_instance = declared_type_name(&current_variable.data_type_declaration).is_some_and(|it| generation_parameters.is_function_block(&it));
+            let maybe_newvar = match is_instance {
+                true => generate_instance_element(current_variable, generation_parameters, target),
+                false => generate_variable_element(current_variable, generation_parameters, target, enums, None, network_publish, None),
+            };
+
+            let new_var = match maybe_newvar {
+                Some(a) => a,
+                None => { continue; }, //no variable element created so skip it
+            };
+            parsed_variables.push(Box::new(new_var));
+        }
+
+        if parsed_variables.is_empty() {
+            continue;
+        }
+
+        let group_name = global_group(&generation_parameters.sources, current_global).or_else(|| generation_parameters.global_groups.then(|| {
+            unnamed_groups += 1;
+            format!("{}_{unnamed_groups}", Path::new(unit_name).file_stem().map(|it| it.to_string_lossy()).unwrap_or_default())
+        }));
+
+        let group_destinations = match group_name {
+            Some(group_name) => match groups.iter().position(|(name, _)| name.eq_ignore_ascii_case(&group_name)) {
+                Some(position) => &mut groups[position].1,
+                None => {
+                    groups.push((group_name, Vec::new()));
+                    &mut groups.last_mut().expect("just pushed").1
+                }
+            },
+            None => &mut destinations,
+        };
+
+        //add globals to the correct element
+        let retention = target.retention_attributes(current_global);
+        match group_destinations.iter_mut().find(|(constant, attributes, _)| *constant == current_global.constant && *attributes == retention) {
+            Some((_, _, variables)) => variables.extend(parsed_variables),
+            None => group_destinations.push((current_global.constant, retention, parsed_variables)),
+        }
+    }
+    
+    //relinquish copies of the nodes into the tree
+    let name_label = String::from("name");
+    let configuration_prefix = generation_parameters.configuration_name.as_deref().unwrap_or(unit_name);
+    let resources_name = format!("{}_{}", configuration_prefix, RESOURCE);
+    
+    let mut resource_node = SResource::new()
+        .attribute(name_label.clone(), resources_name)
+        .attribute_str("resourceTypeName", "");
+
+    let tasks = program_tasks(generation_parameters, current_unit);
+    for task in &tasks {
+        let mut task_node = STask::new().attribute(name_label.clone(), task.name.clone());
+
+        if let Some(interval) = &task.interval {
+            task_node = task_node.attribute(String::from("interval"), interval.clone());
+        }
+        resource_node = resource_node.child(&task_node.attribute(String::from("priority"), task.priority.clone()));
+    }
+
+    for (constant, retention, variables) in destinations {
+        resource_node = resource_node.child(&with_block_attributes(SGlobalVars::new().inner(), constant, &retention).children(variables));
+    }
+
+    for (group_name, group_destinations) in groups {
+        for (constant, retention, variables) in group_destinations {
+            let group_node = SGlobalVars::new().attribute(name_label.clone(), group_name.clone()).inner();
+            resource_node = resource_node.child(&with_block_attributes(group_node, constant, &retention).children(variables));
+        }
+    }
+
+    for task in tasks {
+        for program in task.programs {
+            resource_node = resource_node.child(&SProgramInstance::new()
+                .attribute(name_label.clone(), program.clone())
+                .attribute(String::from("typeName"), program)
+                .attribute(String::from("associatedTaskName"), task.name.clone()));
+        }
+    }
+
+    let config_name = match &generation_parameters.configuration_name {
+        Some(name) => name.clone(),
+        None => format!("{}_{}", unit_name, CONFIGURATION),
+    };
+
+    let mut configuration_node = SConfiguration::new()
+        .attribute(name_label.clone(), config_name.clone())
+        .child(&resource_node);
+
+    if let Some(access_paths_node) = generate_access_paths(generation_parameters, target, &current_unit.var_access) {
+        configuration_node = configuration_node.child(&access_paths_node);
+    }
+
+    if let Some(config_vars_node) = generate_config_vars(generation_parameters, target, &current_unit.var_config) {
+        configuration_node = configuration_node.child(&config_vars_node);
+    }
+
+    //the configuration of another unit with the same name, if they are merged
+    let existing_configuration = globals_root.children.iter_mut()
+        .find(|it| it.name == CONFIGURATION && it.attributes.get(&name_label) == Some(&config_name));
+
+    match existing_configuration {
+        Some(merged_node) => merge_elements(merged_node, configuration_node.inner()),
+        None => {
+            globals_root.child_borrowed(&configuration_node); //need to borrow a mut Node so I don't break the root nodes reference to the globals node
+        },
+    }
+    return Ok(());
+}
+
+/// The order of the elements of a configuration and its resource in the IEC 61131-10 schema
+const CONFIGURATION_ELEMENT_ORDER: [&str; 6] = [RESOURCE, "Task", "GlobalVars", "ProgramInstance", "AccessPaths", "ConfigVars"];
+
+/// Merges the children of a configuration or resource into another one. Elements with the same name and attributes,
+/// e.g. the `<GlobalVars>` lists of the same qualifiers, are joined, everything else is added in schema order
+fn merge_elements(merged_node: &mut Node, other_node: Node) {
+    let rank = |name: &str| CONFIGURATION_ELEMENT_ORDER.iter().position(|it| *it == name).unwrap_or(CONFIGURATION_ELEMENT_ORDER.len());
+
+    for child in other_node.children {
+        if let Some(existing) = merged_node.children.iter_mut().find(|it| it.name == child.name && it.attributes == child.attributes) {
+            merge_elements(existing, child);
+            continue;
+        }
+
+        let position = merged_node.children.iter().rposition(|it| rank(&it.name) <= rank(&child.name)).map_or(0, |it| it + 1);
+        merged_node.children.insert(position, child);
+    }
+}
+
+/// The `VAR_ACCESS` paths, e.g. `speed : main.motor.speed : REAL READ_WRITE;`, through which external clients reach
+/// variables of the configuration under another name
+fn generate_access_paths(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, var_access: &[AccessVariable]) -> Option<SAccessPaths> {
+    let mut access_paths_node = SAccessPaths::new();
+    let mut has_variables = false;
+
+    for access_variable in var_access {
+        let path = match &access_variable.path.stmt {
+            AstStatement::HardwareAccess(hardware_access) => match target.format_address(hardware_access) {
+                Ok(address) => address,
+                Err(_) => continue, //unmappable addresses are rejected up front
+            },
+            _ => access_variable.path.as_string(),
+        };
+
+        let direction = match access_variable.direction {
+            AccessDirection::ReadOnly => "readOnly",
+            AccessDirection::ReadWrite => "readWrite",
+        };
+
+        let mut variable_node = SAccessVariable::new()
+            .attribute(String::from("alias"), access_variable.name.clone())
+            .attribute(String::from("instancePathAndName"), path)
+            .attribute_str("direction", direction);
+
+        if let Some(type_node) = generate_type_element(&access_variable.data_type, generation_parameters, target) {
+            variable_node = variable_node.child(&type_node);
+        }
+
+        access_paths_node = access_paths_node.child(&variable_node);
+        has_variables = true;
+    }
+
+    has_variables.then_some(access_paths_node)
+}
+
+/// The `VAR_CONFIG` entries placing variables of instances, e.g. `main.fb.x AT %IX1.0 : BOOL;`, so the IO mapping is
+/// part of the configuration instead of being redone in the target IDE
+fn generate_config_vars(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, var_config: &[ConfigVariable]) -> Option<SConfigVars> {
+    let mut config_vars_node = SConfigVars::new();
+    let mut has_variables = false;
+
+    for config_variable in var_config {
+        let AstStatement::HardwareAccess(hardware_access) = &config_variable.address.stmt else {
+            continue;
+        };
+
+        let Ok(address) = target.format_address(hardware_access) else {
+            continue; //unmappable addresses are rejected up front
+        };
+
+        let mut variable_node = SConfigVariable::new()
+            .attribute(String::from("instancePathAndName"), config_variable.reference.as_string())
+            .attribute(String::from("address"), address);
+
+        if let Some(type_node) = generate_type_element(&config_variable.data_type, generation_parameters, target) {
+            variable_node = variable_node.child(&type_node);
+        }
+
+        config_vars_node = config_vars_node.child(&variable_node);
+        has_variables = true;
+    }
+
+    has_variables.then_some(config_vars_node)
+}
+
+/// The variables of all blocks with the same constness and retention attributes
+type BlockVariables = (bool, Vec<(&'static str, &'static str)>, Vec<Box<dyn IntoNode>>);
+
+/// Marks a `<GlobalVars>` or `<Vars>` element constant if its block is, and adds the retention attributes of the target
+fn with_block_attributes(element: Node, constant: bool, retention: &[(&'static str, &'static str)]) -> Node {
+    let element = match constant {
+        true => element.attribute_bool("constant", true),
+        false => element,
+    };
+    retention.iter().fold(element, |element, (name, value)| element.attribute_str(name, value))
+}
+
+pub const NETWORK_PUBLISH_ATTRIBUTE: &str = "network_publish";
+
+pub const GROUP_ATTRIBUTE: &str = "group";
+
+/// The group a `VAR_GLOBAL` block is exported in, named with `{attribute 'group' := 'Motion'}` above the block
+fn global_group(sources: &LoadedSources, block: &VariableBlock) -> Option<String> {
+    preceding_attributes(sources, &block.location).into_iter()
+        .find(|(name, value)| name.eq_ignore_ascii_case(GROUP_ATTRIBUTE) && !value.is_empty())
+        .map(|(_, value)| value)
+}
+
+pub const NO_XML_EXPORT_ATTRIBUTE: &str = "no_xml_export";
+
+pub const XML_EXPORT_ATTRIBUTE: &str = "xml_export";
+
+/// Whether a POU, data type or variable block is exported. `{attribute 'no_xml_export'}` above the declaration leaves
+/// it out. With an allow-list, see [`has_export_allow_list`], only declarations marked `{attribute 'xml_export'}` are
+fn is_exported(sources: &LoadedSources, location: &SourceLocation, allow_list: bool) -> bool {
+    let attributes = preceding_attributes(sources, location);
+    let marked = |attribute_name: &str| attributes.iter().any(|(name, _)| name.eq_ignore_ascii_case(attribute_name));
+    !marked(NO_XML_EXPORT_ATTRIBUTE) && (!allow_list || marked(XML_EXPORT_ATTRIBUTE))
+}
+
+/// Whether any of the declarations of a kind, e.g. the data types of a file, is marked `{attribute 'xml_export'}`,
+/// which turns the export of all of them into an allow-list
+fn has_export_allow_list<'a>(sources: &LoadedSources, mut locations: impl Iterator<Item = &'a SourceLocation>) -> bool {
+    locations.any(|location| preceding_attributes(sources, location).iter().any(|(name, _)| name.eq_ignore_ascii_case(XML_EXPORT_ATTRIBUTE)))
+}
+
+/// Whether the POUs of the unit are exported by an allow-list. Methods and actions are exported with their POU
+fn has_pou_allow_list(sources: &LoadedSources, current_unit: &CompilationUnit) -> bool {
+    has_export_allow_list(sources, current_unit.pous.iter()
+        .filter(|it| !matches!(it.kind, PouType::Method { .. } | PouType::Action))
+        .map(|it| &it.location))
+}
+
+/// Whether a POU is exported. Methods are exported with the POU they belong to, unless they are left out themselves
+fn is_pou_exported(sources: &LoadedSources, pou: &Pou, current_unit: &CompilationUnit, allow_list: bool) -> bool {
+    match &pou.kind {
+        PouType::Method { parent, .. } => is_exported(sources, &pou.location, false) && current_unit.pous.iter()
+            .find(|it| it.name.eq_ignore_ascii_case(parent))
+            .is_none_or(|it| is_pou_exported(sources, it, current_unit, allow_list)),
+        _ => is_exported(sources, &pou.location, allow_list),
+    }
+}
+
+pub const TASK_ATTRIBUTE: &str = "task";
+
+pub const TASK_INTERVAL_ATTRIBUTE: &str = "task_interval";
+
+pub const TASK_PRIORITY_ATTRIBUTE: &str = "task_priority";
+
+/// The priority of tasks which do not set their own
+pub const DEFAULT_TASK_PRIORITY: &str = "0";
+
+/// A task of the resource with the programs it runs
+struct ProgramTask {
+    name: String,
+    interval: Option<String>,
+    priority: String,
+    programs: Vec<String>,
+}
+
+/// The tasks running the programs of the unit, chosen with `{attribute 'task' := 'Fast'}` above the program or else
+/// the default task. The interval and priority of a task are set with `{attribute 'task_interval' := 'T#10ms'}` and
+/// `{attribute 'task_priority' := '1'}` above any of its programs
+fn program_tasks(generation_parameters: &GenerationParameters, current_unit: &CompilationUnit) -> Vec<ProgramTask> {
+    let mut tasks: Vec<ProgramTask> = Vec::new();
+    let allow_list = has_pou_allow_list(&generation_parameters.sources, current_unit);
+
+    for current_pou in &current_unit.pous {
+        if current_pou.kind != PouType::Program || current_pou.linkage == LinkageType::External || current_pou.location.span == CodeSpan::None {
+            continue;
+        }
+
+        if !is_pou_exported(&generation_parameters.sources, current_pou, current_unit, allow_list) {
+            continue; //there is no program to instantiate
+        }
+
+        let attributes = preceding_attributes(&generation_parameters.sources, &current_pou.location);
+        let attribute = |attribute_name: &str| attributes.iter()
+            .find(|(name, value)| name.eq_ignore_ascii_case(attribute_name) && !value.is_empty())
+            .map(|(_, value)| value.clone());
+
+        let Some(task_name) = attribute(TASK_ATTRIBUTE).or_else(|| generation_parameters.default_task.clone()) else {
+            continue; //not scheduled
+        };
+
+        let task = match tasks.iter().position(|it| it.name.eq_ignore_ascii_case(&task_name)) {
+            Some(position) => &mut tasks[position],
+            None => {
+                tasks.push(ProgramTask { name: task_name, interval: None, priority: String::from(DEFAULT_TASK_PRIORITY), programs: Vec::new() });
+                tasks.last_mut().expect("just pushed")
+            }
+        };
+
+        if let Some(interval) = attribute(TASK_INTERVAL_ATTRIBUTE) {
+            task.interval = Some(interval);
+        }
+
+        if let Some(priority) = attribute(TASK_PRIORITY_ATTRIBUTE) {
+            task.priority = priority;
+        }
+        task.programs.push(current_pou.name.clone());
+    }
+    tasks
+}
+
+/// The network publish mode of a global, chosen with `{attribute 'network_publish' := 'Output'}` above its declaration.
+/// The same attribute above `VAR_GLOBAL` sets the mode of every variable of the block that does not choose its own
+pub fn variable_network_publish(sources: &LoadedSources, block: &VariableBlock, variable: &Variable) -> NetworkPublishMode {
+    network_publish_attribute(sources, &variable.location)
+        .or_else(|| network_publish_attribute(sources, &block.location))
+        .unwrap_or(NetworkPublishMode::DoNotPublish)
+}
+
+fn network_publish_attribute(sources: &LoadedSources, location: &SourceLocation) -> Option<NetworkPublishMode> {
+    let value = preceding_attributes(sources, location).into_iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case(NETWORK_PUBLISH_ATTRIBUTE))
+        .map(|(_, value)| value)?;
+
+    //an unknown value is reported by `check_network_publish_attributes` and leaves the variable unpublished
+    Some(parse_network_publish_mode(&value).unwrap_or(NetworkPublishMode::DoNotPublish))
+}
+
+fn parse_network_publish_mode(value: &str) -> Option<NetworkPublishMode> {
+    match value.to_lowercase().as_str() {
+        "publishonly" | "publicationonly" | "publish" | "" => Some(NetworkPublishMode::PublishOnly), //also as Sysmac Studio spells it
+        "input" => Some(NetworkPublishMode::Input),
+        "output" => Some(NetworkPublishMode::Output),
+        "donotpublish" => Some(NetworkPublishMode::DoNotPublish), //to leave a variable of a published block out
+        _ => None
+    }
+}
+
+pub fn generate_custom_types(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, current_unit: &CompilationUnit, output_root: &mut Node) -> Result<(), Error> {
+    let global_root = document_element(output_root, GLOBAL_NAMESPACE_PATH)?;
+
+    let allow_list = has_export_allow_list(&generation_parameters.sources, current_unit.user_types.iter().map(|it| &it.location));
+
+    for a in 0..current_unit.user_types.len() {
+        let current_usertype = &current_unit.user_types[a];
+
+        if current_usertype.location.span == CodeSpan::None {
+            continue; //discard internally generated types
+        }
+
+        if current_usertype.linkage == LinkageType::External {
+            continue; //discard externally defined types; same as externally defined functions
+        }
+
+        if !is_exported(&generation_parameters.sources, &current_usertype.location, allow_list) {
+            continue;
+        }
+
+        let customtype_maybe: Option<SDataTypeDecl> = match &current_usertype.data_type {
+            DataType::StructType { name, variables } => { //STRUCT
+                let unwrapped_name = match name {
+                    Some(a) => a.clone(),
+                    None => { continue; }, //every structure must have a name
+                };
+
+                let mut spec_node = SUserDefinedTypeSpec::new()
+                    .attribute_str("xsi:type", "StructTypeSpec");
+
+                for b in 0..variables.len() {
+                    let current_variable = &variables[b];
+                    let type_node = match generate_type_element(&current_variable.data_type_declaration, generation_parameters, target) {
+                        Some(a) => a,
+                        None => { continue; }, //every variable must have a type
+                    };
+
+                    let mut member_node = SMember::new()
+                        .attribute(String::from("name"), current_variable.name.clone())
+                        .child(&type_node);
+
+                    if let Some(initial_node) = generate_initial_value_element(current_variable, generation_parameters, target, enums, Some(&unwrapped_name)) {
+                        member_node = member_node.child(&initial_node);
+                    }
+
+                    if let Some(documentation_node) = generate_documentation_element(&generation_parameters.sources, current_variable) {
+                        member_node = member_node.child(&documentation_node);
+                    }
+
+                    spec_node = spec_node.child(&member_node);
+                }
+
+                if spec_node.inner().children.len() == 0 { //structs must have <Member> elements, otherwise delete it
+                    None
+                }
+
+                else {
+                    let decl_node1 = SDataTypeDecl::new()
+                        .attribute(String::from("name"), unwrapped_name)
+                        .child(&spec_node);
+
+                    Some(decl_node1)
+                }
+            },
+            DataType::EnumType { name, numeric_type, elements } => { //ENUM
+                let unwrapped_enum_type = match name {
+                    Some(a) => a.clone(),
+                    None => { continue; }, //every structure must have a name
+                };
+
+                let enumerators = parse_enum_elements(elements);
+
+                let base_type = match generation_parameters.enum_base_type(numeric_type) {
+                    Some(a) => String::from(a),
+                    None => target.map_enum_base_type(generation_parameters, numeric_type),
+                };
+
+                let base_node = SBaseType::new()
+                    .content(base_type);
+
+                let formatted = format_enum_initials(enumerators);
+
+                let spec_node = SUserDefinedTypeSpec::new()
+                    .attribute_str("xsi:type", "EnumTypeWithNamedValueSpec")                    
+                    .children(formatted)
+                    .child(&base_node); //<BaseType> element must be declared below all the <Member> elements, apparently
+
+                let decl_node2 = SDataTypeDecl::new()
+                    .attribute(String::from("name"), unwrapped_enum_type)
+                    .child(&spec_node);
+
+                Some(decl_node2)
+            },
+            DataType::ArrayType { name, bounds, referenced_type, .. } => { //ARRAY
+                let unwrapped_name = match name {
+                    Some(a) => a.clone(),
+                    None => { continue; }, //every array must have a name
+                };
+
+                let Some(array_children) = array_type_children(generation_parameters, target, Some(&unwrapped_name), bounds, referenced_type) else {
+                    continue; //variable length arrays and bounds that are not plain values cannot be declared
+                };
+
+                let spec_node = SUserDefinedTypeSpec::new()
+                    .attribute_str("xsi:type", "ArrayTypeSpec")
+                    .children(array_children);
+
+                let decl_node3 = SDataTypeDecl::new()
+                    .attribute(String::from("name"), unwrapped_name)
+                    .child(&spec_node);
+
+                Some(decl_node3)
+            },
+            DataType::SubRangeType { name: Some(name), referenced_type, bounds: None } => { //alias, e.g. TYPE Speed : REAL; END_TYPE
+                let typename_node = STypeName::new()
+                    .content(target.map_type_name(generation_parameters, referenced_type));
+
+                let base_node = SBaseType::new()
+                    .child(&typename_node);
+
+                let spec_node = SUserDefinedTypeSpec::new()
+                    .attribute_str("xsi:type", "DerivedTypeSpec")
+                    .child(&base_node);
+
+                let decl_node4 = SDataTypeDecl::new()
+                    .attribute(String::from("name"), name.clone())
+                    .child(&spec_node);
+
+                Some(decl_node4)
+            },
+            _ => None                                
+        };
+
+        if let Some(unwrapped_ready) = customtype_maybe {
+            global_root.child_borrowed(&unwrapped_ready);
+        }        
+    }
+    Ok(())
+}
+
+/// The `<BaseType>` of an array followed by one `<Dimension>` per range, e.g. two for `ARRAY[0..3, 0..7] OF REAL`.
+/// Bounds evaluated by the driver take precedence over the declared ones
+fn array_type_children(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, name: Option<&str>, bounds: &AstNode, referenced_type: &DataTypeDeclaration) -> Option<Vec<Box<dyn IntoNode>>> {
+    let typename_node = STypeName::new()
+        .content(target.map_type_name(generation_parameters, &declared_type_name(referenced_type)?));
+
+    let mut children: Vec<Box<dyn IntoNode>> = vec![Box::new(SBaseType::new().child(&typename_node))];
+
+    let ranges = match &bounds.stmt {
+        AstStatement::ExpressionList(ranges) => ranges.iter().collect(),
+        _ => vec![bounds],
+    };
+
+    match name.and_then(|it| generation_parameters.array_dimensions(it)).filter(|it| it.len() == ranges.len()) {
+        Some(dimensions) => {
+            for (lower, upper) in dimensions {
+                children.push(Box::new(SDimension::new()
+                    .attribute_int("lower", *lower)
+                    .attribute_int("upper", *upper)
+                    .close()));
+            }
+        },
+        None => {
+            for range in ranges {
+                children.push(Box::new(array_dimension(range)?));
+            }
+        }
+    }
+    Some(children)
+}
+
+/// The `<Dimension>` of an array range like `0..9`
+fn array_dimension(range: &AstNode) -> Option<SDimension> {
+    let AstStatement::RangeStatement(range) = &range.stmt else {
+        return None; //variable length arrays have no bounds
+    };
+
+    let dimension = SDimension::new()
+        .attribute(String::from("lower"), array_bound(&range.start)?)
+        .attribute(String::from("upper"), array_bound(&range.end)?)
+        .close();
+
+    Some(dimension)
+}
+
+/// A literal, a possibly negated literal or the name of a constant
+fn array_bound(bound: &AstNode) -> Option<String> {
+    match &bound.stmt {
+        AstStatement::Literal(literal) => Some(literal.to_string()),
+        AstStatement::UnaryExpression(UnaryExpression { operator: Operator::Minus, value }) => array_bound(value).map(|it| format!("-{it}")),
+        _ => bound.get_flat_reference_name().map(String::from),
+    }
+}
+
+fn parse_enum_elements(elements: &AstNode) -> Vec<NameAndInitialValue> {
+    match &elements.stmt {
+        AstStatement::ExpressionList(ast_nodes) => ast_nodes.iter().map(|a| {
+            match &a.stmt {
+                AstStatement::Assignment(assignment) => parse_enum_expression(assignment),
+                other => panic!("Expected Assignment. Instead got: {:?}", other)
+            }
+        }).collect(),
+
+        AstStatement::Assignment(assignment) => vec![parse_enum_expression(assignment)],
+        other => panic!("Expected ExpressionList or Assignment. Instead got: {:?}", other)
+    }
+}
+
+pub(crate) fn parse_enum_expression(input: &Assignment) -> NameAndInitialValue {
+    let enum_variant_name = match &input.left.stmt {
+        AstStatement::ReferenceExpr(reference_exp) => {
+            match &reference_exp.access {
+                ReferenceAccess::Member(member_exp) => {
+                    match &member_exp.stmt {
                         AstStatement::Identifier(name) => {
                             name.clone()
                         }
-                        other => panic!("Expected identifier. Instead got: {:?}", other)
-                    }
+                        other => panic!("Expected identifier. Instead got: {:?}", other)
+                    }
+                }
+                other => panic!("Expected Member. Instead got: {:?}", other)
+            }
+        },
+        other => panic!("Expected ReferenceExpr. Instead got: {:?}", other)
+    };
+
+    let enum_variant_initialiser = match &input.right.stmt {
+        AstStatement::Literal(literal) => literal.to_string(),
+        AstStatement::BinaryExpression(binary_exp) => {
+            match &binary_exp.right.stmt {
+                AstStatement::Literal(literal) => literal.to_string(),
+                other => panic!("Expected Literal. Instead got: {:?}", other)
+            }
+        }
+        other => panic!("Expected LiteralInteger or BinaryExpression. Instead got: {:?}", other)
+    };
+
+    NameAndInitialValue {name: enum_variant_name, initial_value: enum_variant_initialiser}
+}
+
+pub struct NameAndInitialValue {
+    pub name: String,
+    pub initial_value: String
+}
+
+pub fn format_enum_initials(enum_variants: Vec<NameAndInitialValue>) -> Vec<Box<dyn IntoNode>> {
+    number_enum_variants(enum_variants).into_iter().map(|a| {
+        Box::new(SEnumerator::new()
+            .attribute(String::from("name"), a.name)
+            .attribute(String::from("value"), a.initial_value)) as Box<dyn IntoNode>
+    }).collect()
+}
+
+/// Gives variants sharing a value the next unused one, the way they are numbered in the exported enum
+fn number_enum_variants(mut enum_variants: Vec<NameAndInitialValue>) -> Vec<NameAndInitialValue> {
+    let mut viewed_values: HashSet<String> = HashSet::new(); // Own strings for ownership
+    
+    for i in 0..enum_variants.len() {
+        let current_initial = &mut enum_variants[i].initial_value;
+        
+        if !viewed_values.contains(current_initial) {
+            viewed_values.insert(current_initial.clone());
+            continue;
+        }
+        
+        // Conflict: auto-increment
+        let parsed_value = current_initial.parse::<i32>().expect("signed integer");
+        let mut increment = 1;
+        loop {
+            let new_value = parsed_value.checked_add(increment).expect("no overflow");
+            let new_str = new_value.to_string();
+
+            if viewed_values.contains(&new_str) == false {
+                *current_initial = new_str;
+                viewed_values.insert(current_initial.clone());
+                break;
+            }
+            increment += 1;
+        }
+    }
+    enum_variants
+}
+
+/// The numbered variants of every exported enum, to resolve initial values like `state : State := IDLE`
+#[derive(Default)]
+pub struct ProjectEnums {
+    enums: Vec<(String, Vec<NameAndInitialValue>)>,
+}
+
+impl ProjectEnums {
+    /// Collects the same enums [`generate_custom_types`] exports
+    pub fn collect(units: &Vec<&CompilationUnit>) -> Self {
+        let enums = units.iter()
+            .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+            .flat_map(|it| &it.user_types)
+            .filter(|it| it.location.span != CodeSpan::None && it.linkage != LinkageType::External)
+            .filter_map(|it| match &it.data_type {
+                DataType::EnumType { name: Some(name), elements, .. } => Some((name.clone(), number_enum_variants(parse_enum_elements(elements)))),
+                _ => None,
+            })
+            .collect();
+
+        ProjectEnums { enums }
+    }
+
+    /// The variant of the given enum, both compared case-insensitively
+    pub fn find(&self, type_name: &str, variant: &str) -> Option<&NameAndInitialValue> {
+        let (_, variants) = self.enums.iter().find(|(name, _)| name.eq_ignore_ascii_case(type_name))?;
+        variants.iter().find(|it| it.name.eq_ignore_ascii_case(variant))
+    }
+}
+
+/// The variables every function block and class inherits from its bases, to flatten them into it
+#[derive(Default)]
+pub struct InheritedVariables {
+    inherited: Vec<(String, Vec<VariableBlock>)>,
+}
+
+impl InheritedVariables {
+    /// Collects the variable blocks of the bases of every function block and class, the most basic ones first
+    pub fn collect(units: &Vec<&CompilationUnit>) -> Self {
+        let pous: Vec<&Pou> = units.iter()
+            .filter(|it| it.file.get_name().unwrap_or("").to_lowercase().ends_with(".st")) //skip internally generated units
+            .flat_map(|it| &it.pous)
+            .collect();
+
+        let mut inherited = Vec::new();
+
+        for derived in pous.iter().filter(|it| it.super_class.is_some()) {
+            let mut bases: Vec<&Pou> = Vec::new();
+            let mut base_name = derived.super_class.as_ref().map(|it| it.name.as_str());
+
+            while let Some(name) = base_name {
+                let Some(base) = pous.iter().find(|it| it.name.eq_ignore_ascii_case(name)) else {
+                    break; //e.g. a base of a library
+                };
+
+                if bases.iter().any(|it| it.name == base.name) {
+                    break; //the validation rejects circular inheritance
+                }
+                bases.push(base);
+                base_name = base.super_class.as_ref().map(|it| it.name.as_str());
+            }
+
+            let blocks = bases.iter().rev().flat_map(|it| it.variable_blocks.iter().cloned()).collect();
+            inherited.push((derived.name.clone(), blocks));
+        }
+
+        InheritedVariables { inherited }
+    }
+
+    /// The inherited variable blocks of the given function block or class
+    pub fn find(&self, pou_name: &str) -> &[VariableBlock] {
+        self.inherited.iter().find(|(name, _)| name.eq_ignore_ascii_case(pou_name)).map(|(_, blocks)| blocks.as_slice()).unwrap_or_default()
+    }
+}
+
+pub fn generate_pous(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, inherited: &InheritedVariables, current_unit: &CompilationUnit, output_root: &mut Node, diagnostics: &mut Vec<Diagnostic>) -> Result<(), Error> {
+    let global_root = document_element(output_root, GLOBAL_NAMESPACE_PATH)?;
+
+    //the methods generated so far by the name of the class or function block they belong to. They are parsed before it
+    let mut methods: Vec<(String, Node)> = Vec::new();
+
+    //the actions by the name of the POU they belong to, which may be parsed before or after it
+    let actions: Vec<(&str, Node)> = current_unit.implementations.iter()
+        .filter(|it| it.pou_type == PouType::Action && it.linkage != LinkageType::External)
+        .filter_map(|it| {
+            let action_node = SAction::name(String::from(unqualified_method_name(&it.name, &it.type_name)))
+                .child(&st_main_body(exported_body_text(generation_parameters, target, it, diagnostics)?));
+            Some((it.type_name.as_str(), action_node.inner()))
+        })
+        .collect();
+
+    //the concrete POUs the generic ones are exported as
+    let (instance_pous, instance_impls): (Vec<Pou>, Vec<Implementation>) = match generation_parameters.monomorphize_generics {
+        true => generic_instances(current_unit, &generation_parameters.generic_calls).into_iter().unzip(),
+        false => (Vec::new(), Vec::new()),
+    };
+
+    let allow_list = has_pou_allow_list(&generation_parameters.sources, current_unit);
+
+    for current_impl in current_unit.implementations.iter().chain(&instance_impls) {
+        let matching_metadata = current_unit.pous.iter().chain(&instance_pous).find(|a| a.name == current_impl.name).expect("pou metadata matching the current implementation");
+
+        if generation_parameters.monomorphize_generics && !matching_metadata.generics.is_empty() {
+            continue; //exported as its instances instead
+        }
+
+        if !is_pou_exported(&generation_parameters.sources, matching_metadata, current_unit, allow_list) {
+            continue;
+        }
+
+        let is_method = matches!(current_impl.pou_type, PouType::Method { property: None, .. }); //methods of properties are no methods of their own
+        if !matches!(current_impl.pou_type, PouType::Program | PouType::Function | PouType::FunctionBlock | PouType::Class) && !is_method {
+            continue; //currently the only POUs that are supported for xml generation
+        }
+
+        let is_stub = current_impl.linkage == LinkageType::External;
+        if is_stub && !generation_parameters.external_stubs { //discard externally linked POUs since the receiving platform will have those implemented already
+            continue;
+        }
+
+        let procedure_text = match exported_body_text(generation_parameters, target, current_impl, diagnostics) {
+            _ if current_impl.pou_type == PouType::Class => String::new(), //classes only have the bodies of their methods
+            _ if is_stub => String::new(), //the body is implemented elsewhere
+            Some(pou_procedure_text) => pou_procedure_text,
+            None => {
+                continue; //only POUs from ST files have a body to export
+            }
+        };
+
+        let mut resulttype_node = SResultType::new(); //<ResultType>
+
+        let mut typename_node = STypeName::new();
+
+        if (current_impl.pou_type == PouType::Function || current_impl.pou_type == PouType::FunctionBlock || is_method) && 
+            let Some(result_type) = &matching_metadata.return_type && let Some(type_name) = declared_type_name(result_type) {
+            typename_node = typename_node.content(target.map_type_name(generation_parameters, &type_name));
+            resulttype_node = resulttype_node.child(&typename_node);
+        }
+
+        else if !is_method { //methods without a return type have no result
+            typename_node = typename_node.content(String::from("BOOL")); //default to boolean output
+            resulttype_node = resulttype_node.child(&typename_node);
+        }
+
+        //<Parameters>
+        let mut input_vars = SInputVars::new();
+        let mut inout_vars = SInoutVars::new();
+        let mut output_vars = SOutputVars::new();
+        let mut parameters_node = SParameters::new();
+
+        //<ExternalVars>
+        let mut externals = SExternalVars::new();
+
+        let mut constant_externals = SExternalVars::new()
+            .attribute_bool("constant", true);
+
+        //<Vars>
+        let mut vars = SVars::new()
+            .attribute_str("accessSpecifier", "private");
+
+        let mut constant_vars = SVars::new()
+            .attribute_str("accessSpecifier", "private")
+            .attribute_bool("constant", true);
+
+        let mut retain_vars = SVars::new()
+            .attribute_str("accessSpecifier", "private")
+            .attribute_bool("retain", true);
+
+        let mut constant_retain_vars = SVars::new()
+            .attribute_str("accessSpecifier", "private")
+            .attribute_bool("constant", true)
+            .attribute_bool("retain", true);
+
+        //<Vars> with other retention attributes of the target, e.g. persistent
+        let mut retention_vars: Vec<BlockVariables> = Vec::new();
+
+        //<TempVars>
+        let mut temp_vars = STempVars::new();
+
+        let mut constant_temp_vars = STempVars::new()
+            .attribute_bool("constant", true);
+
+        //the parameters are numbered in the order they are declared, across all parameter blocks of the POU
+        let mut parameter_order: usize = 0;
+
+        let blocks: Vec<&VariableBlock> = inherited.find(&matching_metadata.name).iter().chain(&matching_metadata.variable_blocks).collect();
+        let block_allow_list = has_export_allow_list(&generation_parameters.sources, blocks.iter().map(|it| &it.location));
+
+        //put all the variables in the right containers
+        for current_block in blocks.into_iter().filter(|it| is_exported(&generation_parameters.sources, &it.location, block_allow_list)) {
+            let is_parameter = matches!(current_block.kind, VariableBlockType::Input(_) | VariableBlockType::Output | VariableBlockType::InOut);
+
+            for c in 0..current_block.variables.len() {
+                let current_variable = &current_block.variables[c];
+
+                if current_variable.location.span == CodeSpan::None {
+                    continue; //discard compiler interally generated variables
+                }
+
+                let network_publish = match current_block.kind {
+                    _ => String::from("DoNotPublish")
+                };
+
+                let order = is_parameter.then_some(parameter_order);
+                let maybe_variablenode = generate_variable_element(current_variable, generation_parameters, target, enums, Some(&matching_metadata.name), network_publish, order);
+
+                let variable_node = match maybe_variablenode {
+                    Some(a) => a,
+                    None => { continue; },
+                };
+
+                if is_parameter {
+                    parameter_order += 1;
+                }
+
+                let retention = target.retention_attributes(current_block);
+                match variable_section(current_block, is_method, &retention) {
+                    Some(VariableSection::Inputs) => input_vars = input_vars.child(&variable_node),
+                    Some(VariableSection::Inouts) => inout_vars = inout_vars.child(&variable_node),
+                    Some(VariableSection::Outputs) => output_vars = output_vars.child(&variable_node),
+                    Some(VariableSection::Externals) => externals = externals.child(&variable_node),
+                    Some(VariableSection::ConstantExternals) => constant_externals = constant_externals.child(&variable_node),
+                    Some(VariableSection::Vars) => vars = vars.child(&variable_node),
+                    Some(VariableSection::ConstantVars) => constant_vars = constant_vars.child(&variable_node),
+                    Some(VariableSection::RetainVars) => retain_vars = retain_vars.child(&variable_node),
+                    Some(VariableSection::ConstantRetainVars) => constant_retain_vars = constant_retain_vars.child(&variable_node),
+                    Some(VariableSection::RetentionVars) => {
+                        match retention_vars.iter_mut().find(|(constant, attributes, _)| *constant == current_block.constant && *attributes == retention) {
+                            Some((_, _, variables)) => variables.push(Box::new(variable_node)),
+                            None => retention_vars.push((current_block.constant, retention, vec![Box::new(variable_node)])),
+                        }
+                    },
+                    Some(VariableSection::TempVars) => temp_vars = temp_vars.child(&variable_node),
+                    Some(VariableSection::ConstantTempVars) => constant_temp_vars = constant_temp_vars.child(&variable_node),
+                    None => (),
                 }
-                other => panic!("Expected Member. Instead got: {:?}", other)
             }
-        },
-        other => panic!("Expected ReferenceExpr. Instead got: {:?}", other)
-    };
+        }
 
-    let enum_variant_initialiser = match &input.right.stmt {
-        AstStatement::Literal(literal) => literal.to_string(),
-        AstStatement::BinaryExpression(binary_exp) => {
-            match &binary_exp.right.stmt {
-                AstStatement::Literal(literal) => literal.to_string(),
-                other => panic!("Expected Literal. Instead got: {:?}", other)
+        parameters_node = parameters_node.child(&input_vars)
+            .child(&inout_vars)
+            .child(&output_vars);
+
+        //implementation statements, as a network if the target and the body allow it
+        let instance_type = |instance: &str| {
+            inherited.find(&matching_metadata.name).iter().chain(&matching_metadata.variable_blocks).chain(&current_unit.global_vars)
+                .flat_map(|it| &it.variables)
+                .find(|it| it.name.eq_ignore_ascii_case(instance))
+                .and_then(|it| declared_type_name(&it.data_type_declaration))
+                .map(|it| target.map_type_name(generation_parameters, &it))
+        };
+        let network = (generation_parameters.fbd_bodies && target.supports_fbd_bodies() && !is_stub && current_impl.pou_type != PouType::Class)
+            .then(|| fbd_network(&generation_parameters.sources, &current_impl.statements, &instance_type))
+            .flatten();
+
+        let chart = match sfc_state_variable(&generation_parameters.sources, matching_metadata).filter(|_| target.supports_sfc_bodies() && !is_stub) {
+            Some(state_variable) => {
+                let chart = sfc_chart(&generation_parameters.sources, &current_impl.statements, state_variable);
+                if chart.is_none() {
+                    diagnostics.push(Diagnostic::new(format!("The state machine of '{}' is exported as ST, since its body is no single CASE over '{}' assigning it only in IFs", current_impl.name, state_variable.name))
+                        .with_error_code("E128")
+                        .with_location(&matching_metadata.name_location));
+                }
+                chart
+            },
+            None => None,
+        };
+
+        let main_body = match (chart, network) {
+            (Some(chart), _) => graphical_main_body("SFC", &chart),
+            (None, Some(network)) => graphical_main_body("FBD", &network),
+            (None, None) => st_main_body(procedure_text),
+        };
+
+        let name = match &current_impl.pou_type {
+            PouType::Method { parent, .. } => unqualified_method_name(&current_impl.name, parent),
+            _ => &current_impl.name,
+        };
+
+        let (own_methods, other_methods): (Vec<_>, Vec<_>) = std::mem::take(&mut methods).into_iter()
+            .partition(|(parent, _)| !is_method && parent.eq_ignore_ascii_case(&current_impl.name));
+        methods = other_methods;
+
+        let mut own_methods: Vec<Node> = own_methods.into_iter().map(|(_, method)| method).collect();
+        for property in matching_metadata.properties.iter().filter(|_| !is_method) {
+            if let Some(property_elements) = generate_property_elements(property, &current_impl.name, !is_stub, generation_parameters, target, enums, diagnostics) {
+                own_methods.extend(target.emit_property(property_elements));
             }
         }
-        other => panic!("Expected LiteralInteger or BinaryExpression. Instead got: {:?}", other)
+
+        let pou_elements = PouElements {
+            name: name.to_owned(),
+            kind: current_impl.pou_type.clone(),
+            access: current_impl.access,
+            overriding: current_impl.overriding,
+            extends: matching_metadata.super_class.as_ref().filter(|_| target.supports_inheritance()).map(|it| it.name.clone()),
+            implements: matching_metadata.interfaces.iter().filter(|_| target.supports_interfaces()).map(|it| it.name.clone()).collect(),
+            en_eno: generation_parameters.implicit_en_eno && matches!(current_impl.pou_type, PouType::Function | PouType::FunctionBlock),
+            add_data: target.pou_add_data(),
+            documentation: leading_comment(&generation_parameters.sources, &matching_metadata.location),
+            result_type: resulttype_node,
+            parameters: parameters_node,
+            externals,
+            constant_externals,
+            vars,
+            constant_vars,
+            retain_vars,
+            constant_retain_vars,
+            retention_vars: retention_vars.into_iter()
+                .map(|(constant, retention, variables)| {
+                    let element = SVars::new().attribute_str("accessSpecifier", "private").inner();
+                    with_block_attributes(element, constant, &retention).children(variables)
+                })
+                .collect(),
+            temp_vars,
+            constant_temp_vars,
+            main_body,
+            methods: own_methods,
+            actions: actions.iter().filter(|(owner, _)| !is_method && owner.eq_ignore_ascii_case(&current_impl.name)).map(|(_, action)| action.clone()).collect(),
+        };
+
+        match (&current_impl.pou_type, target.emit_pou(pou_elements)) {
+            (PouType::Method { parent, .. }, Some(method_node)) => methods.push((parent.clone(), method_node)),
+            (_, Some(pou_node)) => global_root.children.push(pou_node),
+            _ => (),
+        }
+    }
+    Ok(())
+}
+
+/// The element of a POU the variables of a block are declared in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum VariableSection {
+    Inputs,
+    Inouts,
+    Outputs,
+    Externals,
+    ConstantExternals,
+    Vars,
+    ConstantVars,
+    RetainVars,
+    ConstantRetainVars,
+    /// `<Vars>` with retention attributes besides `retain`, e.g. `persistent`
+    RetentionVars,
+    TempVars,
+    ConstantTempVars,
+}
+
+/// The section the variables of a block are declared in, `None` for blocks which are not exported. The locals of a
+/// method live for a single call, so none of them are retained
+fn variable_section(block: &VariableBlock, is_method: bool, retention: &[(&str, &str)]) -> Option<VariableSection> {
+    let retained = retention == [RETAIN_ATTRIBUTE];
+
+    let section = match block.kind {
+        VariableBlockType::Input(_) => VariableSection::Inputs,
+        VariableBlockType::InOut => VariableSection::Inouts,
+        VariableBlockType::Output => VariableSection::Outputs,
+        VariableBlockType::External if block.constant => VariableSection::ConstantExternals,
+        VariableBlockType::External => VariableSection::Externals,
+        VariableBlockType::Temp if block.constant => VariableSection::ConstantTempVars,
+        VariableBlockType::Temp => VariableSection::TempVars,
+        VariableBlockType::Local if is_method && block.constant => VariableSection::ConstantVars,
+        VariableBlockType::Local if is_method => VariableSection::Vars,
+        VariableBlockType::Local if !retention.is_empty() && !retained => VariableSection::RetentionVars,
+        VariableBlockType::Local if block.constant && retained => VariableSection::ConstantRetainVars,
+        VariableBlockType::Local if block.constant => VariableSection::ConstantVars,
+        VariableBlockType::Local if retained => VariableSection::RetainVars,
+        VariableBlockType::Local => VariableSection::Vars,
+        _ => return None,
     };
+    Some(section)
+}
 
-    NameAndInitialValue {name: enum_variant_name, initial_value: enum_variant_initialiser}
+/// The names [`generate_custom_types`], [`generate_interfaces`] and [`generate_pous`] declare for the unit, in the order
+/// the document declares them, collected from the declarations without generating them. The POUs are expected to be
+/// assembled by [`PouElements::assemble`]
+pub fn collect_unit_declared_names(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, inherited: &InheritedVariables, current_unit: &CompilationUnit, declared_names: &mut Vec<String>) {
+    let sources = &generation_parameters.sources;
+    let has_type = |declaration: &DataTypeDeclaration| generate_type_element(declaration, generation_parameters, target).is_some();
+
+    let allow_list = has_export_allow_list(sources, current_unit.user_types.iter().map(|it| &it.location));
+    let user_types = current_unit.user_types.iter()
+        .filter(|it| it.location.span != CodeSpan::None && it.linkage != LinkageType::External)
+        .filter(|it| is_exported(sources, &it.location, allow_list));
+
+    for current_usertype in user_types {
+        match &current_usertype.data_type {
+            DataType::StructType { name: Some(name), variables } => {
+                let members: Vec<&Variable> = variables.iter().filter(|it| has_type(&it.data_type_declaration)).collect();
+                if !members.is_empty() {
+                    declare_names(declared_names, std::iter::once(name.as_str()).chain(members.iter().map(|it| it.name.as_str())));
+                }
+            },
+            DataType::EnumType { name: Some(name), elements, .. } => {
+                let enumerators = parse_enum_elements(elements);
+                declare_names(declared_names, std::iter::once(name.as_str()).chain(enumerators.iter().map(|it| it.name.as_str())));
+            },
+            DataType::ArrayType { name: Some(name), bounds, referenced_type, .. } if array_type_children(generation_parameters, target, Some(name), bounds, referenced_type).is_some() => {
+                declare_names(declared_names, [name.as_str()]);
+            },
+            DataType::SubRangeType { name: Some(name), bounds: None, .. } => declare_names(declared_names, [name.as_str()]),
+            _ => (),
+        }
+    }
+
+    let interfaces = current_unit.interfaces.iter().filter(|it| target.supports_interfaces() && it.location.span != CodeSpan::None);
+    for current_interface in interfaces {
+        let methods = current_interface.methods.iter().filter(|it| matches!(it.kind, PouType::Method { property: None, .. }));
+        for method in methods {
+            //only the parameters are part of the prototype
+            let parameters = method.variable_blocks.iter().filter(|it| matches!(it.kind, VariableBlockType::Input(_) | VariableBlockType::Output | VariableBlockType::InOut));
+            declare_names(declared_names, section_ordered_names(&method.kind, parameters, false, true, generation_parameters, target));
+        }
+
+        for property in &current_interface.properties {
+            collect_property_declared_names(property, &current_interface.ident.name, false, generation_parameters, target, enums, declared_names);
+        }
+    }
+
+    let (instance_pous, instance_impls): (Vec<Pou>, Vec<Implementation>) = match generation_parameters.monomorphize_generics {
+        true => generic_instances(current_unit, &generation_parameters.generic_calls).into_iter().unzip(),
+        false => (Vec::new(), Vec::new()),
+    };
+    let allow_list = has_pou_allow_list(sources, current_unit);
+
+    //the names of the methods by the POU they belong to, declared along with it
+    let mut methods: Vec<(String, Vec<String>)> = Vec::new();
+
+    for current_impl in current_unit.implementations.iter().chain(&instance_impls) {
+        let matching_metadata = current_unit.pous.iter().chain(&instance_pous).find(|a| a.name == current_impl.name).expect("pou metadata matching the current implementation");
+
+        if generation_parameters.monomorphize_generics && !matching_metadata.generics.is_empty() {
+            continue;
+        }
+
+        if !is_pou_exported(sources, matching_metadata, current_unit, allow_list) {
+            continue;
+        }
+
+        let is_method = matches!(current_impl.pou_type, PouType::Method { property: None, .. });
+        if !matches!(current_impl.pou_type, PouType::Program | PouType::Function | PouType::FunctionBlock | PouType::Class) && !is_method {
+            continue;
+        }
+
+        let is_stub = current_impl.linkage == LinkageType::External;
+        if is_stub && !generation_parameters.external_stubs {
+            continue;
+        }
+
+        let has_body = generation_parameters.canonical_bodies || source_text(sources, &current_impl.location).is_some();
+        if current_impl.pou_type != PouType::Class && !is_stub && !has_body {
+            continue;
+        }
+
+        let mut names: Vec<String> = Vec::new();
+        if matches!(current_impl.pou_type, PouType::Program | PouType::Function | PouType::FunctionBlock) {
+            names.push(current_impl.name.clone());
+        }
+
+        let blocks: Vec<&VariableBlock> = inherited.find(&matching_metadata.name).iter().chain(&matching_metadata.variable_blocks).collect();
+        let block_allow_list = has_export_allow_list(sources, blocks.iter().map(|it| &it.location));
+        let blocks = blocks.into_iter().filter(|it| is_exported(sources, &it.location, block_allow_list));
+        declare_names(&mut names, section_ordered_names(&current_impl.pou_type, blocks, is_method, false, generation_parameters, target));
+
+        if let PouType::Method { parent, .. } = &current_impl.pou_type {
+            methods.push((parent.clone(), names));
+            continue;
+        }
+
+        let (own_methods, other_methods): (Vec<_>, Vec<_>) = std::mem::take(&mut methods).into_iter()
+            .partition(|(parent, _)| parent.eq_ignore_ascii_case(&current_impl.name));
+        methods = other_methods;
+
+        if matches!(current_impl.pou_type, PouType::Program | PouType::FunctionBlock | PouType::Class) { //functions have no methods
+            for (_, method_names) in own_methods {
+                declare_names(&mut names, method_names.iter().map(String::as_str));
+            }
+
+            for property in &matching_metadata.properties {
+                collect_property_declared_names(property, &current_impl.name, !is_stub, generation_parameters, target, enums, &mut names);
+            }
+        }
+        declare_names(declared_names, names.iter().map(String::as_str));
+    }
 }
 
-pub struct NameAndInitialValue {
-    pub name: String,
-    pub initial_value: String
+/// Adds the names which have not been declared yet
+fn declare_names<'a>(declared_names: &mut Vec<String>, names: impl IntoIterator<Item = &'a str>) {
+    for name in names {
+        if !declared_names.iter().any(|it| it == name) {
+            declared_names.push(String::from(name));
+        }
+    }
 }
 
-pub fn format_enum_initials(mut enum_variants: Vec<NameAndInitialValue>) -> Vec<Box<dyn IntoNode>> {
-    let mut viewed_values: HashSet<String> = HashSet::new(); // Own strings for ownership
-    
-    for i in 0..enum_variants.len() {
-        let current_initial = &mut enum_variants[i].initial_value;
-        
-        if !viewed_values.contains(current_initial) {
-            viewed_values.insert(current_initial.clone());
+/// The names of the variables of the blocks which have a type, ordered by the section of the POU they are declared in.
+/// Compiler generated variables are left out of POUs but not of the prototypes of interfaces
+fn section_ordered_names<'a>(kind: &PouType, blocks: impl Iterator<Item = &'a VariableBlock>, is_method: bool, generated: bool, generation_parameters: &GenerationParameters, target: &dyn XmlTarget) -> Vec<&'a str> {
+    let sections = PouElements::variable_sections(kind);
+
+    //the retention attributes besides `retain` by the order their `<Vars>` are first declared in
+    let mut retention_groups: Vec<(bool, Vec<(&'static str, &'static str)>)> = Vec::new();
+    let mut variables: Vec<((usize, usize), &str)> = Vec::new();
+
+    for current_block in blocks {
+        let retention = target.retention_attributes(current_block);
+        let Some(section) = variable_section(current_block, is_method, &retention) else {
             continue;
+        };
+        let Some(position) = sections.iter().position(|it| *it == section) else {
+            continue; //the POU has no such section
+        };
+
+        let group = match section {
+            VariableSection::RetentionVars => {
+                let group = (current_block.constant, retention);
+                retention_groups.iter().position(|it| *it == group).unwrap_or_else(|| {
+                    retention_groups.push(group);
+                    retention_groups.len() - 1
+                })
+            },
+            _ => 0,
+        };
+
+        let exported = current_block.variables.iter()
+            .filter(|it| generated || it.location.span != CodeSpan::None)
+            .filter(|it| generate_type_element(&it.data_type_declaration, generation_parameters, target).is_some());
+        variables.extend(exported.map(|it| ((position, group), it.name.as_str())));
+    }
+    variables.sort_by_key(|(position, _)| *position); //stable, so each section keeps the order of declaration
+    variables.into_iter().map(|(_, name)| name).collect()
+}
+
+/// The names the emitted elements of a property declare, see [`generate_property_elements`]. Its accessors get no
+/// bodies, since the names do not depend on them
+fn collect_property_declared_names(property: &PropertyBlock, parent: &str, with_bodies: bool, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, declared_names: &mut Vec<String>) {
+    let Some(mut property_elements) = generate_property_elements(property, parent, false, generation_parameters, target, enums, &mut Vec::new()) else {
+        return;
+    };
+
+    if with_bodies {
+        let accessors = [(&mut property_elements.getter, PropertyKind::Get), (&mut property_elements.setter, PropertyKind::Set)];
+        for (accessor, kind) in accessors {
+            if let (Some(accessor), Some(implementation)) = (accessor, property.implementations.iter().find(|it| it.kind == kind)) {
+                accessor.vars = generate_accessor_vars(&implementation.variable_blocks, parent, generation_parameters, target, enums);
+                accessor.body = Some(String::new());
+            }
         }
-        
-        // Conflict: auto-increment
-        let parsed_value = current_initial.parse::<i32>().expect("signed integer");
-        let mut increment = 1;
-        loop {
-            let new_value = parsed_value.checked_add(increment).expect("no overflow");
-            let new_str = new_value.to_string();
+    }
 
-            if viewed_values.contains(&new_str) == false {
-                *current_initial = new_str;
-                viewed_values.insert(current_initial.clone());
-                break;
+    for property_node in target.emit_property(property_elements) {
+        collect_declared_names(&property_node, declared_names);
+    }
+}
+
+/// The readable name of an instance of a generic POU, e.g. `MAX_OF_INT` for `MAX_OF` called with `INT` arguments
+pub fn generic_instance_name(generic: &str, types: &[String]) -> String {
+    let types: Vec<String> = types.iter()
+        .map(|it| it.chars().map(|it| if it.is_ascii_alphanumeric() { it } else { '_' }).collect::<String>().trim_matches('_').to_string())
+        .collect();
+    format!("{generic}_{}", types.join("_"))
+}
+
+/// One concrete POU per distinct instantiation of the unit's generic POUs, with the generic types of its variables
+/// and result replaced by the ones of the instantiation
+fn generic_instances(current_unit: &CompilationUnit, generic_calls: &[GenericCall]) -> Vec<(Pou, Implementation)> {
+    let mut instances: Vec<(Pou, Implementation)> = Vec::new();
+
+    for generic_pou in current_unit.pous.iter().filter(|it| !it.generics.is_empty() && it.linkage != LinkageType::External) {
+        let Some(generic_impl) = current_unit.implementations.iter().find(|it| it.name == generic_pou.name) else {
+            continue;
+        };
+
+        for call in generic_calls.iter().filter(|it| it.generic.eq_ignore_ascii_case(&generic_pou.name) && it.types.len() == generic_pou.generics.len()) {
+            let name = generic_instance_name(&generic_pou.name, &call.types);
+            if instances.iter().any(|(pou, _)| pou.name == name) {
+                continue; //one instance per distinct instantiation
             }
-            increment += 1;
+
+            let bindings: Vec<(&str, &str)> = generic_pou.generics.iter().map(|it| it.name.as_str()).zip(call.types.iter().map(String::as_str)).collect();
+            let variable_blocks = generic_pou.variable_blocks.iter()
+                .map(|block| VariableBlock {
+                    variables: block.variables.iter()
+                        .map(|it| Variable { data_type_declaration: substitute_generic_types(&it.data_type_declaration, &bindings), ..it.clone() })
+                        .collect(),
+                    ..block.clone()
+                })
+                .collect();
+
+            let instance_pou = Pou {
+                id: generic_pou.id,
+                name: name.clone(),
+                kind: generic_pou.kind.clone(),
+                variable_blocks,
+                return_type: generic_pou.return_type.as_ref().map(|it| substitute_generic_types(it, &bindings)),
+                location: generic_pou.location.clone(),
+                name_location: generic_pou.name_location.clone(),
+                poly_mode: None, //only functions are generic
+                generics: Vec::new(),
+                linkage: generic_pou.linkage,
+                super_class: generic_pou.super_class.clone(),
+                is_const: generic_pou.is_const,
+                interfaces: generic_pou.interfaces.clone(),
+                properties: generic_pou.properties.clone(),
+            };
+
+            let instance_impl = Implementation {
+                name: name.clone(),
+                type_name: name,
+                linkage: generic_impl.linkage,
+                pou_type: generic_impl.pou_type.clone(),
+                statements: generic_impl.statements.clone(),
+                location: generic_impl.location.clone(),
+                name_location: generic_impl.name_location.clone(),
+                end_location: generic_impl.end_location.clone(),
+                overriding: generic_impl.overriding,
+                generic: false,
+                access: generic_impl.access,
+            };
+            instances.push((instance_pou, instance_impl));
         }
     }
-    
-    enum_variants.into_iter().map(|a| {
-        Box::new(SEnumerator::new()
-            .attribute(String::from("name"), a.name)
-            .attribute(String::from("value"), a.initial_value)) as Box<dyn IntoNode>
-    }).collect()
+    instances
+}
+
+/// The declaration with the generic types bound to concrete ones, e.g. `ARRAY[0..9] OF T` to `ARRAY[0..9] OF INT`
+fn substitute_generic_types(declaration: &DataTypeDeclaration, bindings: &[(&str, &str)]) -> DataTypeDeclaration {
+    match declaration {
+        DataTypeDeclaration::Reference { referenced_type, location } => {
+            let bound = bindings.iter().find(|(generic, _)| generic.eq_ignore_ascii_case(referenced_type)).map(|(_, concrete)| String::from(*concrete));
+            DataTypeDeclaration::Reference { referenced_type: bound.unwrap_or_else(|| referenced_type.clone()), location: location.clone() }
+        },
+        DataTypeDeclaration::Definition { data_type, location, scope, linkage } => {
+            let data_type = match data_type.as_ref() {
+                DataType::ArrayType { name, bounds, referenced_type, is_variable_length } => DataType::ArrayType {
+                    name: name.clone(),
+                    bounds: bounds.clone(),
+                    referenced_type: Box::new(substitute_generic_types(referenced_type, bindings)),
+                    is_variable_length: *is_variable_length,
+                },
+                other => other.clone(),
+            };
+            DataTypeDeclaration::Definition { data_type: Box::new(data_type), location: location.clone(), scope: scope.clone(), linkage: *linkage }
+        },
+        other => other.clone(),
+    }
+}
+
+/// The ST text of a body with its comments and line endings written as configured
+/// The ST body of a POU or action as exported: copied from the source or printed from its statements, with the calls
+/// of generic POUs renamed and its comments and line endings rewritten. None if there is no source to copy it from
+fn exported_body_text(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, implementation: &Implementation, diagnostics: &mut Vec<Diagnostic>) -> Option<String> {
+    let text = match generation_parameters.canonical_bodies {
+        true => print_body(generation_parameters, target, &implementation.name, &implementation.statements, diagnostics),
+        false => source_text(&generation_parameters.sources, &implementation.location)?,
+    };
+
+    let text = match generation_parameters.monomorphize_generics && !generation_parameters.canonical_bodies {
+        true => rename_generic_calls(text, &implementation.location, &generation_parameters.generic_calls),
+        false => text,
+    };
+    Some(finish_body_text(generation_parameters, target, &text))
+}
+
+fn finish_body_text(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, text: &str) -> String {
+    let text = rewrite_body_comments(text, generation_parameters.body_comments, &|comment| target.format_body_comment(comment));
+    normalize_line_endings(&text, generation_parameters.line_endings)
+}
+
+/// The statements printed as ST, with the generic POU of every call replaced by the called instance when exporting
+/// the instances, and the functions called by the names of the target. Calls of functions the target has no
+/// counterpart of are added to the given diagnostics
+fn print_body(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, pou: &str, statements: &[AstNode], diagnostics: &mut Vec<Diagnostic>) -> String {
+    let rename = |location: &SourceLocation| match generation_parameters.monomorphize_generics {
+        true => generation_parameters.generic_calls.iter()
+            .find(|it| it.location == *location)
+            .map(|it| generic_instance_name(&it.generic, &it.types)),
+        false => None,
+    };
+    let unmapped_calls = RefCell::new(Vec::new()); //the printer only lends the callback its names
+    let function_name = |function: &str, location: &SourceLocation| match generation_parameters.exported_function_name(target, function)? {
+        Some(name) => Some(name),
+        None => {
+            unmapped_calls.borrow_mut().push(Diagnostic::new(format!("'{pou}' calls '{function}', which the {} target has no counterpart of, so the call is exported unchanged", target.name()))
+                .with_error_code("E126")
+                .with_location(location));
+            None
+        },
+    };
+    let text = StPrinter::new(&generation_parameters.real_format, &rename, &function_name)
+        .with_case(generation_parameters.keyword_case, generation_parameters.identifier_case)
+        .print_statements(statements);
+    diagnostics.extend(unmapped_calls.into_inner());
+    text
+}
+
+/// The body with the generic POU of every call inside of it replaced by the called instance
+fn rename_generic_calls(mut procedure_text: String, location: &SourceLocation, generic_calls: &[GenericCall]) -> String {
+    let Some(body_range) = location.to_range() else {
+        return procedure_text;
+    };
+
+    let mut calls: Vec<(Range<usize>, String)> = generic_calls.iter()
+        .filter(|it| it.location.get_file_name() == location.get_file_name())
+        .filter_map(|it| Some((it.location.to_range()?, generic_instance_name(&it.generic, &it.types))))
+        .filter(|(range, _)| range.start >= body_range.start && range.end <= body_range.end)
+        .collect();
+    calls.sort_by_key(|(range, _)| std::cmp::Reverse(range.start)); //back to front, so the earlier offsets stay valid
+    calls.dedup_by(|a, b| a.0 == b.0);
+
+    for (range, name) in calls {
+        let relative = range.start - body_range.start..range.end - body_range.start;
+        if procedure_text.get(relative.clone()).is_some() {
+            procedure_text.replace_range(relative, &name);
+        }
+    }
+    procedure_text
+}
+
+/// The building blocks of a POU element, assembled by [`XmlTarget::emit_pou`]
+pub struct PouElements {
+    pub name: String,
+    pub kind: PouType,
+    /// The access specifier of a method, e.g. `PUBLIC`
+    pub access: Option<AccessModifier>,
+    /// Whether a method overrides the one of the base class
+    pub overriding: bool,
+    /// The function block or class this one extends
+    pub extends: Option<String>,
+    /// The interfaces a class or function block implements
+    pub implements: Vec<String>,
+    /// Whether the function or function block has the implicit `EN` input and `ENO` output
+    pub en_eno: bool,
+    pub add_data: Option<SOmronAddData>,
+    /// The comment above the POU's declaration
+    pub documentation: Option<String>,
+    pub result_type: SResultType,
+    pub parameters: SParameters,
+    pub externals: SExternalVars,
+    pub constant_externals: SExternalVars,
+    pub vars: SVars,
+    pub constant_vars: SVars,
+    pub retain_vars: SVars,
+    pub constant_retain_vars: SVars,
+    /// `<Vars>` with retention attributes besides `retain`, e.g. `persistent`. Only targets writing such attributes
+    /// have any
+    pub retention_vars: Vec<Node>,
+    pub temp_vars: STempVars,
+    pub constant_temp_vars: STempVars,
+    pub main_body: SMainBody,
+    /// The emitted `<Method>` and `<Property>` elements of a class, function block or program
+    pub methods: Vec<Node>,
+    /// The `<action>` elements of a program, function or function block
+    pub actions: Vec<Node>,
+}
+
+impl PouElements {
+    /// The sections of variables the kind of POU is assembled with, in the order [`PouElements::assemble`] writes them
+    fn variable_sections(kind: &PouType) -> &'static [VariableSection] {
+        use VariableSection::*;
+
+        match kind {
+            PouType::Program => &[Externals, ConstantExternals, Vars, ConstantVars, RetainVars, ConstantRetainVars, RetentionVars, TempVars, ConstantTempVars],
+            PouType::Class => &[Externals, ConstantExternals, Vars, ConstantVars, RetainVars, ConstantRetainVars, RetentionVars],
+            PouType::Method { .. } => &[Inputs, Inouts, Outputs, Externals, ConstantExternals, Vars, ConstantVars, TempVars, ConstantTempVars],
+            PouType::Function => &[Inputs, Inouts, Outputs, Externals, ConstantExternals, TempVars, ConstantTempVars],
+            _ => &[Inputs, Inouts, Outputs, Externals, ConstantExternals, Vars, RetentionVars],
+        }
+    }
+
+    /// Assembles the elements in the order of the IEC 61131-10 schema
+    pub fn assemble(self) -> Option<Node> {
+        let mut pou_node = match self.kind {
+            PouType::Program => SProgram::new().inner(),
+            PouType::Function => SFunction::new().inner(),
+            PouType::FunctionBlock => SFunctionBlock::new().inner(),
+            PouType::Class => SClass::new().inner(),
+            PouType::Method { .. } => SMethod::new().inner(),
+            _ => {
+                return None
+            }
+        };
+        pou_node = pou_node.attribute(String::from("name"), self.name);
+
+        if let Some(access) = self.access {
+            pou_node = pou_node.attribute_str("accessSpecifier", access_specifier(access));
+        }
+
+        if self.overriding {
+            pou_node = pou_node.attribute_bool("override", true);
+        }
+
+        if let Some(add_data) = &self.add_data {
+            pou_node = pou_node.child(add_data);
+        }
+
+        if let Some(documentation) = self.documentation {
+            pou_node = pou_node.child(&SDocumentation::new().content(documentation));
+        }
+
+        if let Some(base) = self.extends {
+            pou_node = pou_node.child(&SExtends::new().attribute(String::from("name"), base).close());
+        }
+
+        for interface in self.implements {
+            pou_node = pou_node.child(&SImplements::new().attribute(String::from("name"), interface).close());
+        }
+
+        let methods: Vec<Box<dyn IntoNode>> = self.methods.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect();
+
+        let pou_node = match self.kind {
+            PouType::Program => {
+                pou_node
+                    .child(&self.externals)
+                    .child(&self.constant_externals)
+                    .child(&self.vars)
+                    .child(&self.constant_vars)
+                    .child(&self.retain_vars)
+                    .child(&self.constant_retain_vars)
+                    .children(self.retention_vars.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect())
+                    .child(&self.temp_vars)
+                    .child(&self.constant_temp_vars)
+                    .child(&self.main_body)
+                    .children(methods)
+            },
+            PouType::Class => {
+                pou_node
+                    .child(&self.externals)
+                    .child(&self.constant_externals)
+                    .child(&self.vars)
+                    .child(&self.constant_vars)
+                    .child(&self.retain_vars)
+                    .child(&self.constant_retain_vars)
+                    .children(self.retention_vars.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect())
+                    .children(methods)
+            },
+            PouType::Method { .. } => {
+                let result_type = self.result_type.inner();
+                let pou_node = match result_type.children.is_empty() {
+                    true => pou_node,
+                    false => pou_node.child(&result_type),
+                };
+
+                pou_node
+                    .child(&self.parameters)
+                    .child(&self.externals)
+                    .child(&self.constant_externals)
+                    .child(&self.vars)
+                    .child(&self.constant_vars)
+                    .child(&self.temp_vars)
+                    .child(&self.constant_temp_vars)
+                    .child(&self.main_body)
+            },
+            PouType::Function => {
+                pou_node
+                    .child(&self.result_type)
+                    .child(&self.parameters)
+                    .child(&self.externals)
+                    .child(&self.constant_externals)
+                    .child(&self.temp_vars)
+                    .child(&self.constant_temp_vars)
+                    .child(&self.main_body)
+            },
+            _ => {
+                pou_node
+                    .child(&self.parameters)
+                    .child(&self.externals)
+                    .child(&self.constant_externals)
+                    .child(&self.vars)
+                    .children(self.retention_vars.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect())
+                    .child(&self.main_body)
+                    .children(methods)
+            }
+        };
+
+        //actions follow the body and methods of the POU they belong to
+        let pou_node = match self.actions.is_empty() {
+            true => pou_node,
+            false => pou_node.child(&SActions::new().children(boxed_nodes(self.actions))),
+        };
+        Some(pou_node)
+    }
 }
 
-pub fn generate_pous(generation_parameters: &GenerationParameters, current_unit: &CompilationUnit, schema_path: &'static str, param_order: &mut HashSet<(String, usize)>, output_root: &mut Node) -> Result<(), ()> {
-    let maybe_types_root: Option<&mut Node> = output_root.children.iter_mut().find(|a| a.name == TYPES);
-    let types_root: &mut Node = maybe_types_root.ok_or(())?;
-    let maybe_global_root: Option<&mut Node> = types_root.children.iter_mut().find(|a| a.name == GLOBAL_NAMESPACE);
-    let global_root: &mut Node = maybe_global_root.ok_or(())?;
+/// The building blocks of an interface element, assembled by [`XmlTarget::emit_interface`]
+pub struct InterfaceElements {
+    pub name: String,
+    /// The comment above the interface's declaration
+    pub documentation: Option<String>,
+    /// The interfaces it extends
+    pub extends: Vec<String>,
+    /// The `<Method>` and `<Property>` prototypes, without bodies
+    pub methods: Vec<Node>,
+}
 
-    for a in 0..current_unit.implementations.len() {
-        let current_impl = &current_unit.implementations[a];
-        let matching_metadata = current_unit.pous.iter().find(|a| a.name == current_impl.name).expect("pou metadata matching the current implementation");
+impl InterfaceElements {
+    pub fn assemble(self) -> Node {
+        let mut interface_node = SInterfaceDecl::new()
+            .attribute(String::from("name"), self.name);
 
-        if current_impl.pou_type != PouType::Program && current_impl.pou_type != PouType::Function && current_impl.pou_type != PouType::FunctionBlock { 
-            continue; //currently the only POUs that are supported for xml generation
+        if let Some(documentation) = self.documentation {
+            interface_node = interface_node.child(&SDocumentation::new().content(documentation));
         }
 
-        if current_impl.linkage == LinkageType::External { //discard externally linked POUs since the receiving platform will have those implemented already
-            continue;
+        for extended in self.extends {
+            interface_node = interface_node.child(&SExtends::new().attribute(String::from("name"), extended).close());
         }
+        interface_node.children(self.methods.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect()).inner()
+    }
+}
 
-        let procedure_text = match &current_impl.location.span {
-            CodeSpan::Range(inner_range) => {
-                match current_impl.location.file {
-                    plc_source::source_location::FileMarker::File(file_path) => {
-                        match grab_file_statement_from_span(file_path, &inner_range) {
-                            Some(pou_procedure_text) => pou_procedure_text,
-                            None => {
-                                continue;
-                            },
-                        }
-                    },
-                    _ => {
-                        continue; //don't parse FileMarkers that didn't come from ST files
-                    }
-                }
-            },
-            _ => {
-                continue; //dont parse CodeSpans that aren't Ranges
-            }
-        };
+/// The building blocks of a property element, assembled by [`XmlTarget::emit_property`]
+pub struct PropertyElements {
+    pub name: String,
+    /// The `<Type>` of the property
+    pub property_type: SType,
+    /// The property as the local of its getter and the input of its setter
+    pub variable: SGenVariable,
+    pub getter: Option<PropertyAccessor>,
+    pub setter: Option<PropertyAccessor>,
+}
 
-        let info_node = SPouInfo::new()
-            .attribute_str("version", "0.0.0")
-            .attribute(String::from("creationDateTime"), Local::now().to_rfc3339());
+/// The `GET` or `SET` of a property. Those of interfaces have neither variables nor a body
+pub struct PropertyAccessor {
+    /// The `<Vars>` and `<TempVars>` declared inside of it
+    pub vars: Vec<Node>,
+    pub body: Option<String>,
+}
 
-        let data_node = SOmronData::new() //<Data>
-            .attribute_str("name", schema_path)
-            .attribute_str("handleUnknown", "discard")
-            .child(&info_node);
+impl PropertyElements {
+    /// `<Property>` with its `<Type>`, followed by `<Get>` and `<Set>` holding their variables and bodies
+    pub fn assemble(self) -> Node {
+        let mut property_node = SProperty::new()
+            .attribute(String::from("name"), self.name)
+            .child(&self.property_type);
 
-        let adddata_node = SOmronAddData::new() //<AddData>
-            .child(&data_node);
+        if let Some(getter) = self.getter {
+            property_node = property_node.child(&getter.assemble(SGet::new().inner()));
+        }
 
-        let mut resulttype_node = SResultType::new(); //<ResultType>
+        if let Some(setter) = self.setter {
+            property_node = property_node.child(&setter.assemble(SSet::new().inner()));
+        }
+        property_node.inner()
+    }
 
-        let mut typename_node = STypeName::new();
+    /// The getter and setter as the methods `__get_<name>` and `__set_<name>`. The getter returns the property's
+    /// local, which its body assigns, the setter receives the property as its only input
+    pub fn accessor_methods(self) -> Vec<Node> {
+        let mut methods = Vec::new();
+
+        if let Some(getter) = self.getter {
+            let name = format!("__get_{}", self.name);
+            let result_type = SResultType::new().children(boxed_nodes(self.property_type.inner().children));
+            let mut method_node = SMethod::new()
+                .attribute(String::from("name"), name.clone())
+                .child(&result_type);
+
+            if let Some(body) = getter.body {
+                let result = format!("{name} := {};", self.name);
+                let body = if body.is_empty() { result } else { format!("{body}\n{result}") };
+
+                method_node = method_node
+                    .child(&SVars::new().attribute_str("accessSpecifier", "private").child(&self.variable))
+                    .children(boxed_nodes(getter.vars))
+                    .child(&st_main_body(body));
+            }
+            methods.push(method_node.inner());
+        }
+
+        if let Some(setter) = self.setter {
+            let input = self.variable.attribute(String::from("orderWithinParamSet"), String::from("0"));
+            let mut method_node = SMethod::new()
+                .attribute(String::from("name"), format!("__set_{}", self.name))
+                .child(&SParameters::new().child(&SInputVars::new().child(&input)));
 
-        if (current_impl.pou_type == PouType::Function || current_impl.pou_type == PouType::FunctionBlock) && 
-            let Some(result_type) = &matching_metadata.return_type && let Some(type_name) = result_type.get_name() {
-            typename_node = typename_node.content(String::from(type_name));                
+            if let Some(body) = setter.body {
+                method_node = method_node
+                    .children(boxed_nodes(setter.vars))
+                    .child(&st_main_body(body));
+            }
+            methods.push(method_node.inner());
         }
+        methods
+    }
+}
 
-        else {
-            typename_node = typename_node.content(String::from("BOOL")); //default to boolean output
+impl PropertyAccessor {
+    fn assemble(self, element: Node) -> Node {
+        let element = element.children(boxed_nodes(self.vars));
+
+        match self.body {
+            Some(body) => element.child(&st_main_body(body)),
+            None => element,
         }
+    }
+}
 
-        resulttype_node = resulttype_node.child(&typename_node);
+fn boxed_nodes(nodes: Vec<Node>) -> Vec<Box<dyn IntoNode>> {
+    nodes.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect()
+}
 
-        //<Parameters>
-        let mut input_vars = SInputVars::new();
-        let mut inout_vars = SInoutVars::new();
-        let mut output_vars = SOutputVars::new();
-        let mut parameters_node = SParameters::new();
+/// `<MainBody>` holding the given Structured Text
+fn st_main_body(procedure_text: String) -> SMainBody {
+    let mut st_element = SST::new(); //<ST>
 
-        //<ExternalVars>
-        let mut externals = SExternalVars::new();
+    if !procedure_text.is_empty() {
+        st_element = st_element.content(procedure_text);
+    }
 
-        let mut constant_externals = SExternalVars::new()
-            .attribute_str("constant", "true");
+    let body_content = SBodyContent::new()
+        .attribute_str("xsi:type", "ST")
+        .child(&st_element);
 
-        //<Vars>
-        let mut vars = SVars::new()
-            .attribute_str("accessSpecifier", "private");
+    SMainBody::new()
+        .child(&body_content)
+}
 
-        let mut constant_vars = SVars::new()
-            .attribute_str("accessSpecifier", "private")
-            .attribute_str("constant", "true");
+/// A body of the given graphical language, e.g. an FBD network
+fn graphical_main_body(language: &'static str, body: &dyn IntoNode) -> SMainBody {
+    let body_content = SBodyContent::new()
+        .attribute_str("xsi:type", language)
+        .child(body);
 
-        let mut retain_vars = SVars::new()
-            .attribute_str("accessSpecifier", "private")
-            .attribute_str("retain", "true");
+    SMainBody::new()
+        .child(&body_content)
+}
 
-        let mut constant_retain_vars = SVars::new()
-            .attribute_str("accessSpecifier", "private")
-            .attribute_str("constant", "true")
-            .attribute_str("retain", "true");
+/// The elements of a property of a POU, or without bodies the prototype of a property of an interface
+fn generate_property_elements(property: &PropertyBlock, parent: &str, with_bodies: bool, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, diagnostics: &mut Vec<Diagnostic>) -> Option<PropertyElements> {
+    let property_type = generate_type_element(&property.datatype, generation_parameters, target)?;
 
-        //<TempVars>
-        let mut temp_vars = STempVars::new();
+    let variable = Variable {
+        name: property.ident.name.clone(),
+        data_type_declaration: property.datatype.clone(),
+        initializer: None,
+        address: None,
+        location: SourceLocation::internal(),
+    };
+    let network_publish = NetworkPublishMode::DoNotPublish.to_string();
+    let variable = generate_variable_element(&variable, generation_parameters, target, enums, Some(parent), network_publish, None)?;
+
+    let mut accessor = |kind: PropertyKind| {
+        property.implementations.iter().find(|it| it.kind == kind).map(|it| PropertyAccessor {
+            vars: match with_bodies {
+                true => generate_accessor_vars(&it.variable_blocks, parent, generation_parameters, target, enums),
+                false => Vec::new(),
+            },
+            body: with_bodies.then(|| finish_body_text(generation_parameters, target, &accessor_body(it, parent, generation_parameters, target, diagnostics))),
+        })
+    };
 
-        let mut constant_temp_vars = STempVars::new()
-            .attribute_str("constant", "true");
+    Some(PropertyElements {
+        name: property.ident.name.clone(),
+        property_type,
+        variable,
+        getter: accessor(PropertyKind::Get),
+        setter: accessor(PropertyKind::Set),
+    })
+}
 
-        //put all the variables in the right containers
-        for b in 0..matching_metadata.variable_blocks.len() {
-            let current_block = &matching_metadata.variable_blocks[b];
+/// The `<Vars>` and `<TempVars>` of a property's `GET` or `SET`, one per non-empty block and the locals first
+fn generate_accessor_vars(blocks: &[VariableBlock], parent: &str, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums) -> Vec<Node> {
+    let mut vars = Vec::new();
+    let mut temp_vars = Vec::new();
 
-            for c in 0..current_block.variables.len() {
-                let current_variable = &current_block.variables[c];
-                let use_order_attr = current_block.kind != VariableBlockType::Local && current_block.kind != VariableBlockType::External;
+    for current_block in blocks {
+        let variables: Vec<Box<dyn IntoNode>> = current_block.variables.iter()
+            .filter_map(|it| generate_variable_element(it, generation_parameters, target, enums, Some(parent), NetworkPublishMode::DoNotPublish.to_string(), None))
+            .map(|it| Box::new(it) as Box<dyn IntoNode>)
+            .collect();
 
-                if current_variable.location.span == CodeSpan::None {
-                    continue; //discard compiler interally generated variables
-                }
+        if variables.is_empty() {
+            continue;
+        }
 
-                let network_publish = match current_block.kind {
-                    _ => String::from("DoNotPublish")
-                };
+        let (element, container) = match current_block.kind {
+            VariableBlockType::Local => (SVars::new().attribute_str("accessSpecifier", "private").inner(), &mut vars),
+            VariableBlockType::Temp => (STempVars::new().inner(), &mut temp_vars),
+            _ => {
+                continue; //accessors have no parameters of their own
+            }
+        };
 
-                let maybe_variablenode = generate_variable_element(current_variable, generation_parameters, &matching_metadata.name, schema_path, network_publish, param_order, c, use_order_attr);
+        let element = match current_block.constant {
+            true => element.attribute_bool("constant", true),
+            false => element,
+        };
+        container.push(element.children(variables));
+    }
 
-                let variable_node = match maybe_variablenode {
-                    Some(a) => a,
-                    None => { continue; },
-                };
+    vars.extend(temp_vars);
+    vars
+}
 
-                match current_block.kind {
-                    VariableBlockType::Local => {
-                        if current_block.constant && current_block.retain {
-                            constant_retain_vars = constant_retain_vars.child(&variable_node);
-                        }
+/// The source text of the statements of a property's `GET` or `SET`
+fn accessor_body(implementation: &PropertyImplementation, parent: &str, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, diagnostics: &mut Vec<Diagnostic>) -> String {
+    if generation_parameters.canonical_bodies {
+        return print_body(generation_parameters, target, parent, &implementation.body, diagnostics);
+    }
 
-                        else if current_block.constant {
-                            constant_vars = constant_vars.child(&variable_node);
-                        }
+    let (Some(first), Some(last)) = (implementation.body.first(), implementation.body.last()) else {
+        return String::new();
+    };
 
-                        else if current_block.retain {
-                            retain_vars = retain_vars.child(&variable_node);
-                        }
+    source_text(&generation_parameters.sources, &first.location.span(&last.location)).unwrap_or_default()
+}
 
-                        else {
-                            vars = vars.child(&variable_node);
-                        }
-                    },
-                    VariableBlockType::Temp => {
-                        if current_block.constant {
-                            constant_temp_vars = constant_temp_vars.child(&variable_node);
-                        }
+/// Generates an `<Interface>` per `INTERFACE` of the unit, so the POUs implementing it can be built after the import
+pub fn generate_interfaces(generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, current_unit: &CompilationUnit, output_root: &mut Node) -> Result<(), Error> {
+    let global_root = document_element(output_root, GLOBAL_NAMESPACE_PATH)?;
 
-                        else {
-                            temp_vars = temp_vars.child(&variable_node);
-                        }
-                    },
-                    VariableBlockType::Input(_) => {
-                        input_vars = input_vars.child(&variable_node);
-                    },
-                    VariableBlockType::Output => {
-                        output_vars = output_vars.child(&variable_node);
-                    },
-                    VariableBlockType::InOut => {
-                        inout_vars = inout_vars.child(&variable_node);
-                    },
-                    VariableBlockType::External => {
-                        if current_block.constant {
-                            constant_externals = constant_externals.child(&variable_node);                            
-                        }
+    if !target.supports_interfaces() {
+        return Ok(());
+    }
 
-                        else {
-                            externals = externals.child(&variable_node);
-                        }
-                    },
-                    _ => ()
-                }
-            }
+    for current_interface in &current_unit.interfaces {
+        if current_interface.location.span == CodeSpan::None {
+            continue; //discard internally generated interfaces
         }
 
-        parameters_node = parameters_node.child(&input_vars)
-            .child(&inout_vars)
-            .child(&output_vars);
+        let mut methods: Vec<Node> = current_interface.methods.iter()
+            .filter(|it| matches!(it.kind, PouType::Method { property: None, .. })) //methods of properties are no methods of their own
+            .map(|it| generate_method_prototype(it, &current_interface.ident.name, generation_parameters, target, enums))
+            .collect();
 
-        //implementation statements
-        let mut st_element = SST::new(); //<ST>
+        for property in &current_interface.properties {
+            //prototypes have no bodies, so there is nothing to report
+            if let Some(property_elements) = generate_property_elements(property, &current_interface.ident.name, false, generation_parameters, target, enums, &mut Vec::new()) {
+                methods.extend(target.emit_property(property_elements));
+            }
+        }
+
+        let interface_elements = InterfaceElements {
+            name: current_interface.ident.name.clone(),
+            documentation: leading_comment(&generation_parameters.sources, &current_interface.location),
+            extends: current_interface.extensions.iter().map(|it| it.name.clone()).collect(),
+            methods,
+        };
 
-        if procedure_text.len() > 0 {
-            st_element = st_element.content(procedure_text);
+        if let Some(interface_node) = target.emit_interface(interface_elements) {
+            global_root.children.push(interface_node);
         }
+    }
+    Ok(())
+}
 
-        let body_content = SBodyContent::new()
-            .attribute_str("xsi:type", "ST")
-            .child(&st_element);
+/// The `<Method>` of an interface: its result and parameters
+fn generate_method_prototype(method: &Pou, parent: &str, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums) -> Node {
+    let mut method_node = SMethod::new()
+        .attribute(String::from("name"), String::from(unqualified_method_name(&method.name, parent)));
 
-        let main_body = SMainBody::new()
-            .child(&body_content);
+    if let Some(type_name) = method.return_type.as_ref().and_then(declared_type_name) {
+        let typename_node = STypeName::new().content(target.map_type_name(generation_parameters, &type_name));
+        method_node = method_node.child(&SResultType::new().child(&typename_node));
+    }
 
-        let name_key = String::from("name");
-        let name_value = current_impl.name.clone();
+    let mut input_vars = SInputVars::new();
+    let mut inout_vars = SInoutVars::new();
+    let mut output_vars = SOutputVars::new();
+    let mut parameter_order: usize = 0;
 
-        let chosen_element: &dyn IntoNode = match current_impl.pou_type {
-            PouType::Program => {
-                &SProgram::new()
-                    .attribute(name_key, name_value)
-                    .child(&adddata_node)
-                    .child(&externals)
-                    .child(&constant_externals)
-                    .child(&vars)
-                    .child(&constant_vars)
-                    .child(&retain_vars)
-                    .child(&constant_retain_vars)
-                    .child(&main_body)
-            },
-            PouType::Function => {
-                &SFunction::new()
-                    .attribute(name_key, name_value)
-                    .child(&adddata_node)
-                    .child(&resulttype_node)
-                    .child(&parameters_node)                    
-                    .child(&externals)
-                    .child(&constant_externals)
-                    .child(&temp_vars)
-                    .child(&constant_temp_vars)
-                    .child(&main_body)
-            },
-            PouType::FunctionBlock => {
-                &SFunctionBlock::new()
-                    .attribute(name_key, name_value)
-                    .child(&adddata_node)
-                    .child(&parameters_node)
-                    .child(&externals)
-                    .child(&constant_externals)
-                    .child(&vars)
-                    .child(&main_body)
-            },
-            _ => {
-                return Ok(())
+    for current_block in &method.variable_blocks {
+        for current_variable in &current_block.variables {
+            if !matches!(current_block.kind, VariableBlockType::Input(_) | VariableBlockType::Output | VariableBlockType::InOut) {
+                continue; //only the parameters are part of the prototype
             }
-        };
 
-        global_root.child_borrowed(chosen_element);        
+            let network_publish = NetworkPublishMode::DoNotPublish.to_string();
+            let Some(variable_node) = generate_variable_element(current_variable, generation_parameters, target, enums, Some(&method.name), network_publish, Some(parameter_order)) else {
+                continue;
+            };
+            parameter_order += 1;
+
+            match current_block.kind {
+                VariableBlockType::Input(_) => input_vars = input_vars.child(&variable_node),
+                VariableBlockType::InOut => inout_vars = inout_vars.child(&variable_node),
+                _ => output_vars = output_vars.child(&variable_node),
+            }
+        }
     }
-    Ok(())
-}
 
-///returns the generated element.
-/// add_order - whether to add the "orderWithinParamSet" attribute.
-fn generate_variable_element(current_variable: &Variable, generation_parameters: &GenerationParameters, pou_name: &String, schema_path: &'static str, network_publish: String, preused_order: &mut HashSet<(String, usize)>, order: usize, add_order: bool) -> Option<SGenVariable> {
-    let mut variable_node = SGenVariable::new()
-        .attribute(String::from("name"), current_variable.name.clone());
-    
-    //<AddData>
-    let additional_property_node = SOmronGlobalVariableAdditionalProperties::new()
-        .attribute(String::from("networkPublish"), network_publish);
+    let parameters_node = SParameters::new()
+        .child(&input_vars)
+        .child(&inout_vars)
+        .child(&output_vars);
+
+    method_node.child(&parameters_node).inner()
+}
 
-    let data_node = SOmronData::new() //<Data>
-        .attribute_str("name", schema_path)
-        .attribute_str("handleUnknown", "discard")
-        .child(&additional_property_node);
+/// Methods and actions are named without the POU or interface they belong to, e.g. `start` instead of `Motor.start`
+fn unqualified_method_name<'a>(name: &'a str, parent: &str) -> &'a str {
+    name.strip_prefix(parent).and_then(|it| it.strip_prefix('.')).unwrap_or(name)
+}
 
-    let adddata_node = SOmronAddData::new() //<AddData>
-        .child(&data_node);
+/// The `accessSpecifier` of a method
+fn access_specifier(access: AccessModifier) -> &'static str {
+    match access {
+        AccessModifier::Private => "private",
+        AccessModifier::Public => "public",
+        AccessModifier::Protected => "protected",
+        AccessModifier::Internal => "internal",
+    }
+}
 
-    variable_node = variable_node.child(&adddata_node);
+/// The `<Type>` of a variable or struct member. Inline arrays are described by their element type and dimensions,
+/// since the name generated for them is no valid type
+fn generate_type_element(declaration: &DataTypeDeclaration, generation_parameters: &GenerationParameters, target: &dyn XmlTarget) -> Option<SType> {
+    if let DataTypeDeclaration::Definition { data_type, .. } = declaration && 
+        let DataType::ArrayType { name, bounds, referenced_type, .. } = data_type.as_ref() {
+        let array_children = array_type_children(generation_parameters, target, name.as_deref(), bounds, referenced_type)?;
 
-    //<Type>
-    let maybe_typename = current_variable.data_type_declaration.get_name();
+        let array_node = SArrayType::new() //<ArrayType>
+            .children(array_children);
 
-    let mut typename = match maybe_typename {
-        Some(a) => a,
-        None => { return None; }, //every variable must have a typename
-    };
+        let typenode = SType::new()
+            .child(&array_node);
 
-    if typename.to_lowercase().contains("string") && generation_parameters.output_xml_omron { //string[256] produces a type of __global_testString. This is not a valid type for Omron Sysmac Studio
-        typename = "String[1986]";
+        return Some(typenode);
     }
 
+    let typename = target.map_type_name(generation_parameters, &declared_type_name(declaration)?);
+    Some(type_name_element(typename))
+}
+
+fn type_name_element(typename: String) -> SType {
     let typename_node = STypeName::new() //<TypeName>
-        .content(String::from(typename));
+        .content(typename);
 
-    let typenode = SType::new() //<Type>
-        .child(&typename_node);
+    SType::new() //<Type>
+        .child(&typename_node)
+}
 
-    variable_node = variable_node.child(&typenode);
+///returns the generated element.
+/// order - the "orderWithinParamSet" attribute of parameters, none for other variables.
+fn generate_variable_element(current_variable: &Variable, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, container: Option<&str>, network_publish: String, order: Option<usize>) -> Option<SGenVariable> {
+    let mut variable_node = SGenVariable::new()
+        .attribute(String::from("name"), current_variable.name.clone());
+    
+    //<AddData>
+    if let Some(adddata_node) = target.variable_add_data(&network_publish) {
+        variable_node = variable_node.child(&adddata_node);
+    }
 
-    if add_order {
-        let mut iteration_order: usize = order;
-        let mut increment: usize = 0;
+    //<Type>
+    let published_type = match network_publish != NetworkPublishMode::DoNotPublish.to_string() {
+        true => declared_type_name(&current_variable.data_type_declaration).and_then(|it| generation_parameters.published_bit_string_type(target, &it)),
+        false => None,
+    };
 
-        loop {
-            iteration_order += increment;
-            increment += 1;
-            let key = (pou_name.clone(), iteration_order);
+    let typenode = match published_type {
+        Some(a) => type_name_element(target.map_type_name(generation_parameters, &a)),
+        None => generate_type_element(&current_variable.data_type_declaration, generation_parameters, target)?, //every variable must have a type
+    };
 
-            if preused_order.contains(&key) == false { //an unused order number was found. There cannot be duplicate order numbers for any POU variable
-                preused_order.insert(key);
-                break;
-            }
-        };
-        variable_node = variable_node.attribute(String::from("orderWithinParamSet"), iteration_order.to_string());
+    variable_node = variable_node.child(&typenode);
+
+    if let Some(order) = order {
+        variable_node = variable_node.attribute_usize("orderWithinParamSet", order);
     }
 
     //<InitialValue>
-    if let Some(variable_ast) = &current_variable.initializer && let AstStatement::Literal(literal_value
-    ) = &variable_ast.stmt {
-        let simple_node = SSimpleValue::new()
-            .attribute(String::from("value"), literal_value.to_string())
-            .close();
-
-        let initial_node = SInitialValue::new()
-            .child(&simple_node);
-
+    if let Some(initial_node) = generate_initial_value_element(current_variable, generation_parameters, target, enums, container) {
         variable_node = variable_node.child(&initial_node);
     }                            
 
@@ -666,34 +2800,201 @@ fn generate_variable_element(current_variable: &Variable, generation_parameters:
 
                 variable_node = variable_node.child(&address_node);
             },
+            AstStatement::HardwareAccess(hardware_access) => {
+                if let Ok(formatted_address) = target.format_address(hardware_access) { //unmappable addresses are rejected up front
+                    let address_node = SAddress::new()
+                        .attribute(String::from("address"), formatted_address);
+
+                    variable_node = variable_node.child(&address_node);
+                }
+            },
             _ => () //not every variable has an address
         }
     }
+
+    //<Documentation>
+    if let Some(documentation_node) = generate_documentation_element(&generation_parameters.sources, current_variable) {
+        variable_node = variable_node.child(&documentation_node);
+    }
+    Some(variable_node)
+}
+
+/// A global declared as an instance of a function block, e.g. `gTimer : TON := (PT := T#1s);`. Instances are neither
+/// published on the network nor located, and only initialized by assigning their inputs
+fn generate_instance_element(current_variable: &Variable, generation_parameters: &GenerationParameters, target: &dyn XmlTarget) -> Option<SGenVariable> {
+    let mut variable_node = SGenVariable::new()
+        .attribute(String::from("name"), current_variable.name.clone());
+
+    //<AddData>
+    if let Some(adddata_node) = target.variable_add_data(&NetworkPublishMode::DoNotPublish.to_string()) {
+        variable_node = variable_node.child(&adddata_node);
+    }
+
+    //<Type>
+    variable_node = variable_node.child(&generate_type_element(&current_variable.data_type_declaration, generation_parameters, target)?);
+
+    //<InitialValue>
+    if let Some(struct_node) = current_variable.initializer.as_ref().and_then(|it| generate_struct_value(it, generation_parameters, target)) {
+        variable_node = variable_node.child(&SInitialValue::new().child(&struct_node));
+    }
+
+    //<Documentation>
+    if let Some(documentation_node) = generate_documentation_element(&generation_parameters.sources, current_variable) {
+        variable_node = variable_node.child(&documentation_node);
+    }
     Some(variable_node)
 }
 
-fn grab_file_statement_from_span(file_path: &'static str, range: &Range<TextLocation>) -> Option<String> {
-    let mut file = File::open(file_path).expect(format!("source file exists: {}", file_path).as_str());
-    let unsigned_start = TryInto::<u64>::try_into(range.start.offset).expect("u64");
-    file.seek(SeekFrom::Start(unsigned_start)).expect("seeks to starting offset");
-    let maybe_size = range.end.offset.checked_sub(range.start.offset);
+/// The comment documenting a variable or struct member, preferably the one above it, e.g. `(* in m/s *)` or
+/// `/// in m/s`, otherwise the one following it on the same line
+fn generate_documentation_element(sources: &LoadedSources, current_variable: &Variable) -> Option<SDocumentation> {
+    let documentation = leading_comment(sources, &current_variable.location).or_else(|| trailing_comment(sources, &current_variable.location))?;
+    Some(SDocumentation::new().content(documentation))
+}
+
+/// The initial value of a variable or struct member initialized with a literal, a variant of its enum type or an
+/// expression the driver could fold into a literal. The container is the POU or struct declaring the variable
+pub fn format_initial_value(current_variable: &Variable, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, container: Option<&str>) -> Option<String> {
+    let initializer = current_variable.initializer.as_ref()?;
+
+    if let Some(literal_value) = literal_value(initializer) {
+        return Some(target.format_literal(generation_parameters, &literal_value));
+    }
+
+    let variant = declared_type_name(&current_variable.data_type_declaration)
+        .zip(enum_variant_name(initializer))
+        .and_then(|(type_name, variant_name)| enums.find(&type_name, &variant_name));
+
+    if let Some(variant) = variant {
+        return Some(target.format_enum_value(generation_parameters, variant));
+    }
+
+    let qualified_name = match container {
+        Some(container) => format!("{container}.{}", current_variable.name),
+        None => current_variable.name.clone(),
+    };
+    let folded = generation_parameters.folded_initial_value(&qualified_name)?;
+    Some(target.format_literal(generation_parameters, folded))
+}
+
+fn generate_initial_value_element(current_variable: &Variable, generation_parameters: &GenerationParameters, target: &dyn XmlTarget, enums: &ProjectEnums, container: Option<&str>) -> Option<SInitialValue> {
+    if let Some(struct_node) = current_variable.initializer.as_ref().and_then(|it| generate_struct_value(it, generation_parameters, target)) {
+        return Some(SInitialValue::new().child(&struct_node));
+    }
+
+    let value = format_initial_value(current_variable, generation_parameters, target, enums, container)?;
+
+    let simple_node = SSimpleValue::new()
+        .attribute(String::from("value"), value)
+        .close();
+
+    let initial_node = SInitialValue::new()
+        .child(&simple_node);
+
+    Some(initial_node)
+}
 
-    let size = match maybe_size {
-        Some(a) => a,
-        None => { return None; }, //don't parse statement if it has a negative size
+/// The `<StructValue>` of a struct literal like `(x := 1, y := 2)` with a `<Value>` per member. Members initialized with
+/// anything but a literal or another struct literal are left out, so they keep the default of their type
+fn generate_struct_value(initializer: &AstNode, generation_parameters: &GenerationParameters, target: &dyn XmlTarget) -> Option<SStructValue> {
+    let members = match initializer.get_stmt_peeled() {
+        AstStatement::ExpressionList(members) => members.iter().collect(),
+        AstStatement::Assignment(_) => vec![initializer.get_node_peeled()],
+        _ => return None,
     };
-    let mut buffer = vec![0u8; size];
-    file.read_exact(&mut buffer.as_mut_slice()).expect("reads successfully");
-    let formatted = String::from_utf8(buffer).expect("valid utf8 string");
-    Some(formatted)
+
+    let mut struct_node = SStructValue::new();
+    let mut has_values = false;
+
+    for member in members {
+        let AstStatement::Assignment(assignment) = &member.stmt else {
+            return None; //no struct literal, e.g. the arguments of a call
+        };
+        let member_name = assignment.left.get_flat_reference_name()?;
+
+        let value_node: Box<dyn IntoNode> = match literal_value(&assignment.right) {
+            Some(literal) => Box::new(SSimpleValue::new()
+                .attribute(String::from("value"), target.format_literal(generation_parameters, &literal))
+                .close()),
+            _ => match generate_struct_value(&assignment.right, generation_parameters, target) {
+                Some(nested_node) => Box::new(nested_node),
+                None => { continue; },
+            },
+        };
+
+        let member_node = SValue::new() //<Value>
+            .attribute(String::from("member"), String::from(member_name))
+            .children(vec![value_node]);
+
+        struct_node = struct_node.child(&member_node);
+        has_values = true;
+    }
+
+    match has_values {
+        true => Some(struct_node),
+        false => None,
+    }
+}
+
+/// The literal an initializer spells, also behind prefix operators and parentheses like `-5.5`, `-T#5s` or `NOT TRUE`
+fn literal_value(initializer: &AstNode) -> Option<AstLiteral> {
+    match initializer.get_stmt_peeled() {
+        AstStatement::Literal(literal) => Some(literal.clone()),
+        AstStatement::UnaryExpression(UnaryExpression { operator, value }) => {
+            let literal = literal_value(value)?;
+
+            match (operator, literal) {
+                (Operator::Plus, literal @ (AstLiteral::Integer(_) | AstLiteral::Real(_) | AstLiteral::Time(_))) => Some(literal),
+                (Operator::Minus, AstLiteral::Integer(value)) => Some(AstLiteral::Integer(-value)), //e.g. `-(5)`, the parser negates `-5` itself
+                (Operator::Minus, AstLiteral::Real(value)) => match value.strip_prefix('-') {
+                    Some(positive) => Some(AstLiteral::Real(String::from(positive))),
+                    None => Some(AstLiteral::Real(format!("-{value}"))),
+                },
+                (Operator::Minus, AstLiteral::Time(time)) => Some(AstLiteral::Time(Time { negative: !time.negative, ..time })),
+                (Operator::Not, AstLiteral::Bool(value)) => Some(AstLiteral::Bool(!value)),
+                _ => None, //e.g. NOT on a bit string, whose result depends on the width of the type
+            }
+        },
+        _ => None,
+    }
+}
+
+/// The variant named by an initializer, either plain like `IDLE` or qualified like `State#IDLE`
+fn enum_variant_name(initializer: &AstNode) -> Option<String> {
+    match &initializer.stmt {
+        AstStatement::ReferenceExpr(ReferenceExpr { access: ReferenceAccess::Cast(variant), base: Some(_) }) => variant.get_flat_reference_name().map(String::from),
+        AstStatement::ReferenceExpr(ReferenceExpr { base: None, .. }) => initializer.get_flat_reference_name().map(String::from),
+        _ => None,
+    }
 }
 
 pub fn write_xml_file(output_path: &PathBuf, treenode: Node) -> Result<(), Error> {
+    write_formatted_xml_file(output_path, &[], &SerializerOptions::default(), treenode)
+}
+
+/// Reads a document written by [`write_xml_file`] back into its tree
+pub fn read_xml_file(input_path: &Path) -> Result<Node, Error> {
+    Node::parse(BufReader::new(File::open(input_path)?))
+}
+
+/// Writes the document like [`write_xml_file`], formatted as the options ask for and with the given processing
+/// instructions and comments between the XML declaration and the root element
+pub fn write_formatted_xml_file(output_path: &PathBuf, prolog: &[Node], options: &SerializerOptions, treenode: Node) -> Result<(), Error> {
     let file = File::create(output_path)?;
+    write_xml_document(file, prolog, options, treenode)
+}
+
+/// Writes the XML declaration and the prolog followed by the tree into the given sink, e.g. a buffer, a socket or a
+/// compressing stream. The document is the same as the one written by [`write_formatted_xml_file`]
+pub fn write_xml_document<W: Write>(sink: W, prolog: &[Node], options: &SerializerOptions, treenode: Node) -> Result<(), Error> {
+    let mut writer = start_xml_document(sink, prolog, options)?;
+    return recurse_write_xml(&mut writer, treenode);
+}
 
-    let mut writer = EmitterConfig::new()
-        .perform_indent(true)
-        .create_writer(file);
+/// Creates the writer of a document and writes its XML declaration, followed by the processing instructions and comments
+/// of the prolog
+pub fn start_xml_document<W: Write>(sink: W, prolog: &[Node], options: &SerializerOptions) -> Result<EventWriter<W>, Error> {
+    let mut writer = emitter_config(options).create_writer(sink);
 
     let top = XmlEvent::StartDocument {
         encoding: Some("UTF-8"),
@@ -701,15 +3002,31 @@ pub fn write_xml_file(output_path: &PathBuf, treenode: Node) -> Result<(), Error
         standalone: None
     };
 
-    let _ = writer.write(top).or_else(|a| {
-        return Err(Error::new(std::io::ErrorKind::Other, a));
-    });    
+    writer.write(top).map_err(Error::other)?;
+    for node in prolog {
+        recurse_write_xml(&mut writer, node.clone())?;
+    }
+    Ok(writer)
+}
+
+/// Writes the element and everything below it the way the document contains it, without the XML declaration
+pub fn write_xml_element<W: Write>(sink: W, options: &SerializerOptions, treenode: Node) -> Result<(), Error> {
+    let mut writer = emitter_config(options)
+        .write_document_declaration(false)
+        .create_writer(sink);
+    recurse_write_xml(&mut writer, treenode)
+}
 
-    return recurse_write_xml(&mut writer, output_path, treenode);
+fn emitter_config(options: &SerializerOptions) -> EmitterConfig {
+    EmitterConfig::new()
+        .perform_indent(options.indent.is_some())
+        .indent_string(options.indent.clone().unwrap_or_default())
+        .line_separator(options.line_separator.clone())
+        .pad_self_closing(options.pad_self_closing)
 }
 
-fn recurse_write_xml(writer: &mut EventWriter<File>, output_path: &PathBuf, mut treenode: Node) -> Result<(), Error> {
-    //open the element
+/// Opens the element of the node, without writing its content or children
+pub(crate) fn write_start_element<W: Write>(writer: &mut EventWriter<W>, treenode: &Node) -> Result<(), Error> {
     let start = XmlEvent::StartElement {
         name: Name::from(treenode.name.as_str()),
         attributes: treenode.attributes.iter().map(|a| {
@@ -721,34 +3038,69 @@ fn recurse_write_xml(writer: &mut EventWriter<File>, output_path: &PathBuf, mut
         .collect(), 
         namespace: Cow::Owned(Namespace::empty())
     };
+    writer.write(start).map_err(Error::other)
+}
 
-    let _ = writer.write(start).or_else(|a| {
-        return Err(Error::new(std::io::ErrorKind::Other, a));
-    });
+/// Closes the element opened last
+pub(crate) fn write_end_element<W: Write>(writer: &mut EventWriter<W>) -> Result<(), Error> {
+    writer.write(XmlEvent::end_element()).map_err(Error::other)
+}
 
-    if let Some(content) = &treenode.content && treenode.children.len() == 0 {
-        let content_event = XmlEvent::CData(content);
+/// Writes the node and everything below it with a writer created by [`start_xml_document`]
+pub fn recurse_write_xml<W: Write>(writer: &mut EventWriter<W>, mut treenode: Node) -> Result<(), Error> {
+    if treenode.kind == NodeKind::Comment {
+        let text = treenode.content.unwrap_or_default();
+        return writer.write(XmlEvent::Comment(&text)).map_err(Error::other);
+    }
+    if treenode.kind == NodeKind::ProcessingInstruction {
+        let instruction = XmlEvent::ProcessingInstruction { name: &treenode.name, data: treenode.content.as_deref() };
+        return writer.write(instruction).map_err(Error::other);
+    }
+    if treenode.kind == NodeKind::Text {
+        return writer.write(XmlEvent::Characters(treenode.content.as_deref().unwrap_or_default())).map_err(Error::other);
+    }
+    write_start_element(writer, &treenode)?;
 
-        let _ = writer.write(content_event).or_else(|a| {
-            return Err(Error::new(std::io::ErrorKind::Other, a));
-        });
+    if let Some(content) = &treenode.content {
+        for section in cdata_sections(content) {
+            writer.write(XmlEvent::CData(&section)).map_err(Error::other)?;
+        }
     }
 
+    //the writer does not indent markup following text, so empty text keeps it from adding whitespace to mixed content
+    let mixed = treenode.is_mixed();
+    let keep_unindented = |writer: &mut EventWriter<W>| match mixed {
+        true => writer.write(XmlEvent::Characters("")).map_err(Error::other),
+        false => Ok(()),
+    };
+
     //recurse through children
     for item in treenode.children.drain(0..) {
-        recurse_write_xml(writer, output_path, item)?;
+        keep_unindented(writer)?;
+        recurse_write_xml(writer, item)?;
     }
+    keep_unindented(writer)?;
+    write_end_element(writer)
+}
 
-    //close the element
-    let end = XmlEvent::end_element();
-
-    let _ = writer.write(end).or_else(|a| {
-        return Err(Error::new(std::io::ErrorKind::Other, a));
-    });
-    Ok(())
+/// The content split into CDATA sections which can be written as they are. A section ends at the first `]]>`, so every
+/// `]]>` of the content, e.g. in a comment of a body, is split across two sections as `]]` and `>`
+fn cdata_sections(content: &str) -> Vec<String> {
+    let parts: Vec<&str> = content.split("]]>").collect();
+    let last = parts.len() - 1;
+
+    parts.iter().enumerate()
+        .map(|(index, part)| {
+            let start = if index == 0 { "" } else { ">" };
+            let end = if index == last { "" } else { "]]" };
+            format!("{start}{part}{end}")
+        })
+        .collect()
 }
 
-pub fn copy_xmlfile_to_output(temp_paths: Vec<&Path>, output_path: PathBuf) -> Result<PathBuf, Error> {
+/// Copies the generated document to the output location.
+/// When several targets were generated, the file of every target is copied to its [`target_output_path`].
+pub fn copy_xmlfile_to_output(temp_paths: Vec<&Path>, output_path: PathBuf, target_names: &[String]) -> Result<PathBuf, Error> {
     if temp_paths.len() == 0 {
         return Ok(output_path);
     }
@@ -760,6 +3112,38 @@ pub fn copy_xmlfile_to_output(temp_paths: Vec<&Path>, output_path: PathBuf) -> R
     })
     .unwrap(); 
 
-    copy(xml_file, &output_path)?;
+    //the side outputs are written next to the base path, whichever targets were generated
+    for (side_output, copied_side_output) in side_output_paths(xml_file).into_iter().zip(side_output_paths(&output_path)) {
+        if side_output.exists() {
+            copy(side_output, copied_side_output)?;
+        }
+    }
+
+    if target_names.len() > 1 {
+        for name in target_names {
+            copy_document(&target_output_path(xml_file, name), &target_output_path(&output_path, name))?;
+        }
+        return Ok(output_path);
+    }
+
+    if !xml_file.exists() {
+        return Ok(output_path); //only side outputs were generated
+    }
+
+    copy_document(xml_file, &output_path)?;
     Ok(output_path)
 }
+
+/// Copies a document together with the outputs written alongside it: its archive, if one was packaged, its rename
+/// report and source map and the folder it was split into
+fn copy_document(document_path: &Path, output_path: &Path) -> Result<(), Error> {
+    copy(document_path, output_path)?;
+
+    for package_path in [archive_path, rename_report_path, source_map_path] {
+        let package = package_path(document_path);
+        if package.exists() {
+            copy(package, package_path(output_path))?;
+        }
+    }
+    copy_split_output(document_path, output_path)
+}
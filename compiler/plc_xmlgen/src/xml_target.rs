@@ -0,0 +1,331 @@
+use std::{fmt::Debug, io::Error, path::Path, sync::Arc};
+
+use plc_ast::ast::{HardwareAccess, VariableBlock};
+use plc_ast::literals::AstLiteral;
+
+use super::literal_format::{format_bool, format_iec_address, format_iec_literal, BoolCase};
+use super::serializer::*;
+use super::xml_gen::{GenerationParameters, InterfaceElements, NameAndInitialValue, PouElements, PropertyElements};
+
+pub mod aml;
+pub mod codesys;
+pub mod iec;
+pub mod omron;
+pub mod opcua;
+pub mod tia;
+
+pub use aml::AutomationMlTarget;
+pub use codesys::CodesysTarget;
+pub use iec::IecTarget;
+pub use omron::OmronTarget;
+pub use opcua::OpcUaTarget;
+pub use tia::TiaTarget;
+
+/// A vendor backend for the XML generation.
+///
+/// The generators in `xml_gen` walk the compilation units and build the vendor neutral parts of the
+/// IEC 61131-10 document. Everything that differs between IDEs (the document skeleton, type names,
+/// vendor `<AddData>` and the final shape of a POU) is asked from the selected target.
+pub trait XmlTarget: Send + Sync {
+    /// The name used to select this target, e.g. `--xml-target omron`
+    fn name(&self) -> &'static str;
+
+    /// Creates the empty document which the generators populate
+    fn template(&self) -> Node;
+
+    /// Maps a declared type name to the type name accepted by the target IDE
+    fn map_type_name(&self, _generation_parameters: &GenerationParameters, type_name: &str) -> String {
+        String::from(type_name)
+    }
+
+    /// Maps the declared base type of an enum, e.g. `DINT` for `(a, b) DINT`, to the base type of the target IDE.
+    /// Only used if [`GenerationParameters::enum_base_types`] has no entry for it
+    fn map_enum_base_type(&self, generation_parameters: &GenerationParameters, numeric_type: &str) -> String {
+        self.map_type_name(generation_parameters, numeric_type)
+    }
+
+    /// Bit-string types paired with the type network-published variables of that type are exported as, e.g.
+    /// `("WORD", "UINT")` for IDEs which only publish numbers. Entries of [`GenerationParameters::bit_string_types`]
+    /// take precedence
+    fn bit_string_types(&self) -> &'static [(&'static str, &'static str)] {
+        &[]
+    }
+
+    /// Writes a literal, e.g. an initial value, in the syntax the target IDE accepts. Matters most for durations,
+    /// dates and times, written in the IEC short form like `T#1s500ms` by default
+    fn format_literal(&self, generation_parameters: &GenerationParameters, literal: &AstLiteral) -> String {
+        match literal {
+            AstLiteral::Bool(value) => format_bool(*value, self.bool_case(generation_parameters)),
+            _ => format_iec_literal(literal, &generation_parameters.real_format),
+        }
+    }
+
+    /// The spelling of `TRUE` and `FALSE` the target IDE expects, unless [`GenerationParameters::bool_case`] is set
+    fn bool_case(&self, generation_parameters: &GenerationParameters) -> BoolCase {
+        generation_parameters.bool_case.unwrap_or(BoolCase::Upper)
+    }
+
+    /// Writes the variant an enum typed variable is initialized with, by its name unless the target has no enums
+    fn format_enum_value(&self, _generation_parameters: &GenerationParameters, variant: &NameAndInitialValue) -> String {
+        variant.name.clone()
+    }
+
+    /// Writes the location of a variable declared `AT %IX1.2`, in the IEC notation by default. Fails with the reason
+    /// if the target IDE has no place for the address
+    fn format_address(&self, hardware_access: &HardwareAccess) -> Result<String, String> {
+        Ok(format_iec_address(hardware_access))
+    }
+
+    /// Writes the text of a comment inside a body for [`BodyComments::Convert`](crate::source_comments::BodyComments).
+    /// A `(* *)` comment by default, without the delimiters of nested comments which few IDEs accept
+    fn format_body_comment(&self, text: &str) -> String {
+        let text = text.replace("(*", "(").replace("*)", ")").replace("/*", "/").replace("*/", "/");
+        match text.is_empty() {
+            true => String::from("(**)"),
+            false => format!("(* {text} *)"),
+        }
+    }
+
+    /// The attributes of a `<GlobalVars>` or `<Vars>` element for the `RETAIN`, `PERSISTENT` and `NON_RETAIN` qualifiers
+    /// of its block. Each qualifier is an attribute of its own by default, as in PLCopen XML
+    fn retention_attributes(&self, block: &VariableBlock) -> Vec<(&'static str, &'static str)> {
+        let qualifiers = [(block.retain, RETAIN_ATTRIBUTE), (block.persistent, ("persistent", "true")), (block.non_retain, ("nonretain", "true"))];
+        qualifiers.into_iter().filter(|(declared, _)| *declared).map(|(_, attribute)| attribute).collect()
+    }
+
+    /// Standard functions the target IDE calls by another name, paired with that name, e.g. `("UPPER_BOUND",
+    /// Some("UpperBound"))`, or with `None` if it has no counterpart. Applied to the calls of bodies written with
+    /// [`GenerationParameters::canonical_bodies`]. Entries of [`GenerationParameters::function_names`] take precedence
+    fn function_names(&self) -> &'static [(&'static str, Option<&'static str>)] {
+        &[]
+    }
+
+    /// Identifiers the target IDE reserves although they are legal in IEC 61131-3. Declarations named like one are
+    /// exported under another name
+    fn reserved_words(&self) -> &'static [&'static str] {
+        &[]
+    }
+
+    /// The longest identifier the target IDE accepts in bytes. Longer declarations are exported shortened
+    fn max_identifier_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the target IDE has a wide string type. Projects declaring `WSTRING`s are rejected for targets without one
+    fn supports_wide_strings(&self) -> bool {
+        true
+    }
+
+    /// Whether a function block or class can extend another one. Without inheritance the base is left out, or its
+    /// variables flattened into the derived one with [`GenerationParameters::flatten_inheritance`]
+    fn supports_inheritance(&self) -> bool {
+        true
+    }
+
+    /// Whether a function block or class can implement interfaces. Without them the interfaces are left out, as is the
+    /// list of those a POU implements
+    fn supports_interfaces(&self) -> bool {
+        true
+    }
+
+    /// Whether the target IDE has FBD bodies. Without them every body is exported as ST, even with
+    /// [`GenerationParameters::fbd_bodies`]
+    fn supports_fbd_bodies(&self) -> bool {
+        true
+    }
+
+    /// Whether the target IDE has SFC bodies. Without them state machines marked `{attribute 'sfc_state'}` are exported
+    /// as ST
+    fn supports_sfc_bodies(&self) -> bool {
+        true
+    }
+
+    /// The vendor specific `<AddData>` attached to every POU, if any
+    fn pou_add_data(&self) -> Option<SOmronAddData> {
+        None
+    }
+
+    /// The vendor specific `<AddData>` attached to every variable, if any
+    fn variable_add_data(&self, _network_publish: &str) -> Option<SOmronAddData> {
+        None
+    }
+
+    /// Assembles the element of a single POU. Returning `None` skips the POU
+    fn emit_pou(&self, pou: PouElements) -> Option<Node> {
+        pou.assemble()
+    }
+
+    /// Assembles the element of a single interface. Returning `None` skips the interface
+    fn emit_interface(&self, interface: InterfaceElements) -> Option<Node> {
+        Some(interface.assemble())
+    }
+
+    /// Assembles the elements of a single property of a POU or interface. Targets without properties get its getter
+    /// and setter as the methods `__get_<name>` and `__set_<name>` the compiler lowers them to
+    fn emit_property(&self, property: PropertyElements) -> Vec<Node> {
+        property.accessor_methods()
+    }
+
+    /// Whether the document can be written while it is generated, see [`GenerationParameters::streaming`]. Targets
+    /// rewriting the populated tree in [`XmlTarget::post_process`] need all of it at once
+    fn supports_streaming(&self) -> bool {
+        true
+    }
+
+    /// Processing instructions the importer of the target IDE looks for between the XML declaration and the root
+    /// element, see [`Node::processing_instruction`]
+    fn processing_instructions(&self) -> Vec<Node> {
+        Vec::new()
+    }
+
+    /// Called with the fully populated tree right before it is written
+    fn post_process(&self, _output_root: &mut Node) {}
+
+    /// Packages the written document into an archive next to it, together with the auxiliary files.
+    /// Targets without an archive keep the bare document
+    fn package(&self, _generation_parameters: &GenerationParameters, _document_path: &Path) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// The type written into the `<Type>` of a `<Variable>` or `<Member>`: a named type or an array of one
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VariableType {
+    /// The type name, for arrays the name of their element type
+    pub type_name: String,
+
+    /// The lower and upper bound of every dimension, empty unless this is an array
+    pub dimensions: Vec<(String, String)>,
+}
+
+impl VariableType {
+    /// Reads the `<Type>` child of the given element
+    pub fn of(element: &Node) -> Option<Self> {
+        let type_node = element.find_child("Type")?;
+
+        let Some(array_node) = type_node.find_child("ArrayType") else {
+            return Some(VariableType { type_name: type_node.find_child("TypeName")?.content.clone()?, dimensions: Vec::new() });
+        };
+
+        let type_name = array_node.find_child("BaseType")?.find_child("TypeName")?.content.clone()?;
+        let dimensions = array_node.children.iter()
+            .filter(|it| it.name == "Dimension")
+            .map(|it| Some((it.attributes.get("lower")?.clone(), it.attributes.get("upper")?.clone())))
+            .collect::<Option<Vec<_>>>()?;
+
+        Some(VariableType { type_name, dimensions })
+    }
+
+    pub fn is_array(&self) -> bool {
+        !self.dimensions.is_empty()
+    }
+
+    /// The dimensions as declared between the brackets of an array, e.g. `0..3, 0..7`
+    pub fn ranges(&self) -> String {
+        self.dimensions.iter().map(|(lower, upper)| format!("{lower}..{upper}")).collect::<Vec<_>>().join(", ")
+    }
+
+    /// The number of elements of every dimension. Unknown if a bound is given by a constant the driver did not evaluate
+    pub fn lengths(&self) -> Option<Vec<i64>> {
+        self.dimensions.iter().map(|(lower, upper)| Some(upper.parse::<i64>().ok()? - lower.parse::<i64>().ok()? + 1)).collect()
+    }
+
+    /// The type as declared in Structured Text, e.g. `ARRAY[0..9] OF Motor`
+    pub fn iec_name(&self) -> String {
+        if self.is_array() {
+            return format!("ARRAY[{}] OF {}", self.ranges(), self.type_name);
+        }
+        self.type_name.clone()
+    }
+}
+
+/// Marks a variable block as retained
+pub const RETAIN_ATTRIBUTE: (&str, &str) = ("retain", "true");
+
+/// The retention attributes of targets which only know retained variables, i.e. retain persistent variables too
+pub fn retain_only_attributes(block: &VariableBlock) -> Vec<(&'static str, &'static str)> {
+    match block.retain || block.persistent {
+        true => vec![RETAIN_ATTRIBUTE],
+        false => Vec::new(),
+    }
+}
+
+/// The value of the `<InitialValue>` child of a `<Variable>` or `<Member>`, if it has one
+pub fn initial_value(element: &Node) -> Option<&String> {
+    element.find_child("InitialValue")?.find_child("SimpleValue")?.attributes.get("value")
+}
+
+/// The members an `<InitialValue>` holding a `<StructValue>` sets, e.g. of a function block instance, each with its
+/// value. Members of nested structs are named by their path, e.g. `[("PT", "T#5s"), ("limits.low", "5.5")]`
+pub fn struct_initial_values(element: &Node) -> Vec<(String, String)> {
+    let mut values = Vec::new();
+    if let Some(struct_value) = element.find_child("InitialValue").and_then(|it| it.find_child("StructValue")) {
+        collect_struct_values(struct_value, "", &mut values);
+    }
+    values
+}
+
+fn collect_struct_values(struct_value: &Node, prefix: &str, values: &mut Vec<(String, String)>) {
+    for value in struct_value.children.iter().filter(|it| it.name == "Value") {
+        let Some(member) = value.attributes.get("member") else {
+            continue;
+        };
+        let path = format!("{prefix}{member}");
+
+        if let Some(simple_value) = value.find_child("SimpleValue").and_then(|it| it.attributes.get("value")) {
+            values.push((path, simple_value.clone()));
+        }
+
+        else if let Some(nested) = value.find_child("StructValue") {
+            collect_struct_values(nested, &format!("{path}."), values);
+        }
+    }
+}
+
+/// The set of targets the driver can choose from by name.
+///
+/// The built-in targets are always registered. Additional targets can be registered by users of the
+/// driver without having to touch the generation code.
+#[derive(Clone)]
+pub struct TargetRegistry {
+    targets: Vec<Arc<dyn XmlTarget>>,
+}
+
+impl TargetRegistry {
+    pub fn empty() -> Self {
+        TargetRegistry { targets: Vec::new() }
+    }
+
+    /// Registers a target. A target registered under an existing name replaces the previous one
+    pub fn register(&mut self, target: Arc<dyn XmlTarget>) {
+        self.targets.retain(|it| !it.name().eq_ignore_ascii_case(target.name()));
+        self.targets.push(target);
+    }
+
+    pub fn get(&self, name: &str) -> Option<Arc<dyn XmlTarget>> {
+        self.targets.iter().find(|it| it.name().eq_ignore_ascii_case(name)).cloned()
+    }
+
+    pub fn names(&self) -> Vec<&'static str> {
+        self.targets.iter().map(|it| it.name()).collect()
+    }
+}
+
+impl Default for TargetRegistry {
+    fn default() -> Self {
+        let mut registry = TargetRegistry::empty();
+        registry.register(Arc::new(IecTarget));
+        registry.register(Arc::new(OmronTarget));
+        registry.register(Arc::new(CodesysTarget));
+        registry.register(Arc::new(TiaTarget));
+        registry.register(Arc::new(OpcUaTarget));
+        registry.register(Arc::new(AutomationMlTarget));
+        registry
+    }
+}
+
+impl Debug for TargetRegistry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_list().entries(self.names()).finish()
+    }
+}
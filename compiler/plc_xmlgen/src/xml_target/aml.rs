@@ -0,0 +1,364 @@
+use chrono::Local;
+use plc_ast::{ast::{PouType, VariableBlock}, literals::{AstLiteral, Time}};
+
+use crate::serializer::*;
+use crate::literal_format::{format_bool, format_clock, format_date, format_iec_literal, BoolCase};
+use crate::xml_gen::{GenerationParameters, PouElements, get_iec_template};
+
+use super::{initial_value, retain_only_attributes, struct_initial_values, VariableType, XmlTarget};
+
+pub const CAEX_NAMESPACE: &str = "http://www.dke.de/CAEX";
+pub const CAEX_SCHEMA_VERSION: &str = "3.0";
+
+/// The system unit classes every exported element is derived from
+pub const IEC_CLASS_LIB: &str = "IEC61131_3";
+const IEC_CLASSES: [&str; 5] = ["Configuration", "Resource", "Program", "FunctionBlock", "Function"];
+
+/// The attribute types of the user defined structs and enums
+pub const USER_TYPE_LIB: &str = "UserTypes";
+
+/// AutomationML, for integrating the project structure with plant engineering toolchains.
+///
+/// The project becomes a CAEX instance hierarchy: every configuration holds its resource with the globals, every POU
+/// holds its variables. Variables are CAEX attributes typed with the matching XML schema type, or referencing the
+/// attribute type of a struct or enum
+pub struct AutomationMlTarget;
+
+impl XmlTarget for AutomationMlTarget {
+    fn name(&self) -> &'static str {
+        "aml"
+    }
+
+    fn template(&self) -> Node {
+        get_iec_template()
+    }
+
+    fn format_literal(&self, generation_parameters: &GenerationParameters, literal: &AstLiteral) -> String {
+        match literal { //default values are typed with XML schema types
+            AstLiteral::Time(time) => xml_schema_duration(time),
+            AstLiteral::Date(date) => format_date(date.year(), date.month(), date.day()),
+            AstLiteral::TimeOfDay(tod) => format_clock(tod.hour(), tod.min(), tod.sec(), tod.nano()),
+            AstLiteral::DateAndTime(dt) => format!(
+                "{}T{}",
+                format_date(dt.year(), dt.month(), dt.day()),
+                format_clock(dt.hour(), dt.min(), dt.sec(), dt.nano())
+            ),
+            AstLiteral::Bool(value) => format_bool(*value, self.bool_case(generation_parameters)),
+            AstLiteral::String(string) => string.value.clone(), //an xs:string needs neither quotes nor escapes
+            _ => format_iec_literal(literal, &generation_parameters.real_format),
+        }
+    }
+
+    fn retention_attributes(&self, block: &VariableBlock) -> Vec<(&'static str, &'static str)> {
+        retain_only_attributes(block) //the IEC class library only has a Retain attribute
+    }
+
+    fn bool_case(&self, generation_parameters: &GenerationParameters) -> BoolCase {
+        generation_parameters.bool_case.unwrap_or(BoolCase::Lower) //xs:boolean is lower case
+    }
+
+    fn supports_inheritance(&self) -> bool {
+        false //the IEC class library has no inheritance between POUs
+    }
+
+    fn supports_interfaces(&self) -> bool {
+        false //the IEC class library has no interfaces
+    }
+
+    fn supports_fbd_bodies(&self) -> bool {
+        false //the classes describe the interface of a POU, not its body
+    }
+
+    fn supports_sfc_bodies(&self) -> bool {
+        false //no body is exported
+    }
+
+    fn emit_pou(&self, pou: PouElements) -> Option<Node> {
+        let class = match pou.kind {
+            PouType::Program => "Program",
+            PouType::Function => "Function",
+            PouType::FunctionBlock => "FunctionBlock",
+            _ => {
+                return None
+            }
+        };
+
+        let mut element = caex_element(pou.name, class);
+
+        if let Some(documentation) = pou.documentation {
+            element.children.push(SCaexDescription::new().content(documentation).inner());
+        }
+
+        let parameters = pou.parameters.inner();
+        for (container, kind) in [("InputVars", "Input"), ("InoutVars", "InOut"), ("OutputVars", "Output")] {
+            if let Some(variables) = parameters.find_child(container) {
+                element.children.extend(caex_attributes(variables, kind));
+            }
+        }
+
+        let containers = [
+            (pou.externals.inner(), "External"),
+            (pou.constant_externals.inner(), "External"),
+            (pou.vars.inner(), "Local"),
+            (pou.constant_vars.inner(), "Local"),
+            (pou.retain_vars.inner(), "Local"),
+            (pou.constant_retain_vars.inner(), "Local"),
+            (pou.temp_vars.inner(), "Temp"),
+            (pou.constant_temp_vars.inner(), "Temp"),
+        ];
+        for (variables, kind) in &containers {
+            element.children.extend(caex_attributes(variables, kind));
+        }
+
+        if pou.kind == PouType::Function && let Some(return_type) = pou.result_type.inner().find_child("TypeName").and_then(|it| it.content.clone()) {
+            element = element.child(&caex_value("ReturnType", return_type));
+        }
+        Some(element)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false //the CAEX document is built from the whole tree
+    }
+
+    fn post_process(&self, output_root: &mut Node) {
+        let mut hierarchy = SCaexInstanceHierarchy::new()
+            .attribute_str("Name", "Project")
+            .inner();
+        let mut type_lib = SCaexAttributeTypeLib::new()
+            .attribute_str("Name", USER_TYPE_LIB)
+            .inner();
+
+        let configurations = output_root.find_child(INSTANCES).map(|it| it.children.as_slice()).unwrap_or_default();
+        for configuration in configurations {
+            hierarchy.children.push(caex_configuration(configuration));
+        }
+
+        let namespace = output_root.find(GLOBAL_NAMESPACE_PATH);
+        for node in namespace.map(|it| it.children.as_slice()).unwrap_or_default() {
+            if node.name == "DataTypeDecl" {
+                type_lib.children.extend(caex_attribute_type(node));
+            }
+
+            else {
+                hierarchy.children.push(node.clone());
+            }
+        }
+
+        //every internal element needs an ID which is unique within the file
+        let mut next_id = 0;
+        assign_caex_ids(&mut hierarchy, &mut next_id);
+
+        let mut class_lib = SCaexSystemUnitClassLib::new().attribute_str("Name", IEC_CLASS_LIB);
+        for class in IEC_CLASSES {
+            class_lib = class_lib.child(&SCaexSystemUnitClass::new().attribute_str("Name", class).close());
+        }
+
+        let mut caex_file = SCaexFile::new()
+            .attribute_str("SchemaVersion", CAEX_SCHEMA_VERSION)
+            .attribute_str("xmlns", CAEX_NAMESPACE)
+            .attribute_str("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance")
+            .child(&SCaexSourceDocumentInformation::new()
+                .attribute_str("OriginName", "rusty")
+                .attribute_str("OriginID", "rusty")
+                .attribute_str("OriginVersion", env!("CARGO_PKG_VERSION"))
+                .attribute(String::from("LastWritingDateTime"), Local::now().to_rfc3339())
+                .close())
+            .child(&hierarchy);
+
+        if !type_lib.children.is_empty() {
+            caex_file = caex_file.child(&type_lib);
+        }
+        *output_root = caex_file.child(&class_lib).inner();
+    }
+}
+
+fn caex_element(name: String, class: &str) -> Node {
+    SCaexInternalElement::new()
+        .attribute(String::from("Name"), name)
+        .attribute(String::from("RefBaseSystemUnitPath"), format!("{IEC_CLASS_LIB}/{class}"))
+        .inner()
+}
+
+fn caex_value(name: &'static str, value: String) -> SCaexAttribute {
+    SCaexAttribute::new()
+        .attribute_str("Name", name)
+        .attribute_str("AttributeDataType", "xs:string")
+        .child(&SCaexValue::new().content(value))
+}
+
+/// The XML schema type of an elementary IEC type, `None` for user defined types
+/// An `xs:duration` like `PT1.5S` or `-P1DT2H`
+fn xml_schema_duration(time: &Time) -> String {
+    let nanos = time.value();
+    let days = nanos.abs() / 86_400_000_000_000;
+    let rest = nanos.abs() % 86_400_000_000_000;
+    let (hours, minutes, seconds) = (rest / 3_600_000_000_000, rest / 60_000_000_000 % 60, rest % 60_000_000_000);
+
+    let mut duration = String::from(if nanos < 0 { "-P" } else { "P" });
+    if days > 0 {
+        duration.push_str(&format!("{days}D"));
+    }
+
+    if rest > 0 || days == 0 {
+        duration.push('T');
+        if hours > 0 {
+            duration.push_str(&format!("{hours}H"));
+        }
+
+        if minutes > 0 {
+            duration.push_str(&format!("{minutes}M"));
+        }
+
+        if seconds > 0 || rest == 0 {
+            let fraction = format!("{:09}", seconds % 1_000_000_000);
+            let fraction = fraction.trim_end_matches('0');
+            let separator = if fraction.is_empty() { "" } else { "." };
+            duration.push_str(&format!("{}{separator}{fraction}S", seconds / 1_000_000_000));
+        }
+    }
+    duration
+}
+
+fn xml_schema_type(type_name: &str) -> Option<&'static str> {
+    let schema_type = match type_name.to_uppercase().as_str() {
+        "BOOL" => "xs:boolean",
+        "SINT" => "xs:byte",
+        "USINT" | "BYTE" => "xs:unsignedByte",
+        "INT" => "xs:short",
+        "UINT" | "WORD" => "xs:unsignedShort",
+        "DINT" => "xs:int",
+        "UDINT" | "DWORD" => "xs:unsignedInt",
+        "LINT" => "xs:long",
+        "ULINT" | "LWORD" => "xs:unsignedLong",
+        "REAL" => "xs:float",
+        "LREAL" => "xs:double",
+        "TIME" | "LTIME" => "xs:duration",
+        "DATE" => "xs:date",
+        "TIME_OF_DAY" | "TOD" => "xs:time",
+        "DATE_AND_TIME" | "DT" => "xs:dateTime",
+        upper if upper.contains("STRING") || upper.contains("CHAR") => "xs:string", //sized strings produce internal types like __global_textString
+        _ => {
+            return None
+        }
+    };
+    Some(schema_type)
+}
+
+/// Converts the `<Variable>` elements of an IEC 61131-10 variable list into CAEX attributes
+fn caex_attributes(variables: &Node, kind: &'static str) -> Vec<Node> {
+    let constant = variables.attributes.contains_key("constant");
+    let retain = variables.attributes.contains_key("retain");
+
+    variables.children.iter().filter(|it| it.name == "Variable").filter_map(|variable| {
+        let name = variable.attributes.get("name")?;
+        let variable_type = VariableType::of(variable)?;
+
+        let mut attribute = SCaexAttribute::new().attribute(String::from("Name"), name.clone());
+        attribute = match xml_schema_type(&variable_type.type_name) { //arrays are typed by their elements
+            Some(schema_type) => attribute.attribute_str("AttributeDataType", schema_type),
+            None => attribute.attribute(String::from("RefAttributeType"), format!("{USER_TYPE_LIB}/{}", variable_type.type_name)),
+        };
+
+        if let Some(value) = initial_value(variable) {
+            attribute = attribute.child(&SCaexDefaultValue::new().content(value.clone()));
+        }
+        attribute = with_member_defaults(attribute, variable);
+
+        attribute = attribute
+            .child(&caex_value("IecType", variable_type.iec_name()))
+            .child(&caex_value("Kind", String::from(kind)));
+
+        if let Some(address) = variable.find_child("Address").and_then(|it| it.attributes.get("address")) {
+            attribute = attribute.child(&caex_value("Address", address.clone()));
+        }
+
+        if constant {
+            attribute = attribute.child(&caex_value("Constant", String::from("true")));
+        }
+
+        if retain {
+            attribute = attribute.child(&caex_value("Retain", String::from("true")));
+        }
+        Some(attribute.inner())
+    })
+    .collect()
+}
+
+/// The members a struct or function block instance is initialized with become nested attributes holding the
+/// default value, named by the path of the member, e.g. `PT` or `limits.low`
+fn with_member_defaults(mut attribute: SCaexAttribute, variable: &Node) -> SCaexAttribute {
+    for (path, value) in struct_initial_values(variable) {
+        attribute = attribute.child(&SCaexAttribute::new()
+            .attribute(String::from("Name"), path)
+            .child(&SCaexDefaultValue::new().content(value)));
+    }
+    attribute
+}
+
+/// A `<Configuration>` with its resources and their globals
+fn caex_configuration(configuration: &Node) -> Node {
+    let name = configuration.attributes.get("name").cloned().unwrap_or_default();
+    let mut element = caex_element(name, "Configuration");
+
+    for resource in configuration.children.iter().filter(|it| it.name == RESOURCE) {
+        let name = resource.attributes.get("name").cloned().unwrap_or_default();
+        let mut resource_element = caex_element(name, "Resource");
+
+        for globals in resource.children.iter().filter(|it| it.name == "GlobalVars") {
+            resource_element.children.extend(caex_attributes(globals, "Global"));
+        }
+        element.children.push(resource_element);
+    }
+    element
+}
+
+/// A `<DataTypeDecl>` becomes an attribute type: structs hold one attribute per member, enums constrain their values
+fn caex_attribute_type(declaration: &Node) -> Option<Node> {
+    let name = declaration.attributes.get("name")?;
+    let spec = declaration.find_child("UserDefinedTypeSpec")?;
+    let mut attribute_type = SCaexAttributeType::new().attribute(String::from("Name"), name.clone());
+
+    match spec.attributes.get("xsi:type").map(String::as_str) {
+        Some("StructTypeSpec") => {
+            for member in spec.children.iter().filter(|it| it.name == "Member") {
+                let Some(member_type) = VariableType::of(member) else {
+                    continue;
+                };
+
+                let mut attribute = SCaexAttribute::new().attribute(String::from("Name"), member.attributes.get("name")?.clone());
+                attribute = match xml_schema_type(&member_type.type_name) {
+                    Some(schema_type) => attribute.attribute_str("AttributeDataType", schema_type),
+                    None => attribute.attribute(String::from("RefAttributeType"), format!("{USER_TYPE_LIB}/{}", member_type.type_name)),
+                };
+
+                if let Some(value) = initial_value(member) {
+                    attribute = attribute.child(&SCaexDefaultValue::new().content(value.clone()));
+                }
+                attribute = with_member_defaults(attribute, member);
+                attribute_type = attribute_type.child(&attribute.child(&caex_value("IecType", member_type.iec_name())));
+            }
+        },
+        Some("EnumTypeWithNamedValueSpec") => {
+            let mut values = SCaexNominalScaledType::new();
+            for enumerator in spec.children.iter().filter(|it| it.name == "Enumerator") {
+                values = values.child(&SCaexRequiredValue::new().content(enumerator.attributes.get("name")?.clone()));
+            }
+
+            attribute_type = attribute_type
+                .attribute_str("AttributeDataType", "xs:string")
+                .child(&SCaexConstraint::new().attribute_str("Name", "Enumerators").child(&values));
+        },
+        _ => {
+            return None
+        }
+    };
+    Some(attribute_type.inner())
+}
+
+fn assign_caex_ids(node: &mut Node, next_id: &mut u64) {
+    for child in node.children.iter_mut().filter(|it| it.name == "InternalElement") {
+        child.attributes.insert(String::from("ID"), format!("00000000-0000-0000-0000-{next_id:012x}"));
+        *next_id += 1;
+        assign_caex_ids(child, next_id);
+    }
+}
@@ -0,0 +1,41 @@
+use std::{io::Error, path::Path};
+
+use crate::library::package_library;
+use crate::serializer::*;
+use crate::xml_gen::{GenerationParameters, PropertyElements, get_iec_template};
+
+use super::XmlTarget;
+
+/// CODESYS and TwinCAT reserve their operators, the instructions of IL and a few keywords of their own
+pub const CODESYS_RESERVED_WORDS: [&str; 30] = [
+    "ADR", "BITADR", "SIZEOF", "XSIZEOF", "INDEXOF", "INI", "UNION", "END_UNION", "BIT", "WCHAR", "__NEW", "__DELETE",
+    "__ISVALIDREF", "__QUERYINTERFACE", "__QUERYPOINTER", "__TRY", "__CATCH", "__FINALLY", "__ENDTRY", "CAL", "CALC",
+    "CALCN", "JMP", "JMPC", "JMPCN", "RET", "LD", "LDN", "ST", "STN",
+];
+
+/// CODESYS, with its reserved words. The document follows the IEC 61131-10 schema, not the PLCopen TC6 one CODESYS
+/// imports, and is packaged with `--xml-archive` together with the library properties, see [`package_library`]
+pub struct CodesysTarget;
+
+impl XmlTarget for CodesysTarget {
+    fn name(&self) -> &'static str {
+        "codesys"
+    }
+
+    fn template(&self) -> Node {
+        get_iec_template()
+    }
+
+    fn reserved_words(&self) -> &'static [&'static str] {
+        &CODESYS_RESERVED_WORDS
+    }
+
+    fn emit_property(&self, property: PropertyElements) -> Vec<Node> {
+        vec![property.assemble()]
+    }
+
+    fn package(&self, generation_parameters: &GenerationParameters, document_path: &Path) -> Result<(), Error> {
+        package_library(document_path, &generation_parameters.library, &generation_parameters.archive_files)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,20 @@
+use crate::serializer::*;
+use crate::xml_gen::get_iec_template;
+
+use super::XmlTarget;
+
+/// Plain IEC 61131-10 without any vendor extensions.
+///
+/// Only the standard namespace is used and no `<AddData>` is emitted, so the output validates against
+/// the plain TC65 schema.
+pub struct IecTarget;
+
+impl XmlTarget for IecTarget {
+    fn name(&self) -> &'static str {
+        "iec"
+    }
+
+    fn template(&self) -> Node {
+        get_iec_template()
+    }
+}
@@ -0,0 +1,202 @@
+use chrono::Local;
+use plc_ast::ast::{DirectAccessType, HardwareAccess, HardwareAccessType, VariableBlock};
+use plc_ast::literals::{AstLiteral, Time};
+
+use crate::literal_format::{format_bool, format_iec_literal, time_components};
+use crate::serializer::*;
+use crate::xml_gen::{GenerationParameters, OMRON_SCHEMA, get_omron_template};
+
+use super::{retain_only_attributes, XmlTarget};
+
+/// The longest string Sysmac Studio accepts, used for strings whose declared length is unknown
+pub const DEFAULT_OMRON_STRING_LENGTH: u32 = 1986;
+
+/// Network variables of Sysmac Studio are read by HMIs and host software which expect numbers, not bit strings
+pub const OMRON_BIT_STRING_TYPES: [(&str, &str); 4] = [("BYTE", "USINT"), ("WORD", "UINT"), ("DWORD", "UDINT"), ("LWORD", "ULINT")];
+
+/// Sysmac Studio has no pointers, and spells the functions of array bounds in its own way
+pub const OMRON_FUNCTION_NAMES: [(&str, Option<&str>); 5] = [
+    ("UPPER_BOUND", Some("UpperBound")), ("LOWER_BOUND", Some("LowerBound")), ("ADR", None), ("REF", None), ("SIZEOF", None),
+];
+
+/// Sysmac Studio reserves the configuration and SFC keywords of IEC 61131-3 and the enable parameters of instructions
+pub const OMRON_RESERVED_WORDS: [&str; 18] = [
+    "CONFIGURATION", "END_CONFIGURATION", "RESOURCE", "END_RESOURCE", "TASK", "WITH", "ON", "SINGLE", "INTERVAL",
+    "PRIORITY", "STEP", "END_STEP", "INITIAL_STEP", "TRANSITION", "END_TRANSITION", "FROM", "EN", "ENO",
+];
+
+/// Sysmac Studio limits names to 127 bytes of UTF-8
+pub const OMRON_MAX_IDENTIFIER_LENGTH: usize = 127;
+
+/// Omron Sysmac Studio
+pub struct OmronTarget;
+
+impl XmlTarget for OmronTarget {
+    fn name(&self) -> &'static str {
+        "omron"
+    }
+
+    fn template(&self) -> Node {
+        get_omron_template()
+    }
+
+    fn map_type_name(&self, generation_parameters: &GenerationParameters, type_name: &str) -> String {
+        if type_name.to_lowercase().contains("string") { //Sysmac Studio only knows sized strings, e.g. String[256]
+            let length = declared_string_length(type_name).unwrap_or(generation_parameters.default_string_length);
+            return format!("String[{length}]");
+        }
+        String::from(type_name)
+    }
+
+    fn format_literal(&self, generation_parameters: &GenerationParameters, literal: &AstLiteral) -> String {
+        match literal {
+            AstLiteral::Time(time) => format!("T#{}", sysmac_time(time)),
+            AstLiteral::Bool(value) => format_bool(*value, self.bool_case(generation_parameters)),
+            _ => format_iec_literal(literal, &generation_parameters.real_format),
+        }
+    }
+
+    fn bit_string_types(&self) -> &'static [(&'static str, &'static str)] {
+        &OMRON_BIT_STRING_TYPES
+    }
+
+    fn function_names(&self) -> &'static [(&'static str, Option<&'static str>)] {
+        &OMRON_FUNCTION_NAMES
+    }
+
+    fn format_address(&self, hardware_access: &HardwareAccess) -> Result<String, String> {
+        sysmac_address(hardware_access)
+    }
+
+    fn reserved_words(&self) -> &'static [&'static str] {
+        &OMRON_RESERVED_WORDS
+    }
+
+    fn max_identifier_length(&self) -> Option<usize> {
+        Some(OMRON_MAX_IDENTIFIER_LENGTH)
+    }
+
+    fn retention_attributes(&self, block: &VariableBlock) -> Vec<(&'static str, &'static str)> {
+        retain_only_attributes(block) //Sysmac Studio keeps retained variables across downloads too
+    }
+
+    fn supports_wide_strings(&self) -> bool {
+        false //Sysmac Studio strings are UTF-8 encoded, there is no WSTRING
+    }
+
+    fn supports_inheritance(&self) -> bool {
+        false //Sysmac Studio function blocks cannot extend each other
+    }
+
+    fn supports_fbd_bodies(&self) -> bool {
+        false //Sysmac Studio programs in ladder and ST only
+    }
+
+    fn supports_sfc_bodies(&self) -> bool {
+        false //nor SFC
+    }
+
+    fn pou_add_data(&self) -> Option<SOmronAddData> {
+        let info_node = SPouInfo::new()
+            .attribute_str("version", "0.0.0")
+            .attribute(String::from("creationDateTime"), Local::now().to_rfc3339());
+
+        let data_node = SOmronData::new() //<Data>
+            .attribute_str("name", OMRON_SCHEMA)
+            .attribute_str("handleUnknown", "discard")
+            .child(&info_node);
+
+        Some(SOmronAddData::new().child(&data_node))
+    }
+
+    fn variable_add_data(&self, network_publish: &str) -> Option<SOmronAddData> {
+        let additional_property_node = SOmronGlobalVariableAdditionalProperties::new()
+            .attribute(String::from("networkPublish"), String::from(network_publish));
+
+        let data_node = SOmronData::new() //<Data>
+            .attribute_str("name", OMRON_SCHEMA)
+            .attribute_str("handleUnknown", "discard")
+            .child(&additional_property_node);
+
+        Some(SOmronAddData::new().child(&data_node))
+    }
+}
+
+/// The length of a sized string like `STRING[256]`. Lengths given by a constant are unknown
+fn declared_string_length(type_name: &str) -> Option<u32> {
+    let (_, length) = type_name.strip_suffix(']')?.split_once('[')?;
+    length.trim().parse().ok()
+}
+
+/// A duration the way Sysmac Studio writes it, e.g. `1d2h3m4s5ms`. There are no microseconds or nanoseconds,
+/// they are written as the fraction of a millisecond instead, e.g. `1s500.25ms` for 1s 500ms 250us
+fn sysmac_time(time: &Time) -> String {
+    let mut components: Vec<String> = time_components(time).iter()
+        .filter(|(_, unit)| !matches!(*unit, "us" | "ns"))
+        .map(|(value, unit)| format!("{value}{unit}"))
+        .collect();
+
+    let fraction = time.value().abs() % 1_000_000;
+    if fraction != 0 {
+        let millis = (time.value().abs() / 1_000_000) % 1_000;
+        if millis != 0 {
+            components.pop(); //the milliseconds are written again with their fraction
+        }
+        components.push(format!("{millis}.{}ms", format!("{fraction:06}").trim_end_matches('0')));
+    }
+
+    if components.is_empty() {
+        return String::from("0s");
+    }
+
+    let sign = if time.value() < 0 { "-" } else { "" };
+    format!("{sign}{}", components.concat())
+}
+
+/// The bits of a byte, which IEC bit addresses count within
+const BYTE_BITS: u32 = 8;
+
+/// The bits of a word, which Sysmac Studio bit addresses count within
+const SYSMAC_WORD_BITS: u32 = 16;
+
+/// The AT specification of Sysmac Studio for an IEC address. Sysmac Studio addresses 16 bit words, so the byte offsets
+/// of IEC bit addresses are converted, e.g. `%IX3.2` is bit 10 of word 1: `CIO 1.10`. Inputs and outputs live in the
+/// CIO area, memory in the work area, e.g. `%MW10` is `W10`. Wider accesses count in their own unit, e.g. `%QD5` is
+/// `CIO 10`
+pub fn sysmac_address(hardware_access: &HardwareAccess) -> Result<String, String> {
+    let area = match hardware_access.direction {
+        HardwareAccessType::Input | HardwareAccessType::Output => "CIO ",
+        HardwareAccessType::Memory => "W",
+        HardwareAccessType::Global => {
+            return Err(String::from("Sysmac Studio has no global memory area"))
+        }
+    };
+
+    let components = hardware_access.address.iter()
+        .map(|it| it.get_literal_integer_value().and_then(|it| u32::try_from(it).ok()))
+        .collect::<Option<Vec<u32>>>()
+        .ok_or_else(|| String::from("only numeric addresses can be mapped"))?;
+
+    let address = match (hardware_access.access, components.as_slice()) {
+        (DirectAccessType::Bit, [byte, bit]) if *bit < BYTE_BITS => {
+            let bytes_per_word = SYSMAC_WORD_BITS / BYTE_BITS;
+            format!("{area}{}.{:02}", byte / bytes_per_word, (byte % bytes_per_word) * BYTE_BITS + bit)
+        },
+        (DirectAccessType::Bit, [_, bit]) => {
+            return Err(format!("bit {bit} is out of range, a bit address names one of the {BYTE_BITS} bits of a byte"))
+        },
+        (DirectAccessType::Word, [word]) => format!("{area}{word}"),
+        (DirectAccessType::DWord, [double_word]) => format!("{area}{}", double_word * 2),
+        (DirectAccessType::LWord, [long_word]) => format!("{area}{}", long_word * 4),
+        (DirectAccessType::Byte, _) => {
+            return Err(String::from("Sysmac Studio addresses whole words, there are no byte addresses"))
+        },
+        (DirectAccessType::Template, _) => {
+            return Err(String::from("the location of a `*` address is only known once it is configured"))
+        },
+        _ => {
+            return Err(String::from("Sysmac Studio addresses are a word, or a byte and one of its bits"))
+        }
+    };
+    Ok(address)
+}
@@ -0,0 +1,237 @@
+use std::path::Path;
+
+use crate::serializer::*;
+use crate::xml_gen::{GenerationParameters, get_iec_template};
+
+use super::{VariableType, XmlTarget};
+
+pub const UA_NODESET_NAMESPACE: &str = "http://opcfoundation.org/UA/2011/03/UANodeSet.xsd";
+pub const UA_NAMESPACE_URI: &str = "urn:rusty:plc";
+
+/// The prefix of every node id in the exported namespace, which is the first one after the standard namespace
+pub const UA_NODE_ID_PREFIX: &str = "ns=1;s=";
+const UA_DATA_TYPE_PREFIX: &str = "ns=1;s=DataType.";
+
+/// The built-in data types used by the export together with their standard node ids
+const UA_ALIASES: [(&str, &str); 14] = [
+    ("Boolean", "i=1"),
+    ("SByte", "i=2"),
+    ("Byte", "i=3"),
+    ("Int16", "i=4"),
+    ("UInt16", "i=5"),
+    ("Int32", "i=6"),
+    ("UInt32", "i=7"),
+    ("Int64", "i=8"),
+    ("UInt64", "i=9"),
+    ("Float", "i=10"),
+    ("Double", "i=11"),
+    ("String", "i=12"),
+    ("DateTime", "i=13"),
+    ("Duration", "i=290"),
+];
+
+/// OPC UA NodeSet2, for loading the tag model of the PLC into UA servers and clients.
+///
+/// Only the global variables and user types are exported: the globals of every source file become an object below
+/// the `Objects` folder holding one variable per global, structs and enums become data types
+pub struct OpcUaTarget;
+
+impl XmlTarget for OpcUaTarget {
+    fn name(&self) -> &'static str {
+        "opcua"
+    }
+
+    fn template(&self) -> Node {
+        get_iec_template()
+    }
+
+    fn map_type_name(&self, _generation_parameters: &GenerationParameters, type_name: &str) -> String {
+        let ua_name = match type_name.to_uppercase().as_str() {
+            "BOOL" => "Boolean",
+            "SINT" => "SByte",
+            "USINT" | "BYTE" => "Byte",
+            "INT" => "Int16",
+            "UINT" | "WORD" => "UInt16",
+            "DINT" => "Int32",
+            "UDINT" | "DWORD" => "UInt32",
+            "LINT" => "Int64",
+            "ULINT" | "LWORD" => "UInt64",
+            "REAL" => "Float",
+            "LREAL" => "Double",
+            "TIME" | "LTIME" => "Duration",
+            "DATE" | "DATE_AND_TIME" | "DT" => "DateTime",
+            upper if upper.contains("STRING") || upper.contains("CHAR") => "String", //sized strings produce internal types like __global_textString
+            _ => {
+                return format!("{UA_DATA_TYPE_PREFIX}{type_name}"); //user defined types are exported as data types of their own
+            }
+        };
+        String::from(ua_name)
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false //the node set is built from the whole tree
+    }
+
+    fn post_process(&self, output_root: &mut Node) {
+        let mut aliases = SUaAliases::new().inner();
+        for (alias, node_id) in UA_ALIASES {
+            aliases = aliases.child(&SUaAlias::new().attribute_str("Alias", alias).content(String::from(node_id)));
+        }
+
+        let mut node_set = SUaNodeSet::new()
+            .attribute_str("xmlns", UA_NODESET_NAMESPACE)
+            .attribute_str("xmlns:xsi", "http://www.w3.org/2001/XMLSchema-instance")
+            .child(&SUaNamespaceUris::new().child(&SUaUri::new().content(String::from(UA_NAMESPACE_URI))))
+            .child(&aliases)
+            .inner();
+
+        let namespace = output_root.find(GLOBAL_NAMESPACE_PATH);
+        for declaration in namespace.map(|it| it.children.as_slice()).unwrap_or_default() {
+            node_set.children.extend(ua_data_type(declaration));
+        }
+
+        let configurations = output_root.find_child(INSTANCES).map(|it| it.children.as_slice()).unwrap_or_default();
+        for configuration in configurations {
+            node_set.children.extend(ua_globals(configuration));
+        }
+        *output_root = node_set;
+    }
+}
+
+fn ua_references(references: Vec<(&'static str, bool, String)>) -> SUaReferences {
+    let mut references_node = SUaReferences::new();
+    for (reference_type, is_forward, target) in references {
+        let mut reference = SUaReference::new().attribute_str("ReferenceType", reference_type);
+        if !is_forward {
+            reference = reference.attribute_bool("IsForward", false);
+        }
+        references_node = references_node.child(&reference.content(target));
+    }
+    references_node
+}
+
+/// A `<DataTypeDecl>` becomes a structure or enumeration data type
+fn ua_data_type(declaration: &Node) -> Option<Node> {
+    let name = declaration.attributes.get("name")?;
+    let spec = declaration.find_child("UserDefinedTypeSpec")?;
+    let mut definition = SUaDefinition::new().attribute(String::from("Name"), format!("1:{name}"));
+
+    let super_type = match spec.attributes.get("xsi:type").map(String::as_str) {
+        Some("StructTypeSpec") => {
+            for member in spec.children.iter().filter(|it| it.name == "Member") {
+                let Some(member_type) = VariableType::of(member) else {
+                    continue;
+                };
+
+                let field = SUaField::new()
+                    .attribute(String::from("Name"), member.attributes.get("name")?.clone())
+                    .attribute(String::from("DataType"), member_type.type_name.clone())
+                    .inner();
+
+                definition = definition.child(&ua_array_attributes(field, &member_type).close());
+            }
+            "i=22" //Structure
+        },
+        Some("EnumTypeWithNamedValueSpec") => {
+            //enumerators without a value follow the one before them, as in IEC 61131-3
+            let mut next_value: i128 = 0;
+            for enumerator in spec.children.iter().filter(|it| it.name == "Enumerator") {
+                let Some(name) = enumerator.attributes.get("name") else {
+                    continue;
+                };
+                let value = enumerator.attributes.get("value").cloned().unwrap_or_else(|| next_value.to_string());
+                next_value = value.parse::<i128>().unwrap_or(next_value) + 1;
+
+                definition = definition.child(&SUaField::new()
+                    .attribute(String::from("Name"), name.clone())
+                    .attribute(String::from("Value"), value)
+                    .close());
+            }
+            "i=29" //Enumeration
+        },
+        _ => {
+            return None
+        }
+    };
+
+    Some(SUaDataType::new()
+        .attribute(String::from("NodeId"), format!("{UA_DATA_TYPE_PREFIX}{name}"))
+        .attribute(String::from("BrowseName"), format!("1:{name}"))
+        .child(&SUaDisplayName::new().content(name.clone()))
+        .child(&ua_references(vec![("HasSubtype", false, String::from(super_type))]))
+        .child(&definition)
+        .inner())
+}
+
+/// The globals of a `<Configuration>` become an object named after their source file, holding one variable per global
+fn ua_globals(configuration: &Node) -> Vec<Node> {
+    let Some(configuration_name) = configuration.attributes.get("name") else {
+        return Vec::new();
+    };
+    let unit_name = configuration_name.strip_suffix(&format!("_{CONFIGURATION}")).unwrap_or(configuration_name);
+    let object_name = Path::new(unit_name).file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
+    let object_id = format!("{UA_NODE_ID_PREFIX}{object_name}");
+
+    let mut variables = Vec::new();
+    for resource in configuration.children.iter().filter(|it| it.name == RESOURCE) {
+        for globals in resource.children.iter().filter(|it| it.name == "GlobalVars") {
+            let access_level = if globals.attributes.contains_key("constant") { "1" } else { "3" }; //constants are read only
+
+            for variable in globals.children.iter().filter(|it| it.name == "Variable") {
+                let Some(name) = variable.attributes.get("name") else {
+                    continue;
+                };
+                let Some(variable_type) = VariableType::of(variable) else {
+                    continue;
+                };
+
+                let variable_node = SUaVariable::new()
+                    .attribute(String::from("NodeId"), format!("{object_id}.{name}"))
+                    .attribute(String::from("BrowseName"), format!("1:{name}"))
+                    .attribute(String::from("ParentNodeId"), object_id.clone())
+                    .attribute(String::from("DataType"), variable_type.type_name.clone())
+                    .attribute_str("AccessLevel", access_level)
+                    .inner();
+
+                variables.push(ua_array_attributes(variable_node, &variable_type)
+                    .child(&SUaDisplayName::new().content(name.clone()))
+                    .child(&ua_references(vec![
+                        ("HasComponent", false, object_id.clone()),
+                        ("HasTypeDefinition", true, String::from("i=63")), //BaseDataVariableType
+                    ]))
+                    .inner());
+            }
+        }
+    }
+
+    if variables.is_empty() {
+        return variables;
+    }
+
+    let object = SUaObject::new()
+        .attribute(String::from("NodeId"), object_id.clone())
+        .attribute(String::from("BrowseName"), format!("1:{object_name}"))
+        .child(&SUaDisplayName::new().content(object_name.clone()))
+        .child(&ua_references(vec![
+            ("Organizes", false, String::from("i=85")), //Objects folder
+            ("HasTypeDefinition", true, String::from("i=61")), //FolderType
+        ]))
+        .inner();
+
+    let mut nodes = vec![object];
+    nodes.extend(variables);
+    nodes
+}
+
+/// Arrays keep the data type of their elements and state their rank and, if known, the length of every dimension
+fn ua_array_attributes(node: Node, variable_type: &VariableType) -> Node {
+    if !variable_type.is_array() {
+        return node;
+    }
+
+    let node = node.attribute(String::from("ValueRank"), variable_type.dimensions.len().to_string());
+    match variable_type.lengths() {
+        Some(lengths) => node.attribute(String::from("ArrayDimensions"), lengths.iter().map(i64::to_string).collect::<Vec<_>>().join(",")),
+        None => node,
+    }
+}
@@ -0,0 +1,362 @@
+use std::path::Path;
+
+use plc_ast::{ast::{PouType, VariableBlock}, literals::AstLiteral};
+
+use crate::literal_format::{format_bool, format_iec_literal, format_time, BoolCase};
+use crate::serializer::*;
+use crate::xml_gen::{GenerationParameters, NameAndInitialValue, PouElements, get_iec_template};
+
+use super::{initial_value, retain_only_attributes, struct_initial_values, VariableType, XmlTarget};
+
+pub const TIA_ENGINEERING_VERSION: &str = "V17";
+pub const TIA_INTERFACE_NAMESPACE: &str = "http://www.siemens.com/automation/Openness/SW/Interface/v5";
+pub const TIA_STRUCTURED_TEXT_NAMESPACE: &str = "http://www.siemens.com/automation/Openness/SW/NetworkSource/StructuredText/v3";
+
+/// SCL addresses memory through `VARIANT` and `DB_ANY` instead of IEC pointers
+pub const TIA_FUNCTION_NAMES: [(&str, Option<&str>); 3] = [("ADR", None), ("REF", None), ("SIZEOF", None)];
+
+/// Siemens TIA Portal, imported through TIA Openness.
+///
+/// The generators populate the usual IEC 61131-10 skeleton, which is then rewritten into an Openness `<Document>`:
+/// structs become PLC data types, the globals of every file become a global DB and POUs become SCL blocks.
+/// Programs are exported as function blocks since organization blocks cannot hold static variables.
+/// Enumerations have no TIA counterpart and are skipped
+pub struct TiaTarget;
+
+impl XmlTarget for TiaTarget {
+    fn name(&self) -> &'static str {
+        "tia"
+    }
+
+    fn template(&self) -> Node {
+        get_iec_template()
+    }
+
+    fn map_type_name(&self, _generation_parameters: &GenerationParameters, type_name: &str) -> String {
+        let tia_name = match type_name.to_uppercase().as_str() {
+            "BOOL" => "Bool",
+            "BYTE" => "Byte",
+            "WORD" => "Word",
+            "DWORD" => "DWord",
+            "LWORD" => "LWord",
+            "SINT" => "SInt",
+            "USINT" => "USInt",
+            "INT" => "Int",
+            "UINT" => "UInt",
+            "DINT" => "DInt",
+            "UDINT" => "UDInt",
+            "LINT" => "LInt",
+            "ULINT" => "ULInt",
+            "REAL" => "Real",
+            "LREAL" => "LReal",
+            "TIME" => "Time",
+            "LTIME" => "LTime",
+            "DATE" => "Date",
+            "TIME_OF_DAY" | "TOD" => "Time_Of_Day",
+            "DATE_AND_TIME" | "DT" => "Date_And_Time",
+            "CHAR" => "Char",
+            "WCHAR" => "WChar",
+            "STRING" => "String",
+            "WSTRING" => "WString",
+            upper if upper.starts_with("WSTRING[") => {
+                return format!("WString{}", &type_name[7..]); //keep the declared length, e.g. WString[12]
+            },
+            upper if upper.starts_with("STRING[") => {
+                return format!("String{}", &type_name[6..]);
+            },
+            upper if upper.contains("WSTRING") => "WString",
+            upper if upper.contains("STRING") => "String",
+            _ => {
+                return format!("\"{type_name}\""); //user defined types are referenced by their quoted name
+            }
+        };
+        String::from(tia_name)
+    }
+
+    fn format_literal(&self, generation_parameters: &GenerationParameters, literal: &AstLiteral) -> String {
+        match literal {
+            AstLiteral::Time(time) => format!("T#{}", format_time(time, "_")), //TIA separates the units, e.g. T#1s_500ms
+            AstLiteral::Bool(value) => format_bool(*value, self.bool_case(generation_parameters)),
+            _ => format_iec_literal(literal, &generation_parameters.real_format),
+        }
+    }
+
+    fn retention_attributes(&self, block: &VariableBlock) -> Vec<(&'static str, &'static str)> {
+        retain_only_attributes(block) //retained tags keep their values across downloads unless reinitialized
+    }
+
+    fn bool_case(&self, generation_parameters: &GenerationParameters) -> BoolCase {
+        generation_parameters.bool_case.unwrap_or(BoolCase::Lower) //start values are exported as true and false
+    }
+
+    fn format_enum_value(&self, _generation_parameters: &GenerationParameters, variant: &NameAndInitialValue) -> String {
+        variant.initial_value.clone() //without enumerations only the number can be kept
+    }
+
+    fn function_names(&self) -> &'static [(&'static str, Option<&'static str>)] {
+        &TIA_FUNCTION_NAMES
+    }
+
+    fn supports_inheritance(&self) -> bool {
+        false //SCL function blocks cannot extend each other
+    }
+
+    fn supports_interfaces(&self) -> bool {
+        false //SCL has no interfaces
+    }
+
+    fn supports_fbd_bodies(&self) -> bool {
+        false //the blocks are exported as SCL sources
+    }
+
+    fn supports_sfc_bodies(&self) -> bool {
+        false //GRAPH blocks are imported from a format of their own
+    }
+
+    fn emit_pou(&self, pou: PouElements) -> Option<Node> {
+        let parameters = pou.parameters.inner();
+        let inputs = parameters.find_child("InputVars").map(tia_members).unwrap_or_default();
+        let inouts = parameters.find_child("InoutVars").map(tia_members).unwrap_or_default();
+        let outputs = parameters.find_child("OutputVars").map(tia_members).unwrap_or_default();
+
+        let (block, sections) = match pou.kind {
+            PouType::Program | PouType::FunctionBlock => {
+                let mut statics = tia_members(&pou.vars.inner());
+                statics.extend(retained(tia_members(&pou.retain_vars.inner())));
+
+                let mut constants = tia_members(&pou.constant_vars.inner());
+                constants.extend(tia_members(&pou.constant_retain_vars.inner()));
+
+                let sections = vec![
+                    tia_section("Input", inputs),
+                    tia_section("Output", outputs),
+                    tia_section("InOut", inouts),
+                    tia_section("Static", statics),
+                    tia_section("Temp", tia_members(&pou.temp_vars.inner())),
+                    tia_section("Constant", constants),
+                ];
+                (STiaFunctionBlock::new().inner(), sections)
+            },
+            PouType::Function => {
+                let return_type = pou.result_type.inner()
+                    .find_child("TypeName")
+                    .and_then(|it| it.content.clone())
+                    .unwrap_or_else(|| String::from("Void"));
+
+                let return_value = STiaMember::new()
+                    .attribute_str("Name", "Ret_Val")
+                    .attribute(String::from("Datatype"), return_type)
+                    .inner();
+
+                let sections = vec![
+                    tia_section("Input", inputs),
+                    tia_section("Output", outputs),
+                    tia_section("InOut", inouts),
+                    tia_section("Temp", tia_members(&pou.temp_vars.inner())),
+                    tia_section("Constant", tia_members(&pou.constant_temp_vars.inner())),
+                    tia_section("Return", vec![return_value]),
+                ];
+                (STiaFunction::new().inner(), sections)
+            },
+            _ => {
+                return None
+            }
+        };
+
+        let source = pou.main_body.inner()
+            .find_child("BodyContent")
+            .and_then(|it| it.find_child("ST"))
+            .and_then(|it| it.content.clone())
+            .unwrap_or_default();
+
+        let compile_unit = STiaCompileUnit::new()
+            .attribute_str("CompositionName", "CompileUnits")
+            .child(&STiaAttributeList::new()
+                .child(&STiaNetworkSource::new()
+                    .child(&STiaStructuredText::new()
+                        .attribute_str("xmlns", TIA_STRUCTURED_TEXT_NAMESPACE)
+                        .content(source)))
+                .child(&STiaProgrammingLanguage::new().content(String::from("SCL"))));
+
+        let attributes = STiaAttributeList::new()
+            .child(&tia_interface(sections))
+            .child(&STiaMemoryLayout::new().content(String::from("Optimized")))
+            .child(&STiaName::new().content(pou.name))
+            .child(&STiaProgrammingLanguage::new().content(String::from("SCL")))
+            .child(&STiaSetEnoAutomatically::new().content(pou.en_eno.to_string())); //TIA adds EN and ENO to every block, ENO only reports errors if set
+
+        Some(block
+            .child(&attributes)
+            .child(&STiaObjectList::new().child(&compile_unit)))
+    }
+
+    fn supports_streaming(&self) -> bool {
+        false //the blocks are regrouped into a document of their own
+    }
+
+    fn post_process(&self, output_root: &mut Node) {
+        let mut data_types = Vec::new();
+        let mut blocks = Vec::new();
+
+        let namespace = output_root.find(GLOBAL_NAMESPACE_PATH);
+        for node in namespace.map(|it| it.children.as_slice()).unwrap_or_default() {
+            if node.name == "DataTypeDecl" {
+                data_types.extend(tia_plc_struct(node));
+            }
+
+            else {
+                blocks.push(node.clone());
+            }
+        }
+
+        let configurations = output_root.find_child(INSTANCES).map(|it| it.children.as_slice()).unwrap_or_default();
+        let global_dbs = configurations.iter().filter_map(tia_global_db);
+
+        let mut document = STiaDocument::new()
+            .child(&STiaEngineering::new().attribute_str("version", TIA_ENGINEERING_VERSION).close())
+            .inner();
+        document.children.extend(data_types);
+        document.children.extend(global_dbs);
+        document.children.extend(blocks);
+
+        //every block and compile unit needs an ID which is unique within the document
+        let mut next_id = 0;
+        for block in document.children.iter_mut().skip(1) {
+            assign_tia_ids(block, &mut next_id);
+        }
+        *output_root = document;
+    }
+}
+
+/// Converts the `<Variable>` elements of an IEC 61131-10 variable list into TIA `<Member>` elements
+fn tia_members(variables: &Node) -> Vec<Node> {
+    variables.children.iter().filter(|it| it.name == "Variable").filter_map(|variable| {
+        let name = variable.attributes.get("name")?;
+        let variable_type = VariableType::of(variable)?;
+
+        let mut member = STiaMember::new()
+            .attribute(String::from("Name"), name.clone())
+            .attribute(String::from("Datatype"), tia_datatype(&variable_type))
+            .inner();
+
+        if let Some(value) = initial_value(variable) {
+            member = member.child(&STiaStartValue::new().content(value.clone()));
+        }
+        Some(with_subelement_start_values(member, variable))
+    })
+    .collect()
+}
+
+/// The members a struct or function block instance is initialized with become start values of its subelements,
+/// e.g. `<Subelement Path="PT"><StartValue>T#5s</StartValue></Subelement>`
+fn with_subelement_start_values(mut member: Node, variable: &Node) -> Node {
+    for (path, value) in struct_initial_values(variable) {
+        member = member.child(&STiaSubelement::new()
+            .attribute(String::from("Path"), path)
+            .child(&STiaStartValue::new().content(value)));
+    }
+    member
+}
+
+/// Arrays are declared inline, e.g. `Array[0..9] of "Motor"`
+fn tia_datatype(variable_type: &VariableType) -> String {
+    if variable_type.is_array() {
+        return format!("Array[{}] of {}", variable_type.ranges(), variable_type.type_name);
+    }
+    variable_type.type_name.clone()
+}
+
+fn retained(members: Vec<Node>) -> Vec<Node> {
+    members.into_iter().map(|it| it.attribute_str("Remanence", "Retain")).collect()
+}
+
+fn tia_section(name: &'static str, members: Vec<Node>) -> Node {
+    let section = STiaSection::new().attribute_str("Name", name).inner();
+    if members.is_empty() {
+        return section.close();
+    }
+    section.children(members.into_iter().map(|it| Box::new(it) as Box<dyn IntoNode>).collect())
+}
+
+fn tia_interface(sections: Vec<Node>) -> STiaInterface {
+    let mut sections_node = STiaSections::new().attribute_str("xmlns", TIA_INTERFACE_NAMESPACE).inner();
+    sections_node.children.extend(sections);
+    STiaInterface::new().child(&sections_node)
+}
+
+/// A `<DataTypeDecl>` holding a struct becomes a PLC data type
+fn tia_plc_struct(declaration: &Node) -> Option<Node> {
+    let spec = declaration.find_child("UserDefinedTypeSpec")?;
+    if spec.attributes.get("xsi:type").map(String::as_str) != Some("StructTypeSpec") {
+        return None; //TIA has no enumerations
+    }
+
+    let members = spec.children.iter().filter(|it| it.name == "Member").filter_map(|member| {
+        let member_type = VariableType::of(member)?;
+
+        let tia_member = STiaMember::new()
+            .attribute(String::from("Name"), member.attributes.get("name")?.clone())
+            .attribute(String::from("Datatype"), tia_datatype(&member_type))
+            .inner();
+
+        match initial_value(member) {
+            Some(value) => Some(tia_member.child(&STiaStartValue::new().content(value.clone()))),
+            None => Some(with_subelement_start_values(tia_member, member)),
+        }
+    })
+    .collect();
+
+    let attributes = STiaAttributeList::new()
+        .child(&tia_interface(vec![tia_section("None", members)]))
+        .child(&STiaName::new().content(declaration.attributes.get("name")?.clone()));
+
+    Some(STiaPlcStruct::new().child(&attributes).inner())
+}
+
+/// The globals of a `<Configuration>` become a global DB named after their source file
+fn tia_global_db(configuration: &Node) -> Option<Node> {
+    let mut members = Vec::new();
+
+    for resource in configuration.children.iter().filter(|it| it.name == RESOURCE) {
+        for globals in resource.children.iter().filter(|it| it.name == "GlobalVars") {
+            let globals_members = tia_members(globals);
+
+            if globals.attributes.contains_key("retain") {
+                members.extend(retained(globals_members));
+            }
+
+            else {
+                members.extend(globals_members);
+            }
+        }
+    }
+
+    if members.is_empty() {
+        return None;
+    }
+
+    let configuration_name = configuration.attributes.get("name")?;
+    let unit_name = configuration_name.strip_suffix(&format!("_{CONFIGURATION}")).unwrap_or(configuration_name);
+    let file_stem = Path::new(unit_name).file_stem().map(|it| it.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let attributes = STiaAttributeList::new()
+        .child(&tia_interface(vec![tia_section("Static", members)]))
+        .child(&STiaMemoryLayout::new().content(String::from("Optimized")))
+        .child(&STiaName::new().content(format!("{file_stem}_Globals")));
+
+    Some(STiaGlobalDB::new().child(&attributes).inner())
+}
+
+fn assign_tia_ids(block: &mut Node, next_id: &mut usize) {
+    block.attributes.insert(String::from("ID"), format!("{next_id:X}"));
+    *next_id += 1;
+
+    let compile_units = block.children.iter_mut()
+        .filter(|it| it.name == "ObjectList")
+        .flat_map(|it| it.children.iter_mut());
+
+    for compile_unit in compile_units {
+        compile_unit.attributes.insert(String::from("ID"), format!("{next_id:X}"));
+        *next_id += 1;
+    }
+}
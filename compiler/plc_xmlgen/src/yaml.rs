@@ -0,0 +1,110 @@
+use serde_json::Value;
+
+/// Renders a JSON value as block style YAML. Mapping keys come out sorted, so the dump is stable between runs, and
+/// multi-line strings such as POU bodies become literal blocks so they diff line by line
+pub fn to_yaml(value: &Value) -> String {
+    let mut output = String::new();
+
+    match value {
+        Value::Object(map) if !map.is_empty() => write_mapping(&mut output, map, 0),
+        Value::Array(items) if !items.is_empty() => write_sequence(&mut output, items, 0),
+        _ => {
+            write_scalar(&mut output, value, 0);
+            output.push('\n');
+        }
+    }
+    output
+}
+
+fn write_mapping(output: &mut String, map: &serde_json::Map<String, Value>, indent: usize) {
+    for (index, (key, value)) in map.iter().enumerate() {
+        if index > 0 {
+            output.push_str(&" ".repeat(indent)); //the first key continues the line of a sequence dash
+        }
+        output.push_str(&format_string(key));
+        output.push(':');
+        write_nested(output, value, indent);
+    }
+}
+
+fn write_sequence(output: &mut String, items: &[Value], indent: usize) {
+    for (index, item) in items.iter().enumerate() {
+        if index > 0 {
+            output.push_str(&" ".repeat(indent));
+        }
+        output.push_str("- ");
+
+        match item {
+            Value::Object(map) if !map.is_empty() => write_mapping(output, map, indent + 2),
+            Value::Array(nested) if !nested.is_empty() => write_sequence(output, nested, indent + 2),
+            _ => {
+                write_scalar(output, item, indent + 2);
+                output.push('\n');
+            }
+        }
+    }
+}
+
+/// The value of a mapping entry, after its `key:`
+fn write_nested(output: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::Object(map) if !map.is_empty() => {
+            output.push('\n');
+            output.push_str(&" ".repeat(indent + 2));
+            write_mapping(output, map, indent + 2);
+        },
+        Value::Array(items) if !items.is_empty() => {
+            output.push('\n');
+            output.push_str(&" ".repeat(indent));
+            write_sequence(output, items, indent);
+        },
+        _ => {
+            output.push(' ');
+            write_scalar(output, value, indent + 2);
+            output.push('\n');
+        }
+    }
+}
+
+fn write_scalar(output: &mut String, value: &Value, indent: usize) {
+    match value {
+        Value::String(text) if text.contains('\n') && !text.contains('\r') && !text.starts_with(' ') => {
+            //a literal block, the indicator tells whether the text ends with a line break
+            let chomping = if text.ends_with('\n') { "|" } else { "|-" };
+            output.push_str(chomping);
+
+            for line in text.trim_end_matches('\n').split('\n') {
+                output.push('\n');
+                if !line.is_empty() {
+                    output.push_str(&" ".repeat(indent));
+                    output.push_str(line);
+                }
+            }
+        },
+        Value::String(text) => output.push_str(&format_string(text)),
+        Value::Object(_) => output.push_str("{}"),
+        Value::Array(_) => output.push_str("[]"),
+        other => output.push_str(&other.to_string()), //null, booleans and numbers are written the same in YAML
+    }
+}
+
+/// Strings are written plain unless YAML would read them as something else, then they are double quoted
+fn format_string(text: &str) -> String {
+    let reserved = ["", "~", "null", "true", "false", "yes", "no", "on", "off"];
+    let needs_quotes = reserved.contains(&text.to_lowercase().as_str())
+        || text.parse::<f64>().is_ok()
+        || text.starts_with(|it: char| it.is_whitespace() || "-?:,[]{}#&*!|>'\"%@`".contains(it))
+        || text.ends_with(|it: char| it.is_whitespace() || it == ':')
+        || text.contains(": ")
+        || text.contains(" #")
+        || text.contains(|it: char| it.is_control());
+
+    if needs_quotes {
+        //JSON strings are valid double quoted YAML scalars
+        Value::String(String::from(text)).to_string()
+    }
+
+    else {
+        String::from(text)
+    }
+}
@@ -47,7 +47,7 @@ use plc_ast::ast::{CompilationUnit, LinkageType};
 use plc_diagnostics::diagnostics::Diagnostic;
 use plc_llvm::TargetMachineExt;
 use plc_source::source_location::{FileMarker, SourceLocation};
-use plc_xmlgen::{serializer::Node, xml_gen::*};
+use plc_xmlgen::xml_gen::*;
 
 mod debug;
 pub(crate) mod generators;
@@ -408,7 +408,8 @@ impl<'ink> GeneratedModule<'ink> {
         target: &Target,
         optimization_level: OptimizationLevel,
         annotated_project: &Vec<&CompilationUnit>,
-        compilation_options: &GenerationParameters
+        compilation_options: &GenerationParameters,
+        diagnostics: &mut Vec<Diagnostic>
     ) -> Result<PathBuf, CodegenError> {
         let output = Self::get_output_file(output_dir, output_name, target);
         //ensure output exists
@@ -425,7 +426,7 @@ impl<'ink> GeneratedModule<'ink> {
             FormatOption::NoPIC => self.persist_to_shared_object(output, target, optimization_level),
             FormatOption::Bitcode => self.persist_to_bitcode(output),
             FormatOption::IR => self.persist_to_ir(output),
-            FormatOption::XML => self.persist_to_xml(output, annotated_project, compilation_options)
+            FormatOption::XML => Self::persist_to_xml(output, annotated_project, compilation_options, diagnostics)
         }
     }
 
@@ -443,25 +444,24 @@ impl<'ink> GeneratedModule<'ink> {
         &self.location
     }
 
-    fn persist_to_xml(&self, output: PathBuf, annotated_project: &Vec<&CompilationUnit>, compilation_options: &GenerationParameters) -> Result<PathBuf, CodegenError> {
-        let schema_path: &'static str;
-
-        let template: Node = if compilation_options.output_xml_omron {
-            schema_path = OMRON_SCHEMA;
-            get_omron_template()
-        }
-
-        else {
+    /// Exports the units as XML, adding the warnings about declarations which could not be exported as declared to the
+    /// given diagnostics. The document covers all units, so it needs no generated module
+    pub fn persist_to_xml(output: PathBuf, annotated_project: &Vec<&CompilationUnit>, compilation_options: &GenerationParameters, diagnostics: &mut Vec<Diagnostic>) -> Result<PathBuf, CodegenError> {
+        if compilation_options.target_names().is_empty() && !compilation_options.writes_side_outputs() {
             return Err(
                 CodegenError::GenericError(
                     String::from("No XML variant chosen as CLI argument but XML output format was specified."), 
                     SourceLocation::undefined()));
-        };
+        }
 
-        match parse_project_into_nodetree(compilation_options, annotated_project, schema_path, &output, template) {
-            Ok(_) => Ok(output),
+        match generate_xml_targets(compilation_options, annotated_project, &output) {
+            Ok(generated) => {
+                diagnostics.extend(generated.diagnostics);
+                Ok(output) //with several targets this is the base path the per target files are derived from
+            },
             Err(error) => {
-                return Err(CodegenError::GenericError(error.to_string(), SourceLocation::default()));
+                let location = DeclarationError::location_of(&error).cloned().unwrap_or_default(); //report errors of a declaration at it
+                return Err(CodegenError::GenericError(error.to_string(), location));
             }
         }
     }
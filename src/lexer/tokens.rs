@@ -63,6 +63,15 @@ pub enum Token {
     #[token("VAR_CONFIG", ignore(case))]
     KeywordVarConfig,
 
+    #[token("VAR_ACCESS", ignore(case))]
+    KeywordVarAccess,
+
+    #[token("READ_ONLY", ignore(case))]
+    KeywordReadOnly,
+
+    #[token("READ_WRITE", ignore(case))]
+    KeywordReadWrite,
+
     #[token("ABSTRACT", ignore(case))]
     KeywordAbstract,
 
@@ -113,6 +122,9 @@ pub enum Token {
     #[token("NONRETAIN", ignore(case))]
     KeywordNonRetain,
 
+    #[token("PERSISTENT", ignore(case))]
+    KeywordPersistent,
+
     #[token("VAR_TEMP", ignore(case))]
     #[token("VARTEMP", ignore(case))]
     KeywordVarTemp,
@@ -50,6 +50,7 @@ extern crate shell_words;
 
 pub const DEFAULT_DWARF_VERSION: usize = 5;
 pub const DEFAULT_GOT_LAYOUT_FILE: &str = "online_change_got.json";
+pub const DEFAULT_SARIF_OUTPUT_FILE: &str = "diagnostics.sarif";
 
 #[derive(Default, Serialize, Debug, Clone, PartialEq, Eq, Hash)]
 pub enum Target {
@@ -153,6 +154,7 @@ pub enum ErrorFormat {
     #[default]
     Rich,
     Clang,
+    Sarif,
     None,
 }
 
@@ -160,7 +162,7 @@ impl From<ErrorFormat> for IndexErrorFormat {
     fn from(value: ErrorFormat) -> Self {
         match value {
             ErrorFormat::Rich => IndexErrorFormat::Rich,
-            ErrorFormat::Clang => IndexErrorFormat::Clang,
+            ErrorFormat::Clang | ErrorFormat::Sarif => IndexErrorFormat::Clang,
             ErrorFormat::None => IndexErrorFormat::Null,
         }
     }
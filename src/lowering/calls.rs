@@ -223,6 +223,8 @@ impl AstVisitorMut for AggregateTypeLowerer {
                 access: AccessModifier::Public,
                 constant: false,
                 retain: false,
+                persistent: false,
+                non_retain: false,
                 variables: vec![Variable {
                     name: pou.get_return_name().to_string(),
                     data_type_declaration: original_return,
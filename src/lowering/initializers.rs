@@ -501,6 +501,7 @@ fn new_unit(pou: Pou, implementation: Implementation, file_name: &'static str) -
     CompilationUnit {
         global_vars: vec![],
         var_config: Default::default(),
+        var_access: Default::default(),
         pous: vec![pou],
         implementations: vec![implementation],
         interfaces: vec![],
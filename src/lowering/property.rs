@@ -259,6 +259,8 @@ pub fn lower_to_pou(
                     access: AccessModifier::Public,
                     constant: false,
                     retain: false,
+                    persistent: false,
+                    non_retain: false,
                     variables: vec![Variable {
                         name: name.to_string(),
                         data_type_declaration: property.datatype.clone(),
@@ -282,6 +284,8 @@ pub fn lower_to_pou(
                     access: AccessModifier::Public,
                     constant: false,
                     retain: false,
+                    persistent: false,
+                    non_retain: false,
                     variables: vec![Variable {
                         name: name.to_string(),
                         data_type_declaration: property.datatype.clone(),
@@ -143,6 +143,8 @@ impl VirtualTableGenerator {
                 access: AccessModifier::Protected,
                 constant: false,
                 retain: false,
+                persistent: false,
+                non_retain: false,
                 location: location.clone(),
             },
         );
@@ -4,7 +4,7 @@ use std::ops::Range;
 
 use plc_ast::{
     ast::{
-        AccessModifier, ArgumentProperty, AstFactory, AstNode, AstStatement, AutoDerefType, CompilationUnit, ConfigVariable, DataType, DataTypeDeclaration, DeclarationKind, DirectAccessType, GenericBinding, HardwareAccessType, Identifier, Implementation, Interface, LinkageType, PolymorphismMode, Pou, PouType, PropertyBlock, PropertyImplementation, PropertyKind, ReferenceAccess, ReferenceExpr, TypeNature, UserTypeDeclaration, Variable, VariableBlock, VariableBlockType
+        AccessDirection, AccessModifier, AccessVariable, ArgumentProperty, AstFactory, AstNode, AstStatement, AutoDerefType, CompilationUnit, ConfigVariable, DataType, DataTypeDeclaration, DeclarationKind, DirectAccessType, GenericBinding, HardwareAccessType, Identifier, Implementation, Interface, LinkageType, PolymorphismMode, Pou, PouType, PropertyBlock, PropertyImplementation, PropertyKind, ReferenceAccess, ReferenceExpr, TypeNature, UserTypeDeclaration, Variable, VariableBlock, VariableBlockType
     },
     provider::IdProvider,
 };
@@ -87,6 +87,7 @@ pub fn parse(mut lexer: ParseSession, lnk: LinkageType, file_name: &'static str)
             }
             KeywordVarGlobal => unit.global_vars.push(parse_variable_block(&mut lexer, linkage)),
             KeywordVarConfig => unit.var_config.extend(parse_config_variables(&mut lexer, &linkage)),
+            KeywordVarAccess => unit.var_access.extend(parse_access_variables(&mut lexer, &linkage)),
 
             KeywordProgram | KeywordClass | KeywordFunction | KeywordFunctionBlock => {
                 let params = match lexer.token {
@@ -1455,8 +1456,17 @@ fn parse_variable_block(lexer: &mut ParseSession, linkage: LinkageType) -> Varia
 
     let constant = lexer.try_consume(KeywordConstant);
 
-    let retain = lexer.try_consume(KeywordRetain);
-    lexer.try_consume(KeywordNonRetain);
+    //the retention qualifiers may come in any order, e.g. `PERSISTENT RETAIN` or `RETAIN PERSISTENT`
+    let (mut retain, mut persistent, mut non_retain) = (false, false, false);
+    loop {
+        match lexer.token {
+            KeywordRetain => retain = true,
+            KeywordPersistent => persistent = true,
+            KeywordNonRetain => non_retain = true,
+            _ => break,
+        }
+        lexer.advance();
+    }
 
     let access = parse_access_modifier(lexer);
 
@@ -1469,7 +1479,17 @@ fn parse_variable_block(lexer: &mut ParseSession, linkage: LinkageType) -> Varia
         });
     }
     println!("parse_variable_block; linkage: {:?}, access: {:?}, variables: {:?}", linkage, &access, variables);
-    VariableBlock { access, constant, retain, variables, kind: variable_block_type, location, linkage: linkage.clone() }
+    VariableBlock {
+        access,
+        constant,
+        retain,
+        persistent,
+        non_retain,
+        variables,
+        kind: variable_block_type,
+        location,
+        linkage: linkage.clone(),
+    }
 }
 
 fn parse_variable_list(lexer: &mut ParseSession, linkage: &LinkageType) -> Vec<Variable> {
@@ -1530,6 +1550,64 @@ fn try_parse_config_var(lexer: &mut ParseSession, linkage: &LinkageType) -> Opti
     })
 }
 
+fn parse_access_variables(lexer: &mut ParseSession, linkage: &LinkageType) -> Vec<AccessVariable> {
+    parse_any_in_region_with_linkage(lexer, vec![KeywordEndVar], linkage, |lexer: &mut ParseSession, linkage2: &LinkageType| {
+        lexer.advance();
+        let mut variables = vec![];
+        while lexer.token == Identifier {
+            if let Some(access_variable) =
+                parse_any_in_region_with_linkage(lexer, vec![KeywordSemicolon], linkage2, try_parse_access_var)
+            {
+                variables.push(access_variable);
+            }
+        }
+        variables
+    })
+}
+
+fn try_parse_access_var(lexer: &mut ParseSession, linkage: &LinkageType) -> Option<AccessVariable> {
+    let location = lexer.location();
+    let name = lexer.slice_and_advance();
+    if !lexer.try_consume(KeywordColon) {
+        lexer.accept_diagnostic(Diagnostic::missing_token(
+            format!("{KeywordColon:?}").as_str(),
+            lexer.location(),
+        ));
+    }
+
+    let path = match lexer.token {
+        HardwareAccess((direction, access_type)) => parse_hardware_access(lexer, direction, access_type)?,
+        _ => parse_reference(lexer),
+    };
+
+    if !lexer.try_consume(KeywordColon) {
+        lexer.accept_diagnostic(Diagnostic::missing_token(
+            format!("{KeywordColon:?}").as_str(),
+            lexer.location(),
+        ));
+    }
+
+    let (data_type, init) = parse_data_type_definition(lexer, None, linkage)?;
+    if init.is_some() {
+        lexer.accept_diagnostic(Diagnostic::unexpected_token_found(
+            format!("{KeywordSemicolon:?}").as_str(),
+            "Initializer",
+            lexer.last_location().span(&lexer.location()),
+        ))
+    }
+
+    //access paths are read-only unless declared otherwise
+    let direction = match lexer.token {
+        KeywordReadWrite => AccessDirection::ReadWrite,
+        _ => AccessDirection::ReadOnly,
+    };
+    if matches!(lexer.token, KeywordReadOnly | KeywordReadWrite) {
+        lexer.advance();
+    }
+
+    Some(AccessVariable { name, path, data_type, direction, location })
+}
+
 fn parse_aliasing(lexer: &mut ParseSession, names: &(String, Range<usize>), linkage: &LinkageType) -> Option<Variable> {
     let reference = parse_reference(lexer);
     if !lexer.try_consume(KeywordColon) {
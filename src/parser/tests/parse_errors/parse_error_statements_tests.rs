@@ -202,6 +202,8 @@ fn invalid_variable_name_error_recovery() {
                 constant: false,
                 access: AccessModifier::Protected,
                 retain: false,
+                persistent: false,
+                non_retain: false,
                 location: SourceLocation::internal(),
                 variables: vec![Variable {
                     name: "c".into(),
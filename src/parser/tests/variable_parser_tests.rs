@@ -1,4 +1,4 @@
-use plc_ast::ast::{LinkageType, VariableBlock};
+use plc_ast::ast::{AccessDirection, AstStatement, LinkageType, VariableBlock};
 
 use crate::test_utils::tests::parse;
 
@@ -16,6 +16,20 @@ fn empty_global_vars_can_be_parsed() {
     assert_eq!(ast_string, expected_ast)
 }
 
+#[test]
+fn retention_qualifiers_can_be_parsed() {
+    let src = "VAR_GLOBAL RETAIN END_VAR VAR_GLOBAL PERSISTENT RETAIN END_VAR VAR_GLOBAL CONSTANT PERSISTENT END_VAR VAR_GLOBAL NON_RETAIN END_VAR";
+    let (result, diagnostics) = parse(src);
+    assert_eq!(diagnostics, vec![]);
+
+    let qualifiers: Vec<(bool, bool, bool, bool)> =
+        result.global_vars.iter().map(|it| (it.constant, it.retain, it.persistent, it.non_retain)).collect();
+    assert_eq!(
+        qualifiers,
+        vec![(false, true, false, false), (false, true, true, false), (true, false, true, false), (false, false, false, true)]
+    );
+}
+
 #[test]
 fn global_vars_can_be_parsed() {
     let src = "VAR_GLOBAL x : INT; y : BOOL; END_VAR";
@@ -361,6 +375,7 @@ fn var_config_test() {
                 },
             },
         ],
+        var_access: [],
         pous: [],
         implementations: [],
         interfaces: [],
@@ -385,6 +400,36 @@ fn var_config_location() {
     assert_eq!("main.instance.foo", &src[result.var_config[0].location.to_range().unwrap()]);
 }
 
+#[test]
+fn var_access_test() {
+    let src = "
+    VAR_ACCESS
+        speed : main.motor.speed : REAL READ_WRITE;
+        running : main.motor.running : BOOL READ_ONLY;
+        sensor : %IX1.2 : BOOL;
+    END_VAR
+    ";
+    let (result, diag) = parse(src);
+
+    assert_eq!(diag, vec![]);
+    let accesses: Vec<_> = result
+        .var_access
+        .iter()
+        .map(|it| (it.name.as_str(), it.data_type.get_name().unwrap(), it.direction))
+        .collect();
+    assert_eq!(
+        accesses,
+        vec![
+            ("speed", "REAL", AccessDirection::ReadWrite),
+            ("running", "BOOL", AccessDirection::ReadOnly),
+            ("sensor", "BOOL", AccessDirection::ReadOnly),
+        ]
+    );
+    assert_eq!("main.motor.speed", result.var_access[0].path.as_string());
+    assert!(matches!(result.var_access[2].path.stmt, AstStatement::HardwareAccess(_)));
+    assert_eq!("speed", &src[result.var_access[0].location.to_range().unwrap()]);
+}
+
 #[test]
 fn var_external() {
     let src = r#"
@@ -431,6 +476,7 @@ fn var_external() {
             },
         ],
         var_config: [],
+        var_access: [],
         pous: [
             POU {
                 name: "foo",
@@ -543,6 +589,7 @@ fn var_external_constant() {
             },
         ],
         var_config: [],
+        var_access: [],
         pous: [
             POU {
                 name: "foo",
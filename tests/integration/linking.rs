@@ -274,6 +274,7 @@ fn link_with_library_path() {
         got_layout: Default::default(),
         compile_dirs: Default::default(),
         libraries: pipeline.project.get_libraries().to_vec(),
+        diagnostics: Default::default(),
     };
     pipeline.register_participant(Box::new(codegen_participant));
     pipeline.run().unwrap();
@@ -0,0 +1,168 @@
+use std::fs;
+
+use driver::{compile, pipelines::BuildPipeline, pipelines::Pipeline, register_participants};
+use plc_diagnostics::{diagnostician::Diagnostician, reporter::DiagnosticReporter};
+
+/// Exports a single file with the given options and returns the document
+fn export(source: &str, options: &[&str]) -> String {
+    let directory = tempfile::tempdir().unwrap();
+    let file = directory.path().join("prog.st");
+    fs::write(&file, source).unwrap();
+    let output = directory.path().join("project.xml");
+
+    let mut arguments = vec!["plc", file.to_str().unwrap(), "-o", output.to_str().unwrap()];
+    arguments.extend(options);
+    compile(&arguments).unwrap();
+    fs::read_to_string(&output).unwrap()
+}
+
+#[test]
+fn export_warnings_are_reported_once_for_the_whole_project() {
+    // GIVEN a project of two files, one of which declares a function without a return type
+    let directory = tempfile::tempdir().unwrap();
+    let untyped = directory.path().join("untyped.st");
+    fs::write(&untyped, "FUNCTION untyped\nEND_FUNCTION\n").unwrap();
+    let program = directory.path().join("prog.st");
+    fs::write(&program, "PROGRAM prog\nVAR x : INT; END_VAR\nx := 1;\nEND_PROGRAM\n").unwrap();
+    let output = directory.path().join("project.xml");
+
+    // WHEN the project is exported as XML
+    let mut pipeline = BuildPipeline::new(&[
+        "plc",
+        untyped.to_str().unwrap(),
+        program.to_str().unwrap(),
+        "-o",
+        output.to_str().unwrap(),
+        "--xml-target",
+        "iec",
+    ])
+    .unwrap();
+    pipeline.diagnostician = Diagnostician::buffered();
+    register_participants(&mut pipeline);
+    pipeline.run().unwrap();
+
+    // THEN the document is written and the missing return type is reported once, not once per file
+    assert!(output.exists());
+    let reported = pipeline.diagnostician.buffer().unwrap();
+    assert_eq!(reported.matches("E125").count(), 1, "{reported}");
+}
+
+#[test]
+fn side_outputs_read_the_sources_as_the_compiler_decoded_them() {
+    // GIVEN a documented program in another encoding than UTF-8
+    let directory = tempfile::tempdir().unwrap();
+    let program = directory.path().join("prog.st");
+    fs::write(&program, b"(* Fills the tank to its Gr\xf6\xdfe *)\nPROGRAM prog\nEND_PROGRAM\n").unwrap();
+    let output = directory.path().join("project.xml");
+
+    // WHEN only its documentation is written, without an XML target
+    compile(&[
+        "plc",
+        program.to_str().unwrap(),
+        "-o",
+        output.to_str().unwrap(),
+        "--encoding",
+        "windows-1252",
+        "--doc-markdown",
+    ])
+    .unwrap();
+
+    // THEN the comment is read from the decoded source instead of the file
+    let markdown = fs::read_to_string(output.with_extension("md")).unwrap();
+    assert!(markdown.contains("Fills the tank to its Größe"), "{markdown}");
+}
+
+#[test]
+fn array_bounds_are_exported_as_evaluated() {
+    // GIVEN an array type bounded by a constant expression
+    let source = "
+        VAR_GLOBAL CONSTANT
+            SIZE : INT := 4;
+        END_VAR
+
+        TYPE Buffer : ARRAY[0..SIZE-1] OF INT; END_TYPE
+    ";
+
+    // WHEN it is exported
+    let document = export(source, &["--xml-target", "iec"]);
+
+    // THEN the bounds are written as the numbers they evaluate to
+    assert!(document.contains(r#"lower="0" upper="3""#), "{document}");
+}
+
+#[test]
+fn initial_values_are_exported_as_folded() {
+    // GIVEN a global initialized with a constant expression
+    let source = "
+        VAR_GLOBAL
+            gLimit : INT := 2 * 21;
+        END_VAR
+    ";
+
+    // WHEN it is exported
+    let document = export(source, &["--xml-target", "iec"]);
+
+    // THEN its initial value is the literal the expression folds into
+    assert!(document.contains(r#"value="42""#), "{document}");
+}
+
+#[test]
+fn instances_of_function_blocks_are_not_published() {
+    // GIVEN a published block of globals with an instance of a function block
+    let source = "
+        FUNCTION_BLOCK Motor
+        VAR_INPUT
+            speed : INT;
+        END_VAR
+        END_FUNCTION_BLOCK
+
+        {attribute 'network_publish' := 'Input'}
+        VAR_GLOBAL
+            gMotor : Motor;
+            gSpeed : INT;
+        END_VAR
+    ";
+
+    // WHEN it is exported for Sysmac Studio
+    let document = export(source, &["--xml-target", "omron"]);
+
+    // THEN only the variable of an elementary type is published
+    let network_publish = |name: &str| {
+        let variable = &document[document.find(&format!(r#"name="{name}""#)).unwrap()..];
+        let value = &variable[variable.find(r#"networkPublish=""#).unwrap() + r#"networkPublish=""#.len()..];
+        value[..value.find('"').unwrap()].to_string()
+    };
+    assert_eq!(network_publish("gMotor"), "DoNotPublish");
+    assert_eq!(network_publish("gSpeed"), "Input");
+}
+
+#[test]
+fn generic_functions_are_exported_per_instantiation() {
+    // GIVEN a generic function of the project called with INT arguments
+    let source = "
+        FUNCTION MAX_OF <T: ANY_NUM> : T
+        VAR_INPUT
+            a, b : T;
+        END_VAR
+        END_FUNCTION
+
+        FUNCTION MAX_OF__INT : INT
+        VAR_INPUT
+            a, b : INT;
+        END_VAR
+        END_FUNCTION
+
+        PROGRAM prog
+        VAR
+            x : INT;
+        END_VAR
+            x := MAX_OF(x, INT#2);
+        END_PROGRAM
+    ";
+
+    // WHEN it is exported per instantiation
+    let document = export(source, &["--xml-target", "iec", "--xml-monomorphize-generics"]);
+
+    // THEN the instantiation is exported as a concrete function
+    assert!(document.contains(r#"name="MAX_OF_INT""#), "{document}");
+}
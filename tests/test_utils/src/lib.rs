@@ -77,6 +77,7 @@ pub fn codegen_debug_without_unwrap(src: &str, debug_level: DebugLevel) -> Resul
         mutable_participants: Vec::default(),
         participants: Vec::default(),
         module_name: Some("<internal>".to_string()),
+        xml_targets: Default::default(),
     };
 
     driver::compile_with_pipeline(pipeline).map_err(|it| it.to_string())?;
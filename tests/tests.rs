@@ -55,6 +55,7 @@ mod integration {
     mod external_files;
     mod linking;
     mod multi_files;
+    mod xml_export;
 }
 
 #[macro_use]